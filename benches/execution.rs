@@ -0,0 +1,64 @@
+//! Evaluator against compiler + VM on a loop-heavy program.
+//!
+//! Run with `cargo bench --bench execution`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use library::ast::Program;
+use library::compiler::Compiler;
+use library::evaluator::Evaluator;
+use library::lexer::Lexer;
+use library::parser::Parser;
+use library::vm::Vm;
+
+const ITERATIONS: u32 = 5;
+
+const SOURCE: &str = "
+functie prim(n) {
+    daca n < 2 { returneaza false }
+    d ← 2
+    cat timp d * d <= n { daca n % d == 0 { returneaza false } d += 1 }
+    returneaza true
+}
+c ← 0
+pentru i ← 1, 30000 { daca prim(i) { c += 1 } }
+c
+";
+
+fn parse() -> Program {
+    let mut parser = Parser::new(Lexer::new(SOURCE.to_string()));
+    let program = parser.parse_program().unwrap();
+    assert!(parser.errors().is_empty(), "{:?}", parser.errors());
+    program
+}
+
+fn measure<F: FnMut() -> String>(name: &str, mut run: F) -> Duration {
+    let result = run();
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        black_box(run());
+    }
+    let elapsed = start.elapsed() / ITERATIONS;
+    println!("{name:>10}: {result} in {elapsed:?}");
+    elapsed
+}
+
+fn main() {
+    let program = parse();
+
+    let evaluator = measure("evaluator", || {
+        Evaluator::new().eval_program(&program).unwrap().to_string()
+    });
+    let vm = measure("vm", || {
+        let bytecode = Compiler::new().compile(&program).unwrap();
+        let mut vm = Vm::new(bytecode);
+        vm.run().unwrap();
+        vm.last_popped().to_string()
+    });
+
+    println!(
+        "speedup: {:.2}x",
+        evaluator.as_secs_f64() / vm.as_secs_f64()
+    );
+}
@@ -0,0 +1,52 @@
+//! Lexer throughput on a large generated program.
+//!
+//! Run with `cargo bench --bench lexer`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use library::lexer::{BorrowedLexer, Lexer, TokenType};
+
+const ITERATIONS: u32 = 20;
+
+fn generate(lines: usize) -> String {
+    let mut source = String::new();
+    for i in 0..lines {
+        source.push_str(&format!(
+            "let value{i} = fn(x, y) {{ if (x <= {i}) {{ return x * y; }} else {{ return !y; }} }};\n"
+        ));
+    }
+    source
+}
+
+fn measure<F: FnMut() -> usize>(name: &str, bytes: usize, mut run: F) -> Duration {
+    let tokens = run();
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        black_box(run());
+    }
+    let elapsed = start.elapsed() / ITERATIONS;
+    let throughput = bytes as f64 / elapsed.as_secs_f64() / (1024.0 * 1024.0);
+    println!("{name:>10}: {tokens} tokens in {elapsed:?} ({throughput:.1} MiB/s)");
+    elapsed
+}
+
+fn main() {
+    let source = generate(20_000);
+    let bytes = source.len();
+
+    let owned = measure("owned", bytes, || {
+        let mut lexer = Lexer::new(source.clone());
+        let mut count = 0;
+        while lexer.next_token().token_type != TokenType::Eof {
+            count += 1;
+        }
+        count
+    });
+    let borrowed = measure("borrowed", bytes, || BorrowedLexer::new(&source).count());
+
+    println!(
+        "speedup: {:.2}x",
+        owned.as_secs_f64() / borrowed.as_secs_f64()
+    );
+}
@@ -1,24 +1,35 @@
 use core::fmt;
-use std::{cell::RefCell, rc::Rc};
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
 
+use crate::bigint::BigInt;
 use crate::lexer;
 
 pub trait Node {
     fn token_literal(&self) -> Option<String>;
+    /// Where the node is in the source: the first token of a statement, or
+    /// the token that identifies an expression (the operator of an infix).
+    fn span(&self) -> lexer::Span;
     fn string(&self) -> String {
         String::new()
     }
 }
 
-pub trait Statement: Node {
+pub trait Statement: Node + fmt::Debug {
     fn statement_node(&self);
     fn as_any(&self) -> &dyn std::any::Any;
 }
 
 pub trait Expression: Node + fmt::Debug {
     fn expression_node(&self);
+    fn as_any(&self) -> &dyn std::any::Any;
 }
 
+/// A `key: value` entry of a [`MapLiteral`].
+pub type MapPair = (Rc<RefCell<dyn Expression>>, Rc<RefCell<dyn Expression>>);
+
 pub struct Program {
     pub statements: Vec<Rc<RefCell<dyn Statement>>>,
 }
@@ -27,6 +38,10 @@ pub struct Program {
 pub struct Identifier {
     pub token: lexer::Token,
     pub value: String,
+    /// How many function scopes out the name is bound, filled in by
+    /// `resolver::resolve` when the evaluator runs a program. The
+    /// evaluator looks the name up there first.
+    pub depth: Cell<Option<usize>>,
 }
 
 #[derive(Debug)]
@@ -36,6 +51,83 @@ pub struct LetStatement {
     pub value: Option<Rc<RefCell<dyn Expression>>>,
 }
 
+/// `name ← value`, `name = value` or a compound form such as `name += value`.
+/// `token` is the assignment operator.
+#[derive(Debug)]
+pub struct AssignStatement {
+    pub token: lexer::Token,
+    pub name: Identifier,
+    pub value: Option<Rc<RefCell<dyn Expression>>>,
+}
+
+/// `left[index] ← value` or a compound form such as `left[index] += value`.
+/// `token` is the assignment operator and `target` an [`IndexExpression`].
+#[derive(Debug)]
+pub struct IndexAssignStatement {
+    pub token: lexer::Token,
+    pub target: Rc<RefCell<dyn Expression>>,
+    pub value: Option<Rc<RefCell<dyn Expression>>>,
+}
+
+#[derive(Debug)]
+pub struct BlockStatement {
+    pub token: lexer::Token,
+    pub statements: Vec<Rc<RefCell<dyn Statement>>>,
+}
+
+/// `daca condition atunci { ... } altfel { ... }`. An `altfel daca` chain
+/// is an alternative block holding a single nested [`IfStatement`].
+#[derive(Debug)]
+pub struct IfStatement {
+    pub token: lexer::Token,
+    pub condition: Option<Rc<RefCell<dyn Expression>>>,
+    pub consequence: BlockStatement,
+    pub alternative: Option<BlockStatement>,
+}
+
+/// `cat timp condition executa { ... }`
+#[derive(Debug)]
+pub struct WhileStatement {
+    pub token: lexer::Token,
+    pub condition: Option<Rc<RefCell<dyn Expression>>>,
+    pub body: BlockStatement,
+}
+
+/// `pentru counter ← start, end[, step] executa { ... }`. The bounds are
+/// inclusive and the step defaults to 1.
+#[derive(Debug)]
+pub struct ForStatement {
+    pub token: lexer::Token,
+    pub counter: Identifier,
+    pub start: Option<Rc<RefCell<dyn Expression>>>,
+    pub end: Option<Rc<RefCell<dyn Expression>>>,
+    pub step: Option<Rc<RefCell<dyn Expression>>>,
+    pub body: BlockStatement,
+}
+
+/// `repeta { ... } pana cand condition`, which runs the body at least once.
+#[derive(Debug)]
+pub struct RepeatStatement {
+    pub token: lexer::Token,
+    pub body: BlockStatement,
+    pub condition: Option<Rc<RefCell<dyn Expression>>>,
+}
+
+/// `citeste a, v[i]`. Each target is an [`Identifier`] or an
+/// [`IndexExpression`].
+#[derive(Debug)]
+pub struct ReadStatement {
+    pub token: lexer::Token,
+    pub targets: Vec<Rc<RefCell<dyn Expression>>>,
+}
+
+/// `scrie a, " ", b`, which prints its arguments with no separator.
+#[derive(Debug)]
+pub struct WriteStatement {
+    pub token: lexer::Token,
+    pub arguments: Vec<Rc<RefCell<dyn Expression>>>,
+}
+
 #[derive(Debug)]
 pub struct ReturnStatement {
     pub token: lexer::Token,
@@ -51,15 +143,97 @@ pub struct ExpressionStatement {
 #[derive(Debug)]
 pub struct IntegralLiteral {
     pub token: lexer::Token,
-    pub value: i64,
+    pub value: BigInt,
+}
+
+#[derive(Debug)]
+pub struct RealLiteral {
+    pub token: lexer::Token,
+    pub value: f64,
+}
+
+#[derive(Debug)]
+pub struct StringLiteral {
+    pub token: lexer::Token,
+    pub value: String,
+}
+
+#[derive(Debug)]
+pub struct BooleanLiteral {
+    pub token: lexer::Token,
+    pub value: bool,
+}
+
+#[derive(Debug)]
+pub struct PrefixExpression {
+    pub token: lexer::Token,
+    pub operator: String,
+    pub right: Option<Rc<RefCell<dyn Expression>>>,
+}
+
+/// `[value]`, the integer part (floor) of `value`.
+#[derive(Debug)]
+pub struct IntegerPartExpression {
+    pub token: lexer::Token,
+    pub value: Option<Rc<RefCell<dyn Expression>>>,
+}
+
+#[derive(Debug)]
+pub struct ArrayLiteral {
+    pub token: lexer::Token,
+    pub elements: Vec<Rc<RefCell<dyn Expression>>>,
+}
+
+#[derive(Debug)]
+pub struct MapLiteral {
+    pub token: lexer::Token,
+    pub pairs: Vec<MapPair>,
+}
+
+#[derive(Debug)]
+pub struct IndexExpression {
+    pub token: lexer::Token,
+    pub left: Option<Rc<RefCell<dyn Expression>>>,
+    pub index: Option<Rc<RefCell<dyn Expression>>>,
+}
+
+/// `functie(a, b) { ... }`. `name` is set for the declaration form
+/// `functie f(a, b) { ... }` so the body can refer to itself.
+#[derive(Debug)]
+pub struct FunctionLiteral {
+    pub token: lexer::Token,
+    pub name: Option<String>,
+    pub parameters: Vec<Identifier>,
+    pub body: Rc<BlockStatement>,
+}
+
+#[derive(Debug)]
+pub struct CallExpression {
+    pub token: lexer::Token,
+    pub function: Option<Rc<RefCell<dyn Expression>>>,
+    pub arguments: Vec<Rc<RefCell<dyn Expression>>>,
+}
+
+#[derive(Debug)]
+pub struct InfixExpression {
+    pub token: lexer::Token,
+    pub left: Option<Rc<RefCell<dyn Expression>>>,
+    pub operator: String,
+    pub right: Option<Rc<RefCell<dyn Expression>>>,
 }
 
 impl Node for Program {
     fn token_literal(&self) -> Option<String> {
         self.statements
             .first()
-            .map(|s| s.as_ref().borrow().token_literal())
-            .flatten()
+            .and_then(|s| s.as_ref().borrow().token_literal())
+    }
+
+    fn span(&self) -> lexer::Span {
+        self.statements
+            .first()
+            .map(|s| s.as_ref().borrow().span())
+            .unwrap_or_default()
     }
 
     fn string(&self) -> String {
@@ -76,6 +250,10 @@ impl Node for Identifier {
         Some(self.token.literal.clone())
     }
 
+    fn span(&self) -> lexer::Span {
+        self.token.span
+    }
+
     fn string(&self) -> String {
         self.value.clone()
     }
@@ -83,6 +261,10 @@ impl Node for Identifier {
 
 impl Expression for Identifier {
     fn expression_node(&self) {}
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
 }
 
 impl Node for LetStatement {
@@ -90,16 +272,24 @@ impl Node for LetStatement {
         Some(self.token.literal.clone())
     }
 
+    fn span(&self) -> lexer::Span {
+        self.token.span
+    }
+
     fn string(&self) -> String {
+        if self.token.token_type == lexer::TokenType::Function {
+            return optional_string(&self.value);
+        }
+
         let mut out = String::new();
         out.push_str(&self.token.literal);
-        out.push_str(" ");
+        out.push(' ');
         out.push_str(&self.name.as_ref().unwrap().value);
         out.push_str(" = ");
         if let Some(value) = &self.value {
             out.push_str(&value.as_ref().borrow().string());
         }
-        out.push_str(";");
+        out.push(';');
         out
     }
 }
@@ -112,24 +302,30 @@ impl Statement for LetStatement {
     }
 }
 
-impl Node for ReturnStatement {
+impl Node for AssignStatement {
     fn token_literal(&self) -> Option<String> {
         Some(self.token.literal.clone())
     }
 
+    fn span(&self) -> lexer::Span {
+        self.name.token.span
+    }
+
     fn string(&self) -> String {
         let mut out = String::new();
+        out.push_str(&self.name.value);
+        out.push(' ');
         out.push_str(&self.token.literal);
-        out.push_str(" ");
-        if let Some(value) = &self.return_value {
+        out.push(' ');
+        if let Some(value) = &self.value {
             out.push_str(&value.as_ref().borrow().string());
         }
-        out.push_str(";");
+        out.push(';');
         out
     }
 }
 
-impl Statement for ReturnStatement {
+impl Statement for AssignStatement {
     fn statement_node(&self) {}
 
     fn as_any(&self) -> &dyn std::any::Any {
@@ -137,21 +333,30 @@ impl Statement for ReturnStatement {
     }
 }
 
-impl Node for ExpressionStatement {
+impl Node for IndexAssignStatement {
     fn token_literal(&self) -> Option<String> {
         Some(self.token.literal.clone())
     }
 
+    fn span(&self) -> lexer::Span {
+        self.target.as_ref().borrow().span()
+    }
+
     fn string(&self) -> String {
-        if let Some(expression) = &self.expression {
-            expression.as_ref().borrow().string()
-        } else {
-            String::new()
+        let mut out = String::new();
+        out.push_str(&self.target.as_ref().borrow().string());
+        out.push(' ');
+        out.push_str(&self.token.literal);
+        out.push(' ');
+        if let Some(value) = &self.value {
+            out.push_str(&value.as_ref().borrow().string());
         }
+        out.push(';');
+        out
     }
 }
 
-impl Statement for ExpressionStatement {
+impl Statement for IndexAssignStatement {
     fn statement_node(&self) {}
 
     fn as_any(&self) -> &dyn std::any::Any {
@@ -159,16 +364,658 @@ impl Statement for ExpressionStatement {
     }
 }
 
-impl Node for IntegralLiteral {
+impl Node for BlockStatement {
     fn token_literal(&self) -> Option<String> {
         Some(self.token.literal.clone())
     }
 
+    fn span(&self) -> lexer::Span {
+        self.token.span
+    }
+
     fn string(&self) -> String {
-        self.token.literal.clone()
+        let statements: Vec<String> = self
+            .statements
+            .iter()
+            .map(|s| s.as_ref().borrow().string())
+            .collect();
+        let mut out = String::new();
+        out.push_str("{ ");
+        for statement in statements {
+            out.push_str(&statement);
+            out.push(' ');
+        }
+        out.push('}');
+        out
     }
 }
 
-impl Expression for IntegralLiteral {
-    fn expression_node(&self) {}
+impl Statement for BlockStatement {
+    fn statement_node(&self) {}
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl Node for IfStatement {
+    fn token_literal(&self) -> Option<String> {
+        Some(self.token.literal.clone())
+    }
+
+    fn span(&self) -> lexer::Span {
+        self.token.span
+    }
+
+    fn string(&self) -> String {
+        let mut out = String::new();
+        out.push_str(&self.token.literal);
+        out.push(' ');
+        out.push_str(&optional_string(&self.condition));
+        out.push(' ');
+        out.push_str(&self.consequence.string());
+        if let Some(alternative) = &self.alternative {
+            out.push_str(" altfel ");
+            out.push_str(&alternative.string());
+        }
+        out
+    }
+}
+
+impl Statement for IfStatement {
+    fn statement_node(&self) {}
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl Node for WhileStatement {
+    fn token_literal(&self) -> Option<String> {
+        Some(self.token.literal.clone())
+    }
+
+    fn span(&self) -> lexer::Span {
+        self.token.span
+    }
+
+    fn string(&self) -> String {
+        let mut out = String::new();
+        out.push_str(&self.token.literal);
+        out.push(' ');
+        out.push_str(&optional_string(&self.condition));
+        out.push(' ');
+        out.push_str(&self.body.string());
+        out
+    }
+}
+
+impl Statement for WhileStatement {
+    fn statement_node(&self) {}
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl Node for ForStatement {
+    fn token_literal(&self) -> Option<String> {
+        Some(self.token.literal.clone())
+    }
+
+    fn span(&self) -> lexer::Span {
+        self.token.span
+    }
+
+    fn string(&self) -> String {
+        let mut out = String::new();
+        out.push_str(&self.token.literal);
+        out.push(' ');
+        out.push_str(&self.counter.value);
+        out.push_str(" ← ");
+        out.push_str(&optional_string(&self.start));
+        out.push_str(", ");
+        out.push_str(&optional_string(&self.end));
+        if let Some(step) = &self.step {
+            out.push_str(", ");
+            out.push_str(&step.as_ref().borrow().string());
+        }
+        out.push(' ');
+        out.push_str(&self.body.string());
+        out
+    }
+}
+
+impl Statement for ForStatement {
+    fn statement_node(&self) {}
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl Node for RepeatStatement {
+    fn token_literal(&self) -> Option<String> {
+        Some(self.token.literal.clone())
+    }
+
+    fn span(&self) -> lexer::Span {
+        self.token.span
+    }
+
+    fn string(&self) -> String {
+        let mut out = String::new();
+        out.push_str(&self.token.literal);
+        out.push(' ');
+        out.push_str(&self.body.string());
+        out.push_str(" pana cand ");
+        out.push_str(&optional_string(&self.condition));
+        out.push(';');
+        out
+    }
+}
+
+impl Statement for RepeatStatement {
+    fn statement_node(&self) {}
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl Node for ReadStatement {
+    fn token_literal(&self) -> Option<String> {
+        Some(self.token.literal.clone())
+    }
+
+    fn span(&self) -> lexer::Span {
+        self.token.span
+    }
+
+    fn string(&self) -> String {
+        let targets: Vec<String> = self
+            .targets
+            .iter()
+            .map(|t| t.as_ref().borrow().string())
+            .collect();
+        let mut out = String::new();
+        out.push_str(&self.token.literal);
+        out.push(' ');
+        out.push_str(&targets.join(", "));
+        out.push(';');
+        out
+    }
+}
+
+impl Statement for ReadStatement {
+    fn statement_node(&self) {}
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl Node for WriteStatement {
+    fn token_literal(&self) -> Option<String> {
+        Some(self.token.literal.clone())
+    }
+
+    fn span(&self) -> lexer::Span {
+        self.token.span
+    }
+
+    fn string(&self) -> String {
+        let arguments: Vec<String> = self
+            .arguments
+            .iter()
+            .map(|a| a.as_ref().borrow().string())
+            .collect();
+        let mut out = String::new();
+        out.push_str(&self.token.literal);
+        out.push(' ');
+        out.push_str(&arguments.join(", "));
+        out.push(';');
+        out
+    }
+}
+
+impl Statement for WriteStatement {
+    fn statement_node(&self) {}
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl Node for ReturnStatement {
+    fn token_literal(&self) -> Option<String> {
+        Some(self.token.literal.clone())
+    }
+
+    fn span(&self) -> lexer::Span {
+        self.token.span
+    }
+
+    fn string(&self) -> String {
+        let mut out = String::new();
+        out.push_str(&self.token.literal);
+        out.push(' ');
+        if let Some(value) = &self.return_value {
+            out.push_str(&value.as_ref().borrow().string());
+        }
+        out.push(';');
+        out
+    }
+}
+
+impl Statement for ReturnStatement {
+    fn statement_node(&self) {}
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl Node for ExpressionStatement {
+    fn token_literal(&self) -> Option<String> {
+        Some(self.token.literal.clone())
+    }
+
+    fn span(&self) -> lexer::Span {
+        self.token.span
+    }
+
+    fn string(&self) -> String {
+        if let Some(expression) = &self.expression {
+            expression.as_ref().borrow().string()
+        } else {
+            String::new()
+        }
+    }
+}
+
+impl Statement for ExpressionStatement {
+    fn statement_node(&self) {}
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl Node for IntegralLiteral {
+    fn token_literal(&self) -> Option<String> {
+        Some(self.token.literal.clone())
+    }
+
+    fn span(&self) -> lexer::Span {
+        self.token.span
+    }
+
+    fn string(&self) -> String {
+        self.token.literal.clone()
+    }
+}
+
+impl Expression for IntegralLiteral {
+    fn expression_node(&self) {}
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl Node for RealLiteral {
+    fn token_literal(&self) -> Option<String> {
+        Some(self.token.literal.clone())
+    }
+
+    fn span(&self) -> lexer::Span {
+        self.token.span
+    }
+
+    fn string(&self) -> String {
+        self.token.literal.clone()
+    }
+}
+
+impl Expression for RealLiteral {
+    fn expression_node(&self) {}
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl Node for StringLiteral {
+    fn token_literal(&self) -> Option<String> {
+        Some(self.token.literal.clone())
+    }
+
+    fn span(&self) -> lexer::Span {
+        self.token.span
+    }
+
+    fn string(&self) -> String {
+        self.token.literal.clone()
+    }
+}
+
+impl Expression for StringLiteral {
+    fn expression_node(&self) {}
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl Node for BooleanLiteral {
+    fn token_literal(&self) -> Option<String> {
+        Some(self.token.literal.clone())
+    }
+
+    fn span(&self) -> lexer::Span {
+        self.token.span
+    }
+
+    fn string(&self) -> String {
+        self.token.literal.clone()
+    }
+}
+
+impl Expression for BooleanLiteral {
+    fn expression_node(&self) {}
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl Node for PrefixExpression {
+    fn token_literal(&self) -> Option<String> {
+        Some(self.token.literal.clone())
+    }
+
+    fn span(&self) -> lexer::Span {
+        self.token.span
+    }
+
+    fn string(&self) -> String {
+        let mut out = String::new();
+        out.push('(');
+        out.push_str(&self.operator);
+        if let Some(right) = &self.right {
+            out.push_str(&right.as_ref().borrow().string());
+        }
+        out.push(')');
+        out
+    }
+}
+
+impl Expression for PrefixExpression {
+    fn expression_node(&self) {}
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl Node for IntegerPartExpression {
+    fn token_literal(&self) -> Option<String> {
+        Some(self.token.literal.clone())
+    }
+
+    fn span(&self) -> lexer::Span {
+        self.token.span
+    }
+
+    fn string(&self) -> String {
+        let mut out = String::new();
+        out.push('[');
+        if let Some(value) = &self.value {
+            out.push_str(&value.as_ref().borrow().string());
+        }
+        out.push(']');
+        out
+    }
+}
+
+impl Expression for IntegerPartExpression {
+    fn expression_node(&self) {}
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl Node for ArrayLiteral {
+    fn token_literal(&self) -> Option<String> {
+        Some(self.token.literal.clone())
+    }
+
+    fn span(&self) -> lexer::Span {
+        self.token.span
+    }
+
+    fn string(&self) -> String {
+        let elements: Vec<String> = self
+            .elements
+            .iter()
+            .map(|e| e.as_ref().borrow().string())
+            .collect();
+        let mut out = String::new();
+        out.push('[');
+        out.push_str(&elements.join(", "));
+        if elements.len() == 1 {
+            out.push(',');
+        }
+        out.push(']');
+        out
+    }
+}
+
+impl Expression for ArrayLiteral {
+    fn expression_node(&self) {}
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl Node for MapLiteral {
+    fn token_literal(&self) -> Option<String> {
+        Some(self.token.literal.clone())
+    }
+
+    fn span(&self) -> lexer::Span {
+        self.token.span
+    }
+
+    fn string(&self) -> String {
+        let pairs: Vec<String> = self
+            .pairs
+            .iter()
+            .map(|(key, value)| {
+                format!(
+                    "{}: {}",
+                    key.as_ref().borrow().string(),
+                    value.as_ref().borrow().string()
+                )
+            })
+            .collect();
+        let mut out = String::new();
+        out.push('{');
+        out.push_str(&pairs.join(", "));
+        out.push('}');
+        out
+    }
+}
+
+impl Expression for MapLiteral {
+    fn expression_node(&self) {}
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl Node for IndexExpression {
+    fn token_literal(&self) -> Option<String> {
+        Some(self.token.literal.clone())
+    }
+
+    fn span(&self) -> lexer::Span {
+        self.token.span
+    }
+
+    fn string(&self) -> String {
+        let mut out = String::new();
+        out.push('(');
+        if let Some(left) = &self.left {
+            out.push_str(&left.as_ref().borrow().string());
+        }
+        out.push('[');
+        if let Some(index) = &self.index {
+            out.push_str(&index.as_ref().borrow().string());
+        }
+        out.push_str("])");
+        out
+    }
+}
+
+impl Expression for IndexExpression {
+    fn expression_node(&self) {}
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl Node for FunctionLiteral {
+    fn token_literal(&self) -> Option<String> {
+        Some(self.token.literal.clone())
+    }
+
+    fn span(&self) -> lexer::Span {
+        self.token.span
+    }
+
+    fn string(&self) -> String {
+        let parameters: Vec<String> = self.parameters.iter().map(|p| p.string()).collect();
+        let mut out = String::new();
+        out.push_str(&self.token.literal);
+        if let Some(name) = &self.name {
+            out.push(' ');
+            out.push_str(name);
+        }
+        out.push('(');
+        out.push_str(&parameters.join(", "));
+        out.push_str(") ");
+        out.push_str(&self.body.string());
+        out
+    }
+}
+
+impl Expression for FunctionLiteral {
+    fn expression_node(&self) {}
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl Node for CallExpression {
+    fn token_literal(&self) -> Option<String> {
+        Some(self.token.literal.clone())
+    }
+
+    fn span(&self) -> lexer::Span {
+        self.token.span
+    }
+
+    fn string(&self) -> String {
+        let arguments: Vec<String> = self
+            .arguments
+            .iter()
+            .map(|a| a.as_ref().borrow().string())
+            .collect();
+        let mut out = String::new();
+        if let Some(function) = &self.function {
+            out.push_str(&function.as_ref().borrow().string());
+        }
+        out.push('(');
+        out.push_str(&arguments.join(", "));
+        out.push(')');
+        out
+    }
+}
+
+impl Expression for CallExpression {
+    fn expression_node(&self) {}
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl Node for InfixExpression {
+    fn token_literal(&self) -> Option<String> {
+        Some(self.token.literal.clone())
+    }
+
+    fn span(&self) -> lexer::Span {
+        self.token.span
+    }
+
+    fn string(&self) -> String {
+        let mut out = String::new();
+        out.push('(');
+        if let Some(left) = &self.left {
+            out.push_str(&left.as_ref().borrow().string());
+        }
+        out.push(' ');
+        out.push_str(&self.operator);
+        out.push(' ');
+        if let Some(right) = &self.right {
+            out.push_str(&right.as_ref().borrow().string());
+        }
+        out.push(')');
+        out
+    }
+}
+
+impl Expression for InfixExpression {
+    fn expression_node(&self) {}
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+fn optional_string(expression: &Option<Rc<RefCell<dyn Expression>>>) -> String {
+    expression
+        .as_ref()
+        .map(|e| e.as_ref().borrow().string())
+        .unwrap_or_default()
+}
+
+/// Drops the parentheses [`InfixExpression::string`] puts around a whole
+/// expression, so `(i <= n)` reads as `i <= n`.
+pub fn strip_parentheses(condition: &str) -> &str {
+    let Some(inner) = condition
+        .strip_prefix('(')
+        .and_then(|rest| rest.strip_suffix(')'))
+    else {
+        return condition;
+    };
+
+    let mut depth = 0;
+    for c in inner.chars() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return condition,
+            ')' => depth -= 1,
+            _ => {}
+        }
+    }
+    inner
 }
@@ -0,0 +1,448 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+use std::str::FromStr;
+
+#[cfg(test)]
+mod tests;
+
+/// An integer of any size, for the values integer arithmetic overflows
+/// `i64` into.
+///
+/// It is stored as a sign and the base 2^32 digits of its magnitude, least
+/// significant first, without leading zero digits. Zero has no digits and
+/// is never negative, so equal values are equal structurally.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct BigInt {
+    negative: bool,
+    digits: Vec<u32>,
+}
+
+/// A string that is not an optionally signed run of decimal digits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseBigIntError;
+
+const BASE: u64 = 1 << 32;
+
+/// The largest power of ten that fits in a digit, for converting from and
+/// to decimal nine digits at a time.
+const DECIMAL_BASE: u32 = 1_000_000_000;
+
+impl BigInt {
+    fn new(negative: bool, mut digits: Vec<u32>) -> Self {
+        while digits.last() == Some(&0) {
+            digits.pop();
+        }
+        Self {
+            negative: negative && !digits.is_empty(),
+            digits,
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.digits.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn abs(&self) -> Self {
+        Self::new(false, self.digits.clone())
+    }
+
+    /// How many bits the magnitude takes up, without leading zeros.
+    pub fn bit_len(&self) -> usize {
+        match self.digits.last() {
+            Some(last) => self.digits.len() * 32 - last.leading_zeros() as usize,
+            None => 0,
+        }
+    }
+
+    /// How many bytes the magnitude takes up.
+    pub fn byte_len(&self) -> usize {
+        self.digits.len() * 4
+    }
+
+    /// How many bytes [`BigInt::byte_len`] gives for a magnitude of `bits`
+    /// bits.
+    pub fn bytes_for(bits: usize) -> usize {
+        bits.div_ceil(32) * 4
+    }
+
+    /// The value as an `i64`, if it is in range.
+    pub fn to_i64(&self) -> Option<i64> {
+        let magnitude = match self.digits[..] {
+            [] => 0,
+            [low] => u64::from(low),
+            [low, high] => u64::from(high) << 32 | u64::from(low),
+            _ => return None,
+        };
+        match self.negative {
+            true => 0i64.checked_sub_unsigned(magnitude),
+            false => i64::try_from(magnitude).ok(),
+        }
+    }
+
+    /// The nearest `f64`, which is infinite for values out of its range.
+    pub fn to_f64(&self) -> f64 {
+        let magnitude = self
+            .digits
+            .iter()
+            .rev()
+            .fold(0.0, |value, &digit| value * BASE as f64 + f64::from(digit));
+        match self.negative {
+            true => -magnitude,
+            false => magnitude,
+        }
+    }
+
+    /// `value` truncated toward zero, or `None` if it is infinite or NaN.
+    pub fn from_f64(value: f64) -> Option<Self> {
+        if !value.is_finite() {
+            return None;
+        }
+        let value = value.trunc();
+        if value.abs() < i64::MAX as f64 {
+            return Some(Self::from(value as i64));
+        }
+
+        // A float this large is an integer: its 53-bit mantissa shifted
+        // left by the exponent.
+        let bits = value.to_bits();
+        let exponent = ((bits >> 52) & 0x7ff) as usize - 1075;
+        let mantissa = bits & ((1 << 52) - 1) | 1 << 52;
+        let magnitude = Self::from(mantissa as i64).shl(exponent);
+        Some(Self::new(value < 0.0, magnitude.digits))
+    }
+
+    /// The quotient truncated toward zero and the remainder, which takes the
+    /// sign of `self`, or `None` when dividing by zero.
+    pub fn div_rem(&self, divisor: &Self) -> Option<(Self, Self)> {
+        if divisor.is_zero() {
+            return None;
+        }
+        let (quotient, remainder) = div_rem_magnitudes(&self.digits, &divisor.digits);
+        Some((
+            Self::new(self.negative != divisor.negative, quotient),
+            Self::new(self.negative, remainder),
+        ))
+    }
+
+    pub fn pow(&self, mut exponent: u32) -> Self {
+        let mut result = Self::from(1);
+        let mut base = self.clone();
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = &result * &base;
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                base = &base * &base;
+            }
+        }
+        result
+    }
+
+    fn shl(&self, bits: usize) -> Self {
+        let mut digits = vec![0; bits / 32];
+        let shift = bits % 32;
+        let mut carry = 0;
+        for &digit in &self.digits {
+            let shifted = u64::from(digit) << shift | carry;
+            digits.push(shifted as u32);
+            carry = shifted >> 32;
+        }
+        digits.push(carry as u32);
+        Self::new(self.negative, digits)
+    }
+}
+
+fn cmp_magnitudes(a: &[u32], b: &[u32]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let mut sum = Vec::with_capacity(long.len() + 1);
+    let mut carry = 0;
+    for (i, &digit) in long.iter().enumerate() {
+        let total = u64::from(digit) + u64::from(short.get(i).copied().unwrap_or(0)) + carry;
+        sum.push(total as u32);
+        carry = total >> 32;
+    }
+    sum.push(carry as u32);
+    sum
+}
+
+/// `a - b`, where `a` is at least `b`.
+fn sub_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut difference = Vec::with_capacity(a.len());
+    let mut borrow = 0;
+    for (i, &digit) in a.iter().enumerate() {
+        let subtrahend = i64::from(b.get(i).copied().unwrap_or(0)) + borrow;
+        let mut total = i64::from(digit) - subtrahend;
+        borrow = 0;
+        if total < 0 {
+            total += BASE as i64;
+            borrow = 1;
+        }
+        difference.push(total as u32);
+    }
+    difference
+}
+
+/// Below this many digits in the shorter factor, the schoolbook product is
+/// quicker than splitting.
+const KARATSUBA_THRESHOLD: usize = 32;
+
+/// Karatsuba's product, so that factors near the allocation limit multiply
+/// in well under a second: with `a = a1·B^m + a0` and `b` alike, three
+/// half-size products make up the four the schoolbook way needs.
+fn mul_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    if short.len() < KARATSUBA_THRESHOLD {
+        return schoolbook_mul(long, short);
+    }
+
+    let mut product = vec![0u32; a.len() + b.len() + 1];
+    let m = long.len() / 2;
+    let (long_low, long_high) = long.split_at(m);
+    if short.len() <= m {
+        add_at(&mut product, &mul_magnitudes(long_low, short), 0);
+        add_at(&mut product, &mul_magnitudes(long_high, short), m);
+        return product;
+    }
+    let (short_low, short_high) = short.split_at(m);
+
+    let low = mul_magnitudes(long_low, short_low);
+    let high = mul_magnitudes(long_high, short_high);
+    let middle = mul_magnitudes(
+        &add_magnitudes(long_low, long_high),
+        &add_magnitudes(short_low, short_high),
+    );
+    let middle = sub_magnitudes(&sub_magnitudes(&middle, &low), &high);
+    add_at(&mut product, &low, 0);
+    add_at(&mut product, &middle, m);
+    add_at(&mut product, &high, 2 * m);
+    product
+}
+
+/// Adds `value` shifted left by `offset` digits into `sum`, which is long
+/// enough to hold the result.
+fn add_at(sum: &mut [u32], value: &[u32], offset: usize) {
+    let mut carry = 0;
+    for (i, digit) in sum[offset..].iter_mut().enumerate() {
+        if i >= value.len() && carry == 0 {
+            break;
+        }
+        let total = u64::from(*digit) + u64::from(value.get(i).copied().unwrap_or(0)) + carry;
+        *digit = total as u32;
+        carry = total >> 32;
+    }
+}
+
+fn schoolbook_mul(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut product = vec![0u32; a.len() + b.len()];
+    for (i, &x) in a.iter().enumerate() {
+        let mut carry = 0;
+        for (j, &y) in b.iter().enumerate() {
+            let total = u64::from(x) * u64::from(y) + u64::from(product[i + j]) + carry;
+            product[i + j] = total as u32;
+            carry = total >> 32;
+        }
+        product[i + b.len()] = carry as u32;
+    }
+    product
+}
+
+/// Divides in place by a one-digit `divisor`, returning the remainder.
+fn div_rem_digit(digits: &mut [u32], divisor: u32) -> u32 {
+    let mut remainder = 0;
+    for digit in digits.iter_mut().rev() {
+        let dividend = remainder << 32 | u64::from(*digit);
+        *digit = (dividend / u64::from(divisor)) as u32;
+        remainder = dividend % u64::from(divisor);
+    }
+    remainder as u32
+}
+
+/// Long division one bit at a time, which is quick enough for the sizes
+/// programs reach.
+fn div_rem_magnitudes(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if let [divisor] = b {
+        let mut quotient = a.to_vec();
+        let remainder = div_rem_digit(&mut quotient, *divisor);
+        return (quotient, vec![remainder]);
+    }
+    if cmp_magnitudes(a, b) == Ordering::Less {
+        return (Vec::new(), a.to_vec());
+    }
+
+    let mut quotient = vec![0u32; a.len()];
+    let mut remainder: Vec<u32> = Vec::with_capacity(b.len() + 1);
+    for bit in (0..a.len() * 32).rev() {
+        // remainder = remainder * 2 + the next bit of `a`
+        let mut carry = (a[bit / 32] >> (bit % 32)) & 1;
+        for digit in remainder.iter_mut() {
+            let next = *digit >> 31;
+            *digit = *digit << 1 | carry;
+            carry = next;
+        }
+        if carry != 0 {
+            remainder.push(carry);
+        }
+
+        if cmp_magnitudes(&remainder, b) != Ordering::Less {
+            remainder = sub_magnitudes(&remainder, b);
+            while remainder.last() == Some(&0) {
+                remainder.pop();
+            }
+            quotient[bit / 32] |= 1 << (bit % 32);
+        }
+    }
+    (quotient, remainder)
+}
+
+impl From<i64> for BigInt {
+    fn from(value: i64) -> Self {
+        let magnitude = value.unsigned_abs();
+        Self::new(value < 0, vec![magnitude as u32, (magnitude >> 32) as u32])
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => cmp_magnitudes(&self.digits, &other.digits),
+            (true, true) => cmp_magnitudes(&other.digits, &self.digits),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Add for &BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::new(self.negative, add_magnitudes(&self.digits, &other.digits));
+        }
+        match cmp_magnitudes(&self.digits, &other.digits) {
+            Ordering::Less => {
+                BigInt::new(other.negative, sub_magnitudes(&other.digits, &self.digits))
+            }
+            _ => BigInt::new(self.negative, sub_magnitudes(&self.digits, &other.digits)),
+        }
+    }
+}
+
+impl Sub for &BigInt {
+    type Output = BigInt;
+
+    fn sub(self, other: &BigInt) -> BigInt {
+        self + &-other
+    }
+}
+
+impl Mul for &BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: &BigInt) -> BigInt {
+        BigInt::new(
+            self.negative != other.negative,
+            mul_magnitudes(&self.digits, &other.digits),
+        )
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::new(!self.negative, self.digits.clone())
+    }
+}
+
+impl Neg for BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::new(!self.negative, self.digits)
+    }
+}
+
+impl FromStr for BigInt {
+    type Err = ParseBigIntError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let (negative, decimal) = match text.as_bytes().first() {
+            Some(b'-') => (true, &text[1..]),
+            Some(b'+') => (false, &text[1..]),
+            _ => (false, text),
+        };
+        if decimal.is_empty() || !decimal.bytes().all(|byte| byte.is_ascii_digit()) {
+            return Err(ParseBigIntError);
+        }
+
+        let mut digits: Vec<u32> = Vec::new();
+        let mut start = 0;
+        let mut end = match decimal.len() % 9 {
+            0 => 9,
+            len => len,
+        };
+        while start < decimal.len() {
+            // digits = digits * 10^9 + the next nine decimal digits
+            let mut carry = decimal[start..end]
+                .parse::<u64>()
+                .map_err(|_| ParseBigIntError)?;
+            for digit in digits.iter_mut() {
+                let total = u64::from(*digit) * u64::from(DECIMAL_BASE) + carry;
+                *digit = total as u32;
+                carry = total >> 32;
+            }
+            if carry != 0 {
+                digits.push(carry as u32);
+            }
+            start = end;
+            end += 9;
+        }
+        Ok(Self::new(negative, digits))
+    }
+}
+
+impl fmt::Display for ParseBigIntError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid digit found in string")
+    }
+}
+
+impl std::error::Error for ParseBigIntError {}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut magnitude = self.digits.clone();
+        let mut chunks = Vec::new();
+        while !magnitude.is_empty() {
+            chunks.push(div_rem_digit(&mut magnitude, DECIMAL_BASE));
+            while magnitude.last() == Some(&0) {
+                magnitude.pop();
+            }
+        }
+
+        let mut decimal = chunks.pop().unwrap_or(0).to_string();
+        for chunk in chunks.iter().rev() {
+            decimal.push_str(&format!("{:09}", chunk));
+        }
+        f.pad_integral(!self.negative, "", &decimal)
+    }
+}
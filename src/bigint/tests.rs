@@ -0,0 +1,154 @@
+use super::{BigInt, ParseBigIntError};
+
+fn big(text: &str) -> BigInt {
+    text.parse().unwrap()
+}
+
+#[test]
+fn parses_and_displays() {
+    let tests = [
+        "0",
+        "7",
+        "-42",
+        "4294967296",
+        "1000000000",
+        "-9223372036854775808",
+        "265252859812191058636308480000000",
+        "-100000000000000000000000000000000000000000000000001",
+    ];
+    for text in tests {
+        assert_eq!(big(text).to_string(), text);
+    }
+    assert_eq!(big("+0012").to_string(), "12");
+    assert_eq!(big("-0"), BigInt::default());
+    assert_eq!(format!("{:>5}", big("-12")), "  -12");
+
+    for text in ["", "-", "12a", "1.5", " 1"] {
+        assert_eq!(text.parse::<BigInt>(), Err(ParseBigIntError), "{:?}", text);
+    }
+}
+
+#[test]
+fn converts_to_and_from_i64() {
+    for value in [0, 1, -1, i64::MAX, i64::MIN, 1 << 32, -(1 << 40)] {
+        assert_eq!(BigInt::from(value).to_i64(), Some(value));
+        assert_eq!(BigInt::from(value).to_string(), value.to_string());
+    }
+    assert_eq!(big("9223372036854775808").to_i64(), None);
+    assert_eq!(big("-9223372036854775809").to_i64(), None);
+    assert_eq!(big("18446744073709551616").to_i64(), None);
+}
+
+#[test]
+fn converts_to_and_from_f64() {
+    assert_eq!(
+        big("-12345678901234567890").to_f64(),
+        -12345678901234567890.0
+    );
+    assert_eq!(BigInt::from_f64(-2.7), Some(BigInt::from(-2)));
+    assert_eq!(BigInt::from_f64(1e20), Some(big("100000000000000000000")));
+    assert_eq!(
+        BigInt::from_f64(-2f64.powi(70)),
+        Some(big("-1180591620717411303424"))
+    );
+    assert_eq!(BigInt::from_f64(f64::INFINITY), None);
+    assert_eq!(BigInt::from_f64(f64::NAN), None);
+}
+
+#[test]
+fn arithmetic() {
+    let a = big("123456789012345678901234567890");
+    let b = big("-987654321098765432109876543210");
+    assert_eq!((&a + &b).to_string(), "-864197532086419753208641975320");
+    assert_eq!((&a - &b).to_string(), "1111111110111111111011111111100");
+    assert_eq!((&b - &b), BigInt::default());
+    assert_eq!(
+        (&a * &b).to_string(),
+        "-121932631137021795226185032733622923332237463801111263526900"
+    );
+    assert_eq!((-&a).to_string(), "-123456789012345678901234567890");
+    assert_eq!(b.abs().to_string(), "987654321098765432109876543210");
+    assert_eq!(
+        BigInt::from(2).pow(100).to_string(),
+        "1267650600228229401496703205376"
+    );
+    assert_eq!(BigInt::from(-3).pow(3), BigInt::from(-27));
+    assert_eq!(a.pow(0), BigInt::from(1));
+}
+
+#[test]
+fn multiplies_large_factors() {
+    // 2^n - 1 squared is 2^2n - 2^(n+1) + 1, across the sizes where the
+    // product is split and where it is not.
+    let one = BigInt::from(1);
+    for bits in [31 * 32, 32 * 32, 100 * 32 + 7, 1000 * 32 + 3] {
+        let power = |bits| one.shl(bits);
+        let factor = &power(bits) - &one;
+        let expected = &(&power(2 * bits) - &power(bits + 1)) + &one;
+        assert_eq!(&factor * &factor, expected, "{} bits", bits);
+        assert_eq!((&factor * &factor).bit_len(), 2 * bits);
+    }
+
+    let a = big("123456789012345678901234567890").pow(40);
+    let b = big("-987654321098765432109876543210").pow(25);
+    assert_eq!(&a * &b, &b * &a);
+    assert_eq!(
+        (&a * &b).div_rem(&b).unwrap(),
+        (a.clone(), BigInt::default())
+    );
+}
+
+#[test]
+fn divides_like_cpp() {
+    let tests = [
+        ("100000000000000000000", "7", "14285714285714285714", "2"),
+        ("-100000000000000000000", "7", "-14285714285714285714", "-2"),
+        (
+            "100000000000000000000",
+            "-30000000000000000000",
+            "-3",
+            "10000000000000000000",
+        ),
+        (
+            "1267650600228229401496703205376",
+            "18446744073709551616",
+            "68719476736",
+            "0",
+        ),
+        ("5", "100000000000000000000", "0", "5"),
+    ];
+    for (dividend, divisor, quotient, remainder) in tests {
+        let (q, r) = big(dividend).div_rem(&big(divisor)).unwrap();
+        assert_eq!(
+            (q.to_string(), r.to_string()),
+            (quotient.to_string(), remainder.to_string()),
+            "{} / {}",
+            dividend,
+            divisor
+        );
+    }
+    assert_eq!(big("1").div_rem(&BigInt::default()), None);
+}
+
+#[test]
+fn compares() {
+    let mut values = [
+        big("100000000000000000000"),
+        big("-5"),
+        big("0"),
+        big("-100000000000000000000"),
+        big("18446744073709551616"),
+    ];
+    values.sort();
+    let values: Vec<String> = values.iter().map(BigInt::to_string).collect();
+    assert_eq!(
+        values,
+        [
+            "-100000000000000000000",
+            "-5",
+            "0",
+            "18446744073709551616",
+            "100000000000000000000"
+        ]
+    );
+}
@@ -0,0 +1,14 @@
+use std::io;
+use std::thread;
+
+use library::evaluator::STACK_SIZE;
+
+/// A debug adapter for editors, speaking the Debug Adapter Protocol over
+/// stdin and stdout. Programs run on a thread with room for deep recursion.
+fn main() -> io::Result<()> {
+    thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(|| library::dap::run(io::stdin().lock(), io::stdout()))?
+        .join()
+        .unwrap_or_else(|_| Err(io::Error::other("the debug adapter panicked")))
+}
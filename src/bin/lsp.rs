@@ -0,0 +1,6 @@
+use std::io;
+
+/// A language server for editors, speaking JSON-RPC over stdin and stdout.
+fn main() -> io::Result<()> {
+    library::lsp::run(io::stdin().lock(), io::stdout().lock())
+}
@@ -1,5 +1,22 @@
+use library::ast::Program;
+use library::debugger::{Breakpoints, Controller, Debugger, Reason, Resume, Stop};
+use library::evaluator::{Evaluator, RuntimeError, STACK_SIZE};
+use library::highlight;
 use library::lexer::{Lexer, Token, TokenType};
-use std::io::{self, Write};
+use library::lint::{self, Config};
+use library::messages::Lang;
+use library::parser::Parser;
+use std::cell::RefCell;
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+use std::process;
+use std::rc::Rc;
+use std::thread;
+
+/// Read when `lint` is not given `--config`, if it exists.
+const LINT_CONFIG: &str = "lint.conf";
 
 fn start() -> std::io::Result<Vec<Token>> {
     print!(">> ");
@@ -20,7 +37,7 @@ fn start() -> std::io::Result<Vec<Token>> {
     Ok(vec)
 }
 
-fn main() {
+fn repl() {
     loop {
         match start() {
             Ok(tokens) => {
@@ -34,3 +51,262 @@ fn main() {
         }
     }
 }
+
+/// Parses `source`, read from `file`, or shows where it is wrong.
+fn parse(file: &str, source: &str, lang: Lang) -> Result<Program, String> {
+    let mut parser = Parser::new(Lexer::new(source.to_string()));
+    let program = parser.parse_program();
+    match program {
+        Some(program) if parser.parse_errors().is_empty() => Ok(program),
+        _ => {
+            let errors: Vec<String> = parser
+                .parse_errors()
+                .iter()
+                .map(|error| error.render(source, lang))
+                .collect();
+            Err(format!("{}: {}", file, errors.join("\n\n")))
+        }
+    }
+}
+
+/// `lint <file> [--config <path>]`: prints one line per warning and exits
+/// with 1 if there were any.
+fn lint(args: &[String], lang: Lang) -> Result<bool, String> {
+    let mut file = None;
+    let mut config_path = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--config" => {
+                let path = args.next().ok_or("--config needs a path")?;
+                config_path = Some(path.clone());
+            }
+            _ if file.is_none() => file = Some(arg.clone()),
+            _ => return Err(format!("unexpected argument `{}`", arg)),
+        }
+    }
+    let file = file.ok_or("usage: src lint <file> [--config <path>] [--lang ro|en]")?;
+
+    let config_path = config_path.or_else(|| {
+        Path::new(LINT_CONFIG)
+            .exists()
+            .then(|| LINT_CONFIG.to_string())
+    });
+    let config = match config_path {
+        Some(path) => {
+            let text = fs::read_to_string(&path).map_err(|e| format!("{}: {}", path, e))?;
+            Config::parse(&text).map_err(|e| format!("{}: {}", path, e))?
+        }
+        None => Config::new(),
+    };
+
+    let source = fs::read_to_string(&file).map_err(|e| format!("{}: {}", file, e))?;
+    let program = parse(&file, &source, lang)?;
+
+    let warnings = lint::lint(&program, &config);
+    for warning in &warnings {
+        println!("{}:{}", file, warning.render(lang));
+    }
+    Ok(!warnings.is_empty())
+}
+
+/// `highlight <file> [--html]`: prints the file coloured for the terminal,
+/// or as an HTML fragment.
+fn highlight(args: &[String]) -> Result<(), String> {
+    let (file, as_html) = match args {
+        [file] => (file, false),
+        [file, flag] | [flag, file] if flag == "--html" => (file, true),
+        _ => return Err("usage: src highlight <file> [--html]".to_string()),
+    };
+    let source = fs::read_to_string(file).map_err(|e| format!("{}: {}", file, e))?;
+    if as_html {
+        println!("{}", highlight::html(&source));
+    } else {
+        print!("{}", highlight::ansi(&source));
+    }
+    Ok(())
+}
+
+const DEBUG_HELP: &str = "\
+c, continue      run to the next breakpoint
+s, step          go to the next line, into a function call
+n, next          go to the next line, over function calls
+o, out           run until the current function returns
+b, break <line>  set a breakpoint
+d, delete <line> remove a breakpoint
+p, print <name>  show a variable
+v, vars          show the variables in scope
+bt, stack        show the call stack
+l, list          show the source around the current line
+q, quit          stop the program";
+
+/// Asks the user what to do at each stop, on standard input.
+struct Terminal {
+    source: Vec<String>,
+}
+
+impl Terminal {
+    fn list(&self, line: usize) {
+        let first = line.saturating_sub(3).max(1);
+        let last = (line + 3).min(self.source.len());
+        for number in first..=last {
+            let marker = if number == line { "->" } else { "  " };
+            println!("{} {:>3} {}", marker, number, self.source[number - 1]);
+        }
+    }
+}
+
+impl Controller for Terminal {
+    fn stopped(&mut self, stop: &Stop<'_>, breakpoints: &mut Breakpoints) -> Resume {
+        let line = stop.span.line;
+        if stop.reason == Reason::Breakpoint {
+            println!("breakpoint at line {}", line);
+        }
+        let text = self.source.get(line - 1).map_or("", |text| text.trim());
+        println!("{:>3} {}", line, text);
+
+        loop {
+            print!("(debug) ");
+            let _ = io::stdout().flush();
+            let mut command = String::new();
+            match io::stdin().read_line(&mut command) {
+                Ok(0) | Err(_) => return Resume::Stop,
+                Ok(_) => {}
+            }
+            let mut words = command.split_whitespace();
+            let (command, argument) = (words.next().unwrap_or(""), words.next());
+            let line_argument = argument.and_then(|line| line.parse::<usize>().ok());
+
+            match (command, line_argument) {
+                ("c" | "continue", _) => return Resume::Continue,
+                ("s" | "step", _) => return Resume::StepInto,
+                ("n" | "next", _) => return Resume::StepOver,
+                ("o" | "out", _) => return Resume::StepOut,
+                ("q" | "quit", _) => return Resume::Stop,
+                ("b" | "break", Some(line)) => match breakpoints.insert(line) {
+                    Some(line) => println!("breakpoint set at line {}", line),
+                    None => println!("no statement at or after line {}", line),
+                },
+                ("d" | "delete", Some(line)) => {
+                    if !breakpoints.remove(line) {
+                        println!("no breakpoint at line {}", line);
+                    }
+                }
+                ("p" | "print", _) if argument.is_some() => {
+                    let name = argument.unwrap_or_default();
+                    match stop.value(name) {
+                        Some(value) => println!("{} = {}", name, value),
+                        None => println!("{} is not defined", name),
+                    }
+                }
+                ("v" | "vars", _) => {
+                    for (name, value) in stop.locals() {
+                        println!("{} = {}", name, value);
+                    }
+                    if !stop.frames.is_empty() {
+                        println!("globals:");
+                        for (name, value) in stop.globals() {
+                            println!("  {} = {}", name, value);
+                        }
+                    }
+                }
+                ("bt" | "stack", _) => {
+                    for entry in stop.call_stack() {
+                        let function = entry.function.as_deref().unwrap_or("<main>");
+                        println!("{} at line {}", function, entry.line);
+                    }
+                }
+                ("l" | "list", _) => self.list(line),
+                ("h" | "help", _) => println!("{}", DEBUG_HELP),
+                _ => println!("unknown command, type `help` for a list"),
+            }
+        }
+    }
+}
+
+/// `debug <file> [--input <path>]`: runs the file under the debugger,
+/// stopping before the first statement. `citeste` reads from `--input` if
+/// given, otherwise from standard input along with the commands.
+fn debug(args: &[String], lang: Lang) -> Result<(), String> {
+    let (file, input) = match args {
+        [file] => (file, None),
+        [file, flag, path] if flag == "--input" => (file, Some(path)),
+        _ => return Err("usage: src debug <file> [--input <path>] [--lang ro|en]".to_string()),
+    };
+    let source = fs::read_to_string(file).map_err(|e| format!("{}: {}", file, e))?;
+    let program = parse(file, &source, lang)?;
+
+    let input: Box<dyn BufRead> = match input {
+        Some(path) => {
+            let file = fs::File::open(path).map_err(|e| format!("{}: {}", path, e))?;
+            Box::new(BufReader::new(file))
+        }
+        // A one-byte buffer leaves the rest of each line in the standard
+        // input's own buffer, where the debugger's commands are read from.
+        None => Box::new(BufReader::with_capacity(1, io::stdin())),
+    };
+    let terminal = Terminal {
+        source: source.lines().map(str::to_string).collect(),
+    };
+    let debugger = Rc::new(RefCell::new(Debugger::new(&program, terminal)));
+    let mut evaluator = Evaluator::new()
+        .stack_size(STACK_SIZE)
+        .hook(debugger)
+        .with_io(input, Box::new(io::stdout()));
+    match evaluator.eval_program(&program) {
+        Ok(_) => println!("program finished"),
+        Err(failure) if failure.error == RuntimeError::Interrupted => println!("program stopped"),
+        Err(failure) => println!("{}: {}", file, failure.render(&source, lang)),
+    }
+    Ok(())
+}
+
+/// Takes `--lang ro|en`, which any subcommand accepts, out of `args`.
+fn take_lang(args: &mut Vec<String>) -> Result<Lang, String> {
+    let Some(i) = args.iter().position(|arg| arg == "--lang") else {
+        return Ok(Lang::default());
+    };
+    let lang = args.get(i + 1).ok_or("--lang needs ro or en")?.parse()?;
+    args.drain(i..i + 2);
+    Ok(lang)
+}
+
+fn main() {
+    let cli = thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(cli)
+        .expect("failed to start the interpreter thread");
+    if cli.join().is_err() {
+        process::exit(101);
+    }
+}
+
+fn cli() {
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let lang = take_lang(&mut args).unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        process::exit(2);
+    });
+    match args.first().map(String::as_str) {
+        Some("lint") => match lint(&args[1..], lang) {
+            Ok(found) => process::exit(i32::from(found)),
+            Err(message) => {
+                eprintln!("{}", message);
+                process::exit(2);
+            }
+        },
+        Some("debug") => {
+            if let Err(message) = debug(&args[1..], lang) {
+                eprintln!("{}", message);
+                process::exit(2);
+            }
+        }
+        Some("highlight") => {
+            if let Err(message) = highlight(&args[1..]) {
+                eprintln!("{}", message);
+                process::exit(2);
+            }
+        }
+        _ => repl(),
+    }
+}
@@ -0,0 +1,514 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::rc::Rc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::bigint::BigInt;
+use crate::evaluator::RuntimeError;
+use crate::object::Object;
+
+#[cfg(test)]
+mod tests;
+
+/// How many arguments a builtin takes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arity {
+    Exactly(usize),
+    Between(usize, usize),
+    AtLeast(usize),
+}
+
+/// The body of a builtin. It gets the arguments, already checked against
+/// the builtin's [`Arity`], and the random number generator `random` uses.
+pub type Function = fn(&[Object], &mut Rng) -> Result<Object, RuntimeError>;
+
+/// A lower bound on the bytes a builtin's result takes, from its
+/// arguments, to check against the allocation limit before calling it.
+type Size = fn(&[Object]) -> usize;
+
+/// A [`Function`] that may capture state, as registered ones can.
+type Closure = dyn Fn(&[Object], &mut Rng) -> Result<Object, RuntimeError>;
+
+/// A function every program can call without defining it.
+#[derive(Clone)]
+pub struct Builtin {
+    pub name: Rc<str>,
+    pub arity: Arity,
+    function: Rc<Closure>,
+    size: Option<Size>,
+}
+
+/// The builtins a program can call, by name, and the state they share.
+#[derive(Clone)]
+pub struct Builtins {
+    functions: BTreeMap<Rc<str>, Builtin>,
+    rng: Rng,
+}
+
+/// A small pseudo-random number generator (SplitMix64). Seeding it makes
+/// `random` repeat the same numbers, e.g. in tests.
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+const STANDARD: [(&str, Arity, Function); 17] = [
+    ("len", Arity::Exactly(1), len),
+    ("push", Arity::Exactly(2), push),
+    ("keys", Arity::Exactly(1), keys),
+    ("values", Arity::Exactly(1), values),
+    ("abs", Arity::Exactly(1), abs),
+    ("min", Arity::AtLeast(1), min),
+    ("max", Arity::AtLeast(1), max),
+    ("sqrt", Arity::Exactly(1), sqrt),
+    ("pow", Arity::Exactly(2), pow),
+    ("floor", Arity::Exactly(1), floor),
+    ("ceil", Arity::Exactly(1), ceil),
+    ("str", Arity::Exactly(1), str),
+    ("int", Arity::Exactly(1), int),
+    ("real", Arity::Exactly(1), real),
+    ("random", Arity::Between(0, 2), random),
+    ("sort", Arity::Exactly(1), sort),
+    ("seed", Arity::Exactly(1), seed),
+];
+
+/// The standard builtins whose results can grow much larger than their
+/// arguments.
+const SIZES: [(&str, Size); 1] = [("pow", pow_size)];
+
+/// The names of the standard builtins. The compiler numbers them in this
+/// order.
+pub const BUILTINS: [&str; STANDARD.len()] = {
+    let mut names = [""; STANDARD.len()];
+    let mut i = 0;
+    while i < STANDARD.len() {
+        names[i] = STANDARD[i].0;
+        i += 1;
+    }
+    names
+};
+
+/// The arity of the standard builtin `name`.
+pub fn arity(name: &str) -> Option<Arity> {
+    STANDARD
+        .iter()
+        .find(|(builtin, _, _)| *builtin == name)
+        .map(|(_, arity, _)| *arity)
+}
+
+impl Arity {
+    pub fn accepts(&self, count: usize) -> bool {
+        match *self {
+            Arity::Exactly(n) => count == n,
+            Arity::Between(min, max) => (min..=max).contains(&count),
+            Arity::AtLeast(min) => count >= min,
+        }
+    }
+
+    /// The accepted count closest to `count`, to report when it is not
+    /// accepted.
+    pub fn nearest(&self, count: usize) -> usize {
+        match *self {
+            Arity::Exactly(n) => n,
+            Arity::Between(min, max) => count.clamp(min, max),
+            Arity::AtLeast(min) => count.max(min),
+        }
+    }
+}
+
+impl Builtins {
+    /// The standard builtins, with `random` seeded from the clock.
+    pub fn new() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos() as u64);
+        let mut builtins = Self {
+            functions: BTreeMap::new(),
+            rng: Rng::new(nanos),
+        };
+        for (name, arity, function) in STANDARD {
+            builtins.register(name, arity, function);
+        }
+        for (name, size) in SIZES {
+            if let Some(builtin) = builtins.functions.get_mut(name) {
+                builtin.size = Some(size);
+            }
+        }
+        builtins
+    }
+
+    /// Makes `name` call `function`, replacing any builtin of that name.
+    pub fn register(
+        &mut self,
+        name: impl Into<Rc<str>>,
+        arity: Arity,
+        function: impl Fn(&[Object], &mut Rng) -> Result<Object, RuntimeError> + 'static,
+    ) {
+        let name = name.into();
+        let builtin = Builtin {
+            name: Rc::clone(&name),
+            arity,
+            function: Rc::new(function),
+            size: None,
+        };
+        self.functions.insert(name, builtin);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Builtin> {
+        self.functions.get(name)
+    }
+
+    /// A lower bound on the bytes calling `name` with `arguments` returns,
+    /// or 0 if there is nothing to say about it.
+    pub fn result_size(&self, name: &str, arguments: &[Object]) -> usize {
+        match self.functions.get(name) {
+            Some(Builtin {
+                arity,
+                size: Some(size),
+                ..
+            }) if arity.accepts(arguments.len()) => size(arguments),
+            _ => 0,
+        }
+    }
+
+    /// Restarts the numbers `random` returns from `seed`.
+    pub fn seed(&mut self, seed: u64) {
+        self.rng = Rng::new(seed);
+    }
+
+    /// Calls the builtin `name`, which must have been registered.
+    pub fn call(&mut self, name: &str, arguments: &[Object]) -> Result<Object, RuntimeError> {
+        let builtin = &self.functions[name];
+        if !builtin.arity.accepts(arguments.len()) {
+            return Err(RuntimeError::WrongArgumentCount {
+                function: name.to_string(),
+                expected: builtin.arity.nearest(arguments.len()),
+                got: arguments.len(),
+            });
+        }
+        (builtin.function)(arguments, &mut self.rng)
+    }
+}
+
+impl Default for Builtins {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Builtins {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.functions.keys()).finish()
+    }
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `[0, 1)`.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// A number in `[0, bound)`. `bound` must not be zero.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        ((u128::from(self.next_u64()) * u128::from(bound)) >> 64) as u64
+    }
+}
+
+fn invalid(function: &str, argument: &Object) -> RuntimeError {
+    RuntimeError::InvalidArgument {
+        function: function.to_string(),
+        argument: argument.type_name(),
+    }
+}
+
+fn invalid_value(function: &str, value: impl fmt::Display) -> RuntimeError {
+    RuntimeError::InvalidValue {
+        function: function.to_string(),
+        value: value.to_string(),
+    }
+}
+
+fn integer(function: &str, argument: &Object) -> Result<i64, RuntimeError> {
+    match argument {
+        Object::Integer(value) => Ok(*value),
+        Object::BigInteger(_) => Err(invalid_value(function, argument)),
+        argument => Err(invalid(function, argument)),
+    }
+}
+
+fn number(function: &str, argument: &Object) -> Result<f64, RuntimeError> {
+    argument
+        .as_real()
+        .ok_or_else(|| invalid(function, argument))
+}
+
+/// `value`, a whole number, as an integer, if it is finite.
+fn to_integer(value: f64) -> Result<Object, RuntimeError> {
+    BigInt::from_f64(value)
+        .map(Object::from)
+        .ok_or(RuntimeError::IntegerOverflow)
+}
+
+/// Orders two numbers, exactly if both are integers.
+fn compare(a: &Object, b: &Object) -> Option<Ordering> {
+    match (a, b) {
+        (Object::Integer(a), Object::Integer(b)) => Some(a.cmp(b)),
+        (
+            Object::Integer(_) | Object::BigInteger(_),
+            Object::Integer(_) | Object::BigInteger(_),
+        ) => {
+            let a = BigInt::try_from(a.clone()).ok()?;
+            let b = BigInt::try_from(b.clone()).ok()?;
+            Some(a.cmp(&b))
+        }
+        _ => a.as_real().partial_cmp(&b.as_real()),
+    }
+}
+
+fn len(arguments: &[Object], _: &mut Rng) -> Result<Object, RuntimeError> {
+    match &arguments[0] {
+        Object::Array(elements) => Ok(Object::Integer(elements.borrow().len() as i64)),
+        Object::Map(pairs) => Ok(Object::Integer(pairs.borrow().len() as i64)),
+        Object::Str(value) => Ok(Object::Integer(value.chars().count() as i64)),
+        argument => Err(invalid("len", argument)),
+    }
+}
+
+fn push(arguments: &[Object], _: &mut Rng) -> Result<Object, RuntimeError> {
+    match &arguments[0] {
+        Object::Array(elements) => {
+            elements.borrow_mut().push(arguments[1].clone());
+            Ok(Object::Null)
+        }
+        argument => Err(invalid("push", argument)),
+    }
+}
+
+fn keys(arguments: &[Object], _: &mut Rng) -> Result<Object, RuntimeError> {
+    match &arguments[0] {
+        Object::Map(pairs) => Ok(Object::array(
+            pairs.borrow().keys().cloned().map(Object::from).collect(),
+        )),
+        argument => Err(invalid("keys", argument)),
+    }
+}
+
+fn values(arguments: &[Object], _: &mut Rng) -> Result<Object, RuntimeError> {
+    match &arguments[0] {
+        Object::Map(pairs) => Ok(Object::array(pairs.borrow().values().cloned().collect())),
+        argument => Err(invalid("values", argument)),
+    }
+}
+
+fn abs(arguments: &[Object], _: &mut Rng) -> Result<Object, RuntimeError> {
+    match &arguments[0] {
+        Object::Integer(value) => Ok(value
+            .checked_abs()
+            .map(Object::Integer)
+            .unwrap_or_else(|| Object::from(BigInt::from(*value).abs()))),
+        Object::BigInteger(value) => Ok(Object::BigInteger(value.abs())),
+        Object::Real(value) => Ok(Object::Real(value.abs())),
+        argument => Err(invalid("abs", argument)),
+    }
+}
+
+/// The numbers `min` and `max` choose from: their arguments, or the
+/// elements of an array if that is the only one.
+fn candidates(function: &str, arguments: &[Object]) -> Result<Vec<Object>, RuntimeError> {
+    let candidates = match arguments {
+        [Object::Array(elements)] => elements.borrow().clone(),
+        arguments => arguments.to_vec(),
+    };
+    if candidates.is_empty() {
+        return Err(invalid_value(function, "[]"));
+    }
+    for candidate in &candidates {
+        number(function, candidate)?;
+    }
+    Ok(candidates)
+}
+
+fn min(arguments: &[Object], _: &mut Rng) -> Result<Object, RuntimeError> {
+    let candidates = candidates("min", arguments)?;
+    let least = candidates.into_iter().reduce(|least, candidate| {
+        match compare(&candidate, &least) == Some(Ordering::Less) {
+            true => candidate,
+            false => least,
+        }
+    });
+    Ok(least.unwrap_or(Object::Null))
+}
+
+fn max(arguments: &[Object], _: &mut Rng) -> Result<Object, RuntimeError> {
+    let candidates = candidates("max", arguments)?;
+    let greatest = candidates.into_iter().reduce(|greatest, candidate| {
+        match compare(&candidate, &greatest) == Some(Ordering::Greater) {
+            true => candidate,
+            false => greatest,
+        }
+    });
+    Ok(greatest.unwrap_or(Object::Null))
+}
+
+fn sqrt(arguments: &[Object], _: &mut Rng) -> Result<Object, RuntimeError> {
+    let value = number("sqrt", &arguments[0])?;
+    if value < 0.0 {
+        return Err(invalid_value("sqrt", &arguments[0]));
+    }
+    Ok(Object::Real(value.sqrt()))
+}
+
+/// An integer raised to a non-negative integer stays an integer; anything
+/// else is a real.
+fn pow(arguments: &[Object], _: &mut Rng) -> Result<Object, RuntimeError> {
+    match (&arguments[0], &arguments[1]) {
+        (Object::Integer(base), Object::Integer(exponent)) if *exponent >= 0 => {
+            let exponent = u32::try_from(*exponent).map_err(|_| RuntimeError::IntegerOverflow)?;
+            Ok(base
+                .checked_pow(exponent)
+                .map(Object::Integer)
+                .unwrap_or_else(|| Object::from(BigInt::from(*base).pow(exponent))))
+        }
+        (Object::BigInteger(base), Object::Integer(exponent)) if *exponent >= 0 => {
+            let exponent = u32::try_from(*exponent).map_err(|_| RuntimeError::IntegerOverflow)?;
+            Ok(Object::from(base.pow(exponent)))
+        }
+        (base, exponent) => {
+            let base = number("pow", base)?;
+            let exponent = number("pow", exponent)?;
+            Ok(Object::Real(base.powf(exponent)))
+        }
+    }
+}
+
+/// `base` to the power `exponent` has at least `exponent` times one bit
+/// less than `base`, and one more.
+fn pow_size(arguments: &[Object]) -> usize {
+    match (arguments[0].bit_len(), &arguments[1]) {
+        (Some(bits), Object::Integer(exponent)) if bits > 0 && *exponent > 0 => {
+            let exponent = usize::try_from(*exponent).unwrap_or(usize::MAX);
+            let bits = (bits - 1).saturating_mul(exponent).saturating_add(1);
+            BigInt::bytes_for(bits)
+        }
+        _ => 0,
+    }
+}
+
+fn floor(arguments: &[Object], _: &mut Rng) -> Result<Object, RuntimeError> {
+    match &arguments[0] {
+        integer @ (Object::Integer(_) | Object::BigInteger(_)) => Ok(integer.clone()),
+        Object::Real(value) => to_integer(value.floor()),
+        argument => Err(invalid("floor", argument)),
+    }
+}
+
+fn ceil(arguments: &[Object], _: &mut Rng) -> Result<Object, RuntimeError> {
+    match &arguments[0] {
+        integer @ (Object::Integer(_) | Object::BigInteger(_)) => Ok(integer.clone()),
+        Object::Real(value) => to_integer(value.ceil()),
+        argument => Err(invalid("ceil", argument)),
+    }
+}
+
+fn str(arguments: &[Object], _: &mut Rng) -> Result<Object, RuntimeError> {
+    Ok(Object::Str(arguments[0].to_string()))
+}
+
+/// Reals are truncated towards zero; strings are parsed.
+fn int(arguments: &[Object], _: &mut Rng) -> Result<Object, RuntimeError> {
+    match &arguments[0] {
+        integer @ (Object::Integer(_) | Object::BigInteger(_)) => Ok(integer.clone()),
+        Object::Real(value) => to_integer(value.trunc()),
+        Object::Str(text) => text
+            .trim()
+            .parse::<BigInt>()
+            .map(Object::from)
+            .map_err(|_| invalid_value("int", format!("{:?}", text))),
+        argument => Err(invalid("int", argument)),
+    }
+}
+
+fn real(arguments: &[Object], _: &mut Rng) -> Result<Object, RuntimeError> {
+    match &arguments[0] {
+        Object::Str(text) => text
+            .trim()
+            .parse()
+            .map(Object::Real)
+            .map_err(|_| invalid_value("real", format!("{:?}", text))),
+        argument => Ok(Object::Real(number("real", argument)?)),
+    }
+}
+
+/// `random()` is a real in `[0, 1)`, `random(n)` an integer in `[0, n)`
+/// and `random(a, b)` an integer in `[a, b]`.
+fn random(arguments: &[Object], rng: &mut Rng) -> Result<Object, RuntimeError> {
+    let (low, high) = match arguments {
+        [] => return Ok(Object::Real(rng.next_f64())),
+        [n] => (0, integer("random", n)? - 1),
+        [a, b] => (integer("random", a)?, integer("random", b)?),
+        _ => unreachable!("checked by the arity"),
+    };
+    if low > high {
+        let range = match arguments {
+            [n] => n.to_string(),
+            _ => format!("{}, {}", low, high),
+        };
+        return Err(invalid_value("random", range));
+    }
+    let span = high.abs_diff(low).wrapping_add(1);
+    let offset = match span {
+        0 => rng.next_u64(),
+        span => rng.below(span),
+    };
+    Ok(Object::Integer(low.wrapping_add_unsigned(offset)))
+}
+
+/// A sorted copy of an array of numbers or of strings.
+fn sort(arguments: &[Object], _: &mut Rng) -> Result<Object, RuntimeError> {
+    let Object::Array(elements) = &arguments[0] else {
+        return Err(invalid("sort", &arguments[0]));
+    };
+    let mut elements = elements.borrow().clone();
+    let is_string = |element: &Object| match element {
+        Object::Str(_) => Ok(true),
+        element if element.as_real().is_some() => Ok(false),
+        element => Err(invalid("sort", element)),
+    };
+    if let Some(first) = elements.first() {
+        let strings = is_string(first)?;
+        for element in &elements[1..] {
+            if is_string(element)? != strings {
+                return Err(RuntimeError::TypeMismatch {
+                    left: first.type_name(),
+                    operator: "<".to_string(),
+                    right: element.type_name(),
+                });
+            }
+        }
+    }
+    // NaN is unordered, so it goes after every other number to keep the
+    // order total.
+    let is_nan = |number: &Object| number.as_real().is_some_and(f64::is_nan);
+    elements.sort_by(|a, b| match (a, b) {
+        (Object::Str(a), Object::Str(b)) => a.cmp(b),
+        _ => compare(a, b).unwrap_or_else(|| is_nan(a).cmp(&is_nan(b))),
+    });
+    Ok(Object::array(elements))
+}
+
+/// `seed(n)` makes the numbers `random` returns from then on repeatable.
+fn seed(arguments: &[Object], rng: &mut Rng) -> Result<Object, RuntimeError> {
+    *rng = Rng::new(integer("seed", &arguments[0])? as u64);
+    Ok(Object::Null)
+}
@@ -0,0 +1,294 @@
+use crate::evaluator::{Evaluator, RuntimeError};
+use crate::object::Object;
+use crate::test_support::parse;
+
+use super::{Arity, Builtins, BUILTINS};
+
+fn eval_with(builtins: Builtins, input: &str) -> Result<String, RuntimeError> {
+    Evaluator::new()
+        .builtins(builtins)
+        .eval_program(&parse(input))
+        .map(|value| value.to_string())
+        .map_err(|failure| failure.error)
+}
+
+fn eval(input: &str) -> Result<String, RuntimeError> {
+    eval_with(Builtins::new(), input)
+}
+
+/// Checks each input evaluates to its expected value, or fails with the
+/// expected error message.
+fn check(tests: &[(&str, &str)]) {
+    for (input, expected) in tests {
+        let result = eval(input).unwrap_or_else(|error| error.to_string());
+        assert_eq!(result, *expected, "{}", input);
+    }
+}
+
+#[test]
+fn abs() {
+    check(&[
+        ("abs(-5)", "5"),
+        ("abs(3)", "3"),
+        ("abs(-2.5)", "2.5"),
+        ("abs(\"x\")", "argument to `abs` not supported, got STRING"),
+        ("abs(-9223372036854775807 - 1)", "9223372036854775808"),
+        ("abs(-100000000000000000000)", "100000000000000000000"),
+    ]);
+}
+
+#[test]
+fn min() {
+    check(&[
+        ("min(3, 1, 2)", "1"),
+        ("min(2, 1.5)", "1.5"),
+        ("min([4, 2, 8,])", "2"),
+        ("min(7)", "7"),
+        ("min([])", "invalid argument to `min`: []"),
+        (
+            "min(1, true)",
+            "argument to `min` not supported, got BOOLEAN",
+        ),
+        ("min()", "wrong number of arguments to `min`: got=0, want=1"),
+    ]);
+}
+
+#[test]
+fn max() {
+    check(&[
+        ("max(3, 1, 2)", "3"),
+        ("max(2, 2.5)", "2.5"),
+        ("max([4, 9, 8,])", "9"),
+        ("let v = [1,]; v[0] ← -1; max(v)", "-1"),
+        (
+            "max(\"a\", 1)",
+            "argument to `max` not supported, got STRING",
+        ),
+    ]);
+}
+
+#[test]
+fn sqrt() {
+    check(&[
+        ("sqrt(16)", "4"),
+        ("sqrt(2.25)", "1.5"),
+        ("sqrt(-1)", "invalid argument to `sqrt`: -1"),
+        ("sqrt([1,])", "argument to `sqrt` not supported, got ARRAY"),
+    ]);
+}
+
+#[test]
+fn pow() {
+    check(&[
+        ("pow(2, 10)", "1024"),
+        ("pow(2, -1)", "0.5"),
+        ("pow(4, 0.5)", "2"),
+        ("pow(2.5, 2)", "6.25"),
+        ("pow(10, 19)", "10000000000000000000"),
+        ("pow(-3, 41)", "-36472996377170786403"),
+        (
+            "pow(pow(2, 64), 2)",
+            "340282366920938463463374607431768211456",
+        ),
+        ("pow(2, 4294967296)", "integer overflow"),
+        (
+            "pow(2)",
+            "wrong number of arguments to `pow`: got=1, want=2",
+        ),
+        (
+            "pow(2, \"3\")",
+            "argument to `pow` not supported, got STRING",
+        ),
+    ]);
+}
+
+#[test]
+fn floor() {
+    check(&[
+        ("floor(2.7)", "2"),
+        ("floor(-2.5)", "-3"),
+        ("floor(5)", "5"),
+        ("floor(pow(2, 70.0))", "1180591620717411303424"),
+        ("floor(100000000000000000000)", "100000000000000000000"),
+        ("floor(pow(10, 400.0))", "integer overflow"),
+        (
+            "floor(\"1\")",
+            "argument to `floor` not supported, got STRING",
+        ),
+    ]);
+}
+
+#[test]
+fn ceil() {
+    check(&[
+        ("ceil(2.1)", "3"),
+        ("ceil(-2.5)", "-2"),
+        ("ceil(5)", "5"),
+        (
+            "ceil(true)",
+            "argument to `ceil` not supported, got BOOLEAN",
+        ),
+    ]);
+}
+
+#[test]
+fn len() {
+    check(&[
+        ("len(\"țară\")", "4"),
+        ("len([1, 2, 3])", "3"),
+        ("len({1: 2})", "1"),
+        ("len(1)", "argument to `len` not supported, got INTEGER"),
+    ]);
+}
+
+#[test]
+fn str() {
+    check(&[
+        ("str(12) + \"3\"", "123"),
+        ("str(1.5)", "1.5"),
+        ("str([1, true])", "[1, true]"),
+        ("str()", "wrong number of arguments to `str`: got=0, want=1"),
+    ]);
+}
+
+#[test]
+fn int() {
+    check(&[
+        ("int(2.9)", "2"),
+        ("int(-2.9)", "-2"),
+        ("int(\" 42 \") + 1", "43"),
+        ("int(7)", "7"),
+        (
+            "int(\"123456789012345678901234567890\")",
+            "123456789012345678901234567890",
+        ),
+        ("int(\"4x\")", "invalid argument to `int`: \"4x\""),
+        ("int(true)", "argument to `int` not supported, got BOOLEAN"),
+    ]);
+}
+
+#[test]
+fn real() {
+    check(&[
+        ("real(2) / 4", "0.5"),
+        ("real(\"1.25\")", "1.25"),
+        ("real(0.5)", "0.5"),
+        ("real(\"x\")", "invalid argument to `real`: \"x\""),
+        ("real([])", "argument to `real` not supported, got ARRAY"),
+    ]);
+}
+
+#[test]
+fn random() {
+    let mut builtins = Builtins::new();
+    builtins.seed(7);
+    let first = eval_with(builtins.clone(), "[random(), random(10), random(5, 6)]").unwrap();
+    let again = eval_with(builtins, "[random(), random(10), random(5, 6)]").unwrap();
+    assert_eq!(first, again);
+
+    let rolls = eval("let v = []; pentru i ← 1, 200 { push(v, random(1, 6)) }; v").unwrap();
+    let rolls: Vec<i64> = rolls
+        .trim_matches(|c| c == '[' || c == ']')
+        .split(", ")
+        .map(|roll| roll.parse().unwrap())
+        .collect();
+    assert!(rolls.iter().all(|roll| (1..=6).contains(roll)));
+    assert!((1..=6).all(|side| rolls.contains(&side)));
+
+    let real = eval("random()").unwrap().parse::<f64>().unwrap();
+    assert!((0.0..1.0).contains(&real));
+    assert_eq!(
+        eval("seed(3); let a = random(1000); seed(3); a == random(1000)"),
+        Ok("true".to_string())
+    );
+    check(&[
+        ("random(0)", "invalid argument to `random`: 0"),
+        (
+            "random(100000000000000000000)",
+            "invalid argument to `random`: 100000000000000000000",
+        ),
+        ("random(3, 1)", "invalid argument to `random`: 3, 1"),
+        (
+            "random(1.5)",
+            "argument to `random` not supported, got REAL",
+        ),
+        (
+            "random(1, 2, 3)",
+            "wrong number of arguments to `random`: got=3, want=2",
+        ),
+    ]);
+}
+
+#[test]
+fn sort() {
+    check(&[
+        ("sort([3, 1.5, 2])", "[1.5, 2, 3]"),
+        ("sort([\"pere\", \"mere\"])", "[mere, pere]"),
+        ("let v = [2, 1]; sort(v); v", "[2, 1]"),
+        ("sort([])", "[]"),
+        (
+            "sort([100000000000000000001, 100000000000000000000, 1])",
+            "[1, 100000000000000000000, 100000000000000000001]",
+        ),
+        ("sort([1, \"a\"])", "type mismatch: INTEGER < STRING"),
+        (
+            "sort([[1,],])",
+            "argument to `sort` not supported, got ARRAY",
+        ),
+        ("sort(1)", "argument to `sort` not supported, got INTEGER"),
+    ]);
+}
+
+#[test]
+fn sort_puts_nan_last() {
+    assert_eq!(
+        eval("sort([real(\"nan\"), 2, pow(-1, 0.5), 1.5])"),
+        Ok("[1.5, 2, NaN, NaN]".to_string())
+    );
+
+    // Enough elements that the standard sort checks its comparator.
+    let sorted = eval(
+        "let v = [real(\"nan\"),]
+         pentru i ← 1, 40 { push(v, (i * 17) % 41) }
+         sort(v)",
+    )
+    .unwrap();
+    let expected: Vec<String> = (1..=40).map(|i| i.to_string()).collect();
+    assert_eq!(sorted, format!("[{}, NaN]", expected.join(", ")));
+}
+
+#[test]
+fn registers_functions() {
+    let mut builtins = Builtins::new();
+    builtins.register(
+        "dublu",
+        Arity::Exactly(1),
+        |arguments, _| match &arguments[0] {
+            Object::Integer(value) => Ok(Object::Integer(value * 2)),
+            argument => Err(RuntimeError::InvalidArgument {
+                function: "dublu".to_string(),
+                argument: argument.type_name(),
+            }),
+        },
+    );
+    builtins.register("abs", Arity::Exactly(1), |_, _| Ok(Object::Integer(0)));
+
+    assert_eq!(
+        eval_with(builtins.clone(), "dublu(21)"),
+        Ok("42".to_string())
+    );
+    assert_eq!(eval_with(builtins.clone(), "abs(-1)"), Ok("0".to_string()));
+    assert_eq!(
+        eval_with(builtins, "dublu(1, 2)").unwrap_err().to_string(),
+        "wrong number of arguments to `dublu`: got=2, want=1"
+    );
+    assert!(eval("dublu(1)").is_err());
+}
+
+#[test]
+fn every_standard_builtin_is_registered() {
+    let builtins = Builtins::new();
+    for name in BUILTINS {
+        assert_eq!(builtins.get(name).map(|builtin| &*builtin.name), Some(name));
+    }
+}
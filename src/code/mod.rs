@@ -0,0 +1,215 @@
+use std::fmt::Write;
+
+#[cfg(test)]
+mod tests;
+
+/// A flat sequence of encoded instructions. Every instruction is one
+/// opcode byte followed by its operands, big-endian.
+pub type Instructions = Vec<u8>;
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Opcode {
+    Constant,
+    Pop,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    IntDiv,
+    Mod,
+    Equal,
+    NotEqual,
+    LessThan,
+    LessEqual,
+    GreaterThan,
+    GreaterEqual,
+    Minus,
+    Bang,
+    IntegerPart,
+    True,
+    False,
+    Null,
+    Jump,
+    JumpNotTruthy,
+    GetGlobal,
+    SetGlobal,
+    GetLocal,
+    SetLocal,
+    GetBuiltin,
+    GetFree,
+    CurrentClosure,
+    Closure,
+    Call,
+    ReturnValue,
+    Return,
+    Array,
+    Map,
+    Index,
+    SetIndex,
+    DupPair,
+    Write,
+    Read,
+    ForCondition,
+}
+
+const OPCODES: [Opcode; 41] = [
+    Opcode::Constant,
+    Opcode::Pop,
+    Opcode::Add,
+    Opcode::Sub,
+    Opcode::Mul,
+    Opcode::Div,
+    Opcode::IntDiv,
+    Opcode::Mod,
+    Opcode::Equal,
+    Opcode::NotEqual,
+    Opcode::LessThan,
+    Opcode::LessEqual,
+    Opcode::GreaterThan,
+    Opcode::GreaterEqual,
+    Opcode::Minus,
+    Opcode::Bang,
+    Opcode::IntegerPart,
+    Opcode::True,
+    Opcode::False,
+    Opcode::Null,
+    Opcode::Jump,
+    Opcode::JumpNotTruthy,
+    Opcode::GetGlobal,
+    Opcode::SetGlobal,
+    Opcode::GetLocal,
+    Opcode::SetLocal,
+    Opcode::GetBuiltin,
+    Opcode::GetFree,
+    Opcode::CurrentClosure,
+    Opcode::Closure,
+    Opcode::Call,
+    Opcode::ReturnValue,
+    Opcode::Return,
+    Opcode::Array,
+    Opcode::Map,
+    Opcode::Index,
+    Opcode::SetIndex,
+    Opcode::DupPair,
+    Opcode::Write,
+    Opcode::Read,
+    Opcode::ForCondition,
+];
+
+impl Opcode {
+    pub fn from_byte(byte: u8) -> Option<Opcode> {
+        OPCODES.get(byte as usize).copied()
+    }
+
+    /// The width in bytes of each of this opcode's operands.
+    pub fn operand_widths(self) -> &'static [usize] {
+        match self {
+            Opcode::Constant
+            | Opcode::Jump
+            | Opcode::JumpNotTruthy
+            | Opcode::GetGlobal
+            | Opcode::SetGlobal
+            | Opcode::Array
+            | Opcode::Map => &[2],
+            Opcode::GetLocal
+            | Opcode::SetLocal
+            | Opcode::GetBuiltin
+            | Opcode::GetFree
+            | Opcode::Call
+            | Opcode::Write => &[1],
+            Opcode::Closure => &[2, 1],
+            _ => &[],
+        }
+    }
+
+    /// The operator the evaluator uses for an arithmetic or comparison
+    /// opcode, so both engines share one implementation.
+    pub fn infix_operator(self) -> Option<&'static str> {
+        match self {
+            Opcode::Add => Some("+"),
+            Opcode::Sub => Some("-"),
+            Opcode::Mul => Some("*"),
+            Opcode::Div => Some("/"),
+            Opcode::IntDiv => Some("div"),
+            Opcode::Mod => Some("%"),
+            Opcode::Equal => Some("=="),
+            Opcode::NotEqual => Some("!="),
+            Opcode::LessThan => Some("<"),
+            Opcode::LessEqual => Some("<="),
+            Opcode::GreaterThan => Some(">"),
+            Opcode::GreaterEqual => Some(">="),
+            _ => None,
+        }
+    }
+
+    /// The inverse of [`Opcode::infix_operator`].
+    pub fn from_infix_operator(operator: &str) -> Option<Opcode> {
+        OPCODES
+            .iter()
+            .copied()
+            .find(|op| op.infix_operator() == Some(operator))
+    }
+}
+
+/// Encodes one instruction. Operands wider than their slot are truncated,
+/// so callers check limits beforehand.
+pub fn make(op: Opcode, operands: &[usize]) -> Instructions {
+    let widths = op.operand_widths();
+    let mut instruction = Vec::with_capacity(1 + widths.iter().sum::<usize>());
+    instruction.push(op as u8);
+
+    for (operand, width) in operands.iter().zip(widths) {
+        match width {
+            2 => instruction.extend_from_slice(&(*operand as u16).to_be_bytes()),
+            _ => instruction.push(*operand as u8),
+        }
+    }
+
+    instruction
+}
+
+/// Decodes the operands of `op` from the start of `instructions`, returning
+/// them along with the number of bytes read.
+pub fn read_operands(op: Opcode, instructions: &[u8]) -> (Vec<usize>, usize) {
+    let mut operands = Vec::new();
+    let mut offset = 0;
+
+    for width in op.operand_widths() {
+        match width {
+            2 => operands.push(read_u16(&instructions[offset..]) as usize),
+            _ => operands.push(instructions[offset] as usize),
+        }
+        offset += width;
+    }
+
+    (operands, offset)
+}
+
+pub fn read_u16(instructions: &[u8]) -> u16 {
+    u16::from_be_bytes([instructions[0], instructions[1]])
+}
+
+/// Renders instructions one per line as `offset Opcode operands`.
+pub fn disassemble(instructions: &[u8]) -> String {
+    let mut out = String::new();
+    let mut ip = 0;
+
+    while ip < instructions.len() {
+        let Some(op) = Opcode::from_byte(instructions[ip]) else {
+            let _ = writeln!(out, "{:04} ERROR: unknown opcode {}", ip, instructions[ip]);
+            ip += 1;
+            continue;
+        };
+
+        let (operands, read) = read_operands(op, &instructions[ip + 1..]);
+        let _ = write!(out, "{:04} {:?}", ip, op);
+        for operand in operands {
+            let _ = write!(out, " {}", operand);
+        }
+        out.push('\n');
+        ip += 1 + read;
+    }
+
+    out
+}
@@ -0,0 +1,72 @@
+use super::*;
+
+#[test]
+fn make_encodes_operands() {
+    let tests = [
+        (
+            Opcode::Constant,
+            vec![65534],
+            vec![Opcode::Constant as u8, 255, 254],
+        ),
+        (
+            Opcode::GetLocal,
+            vec![255],
+            vec![Opcode::GetLocal as u8, 255],
+        ),
+        (
+            Opcode::Closure,
+            vec![65534, 255],
+            vec![Opcode::Closure as u8, 255, 254, 255],
+        ),
+        (Opcode::Add, vec![], vec![Opcode::Add as u8]),
+    ];
+
+    for (op, operands, expected) in tests {
+        assert_eq!(make(op, &operands), expected, "{:?}", op);
+    }
+}
+
+#[test]
+fn read_operands_round_trips() {
+    let tests = [
+        (Opcode::Constant, vec![65535], 2),
+        (Opcode::GetLocal, vec![255], 1),
+        (Opcode::Closure, vec![65535, 255], 3),
+    ];
+
+    for (op, operands, bytes) in tests {
+        let instruction = make(op, &operands);
+        let (read, n) = read_operands(op, &instruction[1..]);
+        assert_eq!(n, bytes);
+        assert_eq!(read, operands);
+    }
+}
+
+#[test]
+fn opcodes_round_trip_through_bytes() {
+    for (byte, op) in OPCODES.iter().enumerate() {
+        assert_eq!(*op as u8, byte as u8);
+        assert_eq!(Opcode::from_byte(byte as u8), Some(*op));
+    }
+    assert_eq!(Opcode::from_byte(OPCODES.len() as u8), None);
+}
+
+#[test]
+fn disassemble_instructions() {
+    let instructions = [
+        make(Opcode::Add, &[]),
+        make(Opcode::GetLocal, &[1]),
+        make(Opcode::Constant, &[2]),
+        make(Opcode::Constant, &[65535]),
+        make(Opcode::Closure, &[65535, 255]),
+    ]
+    .concat();
+
+    let expected = "0000 Add
+0001 GetLocal 1
+0003 Constant 2
+0006 Constant 65535
+0009 Closure 65535 255
+";
+    assert_eq!(disassemble(&instructions), expected);
+}
@@ -0,0 +1,740 @@
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::rc::Rc;
+
+use crate::ast::{
+    ArrayLiteral, AssignStatement, BlockStatement, BooleanLiteral, CallExpression, Expression,
+    ExpressionStatement, ForStatement, Identifier, IfStatement, IndexAssignStatement,
+    IndexExpression, InfixExpression, IntegerPartExpression, IntegralLiteral, LetStatement,
+    MapLiteral, Node, PrefixExpression, Program, ReadStatement, RealLiteral, RepeatStatement,
+    ReturnStatement, Statement, StringLiteral, WhileStatement, WriteStatement,
+};
+
+use super::{CodegenError, Writer};
+use crate::builtins::BUILTINS;
+use crate::types::{callee, function_declaration, Analysis, Scope, Type};
+
+/// Translates a program to a C++ program reading `cin` and writing `cout`.
+/// Variables are declared up front with the types [`Analysis`] infers for
+/// them; top-level variables a function uses become globals.
+#[derive(Debug, Default)]
+pub struct Cpp {
+    one_based: bool,
+}
+
+const OR: u8 = 1;
+const AND: u8 = 2;
+const EQUALITY: u8 = 3;
+const RELATIONAL: u8 = 4;
+const ADDITIVE: u8 = 5;
+const MULTIPLICATIVE: u8 = 6;
+const PREFIX: u8 = 7;
+const POSTFIX: u8 = 8;
+const ATOM: u8 = 9;
+
+/// Functions a translation may rely on, emitted only when used, with the
+/// headers they need.
+const HELPERS: [(&str, &[&str], &str); 5] = [
+    (
+        "pow_",
+        &[],
+        "long long pow_(long long base, long long exponent) {
+    unsigned long long result = 1, factor = base;
+    for (; exponent > 0; exponent /= 2) {
+        if (exponent % 2 == 1) {
+            result *= factor;
+        }
+        factor *= factor;
+    }
+    return (long long)result;
+}",
+    ),
+    (
+        "str_",
+        &["charconv", "string"],
+        "string str_(double value) {
+    char text[400];
+    return string(text, to_chars(text, text + sizeof text, value, chars_format::fixed).ptr);
+}",
+    ),
+    (
+        "random_",
+        &["random"],
+        "mt19937_64 rng_(random_device{}());
+
+double random_() {
+    return uniform_real_distribution<double>(0, 1)(rng_);
+}
+
+long long random_(long long n) {
+    return uniform_int_distribution<long long>(0, n - 1)(rng_);
+}
+
+long long random_(long long low, long long high) {
+    return uniform_int_distribution<long long>(low, high)(rng_);
+}",
+    ),
+    (
+        "sorted_",
+        &["algorithm", "vector"],
+        "template <typename T>
+vector<T> sorted_(vector<T> values) {
+    sort(values.begin(), values.end());
+    return values;
+}",
+    ),
+    (
+        "extremes_",
+        &["algorithm", "vector"],
+        "template <typename T>
+T min_(const vector<T>& values) {
+    return *min_element(values.begin(), values.end());
+}
+
+template <typename T>
+T max_(const vector<T>& values) {
+    return *max_element(values.begin(), values.end());
+}",
+    ),
+];
+
+impl Cpp {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Translates `v[1]` to `v[0]`, for programs written for an evaluator
+    /// with one-based arrays.
+    pub fn one_based_arrays(mut self, one_based: bool) -> Self {
+        self.one_based = one_based;
+        self
+    }
+
+    pub fn generate(&self, program: &Program) -> Result<String, CodegenError> {
+        let analysis = Analysis::new(program);
+        let mut emitter = Emitter {
+            analysis: &analysis,
+            one_based: self.one_based,
+            scope: None,
+            writer: Writer::default(),
+            headers: BTreeSet::from(["iostream"]),
+            helpers: BTreeSet::new(),
+            boolalpha: false,
+        };
+
+        for (name, ty) in analysis.variables.iter() {
+            if analysis.shared.contains(name) {
+                let declaration = emitter.declaration(name, ty);
+                emitter.writer.line(&format!("{};", declaration));
+            }
+        }
+        if !analysis.shared.is_empty() {
+            emitter.writer.line("");
+        }
+
+        if analysis.functions.len() > 1 {
+            for index in 0..analysis.functions.len() {
+                let signature = emitter.signature(index);
+                emitter.writer.line(&format!("{};", signature));
+            }
+            emitter.writer.line("");
+        }
+        for index in 0..analysis.functions.len() {
+            emitter.function(index)?;
+            emitter.writer.line("");
+        }
+
+        emitter.scope = None;
+        emitter.writer.line("int main() {");
+        emitter.writer.indent += 1;
+        let variables: Vec<(String, Type)> = analysis
+            .variables
+            .iter()
+            .filter(|(name, _)| !analysis.shared.contains(name))
+            .cloned()
+            .collect();
+        emitter.declarations(&variables);
+        let start = emitter.writer.out.len();
+        for statement in &program.statements {
+            let statement = statement.as_ref().borrow();
+            if function_declaration(&*statement).is_none() {
+                emitter.statement(&*statement)?;
+            }
+        }
+        if emitter.boolalpha {
+            let indent = "    ".repeat(emitter.writer.indent);
+            let line = format!("{}cout << boolalpha;\n", indent);
+            emitter.writer.out.insert_str(start, &line);
+        }
+        emitter.writer.line("return 0;");
+        emitter.writer.indent -= 1;
+        emitter.writer.line("}");
+
+        for (name, headers, _) in HELPERS {
+            if emitter.helpers.contains(name) {
+                emitter.headers.extend(headers);
+            }
+        }
+        let mut out = String::new();
+        for header in &emitter.headers {
+            out.push_str(&format!("#include <{}>\n", header));
+        }
+        out.push_str("using namespace std;\n\n");
+        for (name, _, helper) in HELPERS {
+            if emitter.helpers.contains(name) {
+                out.push_str(&format!("{}\n\n", helper));
+            }
+        }
+        out.push_str(&emitter.writer.out);
+        Ok(out)
+    }
+}
+
+struct Emitter<'a> {
+    analysis: &'a Analysis,
+    one_based: bool,
+    scope: Scope,
+    writer: Writer,
+    headers: BTreeSet<&'static str>,
+    helpers: BTreeSet<&'static str>,
+    /// Whether a boolean is printed, so `cout` must spell out `true`.
+    boolalpha: bool,
+}
+
+impl Emitter<'_> {
+    fn type_name(&mut self, ty: &Type) -> String {
+        match ty {
+            Type::Unknown | Type::Integer => "long long".to_string(),
+            Type::Real => "double".to_string(),
+            Type::Boolean => "bool".to_string(),
+            Type::Str => {
+                self.headers.insert("string");
+                "string".to_string()
+            }
+            Type::Array(element) => {
+                self.headers.insert("vector");
+                format!("vector<{}>", self.type_name(element))
+            }
+            Type::Map(key, value) => {
+                self.headers.insert("map");
+                format!("map<{}, {}>", self.type_name(key), self.type_name(value))
+            }
+        }
+    }
+
+    fn declaration(&mut self, name: &str, ty: &Type) -> String {
+        format!("{} {}", self.type_name(ty), name)
+    }
+
+    fn declarations(&mut self, variables: &[(String, Type)]) {
+        for (name, ty) in variables {
+            let declaration = self.declaration(name, ty);
+            self.writer.line(&format!("{};", declaration));
+        }
+        if !variables.is_empty() {
+            self.writer.line("");
+        }
+    }
+
+    /// Arrays and maps are shared with the caller, as in the evaluator, so
+    /// they are passed by reference.
+    fn signature(&mut self, index: usize) -> String {
+        let function = &self.analysis.functions[index];
+        let returns = match &function.returns {
+            Some(ty) => self.type_name(ty),
+            None => "void".to_string(),
+        };
+        let parameters: Vec<String> = function
+            .parameters
+            .iter()
+            .map(|(name, ty)| match ty {
+                Type::Array(_) | Type::Map(..) => format!("{}& {}", self.type_name(ty), name),
+                _ => self.declaration(name, ty),
+            })
+            .collect();
+        format!("{} {}({})", returns, function.name, parameters.join(", "))
+    }
+
+    fn function(&mut self, index: usize) -> Result<(), CodegenError> {
+        self.scope = Some(index);
+        let signature = self.signature(index);
+        self.writer.line(&format!("{} {{", signature));
+        self.writer.indent += 1;
+        let function = &self.analysis.functions[index];
+        let locals: Vec<(String, Type)> = function.locals.iter().cloned().collect();
+        self.declarations(&locals);
+        self.statements(&function.body.statements)?;
+        self.writer.indent -= 1;
+        self.writer.line("}");
+        Ok(())
+    }
+
+    fn block(&mut self, header: &str, block: &BlockStatement) -> Result<(), CodegenError> {
+        match header {
+            "" => self.writer.line("{"),
+            header => self.writer.line(&format!("{} {{", header)),
+        }
+        self.writer.indent += 1;
+        self.statements(&block.statements)?;
+        self.writer.indent -= 1;
+        Ok(())
+    }
+
+    fn statements(
+        &mut self,
+        statements: &[Rc<RefCell<dyn Statement>>],
+    ) -> Result<(), CodegenError> {
+        for statement in statements {
+            self.statement(&*statement.as_ref().borrow())?;
+        }
+        Ok(())
+    }
+
+    fn statement(&mut self, statement: &dyn Statement) -> Result<(), CodegenError> {
+        let any = statement.as_any();
+
+        if let Some(statement) = any.downcast_ref::<ExpressionStatement>() {
+            if let Some(expression) = &statement.expression {
+                let expression = self.expression(&*expression.as_ref().borrow())?.0;
+                self.writer.line(&format!("{};", expression));
+            }
+        } else if let Some(let_statement) = any.downcast_ref::<LetStatement>() {
+            let (Some(name), Some(value)) = (&let_statement.name, &let_statement.value) else {
+                return Ok(());
+            };
+            if function_declaration(let_statement).is_some() {
+                return Err(CodegenError::Unsupported(statement.string()));
+            }
+            let value = self.expression(&*value.as_ref().borrow())?.0;
+            self.writer.line(&format!("{} = {};", name.value, value));
+        } else if let Some(statement) = any.downcast_ref::<AssignStatement>() {
+            let value = self.optional(&statement.value, OR)?;
+            let operator = assignment_operator(&statement.token.literal);
+            let name = &statement.name.value;
+            self.writer
+                .line(&format!("{} {} {};", name, operator, value));
+        } else if let Some(statement) = any.downcast_ref::<IndexAssignStatement>() {
+            let target = self.expression(&*statement.target.as_ref().borrow())?.0;
+            let value = self.optional(&statement.value, OR)?;
+            let operator = assignment_operator(&statement.token.literal);
+            self.writer
+                .line(&format!("{} {} {};", target, operator, value));
+        } else if let Some(statement) = any.downcast_ref::<ReturnStatement>() {
+            match (&statement.return_value, self.scope) {
+                (_, None) => self.writer.line("return 0;"),
+                (Some(value), _) => {
+                    let value = self.expression(&*value.as_ref().borrow())?.0;
+                    self.writer.line(&format!("return {};", value));
+                }
+                (None, _) => self.writer.line("return;"),
+            }
+        } else if let Some(block) = any.downcast_ref::<BlockStatement>() {
+            self.block("", block)?;
+            self.writer.line("}");
+        } else if let Some(statement) = any.downcast_ref::<IfStatement>() {
+            self.if_statement(statement, "")?;
+        } else if let Some(statement) = any.downcast_ref::<WhileStatement>() {
+            let condition = self.optional(&statement.condition, OR)?;
+            self.block(&format!("while ({})", condition), &statement.body)?;
+            self.writer.line("}");
+        } else if let Some(statement) = any.downcast_ref::<RepeatStatement>() {
+            self.block("do", &statement.body)?;
+            let condition = self.optional(&statement.condition, PREFIX)?;
+            self.writer.line(&format!("}} while (!{});", condition));
+        } else if let Some(statement) = any.downcast_ref::<ForStatement>() {
+            self.for_statement(statement)?;
+        } else if let Some(statement) = any.downcast_ref::<ReadStatement>() {
+            let mut line = "cin".to_string();
+            for target in &statement.targets {
+                line.push_str(" >> ");
+                line.push_str(&self.expression(&*target.as_ref().borrow())?.0);
+            }
+            self.writer.line(&format!("{};", line));
+        } else if let Some(statement) = any.downcast_ref::<WriteStatement>() {
+            let mut line = "cout".to_string();
+            for argument in &statement.arguments {
+                let argument = argument.as_ref().borrow();
+                line.push_str(" << ");
+                match self.analysis.type_of(self.scope, &*argument) {
+                    Type::Array(_) | Type::Map(..) => {
+                        return Err(CodegenError::Unsupported(statement.string()));
+                    }
+                    // `cout` rounds reals to six digits, while the
+                    // evaluator prints as many as tell the value apart.
+                    Type::Real => {
+                        self.helpers.insert("str_");
+                        let argument = self.operand(&*argument, OR)?;
+                        line.push_str(&format!("str_({})", argument));
+                        continue;
+                    }
+                    Type::Boolean => self.boolalpha = true,
+                    _ => {}
+                }
+                line.push_str(&self.operand(&*argument, ADDITIVE)?);
+            }
+            self.writer.line(&format!("{} << endl;", line));
+        } else {
+            return Err(CodegenError::Unsupported(statement.string()));
+        }
+        Ok(())
+    }
+
+    fn if_statement(&mut self, statement: &IfStatement, prefix: &str) -> Result<(), CodegenError> {
+        let condition = self.optional(&statement.condition, OR)?;
+        self.block(
+            &format!("{}if ({})", prefix, condition),
+            &statement.consequence,
+        )?;
+
+        let Some(alternative) = &statement.alternative else {
+            self.writer.line("}");
+            return Ok(());
+        };
+        if let [statement] = alternative.statements.as_slice() {
+            let statement = statement.as_ref().borrow();
+            if let Some(statement) = statement.as_any().downcast_ref::<IfStatement>() {
+                return self.if_statement(statement, "} else ");
+            }
+        }
+        self.block("} else", alternative)?;
+        self.writer.line("}");
+        Ok(())
+    }
+
+    /// `pentru i ← a, b, p` runs while `i <= b` for a positive step and
+    /// while `i >= b` for a negative one. When the sign of the step is
+    /// only known at run time, so is the comparison.
+    fn for_statement(&mut self, statement: &ForStatement) -> Result<(), CodegenError> {
+        let counter = &statement.counter.value;
+        let start = self.optional(&statement.start, OR)?;
+        let end = self.optional(&statement.end, RELATIONAL + 1)?;
+
+        let (condition, increment) = match &statement.step {
+            None => (format!("{} <= {}", counter, end), format!("{}++", counter)),
+            Some(step) => {
+                let step = step.as_ref().borrow();
+                let text = self.operand(&*step, OR)?;
+                match text.strip_prefix('-') {
+                    Some(magnitude) if is_negative_literal(&*step) => (
+                        format!("{} >= {}", counter, end),
+                        format!("{} -= {}", counter, magnitude),
+                    ),
+                    _ if is_literal(&*step) => (
+                        format!("{} <= {}", counter, end),
+                        format!("{} += {}", counter, text),
+                    ),
+                    _ => (
+                        format!(
+                            "{} > 0 ? {} <= {} : {} >= {}",
+                            self.operand(&*step, RELATIONAL + 1)?,
+                            counter,
+                            end,
+                            counter,
+                            end
+                        ),
+                        format!("{} += {}", counter, text),
+                    ),
+                }
+            }
+        };
+
+        let header = format!(
+            "for ({} = {}; {}; {})",
+            counter, start, condition, increment
+        );
+        self.block(&header, &statement.body)?;
+        self.writer.line("}");
+        Ok(())
+    }
+
+    fn optional(
+        &mut self,
+        expression: &Option<Rc<RefCell<dyn Expression>>>,
+        precedence: u8,
+    ) -> Result<String, CodegenError> {
+        match expression {
+            Some(expression) => self.operand(&*expression.as_ref().borrow(), precedence),
+            None => Err(CodegenError::Unsupported("missing expression".to_string())),
+        }
+    }
+
+    /// `expression`, parenthesised unless it binds at least as tightly as
+    /// `precedence`.
+    fn operand(
+        &mut self,
+        expression: &dyn Expression,
+        precedence: u8,
+    ) -> Result<String, CodegenError> {
+        let (text, own) = self.expression(expression)?;
+        Ok(match own < precedence {
+            true => format!("({})", text),
+            false => text,
+        })
+    }
+
+    fn expression(&mut self, expression: &dyn Expression) -> Result<(String, u8), CodegenError> {
+        let any = expression.as_any();
+
+        if let Some(literal) = any.downcast_ref::<IntegralLiteral>() {
+            match literal.value.to_i64() {
+                Some(value) => Ok((value.to_string(), ATOM)),
+                None => Err(CodegenError::IntegerTooLarge(literal.value.to_string())),
+            }
+        } else if let Some(literal) = any.downcast_ref::<RealLiteral>() {
+            Ok((literal.token.literal.clone(), ATOM))
+        } else if let Some(literal) = any.downcast_ref::<StringLiteral>() {
+            Ok((string_literal(&literal.value), ATOM))
+        } else if let Some(literal) = any.downcast_ref::<BooleanLiteral>() {
+            Ok((literal.value.to_string(), ATOM))
+        } else if let Some(identifier) = any.downcast_ref::<Identifier>() {
+            Ok((identifier.value.clone(), ATOM))
+        } else if let Some(prefix) = any.downcast_ref::<PrefixExpression>() {
+            let right = self.optional(&prefix.right, PREFIX)?;
+            // `- -x` would otherwise read as the decrement `--x`.
+            let separator = match right.starts_with(prefix.operator.as_str()) {
+                true => " ",
+                false => "",
+            };
+            Ok((format!("{}{}{}", prefix.operator, separator, right), PREFIX))
+        } else if let Some(integer_part) = any.downcast_ref::<IntegerPartExpression>() {
+            let ty = self.analysis.optional_type(self.scope, &integer_part.value);
+            if ty == Type::Integer {
+                let value = integer_part.value.as_ref();
+                return match value {
+                    Some(value) => self.expression(&*value.as_ref().borrow()),
+                    None => Err(CodegenError::Unsupported(expression.string())),
+                };
+            }
+            self.headers.insert("cmath");
+            let value = self.optional(&integer_part.value, OR)?;
+            Ok((format!("(long long)floor({})", value), PREFIX))
+        } else if let Some(infix) = any.downcast_ref::<InfixExpression>() {
+            self.infix_expression(infix)
+        } else if let Some(array) = any.downcast_ref::<ArrayLiteral>() {
+            let mut elements = Vec::new();
+            for element in &array.elements {
+                elements.push(self.operand(&*element.as_ref().borrow(), OR)?);
+            }
+            Ok((format!("{{{}}}", elements.join(", ")), ATOM))
+        } else if let Some(map) = any.downcast_ref::<MapLiteral>() {
+            let mut pairs = Vec::new();
+            for (key, value) in &map.pairs {
+                let key = self.operand(&*key.as_ref().borrow(), OR)?;
+                let value = self.operand(&*value.as_ref().borrow(), OR)?;
+                pairs.push(format!("{{{}, {}}}", key, value));
+            }
+            Ok((format!("{{{}}}", pairs.join(", ")), ATOM))
+        } else if let Some(index) = any.downcast_ref::<IndexExpression>() {
+            let left = self.optional(&index.left, POSTFIX)?;
+            let is_array = matches!(
+                self.analysis.optional_type(self.scope, &index.left),
+                Type::Array(_)
+            );
+            let index = match self.one_based && is_array {
+                true => format!("{} - 1", self.optional(&index.index, ADDITIVE)?),
+                false => self.optional(&index.index, OR)?,
+            };
+            Ok((format!("{}[{}]", left, index), POSTFIX))
+        } else if let Some(call) = any.downcast_ref::<CallExpression>() {
+            self.call_expression(call)
+        } else {
+            Err(CodegenError::Unsupported(expression.string()))
+        }
+    }
+
+    fn infix_expression(&mut self, infix: &InfixExpression) -> Result<(String, u8), CodegenError> {
+        let (operator, precedence) = match infix.operator.as_str() {
+            "||" => ("||", OR),
+            "&&" => ("&&", AND),
+            "==" | "!=" => (infix.operator.as_str(), EQUALITY),
+            "<" | "<=" | ">" | ">=" => (infix.operator.as_str(), RELATIONAL),
+            "+" | "-" => (infix.operator.as_str(), ADDITIVE),
+            "*" | "/" | "%" => (infix.operator.as_str(), MULTIPLICATIVE),
+            "div" => ("/", MULTIPLICATIVE),
+            _ => return Err(CodegenError::Unsupported(infix.string())),
+        };
+
+        let scope = self.scope;
+        let left_type = self.analysis.optional_type(scope, &infix.left);
+        let right_type = self.analysis.optional_type(scope, &infix.right);
+        let mut left = self.optional(&infix.left, precedence)?;
+        let right = self.optional(&infix.right, precedence + 1)?;
+
+        // `/` is exact division, while C++ truncates the quotient of two
+        // integers.
+        if infix.operator == "/" && left_type == Type::Integer && right_type == Type::Integer {
+            left = format!("(double){}", self.optional(&infix.left, PREFIX)?);
+        }
+        // Two string literals are arrays of `char` that cannot be added.
+        if operator == "+" && is_string_literal(&infix.left) && is_string_literal(&infix.right) {
+            left = format!("string({})", left);
+        }
+
+        Ok((format!("{} {} {}", left, operator, right), precedence))
+    }
+
+    fn call_expression(&mut self, call: &CallExpression) -> Result<(String, u8), CodegenError> {
+        let mut arguments = Vec::new();
+        for argument in &call.arguments {
+            arguments.push(self.operand(&*argument.as_ref().borrow(), OR)?);
+        }
+
+        let Some(name) = callee(call) else {
+            return Err(CodegenError::Unsupported(call.string()));
+        };
+        if self.analysis.function(&name).is_some() || !BUILTINS.contains(&name.as_str()) {
+            return Ok((format!("{}({})", name, arguments.join(", ")), POSTFIX));
+        }
+        self.builtin(call, &name, &arguments)
+    }
+
+    /// A call of the builtin `name` with the already translated
+    /// `arguments`, in the C++ standard library's terms. Integers stay
+    /// `long long` throughout.
+    fn builtin(
+        &mut self,
+        call: &CallExpression,
+        name: &str,
+        arguments: &[String],
+    ) -> Result<(String, u8), CodegenError> {
+        let types: Vec<Type> = call
+            .arguments
+            .iter()
+            .map(|argument| {
+                self.analysis
+                    .type_of(self.scope, &*argument.as_ref().borrow())
+            })
+            .collect();
+        let first = |emitter: &mut Self, precedence| {
+            emitter.operand(&*call.arguments[0].as_ref().borrow(), precedence)
+        };
+        let called =
+            |function: &str| Ok((format!("{}({})", function, arguments.join(", ")), POSTFIX));
+
+        match (name, types.as_slice()) {
+            ("len", [_]) => Ok((
+                format!("(long long){}.size()", first(self, POSTFIX)?),
+                PREFIX,
+            )),
+            ("push", [_, _]) => {
+                let array = first(self, POSTFIX)?;
+                Ok((format!("{}.push_back({})", array, arguments[1]), POSTFIX))
+            }
+            ("abs", [Type::Real]) => {
+                self.headers.insert("cmath");
+                called("fabs")
+            }
+            ("abs", [_]) => {
+                self.headers.insert("cstdlib");
+                called("llabs")
+            }
+            ("min" | "max", [Type::Array(_)]) => {
+                self.helpers.insert("extremes_");
+                called(&format!("{}_", name))
+            }
+            ("min" | "max", _) => {
+                self.headers.insert("algorithm");
+                let ty = match types.contains(&Type::Real) {
+                    true => "double",
+                    false => "long long",
+                };
+                Ok((
+                    format!("{}<{}>({{{}}})", name, ty, arguments.join(", ")),
+                    POSTFIX,
+                ))
+            }
+            ("sqrt", [_]) => {
+                self.headers.insert("cmath");
+                called("sqrt")
+            }
+            ("pow", [Type::Real, _] | [_, Type::Real]) => {
+                self.headers.insert("cmath");
+                called("pow")
+            }
+            ("pow", [_, _]) => {
+                self.helpers.insert("pow_");
+                called("pow_")
+            }
+            ("floor" | "ceil" | "int", [Type::Integer | Type::Unknown])
+            | ("real", [Type::Real])
+            | ("str", [Type::Str]) => self.expression(&*call.arguments[0].as_ref().borrow()),
+            ("floor" | "ceil", [_]) => {
+                self.headers.insert("cmath");
+                Ok((format!("(long long){}({})", name, arguments[0]), PREFIX))
+            }
+            ("int", [Type::Real]) => Ok((format!("(long long){}", first(self, PREFIX)?), PREFIX)),
+            ("int", [Type::Str]) => {
+                self.headers.insert("string");
+                called("stoll")
+            }
+            ("real", [Type::Integer | Type::Unknown]) => {
+                Ok((format!("(double){}", first(self, PREFIX)?), PREFIX))
+            }
+            ("real", [Type::Str]) => {
+                self.headers.insert("string");
+                called("stod")
+            }
+            ("str", [Type::Integer | Type::Unknown]) => {
+                self.headers.insert("string");
+                called("to_string")
+            }
+            ("str", [Type::Real]) => {
+                self.helpers.insert("str_");
+                called("str_")
+            }
+            ("str", [Type::Boolean]) => {
+                self.headers.insert("string");
+                Ok((
+                    format!("string({} ? \"true\" : \"false\")", arguments[0]),
+                    POSTFIX,
+                ))
+            }
+            ("random", _) => {
+                self.helpers.insert("random_");
+                called("random_")
+            }
+            ("seed", [_]) => {
+                self.helpers.insert("random_");
+                called("rng_.seed")
+            }
+            ("sort", [Type::Array(_)]) => {
+                self.helpers.insert("sorted_");
+                called("sorted_")
+            }
+            _ => Err(CodegenError::Unsupported(call.string())),
+        }
+    }
+}
+
+fn assignment_operator(operator: &str) -> &str {
+    match operator {
+        "+=" | "-=" | "*=" | "/=" => operator,
+        _ => "=",
+    }
+}
+
+fn string_literal(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn is_string_literal(expression: &Option<Rc<RefCell<dyn Expression>>>) -> bool {
+    expression
+        .as_ref()
+        .is_some_and(|e| e.as_ref().borrow().as_any().is::<StringLiteral>())
+}
+
+fn is_literal(expression: &dyn Expression) -> bool {
+    let any = expression.as_any();
+    any.is::<IntegralLiteral>() || any.is::<RealLiteral>()
+}
+
+fn is_negative_literal(expression: &dyn Expression) -> bool {
+    let Some(prefix) = expression.as_any().downcast_ref::<PrefixExpression>() else {
+        return false;
+    };
+    prefix.operator == "-"
+        && prefix
+            .right
+            .as_ref()
+            .is_some_and(|right| is_literal(&*right.as_ref().borrow()))
+}
@@ -0,0 +1,47 @@
+use std::fmt;
+
+pub mod cpp;
+pub mod pascal;
+pub mod python;
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CodegenError {
+    /// A construct the target language has no direct counterpart for, such
+    /// as a closure or printing a whole array.
+    Unsupported(String),
+    /// An integer literal too large for the target's 64-bit integers.
+    IntegerTooLarge(String),
+}
+
+impl fmt::Display for CodegenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CodegenError::Unsupported(node) => write!(f, "cannot translate: {}", node),
+            CodegenError::IntegerTooLarge(literal) => {
+                write!(f, "integer {} does not fit in 64 bits", literal)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CodegenError {}
+
+/// Collects output line by line at the current indentation.
+#[derive(Debug, Default)]
+struct Writer {
+    out: String,
+    indent: usize,
+}
+
+impl Writer {
+    fn line(&mut self, text: &str) {
+        if !text.is_empty() {
+            self.out.push_str(&"    ".repeat(self.indent));
+            self.out.push_str(text);
+        }
+        self.out.push('\n');
+    }
+}
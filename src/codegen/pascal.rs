@@ -0,0 +1,717 @@
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::rc::Rc;
+
+use crate::ast::{
+    ArrayLiteral, AssignStatement, BlockStatement, BooleanLiteral, CallExpression, Expression,
+    ExpressionStatement, ForStatement, Identifier, IfStatement, IndexAssignStatement,
+    IndexExpression, InfixExpression, IntegerPartExpression, IntegralLiteral, LetStatement, Node,
+    PrefixExpression, Program, ReadStatement, RealLiteral, RepeatStatement, ReturnStatement,
+    Statement, StringLiteral, WhileStatement, WriteStatement,
+};
+
+use super::{CodegenError, Writer};
+use crate::builtins::BUILTINS;
+use crate::types::{callee, function_declaration, Analysis, Scope, Type};
+
+/// Translates a program to Free Pascal (`objfpc` mode). Every top-level
+/// variable is declared in the program's `var` section, so functions see
+/// them as they do in the evaluator. Integers are `int64`, the range the
+/// evaluator computes in before it widens.
+///
+/// `citeste` of a number becomes `read`, not `readln`: the evaluator takes
+/// whitespace-separated words across lines, and `readln` would throw away
+/// the rest of the line, so a loop reading one number at a time could not
+/// take several from one line. `citeste` of a string becomes `readln`, as
+/// Pascal has no way to read a single word.
+#[derive(Debug)]
+pub struct Pascal {
+    name: String,
+    one_based: bool,
+}
+
+const RELATIONAL: u8 = 1;
+const ADDITIVE: u8 = 2;
+const MULTIPLICATIVE: u8 = 3;
+const PREFIX: u8 = 4;
+const POSTFIX: u8 = 5;
+const ATOM: u8 = 6;
+
+/// Functions a translation may rely on, emitted only when used.
+const HELPERS: [(&str, &str); 1] = [(
+    "IntPow",
+    "function IntPow(base, exponent: int64): int64;
+begin
+    result := 1;
+    while exponent > 0 do begin
+        if odd(exponent) then
+            result := result * base;
+        base := base * base;
+        exponent := exponent div 2;
+    end;
+end;",
+)];
+
+impl Default for Pascal {
+    fn default() -> Self {
+        Self {
+            name: "main".to_string(),
+            one_based: false,
+        }
+    }
+}
+
+impl Pascal {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The name after `program`, `main` by default.
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    /// Translates `v[1]` to `v[0]`, for programs written for an evaluator
+    /// with one-based arrays.
+    pub fn one_based_arrays(mut self, one_based: bool) -> Self {
+        self.one_based = one_based;
+        self
+    }
+
+    pub fn generate(&self, program: &Program) -> Result<String, CodegenError> {
+        let analysis = Analysis::new(program);
+        let mut emitter = Emitter {
+            analysis: &analysis,
+            one_based: self.one_based,
+            scope: None,
+            writer: Writer::default(),
+            units: BTreeSet::new(),
+            arrays: BTreeSet::new(),
+            helpers: BTreeSet::new(),
+            random: false,
+        };
+
+        let variables: Vec<(String, Type)> = analysis.variables.iter().cloned().collect();
+        emitter.declarations(&variables)?;
+
+        if analysis.functions.len() > 1 {
+            for index in 0..analysis.functions.len() {
+                let heading = emitter.heading(index)?;
+                emitter.writer.line(&format!("{} forward;", heading));
+            }
+            emitter.writer.line("");
+        }
+        for index in 0..analysis.functions.len() {
+            emitter.function(index)?;
+            emitter.writer.line("");
+        }
+
+        emitter.scope = None;
+        emitter.writer.line("begin");
+        emitter.writer.indent += 1;
+        let start = emitter.writer.out.len();
+        for statement in &program.statements {
+            let statement = statement.as_ref().borrow();
+            if function_declaration(&*statement).is_none() {
+                emitter.statement(&*statement)?;
+            }
+        }
+        if emitter.random {
+            let indent = "    ".repeat(emitter.writer.indent);
+            let line = format!("{}Randomize;\n", indent);
+            emitter.writer.out.insert_str(start, &line);
+        }
+        emitter.writer.indent -= 1;
+        emitter.writer.line("end.");
+
+        let mut out = format!("program {};\n\n{{$mode objfpc}}{{$H+}}\n\n", self.name);
+        if !emitter.units.is_empty() {
+            let units: Vec<&str> = emitter.units.iter().copied().collect();
+            out.push_str(&format!("uses {};\n\n", units.join(", ")));
+        }
+        if !emitter.arrays.is_empty() {
+            out.push_str("type\n");
+            for (name, element) in &emitter.arrays {
+                out.push_str(&format!("    {} = array of {};\n", name, element));
+            }
+            out.push('\n');
+        }
+        for (name, helper) in HELPERS {
+            if emitter.helpers.contains(name) {
+                out.push_str(&format!("{}\n\n", helper));
+            }
+        }
+        out.push_str(&emitter.writer.out);
+        Ok(out)
+    }
+}
+
+struct Emitter<'a> {
+    analysis: &'a Analysis,
+    one_based: bool,
+    scope: Scope,
+    writer: Writer,
+    units: BTreeSet<&'static str>,
+    /// Named dynamic array types, which parameters need, with their
+    /// element types.
+    arrays: BTreeSet<(String, String)>,
+    helpers: BTreeSet<&'static str>,
+    /// Whether `random` is called, so the generator must be seeded.
+    random: bool,
+}
+
+impl Emitter<'_> {
+    fn type_name(&mut self, ty: &Type) -> Result<String, CodegenError> {
+        Ok(match ty {
+            Type::Unknown | Type::Integer => "int64".to_string(),
+            Type::Real => "real".to_string(),
+            Type::Boolean => "boolean".to_string(),
+            Type::Str => "string".to_string(),
+            Type::Array(element) => {
+                let element = self.type_name(element)?;
+                let mut name = element.clone();
+                name[..1].make_ascii_uppercase();
+                let name = format!("T{}Array", name.trim_start_matches('T'));
+                self.arrays.insert((name.clone(), element));
+                name
+            }
+            Type::Map(..) => {
+                return Err(CodegenError::Unsupported("map".to_string()));
+            }
+        })
+    }
+
+    fn declarations(&mut self, variables: &[(String, Type)]) -> Result<(), CodegenError> {
+        if variables.is_empty() {
+            return Ok(());
+        }
+        self.writer.line("var");
+        self.writer.indent += 1;
+        for (name, ty) in variables {
+            let ty = self.type_name(ty)?;
+            self.writer.line(&format!("{}: {};", name, ty));
+        }
+        self.writer.indent -= 1;
+        self.writer.line("");
+        Ok(())
+    }
+
+    /// Arrays are shared with the caller, as in the evaluator, so they are
+    /// passed as `var` parameters.
+    fn heading(&mut self, index: usize) -> Result<String, CodegenError> {
+        let function = &self.analysis.functions[index];
+        let mut parameters = Vec::new();
+        for (name, ty) in function.parameters.iter() {
+            let prefix = match ty {
+                Type::Array(_) => "var ",
+                _ => "",
+            };
+            parameters.push(format!("{}{}: {}", prefix, name, self.type_name(ty)?));
+        }
+        let parameters = match parameters.is_empty() {
+            true => String::new(),
+            false => format!("({})", parameters.join("; ")),
+        };
+        Ok(match &function.returns {
+            Some(ty) => format!(
+                "function {}{}: {};",
+                function.name,
+                parameters,
+                self.type_name(ty)?
+            ),
+            None => format!("procedure {}{};", function.name, parameters),
+        })
+    }
+
+    fn function(&mut self, index: usize) -> Result<(), CodegenError> {
+        self.scope = Some(index);
+        let heading = self.heading(index)?;
+        self.writer.line(&heading);
+        let function = &self.analysis.functions[index];
+        let locals: Vec<(String, Type)> = function.locals.iter().cloned().collect();
+        self.declarations(&locals)?;
+        self.writer.line("begin");
+        self.writer.indent += 1;
+        self.statements(&function.body.statements)?;
+        self.writer.indent -= 1;
+        self.writer.line("end;");
+        Ok(())
+    }
+
+    /// Writes `header begin`, the statements of `block` and an `end` the
+    /// caller completes, since it is followed by `;` or `else`.
+    fn block(&mut self, header: &str, block: &BlockStatement) -> Result<(), CodegenError> {
+        match header {
+            "" => self.writer.line("begin"),
+            header => self.writer.line(&format!("{} begin", header)),
+        }
+        self.writer.indent += 1;
+        self.statements(&block.statements)?;
+        self.writer.indent -= 1;
+        Ok(())
+    }
+
+    fn statements(
+        &mut self,
+        statements: &[Rc<RefCell<dyn Statement>>],
+    ) -> Result<(), CodegenError> {
+        for statement in statements {
+            self.statement(&*statement.as_ref().borrow())?;
+        }
+        Ok(())
+    }
+
+    fn statement(&mut self, statement: &dyn Statement) -> Result<(), CodegenError> {
+        let any = statement.as_any();
+
+        if let Some(statement) = any.downcast_ref::<ExpressionStatement>() {
+            let Some(expression) = &statement.expression else {
+                return Ok(());
+            };
+            let expression = expression.as_ref().borrow();
+            let Some(call) = expression.as_any().downcast_ref::<CallExpression>() else {
+                return Err(CodegenError::Unsupported(statement.string()));
+            };
+            if let (Some("push"), [array, value]) =
+                (callee(call).as_deref(), call.arguments.as_slice())
+            {
+                let array = self.operand(&*array.as_ref().borrow(), POSTFIX)?;
+                let value = self.operand(&*value.as_ref().borrow(), RELATIONAL)?;
+                self.writer
+                    .line(&format!("SetLength({}, Length({}) + 1);", array, array));
+                self.writer
+                    .line(&format!("{}[High({})] := {};", array, array, value));
+                return Ok(());
+            }
+            let call = self.call_expression(call)?.0;
+            self.writer.line(&format!("{};", call));
+        } else if let Some(let_statement) = any.downcast_ref::<LetStatement>() {
+            let (Some(name), Some(value)) = (&let_statement.name, &let_statement.value) else {
+                return Ok(());
+            };
+            if function_declaration(let_statement).is_some() {
+                return Err(CodegenError::Unsupported(statement.string()));
+            }
+            self.assignment(&name.value, "=", &*value.as_ref().borrow())?;
+        } else if let Some(statement) = any.downcast_ref::<AssignStatement>() {
+            let Some(value) = &statement.value else {
+                return Err(CodegenError::Unsupported(statement.string()));
+            };
+            let operator = &statement.token.literal;
+            self.assignment(&statement.name.value, operator, &*value.as_ref().borrow())?;
+        } else if let Some(statement) = any.downcast_ref::<IndexAssignStatement>() {
+            let Some(value) = &statement.value else {
+                return Err(CodegenError::Unsupported(statement.string()));
+            };
+            let target = self.expression(&*statement.target.as_ref().borrow())?.0;
+            let operator = &statement.token.literal;
+            self.assignment(&target, operator, &*value.as_ref().borrow())?;
+        } else if let Some(statement) = any.downcast_ref::<ReturnStatement>() {
+            match (&statement.return_value, self.scope) {
+                (Some(value), Some(_)) => {
+                    let value = self.expression(&*value.as_ref().borrow())?.0;
+                    self.writer.line(&format!("exit({});", value));
+                }
+                _ => self.writer.line("exit;"),
+            }
+        } else if let Some(block) = any.downcast_ref::<BlockStatement>() {
+            self.block("", block)?;
+            self.writer.line("end;");
+        } else if let Some(statement) = any.downcast_ref::<IfStatement>() {
+            self.if_statement(statement, "")?;
+        } else if let Some(statement) = any.downcast_ref::<WhileStatement>() {
+            let condition = self.optional(&statement.condition, RELATIONAL)?;
+            self.block(&format!("while {} do", condition), &statement.body)?;
+            self.writer.line("end;");
+        } else if let Some(statement) = any.downcast_ref::<RepeatStatement>() {
+            self.writer.line("repeat");
+            self.writer.indent += 1;
+            self.statements(&statement.body.statements)?;
+            self.writer.indent -= 1;
+            let condition = self.optional(&statement.condition, RELATIONAL)?;
+            self.writer.line(&format!("until {};", condition));
+        } else if let Some(statement) = any.downcast_ref::<ForStatement>() {
+            self.for_statement(statement)?;
+        } else if let Some(statement) = any.downcast_ref::<ReadStatement>() {
+            for target in &statement.targets {
+                let target = target.as_ref().borrow();
+                let procedure = match self.analysis.type_of(self.scope, &*target) {
+                    Type::Str => "readln",
+                    _ => "read",
+                };
+                let target = self.expression(&*target)?.0;
+                self.writer.line(&format!("{}({});", procedure, target));
+            }
+        } else if let Some(statement) = any.downcast_ref::<WriteStatement>() {
+            let mut arguments = Vec::new();
+            for argument in &statement.arguments {
+                let argument = argument.as_ref().borrow();
+                arguments.push(self.printed(&*argument, statement)?);
+            }
+            self.writer
+                .line(&format!("writeln({});", arguments.join(", ")));
+        } else {
+            return Err(CodegenError::Unsupported(statement.string()));
+        }
+        Ok(())
+    }
+
+    /// Pascal has no compound assignment, so `x += 1` is `x := x + 1`.
+    fn assignment(
+        &mut self,
+        target: &str,
+        operator: &str,
+        value: &dyn Expression,
+    ) -> Result<(), CodegenError> {
+        if let Some(array) = value.as_any().downcast_ref::<ArrayLiteral>() {
+            if array.elements.is_empty() {
+                self.writer.line(&format!("SetLength({}, 0);", target));
+                return Ok(());
+            }
+        }
+
+        let value = match operator {
+            "+=" | "-=" => format!(
+                "{} {} {}",
+                target,
+                &operator[..1],
+                self.operand(value, ADDITIVE + 1)?
+            ),
+            "*=" | "/=" => format!(
+                "{} {} {}",
+                target,
+                &operator[..1],
+                self.operand(value, MULTIPLICATIVE + 1)?
+            ),
+            _ => self.expression(value)?.0,
+        };
+        self.writer.line(&format!("{} := {};", target, value));
+        Ok(())
+    }
+
+    /// How `argument` is printed so the text is the evaluator's: `true`
+    /// rather than `TRUE`, and `2.5` rather than `2.5000000000000000E+000`.
+    fn printed(
+        &mut self,
+        argument: &dyn Expression,
+        statement: &WriteStatement,
+    ) -> Result<String, CodegenError> {
+        match self.analysis.type_of(self.scope, argument) {
+            Type::Boolean => {
+                self.units.insert("SysUtils");
+                let text = self.expression(argument)?.0;
+                Ok(format!("LowerCase(BoolToStr({}, True))", text))
+            }
+            Type::Real => {
+                self.units.insert("SysUtils");
+                Ok(format!("FloatToStr({})", self.expression(argument)?.0))
+            }
+            Type::Array(_) | Type::Map(..) => Err(CodegenError::Unsupported(statement.string())),
+            _ => Ok(self.expression(argument)?.0),
+        }
+    }
+
+    fn if_statement(&mut self, statement: &IfStatement, prefix: &str) -> Result<(), CodegenError> {
+        let condition = self.optional(&statement.condition, RELATIONAL)?;
+        self.block(
+            &format!("{}if {} then", prefix, condition),
+            &statement.consequence,
+        )?;
+
+        let Some(alternative) = &statement.alternative else {
+            self.writer.line("end;");
+            return Ok(());
+        };
+        if let [statement] = alternative.statements.as_slice() {
+            let statement = statement.as_ref().borrow();
+            if let Some(statement) = statement.as_any().downcast_ref::<IfStatement>() {
+                return self.if_statement(statement, "end else ");
+            }
+        }
+        self.block("end else", alternative)?;
+        self.writer.line("end;");
+        Ok(())
+    }
+
+    /// Pascal's `for` only counts by one, up with `to` or down with
+    /// `downto`, and only over integers. Any other `pentru` becomes a
+    /// `while` loop.
+    fn for_statement(&mut self, statement: &ForStatement) -> Result<(), CodegenError> {
+        let counter = &statement.counter.value;
+        let start = self.optional(&statement.start, RELATIONAL)?;
+        let end = self.optional(&statement.end, RELATIONAL)?;
+        let step = match &statement.step {
+            Some(step) => Some(self.operand(&*step.as_ref().borrow(), ADDITIVE + 1)?),
+            None => None,
+        };
+        let integral = self.analysis.variable(self.scope, counter) == Type::Integer;
+
+        let direction = match step.as_deref() {
+            None | Some("1") if integral => Some("to"),
+            Some("-1") if integral => Some("downto"),
+            _ => None,
+        };
+        if let Some(direction) = direction {
+            let header = format!("for {} := {} {} {} do", counter, start, direction, end);
+            self.block(&header, &statement.body)?;
+            self.writer.line("end;");
+            return Ok(());
+        }
+
+        let step = step.unwrap_or_else(|| "1".to_string());
+        let (condition, increment) = match step.strip_prefix('-') {
+            Some(magnitude) => (
+                format!("{} >= {}", counter, end),
+                format!("{} - {}", counter, magnitude),
+            ),
+            None => (
+                format!("{} <= {}", counter, end),
+                format!("{} + {}", counter, step),
+            ),
+        };
+        self.writer.line(&format!("{} := {};", counter, start));
+        self.block(&format!("while {} do", condition), &statement.body)?;
+        self.writer.indent += 1;
+        self.writer.line(&format!("{} := {};", counter, increment));
+        self.writer.indent -= 1;
+        self.writer.line("end;");
+        Ok(())
+    }
+
+    fn optional(
+        &mut self,
+        expression: &Option<Rc<RefCell<dyn Expression>>>,
+        precedence: u8,
+    ) -> Result<String, CodegenError> {
+        match expression {
+            Some(expression) => self.operand(&*expression.as_ref().borrow(), precedence),
+            None => Err(CodegenError::Unsupported("missing expression".to_string())),
+        }
+    }
+
+    /// `expression`, parenthesised unless it binds at least as tightly as
+    /// `precedence`.
+    fn operand(
+        &mut self,
+        expression: &dyn Expression,
+        precedence: u8,
+    ) -> Result<String, CodegenError> {
+        let (text, own) = self.expression(expression)?;
+        Ok(match own < precedence {
+            true => format!("({})", text),
+            false => text,
+        })
+    }
+
+    fn expression(&mut self, expression: &dyn Expression) -> Result<(String, u8), CodegenError> {
+        let any = expression.as_any();
+
+        if let Some(literal) = any.downcast_ref::<IntegralLiteral>() {
+            match literal.value.to_i64() {
+                Some(value) => Ok((value.to_string(), ATOM)),
+                None => Err(CodegenError::IntegerTooLarge(literal.value.to_string())),
+            }
+        } else if let Some(literal) = any.downcast_ref::<RealLiteral>() {
+            Ok((literal.token.literal.clone(), ATOM))
+        } else if let Some(literal) = any.downcast_ref::<StringLiteral>() {
+            Ok((format!("'{}'", literal.value.replace('\'', "''")), ATOM))
+        } else if let Some(literal) = any.downcast_ref::<BooleanLiteral>() {
+            let value = match literal.value {
+                true => "True",
+                false => "False",
+            };
+            Ok((value.to_string(), ATOM))
+        } else if let Some(identifier) = any.downcast_ref::<Identifier>() {
+            Ok((identifier.value.clone(), ATOM))
+        } else if let Some(prefix) = any.downcast_ref::<PrefixExpression>() {
+            let operator = match prefix.operator.as_str() {
+                "!" => "not ",
+                operator => operator,
+            };
+            let right = self.optional(&prefix.right, PREFIX)?;
+            Ok((format!("{}{}", operator, right), PREFIX))
+        } else if let Some(integer_part) = any.downcast_ref::<IntegerPartExpression>() {
+            let ty = self.analysis.optional_type(self.scope, &integer_part.value);
+            if ty == Type::Integer {
+                let value = integer_part.value.as_ref();
+                return match value {
+                    Some(value) => self.expression(&*value.as_ref().borrow()),
+                    None => Err(CodegenError::Unsupported(expression.string())),
+                };
+            }
+            self.units.insert("Math");
+            let value = self.optional(&integer_part.value, RELATIONAL)?;
+            Ok((format!("Floor({})", value), POSTFIX))
+        } else if let Some(infix) = any.downcast_ref::<InfixExpression>() {
+            self.infix_expression(infix)
+        } else if let Some(array) = any.downcast_ref::<ArrayLiteral>() {
+            let mut elements = Vec::new();
+            for element in &array.elements {
+                elements.push(self.operand(&*element.as_ref().borrow(), RELATIONAL)?);
+            }
+            Ok((format!("[{}]", elements.join(", ")), ATOM))
+        } else if let Some(index) = any.downcast_ref::<IndexExpression>() {
+            let left = self.optional(&index.left, POSTFIX)?;
+            let index = match self.one_based {
+                true => format!("{} - 1", self.optional(&index.index, ADDITIVE)?),
+                false => self.optional(&index.index, RELATIONAL)?,
+            };
+            Ok((format!("{}[{}]", left, index), POSTFIX))
+        } else if let Some(call) = any.downcast_ref::<CallExpression>() {
+            self.call_expression(call)
+        } else {
+            Err(CodegenError::Unsupported(expression.string()))
+        }
+    }
+
+    /// Pascal binds `and` and `or` tighter than comparisons, so a
+    /// comparison operand of either is always parenthesised.
+    fn infix_expression(&mut self, infix: &InfixExpression) -> Result<(String, u8), CodegenError> {
+        let (operator, precedence) = match infix.operator.as_str() {
+            "==" => ("=", RELATIONAL),
+            "!=" => ("<>", RELATIONAL),
+            "<" | "<=" | ">" | ">=" => (infix.operator.as_str(), RELATIONAL),
+            "||" => ("or", ADDITIVE),
+            "+" | "-" => (infix.operator.as_str(), ADDITIVE),
+            "&&" => ("and", MULTIPLICATIVE),
+            "*" | "/" | "div" => (infix.operator.as_str(), MULTIPLICATIVE),
+            "%" => ("mod", MULTIPLICATIVE),
+            _ => return Err(CodegenError::Unsupported(infix.string())),
+        };
+
+        let left_precedence = match precedence {
+            RELATIONAL => precedence + 1,
+            _ => precedence,
+        };
+        let left = self.optional(&infix.left, left_precedence)?;
+        let right = self.optional(&infix.right, precedence + 1)?;
+        Ok((format!("{} {} {}", left, operator, right), precedence))
+    }
+
+    fn call_expression(&mut self, call: &CallExpression) -> Result<(String, u8), CodegenError> {
+        let mut arguments = Vec::new();
+        for argument in &call.arguments {
+            arguments.push(self.operand(&*argument.as_ref().borrow(), RELATIONAL)?);
+        }
+
+        let Some(name) = callee(call) else {
+            return Err(CodegenError::Unsupported(call.string()));
+        };
+        if self.analysis.function(&name).is_some() || !BUILTINS.contains(&name.as_str()) {
+            return match arguments.is_empty() {
+                true => Ok((name, POSTFIX)),
+                false => Ok((format!("{}({})", name, arguments.join(", ")), POSTFIX)),
+            };
+        }
+        self.builtin(call, &name, &arguments)
+    }
+
+    /// A call of the builtin `name` with the already translated
+    /// `arguments`, in terms of the `System`, `SysUtils` and `Math` units.
+    fn builtin(
+        &mut self,
+        call: &CallExpression,
+        name: &str,
+        arguments: &[String],
+    ) -> Result<(String, u8), CodegenError> {
+        let types: Vec<Type> = call
+            .arguments
+            .iter()
+            .map(|argument| {
+                self.analysis
+                    .type_of(self.scope, &*argument.as_ref().borrow())
+            })
+            .collect();
+        let called =
+            |function: &str| Ok((format!("{}({})", function, arguments.join(", ")), POSTFIX));
+
+        match (name, types.as_slice()) {
+            ("len", [_]) => called("Length"),
+            ("abs", [_]) => called("Abs"),
+            ("sqrt", [_]) => called("Sqrt"),
+            ("min" | "max", [Type::Array(_)]) => {
+                self.units.insert("Math");
+                match name {
+                    "min" => called("MinValue"),
+                    _ => called("MaxValue"),
+                }
+            }
+            ("min" | "max", [_, ..]) => {
+                self.units.insert("Math");
+                let function = match name {
+                    "min" => "Min",
+                    _ => "Max",
+                };
+                let (last, rest) = arguments.split_last().expect("at least one argument");
+                let nested = rest.iter().rev().fold(last.clone(), |nested, argument| {
+                    format!("{}({}, {})", function, argument, nested)
+                });
+                Ok((nested, POSTFIX))
+            }
+            ("pow", [Type::Real, _] | [_, Type::Real]) => {
+                self.units.insert("Math");
+                called("Power")
+            }
+            ("pow", [_, _]) => {
+                self.helpers.insert("IntPow");
+                called("IntPow")
+            }
+            ("floor" | "ceil" | "int" | "real", [Type::Integer | Type::Unknown])
+            | ("real", [Type::Real])
+            | ("str", [Type::Str]) => self.expression(&*call.arguments[0].as_ref().borrow()),
+            ("floor", [_]) => {
+                self.units.insert("Math");
+                called("Floor64")
+            }
+            ("ceil", [_]) => {
+                self.units.insert("Math");
+                called("Ceil64")
+            }
+            ("int", [Type::Real]) => called("Trunc"),
+            ("int", [Type::Str]) => {
+                self.units.insert("SysUtils");
+                Ok((format!("StrToInt64(Trim({}))", arguments[0]), POSTFIX))
+            }
+            ("real", [Type::Str]) => {
+                self.units.insert("SysUtils");
+                Ok((format!("StrToFloat(Trim({}))", arguments[0]), POSTFIX))
+            }
+            ("str", [Type::Integer | Type::Unknown]) => {
+                self.units.insert("SysUtils");
+                called("IntToStr")
+            }
+            ("str", [Type::Real]) => {
+                self.units.insert("SysUtils");
+                called("FloatToStr")
+            }
+            ("str", [Type::Boolean]) => {
+                self.units.insert("SysUtils");
+                Ok((
+                    format!("LowerCase(BoolToStr({}, True))", arguments[0]),
+                    POSTFIX,
+                ))
+            }
+            ("random", []) => {
+                self.random = true;
+                Ok(("Random".to_string(), POSTFIX))
+            }
+            ("random", [_]) => {
+                self.random = true;
+                called("Random")
+            }
+            // `RandomRange` leaves out its upper bound.
+            ("random", [_, _]) => {
+                self.random = true;
+                self.units.insert("Math");
+                let high = self.operand(&*call.arguments[1].as_ref().borrow(), ADDITIVE)?;
+                Ok((
+                    format!("RandomRange({}, {} + 1)", arguments[0], high),
+                    POSTFIX,
+                ))
+            }
+            ("seed", [_]) => Ok((format!("RandSeed := {}", arguments[0]), RELATIONAL)),
+            _ => Err(CodegenError::Unsupported(call.string())),
+        }
+    }
+}
@@ -0,0 +1,667 @@
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::rc::Rc;
+
+use crate::ast::{
+    ArrayLiteral, AssignStatement, BlockStatement, BooleanLiteral, CallExpression, Expression,
+    ExpressionStatement, ForStatement, Identifier, IfStatement, IndexAssignStatement,
+    IndexExpression, InfixExpression, IntegerPartExpression, IntegralLiteral, LetStatement,
+    MapLiteral, Node, PrefixExpression, Program, ReadStatement, RealLiteral, RepeatStatement,
+    ReturnStatement, Statement, StringLiteral, WhileStatement, WriteStatement,
+};
+
+use super::{CodegenError, Writer};
+use crate::bigint::BigInt;
+use crate::builtins::BUILTINS;
+use crate::types::{assigned_names, callee, function_declaration, Analysis, Scope, Type};
+
+/// Translates a program to a Python 3 script. Input is read word by word
+/// from standard input as `citeste` does, and `div` and `%` keep their
+/// truncating meaning.
+#[derive(Debug, Default)]
+pub struct Python {
+    one_based: bool,
+}
+
+const OR: u8 = 1;
+const AND: u8 = 2;
+const NOT: u8 = 3;
+const COMPARISON: u8 = 4;
+const ADDITIVE: u8 = 5;
+const MULTIPLICATIVE: u8 = 6;
+const PREFIX: u8 = 7;
+const POSTFIX: u8 = 8;
+const ATOM: u8 = 9;
+
+/// Functions a translation may rely on, emitted only when used.
+const HELPERS: [(&str, &str); 3] = [
+    (
+        "_div",
+        "def _div(a, b):
+    q = abs(a) // abs(b)
+    return q if (a < 0) == (b < 0) else -q",
+    ),
+    (
+        "_mod",
+        "def _mod(a, b):
+    return a - b * _div(a, b)",
+    ),
+    (
+        "_real",
+        "def _real(x):
+    return str(int(x)) if x == int(x) else repr(x)",
+    ),
+];
+
+impl Python {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Translates `v[1]` to `v[0]`, for programs written for an evaluator
+    /// with one-based arrays.
+    pub fn one_based_arrays(mut self, one_based: bool) -> Self {
+        self.one_based = one_based;
+        self
+    }
+
+    pub fn generate(&self, program: &Program) -> Result<String, CodegenError> {
+        let analysis = Analysis::new(program);
+        let mut emitter = Emitter {
+            analysis: &analysis,
+            one_based: self.one_based,
+            scope: None,
+            writer: Writer::default(),
+            imports: BTreeSet::new(),
+            helpers: BTreeSet::new(),
+        };
+
+        let mut functions = Vec::new();
+        for index in 0..analysis.functions.len() {
+            emitter.function(index)?;
+            functions.push(std::mem::take(&mut emitter.writer.out));
+        }
+
+        emitter.scope = None;
+        let reads = program.statements.iter().any(|statement| {
+            let statement = statement.as_ref().borrow();
+            reads(&*statement)
+        });
+        if reads {
+            emitter.imports.insert("sys");
+            emitter
+                .writer
+                .line("_words = iter(sys.stdin.read().split())");
+        }
+        for statement in &program.statements {
+            let statement = statement.as_ref().borrow();
+            if function_declaration(&*statement).is_none() {
+                emitter.statement(&*statement)?;
+            }
+        }
+        let main = std::mem::take(&mut emitter.writer.out);
+
+        let mut sections: Vec<String> = Vec::new();
+        if !emitter.imports.is_empty() {
+            let imports: Vec<String> = emitter
+                .imports
+                .iter()
+                .map(|module| format!("import {}\n", module))
+                .collect();
+            sections.push(imports.concat());
+        }
+        if emitter.helpers.contains("_mod") {
+            emitter.helpers.insert("_div");
+        }
+        for (name, helper) in HELPERS {
+            if emitter.helpers.contains(name) {
+                sections.push(format!("{}\n", helper));
+            }
+        }
+        sections.extend(functions);
+        sections.push(main);
+        Ok(sections.join("\n\n"))
+    }
+}
+
+/// Whether `statement` reads input, including in a function it declares.
+fn reads(statement: &dyn Statement) -> bool {
+    let any = statement.as_any();
+
+    if any.is::<ReadStatement>() {
+        true
+    } else if let Some(declaration) = function_declaration(statement) {
+        block_reads(&declaration.body)
+    } else if let Some(block) = any.downcast_ref::<BlockStatement>() {
+        block_reads(block)
+    } else if let Some(statement) = any.downcast_ref::<IfStatement>() {
+        block_reads(&statement.consequence)
+            || statement.alternative.as_ref().is_some_and(block_reads)
+    } else if let Some(statement) = any.downcast_ref::<WhileStatement>() {
+        block_reads(&statement.body)
+    } else if let Some(statement) = any.downcast_ref::<RepeatStatement>() {
+        block_reads(&statement.body)
+    } else if let Some(statement) = any.downcast_ref::<ForStatement>() {
+        block_reads(&statement.body)
+    } else {
+        false
+    }
+}
+
+fn block_reads(block: &BlockStatement) -> bool {
+    block
+        .statements
+        .iter()
+        .any(|statement| reads(&*statement.as_ref().borrow()))
+}
+
+struct Emitter<'a> {
+    analysis: &'a Analysis,
+    one_based: bool,
+    scope: Scope,
+    writer: Writer,
+    imports: BTreeSet<&'static str>,
+    helpers: BTreeSet<&'static str>,
+}
+
+impl Emitter<'_> {
+    /// Top-level variables the function assigns must be declared `global`,
+    /// or Python would make them local.
+    fn function(&mut self, index: usize) -> Result<(), CodegenError> {
+        self.scope = Some(index);
+        let function = &self.analysis.functions[index];
+        let parameters: Vec<&str> = function
+            .parameters
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect();
+        self.writer.line(&format!(
+            "def {}({}):",
+            function.name,
+            parameters.join(", ")
+        ));
+        self.writer.indent += 1;
+
+        let mut assigned = Vec::new();
+        for statement in &function.body.statements {
+            assigned_names(&*statement.as_ref().borrow(), &mut assigned);
+        }
+        let globals: Vec<String> = assigned
+            .into_iter()
+            .filter(|name| {
+                self.analysis.shared.contains(name) && function.parameters.get(name).is_none()
+            })
+            .collect();
+        if !globals.is_empty() {
+            self.writer.line(&format!("global {}", globals.join(", ")));
+        }
+
+        self.statements(&function.body.statements)?;
+        if function.body.statements.is_empty() && globals.is_empty() {
+            self.writer.line("pass");
+        }
+        self.writer.indent -= 1;
+        Ok(())
+    }
+
+    fn block(&mut self, header: &str, block: &BlockStatement) -> Result<(), CodegenError> {
+        self.writer.line(&format!("{}:", header));
+        self.writer.indent += 1;
+        let start = self.writer.out.len();
+        self.statements(&block.statements)?;
+        if self.writer.out.len() == start {
+            self.writer.line("pass");
+        }
+        self.writer.indent -= 1;
+        Ok(())
+    }
+
+    fn statements(
+        &mut self,
+        statements: &[Rc<RefCell<dyn Statement>>],
+    ) -> Result<(), CodegenError> {
+        for statement in statements {
+            self.statement(&*statement.as_ref().borrow())?;
+        }
+        Ok(())
+    }
+
+    fn statement(&mut self, statement: &dyn Statement) -> Result<(), CodegenError> {
+        let any = statement.as_any();
+
+        if let Some(statement) = any.downcast_ref::<ExpressionStatement>() {
+            if let Some(expression) = &statement.expression {
+                let expression = self.expression(&*expression.as_ref().borrow())?.0;
+                self.writer.line(&expression);
+            }
+        } else if let Some(let_statement) = any.downcast_ref::<LetStatement>() {
+            let (Some(name), Some(value)) = (&let_statement.name, &let_statement.value) else {
+                return Ok(());
+            };
+            if function_declaration(let_statement).is_some() {
+                return Err(CodegenError::Unsupported(statement.string()));
+            }
+            let value = self.expression(&*value.as_ref().borrow())?.0;
+            self.writer.line(&format!("{} = {}", name.value, value));
+        } else if let Some(statement) = any.downcast_ref::<AssignStatement>() {
+            let target = statement.name.value.clone();
+            self.assignment(target, &statement.token.literal, &statement.value)?;
+        } else if let Some(statement) = any.downcast_ref::<IndexAssignStatement>() {
+            let target = self.expression(&*statement.target.as_ref().borrow())?.0;
+            self.assignment(target, &statement.token.literal, &statement.value)?;
+        } else if let Some(statement) = any.downcast_ref::<ReturnStatement>() {
+            match (&statement.return_value, self.scope) {
+                (_, None) => {
+                    self.imports.insert("sys");
+                    self.writer.line("sys.exit()");
+                }
+                (Some(value), _) => {
+                    let value = self.expression(&*value.as_ref().borrow())?.0;
+                    self.writer.line(&format!("return {}", value));
+                }
+                (None, _) => self.writer.line("return"),
+            }
+        } else if let Some(block) = any.downcast_ref::<BlockStatement>() {
+            self.statements(&block.statements)?;
+        } else if let Some(statement) = any.downcast_ref::<IfStatement>() {
+            self.if_statement(statement, "if")?;
+        } else if let Some(statement) = any.downcast_ref::<WhileStatement>() {
+            let condition = self.optional(&statement.condition, OR)?;
+            self.block(&format!("while {}", condition), &statement.body)?;
+        } else if let Some(statement) = any.downcast_ref::<RepeatStatement>() {
+            self.writer.line("while True:");
+            self.writer.indent += 1;
+            self.statements(&statement.body.statements)?;
+            let condition = self.optional(&statement.condition, OR)?;
+            self.writer.line(&format!("if {}:", condition));
+            self.writer.indent += 1;
+            self.writer.line("break");
+            self.writer.indent -= 2;
+        } else if let Some(statement) = any.downcast_ref::<ForStatement>() {
+            self.for_statement(statement)?;
+        } else if let Some(statement) = any.downcast_ref::<ReadStatement>() {
+            for target in &statement.targets {
+                let target = target.as_ref().borrow();
+                let word = match self.analysis.type_of(self.scope, &*target) {
+                    Type::Real => "float(next(_words))",
+                    Type::Str => "next(_words)",
+                    _ => "int(next(_words))",
+                };
+                let target = self.expression(&*target)?.0;
+                self.writer.line(&format!("{} = {}", target, word));
+            }
+        } else if let Some(statement) = any.downcast_ref::<WriteStatement>() {
+            let mut arguments = Vec::new();
+            for argument in &statement.arguments {
+                let argument = argument.as_ref().borrow();
+                arguments.push(self.printed(&*argument, statement)?);
+            }
+            arguments.push("sep=\"\"".to_string());
+            self.writer
+                .line(&format!("print({})", arguments.join(", ")));
+        } else {
+            return Err(CodegenError::Unsupported(statement.string()));
+        }
+        Ok(())
+    }
+
+    fn assignment(
+        &mut self,
+        target: String,
+        operator: &str,
+        value: &Option<Rc<RefCell<dyn Expression>>>,
+    ) -> Result<(), CodegenError> {
+        let operator = match operator {
+            "+=" | "-=" | "*=" | "/=" => operator,
+            _ => "=",
+        };
+        let value = self.optional(value, OR)?;
+        self.writer
+            .line(&format!("{} {} {}", target, operator, value));
+        Ok(())
+    }
+
+    /// How `argument` is printed so the text is the evaluator's: `true`
+    /// rather than `True`, and `2` rather than `2.0` for a whole real.
+    fn printed(
+        &mut self,
+        argument: &dyn Expression,
+        statement: &WriteStatement,
+    ) -> Result<String, CodegenError> {
+        match self.analysis.type_of(self.scope, argument) {
+            Type::Boolean => {
+                let text = self.operand(argument, OR)?;
+                Ok(format!("str({}).lower()", text))
+            }
+            Type::Real => {
+                self.helpers.insert("_real");
+                Ok(format!("_real({})", self.operand(argument, OR)?))
+            }
+            Type::Array(element) if *element != Type::Integer => {
+                Err(CodegenError::Unsupported(statement.string()))
+            }
+            Type::Map(key, value) if *key != Type::Integer || *value != Type::Integer => {
+                Err(CodegenError::Unsupported(statement.string()))
+            }
+            _ => self.operand(argument, OR),
+        }
+    }
+
+    fn if_statement(&mut self, statement: &IfStatement, keyword: &str) -> Result<(), CodegenError> {
+        let condition = self.optional(&statement.condition, OR)?;
+        self.block(
+            &format!("{} {}", keyword, condition),
+            &statement.consequence,
+        )?;
+
+        let Some(alternative) = &statement.alternative else {
+            return Ok(());
+        };
+        if let [statement] = alternative.statements.as_slice() {
+            let statement = statement.as_ref().borrow();
+            if let Some(statement) = statement.as_any().downcast_ref::<IfStatement>() {
+                return self.if_statement(statement, "elif");
+            }
+        }
+        self.block("else", alternative)
+    }
+
+    /// `pentru` over integers becomes a `range`, whose end is exclusive.
+    /// A real counter cannot use `range` and gets a `while` loop instead.
+    fn for_statement(&mut self, statement: &ForStatement) -> Result<(), CodegenError> {
+        let counter = &statement.counter.value;
+        let start = self.optional(&statement.start, OR)?;
+
+        if self.analysis.variable(self.scope, counter) == Type::Real {
+            let step = match &statement.step {
+                Some(step) => self.operand(&*step.as_ref().borrow(), OR)?,
+                None => "1".to_string(),
+            };
+            let end = self.optional(&statement.end, COMPARISON + 1)?;
+            let operator = match step.starts_with('-') {
+                true => ">=",
+                false => "<=",
+            };
+            self.writer.line(&format!("{} = {}", counter, start));
+            self.writer
+                .line(&format!("while {} {} {}:", counter, operator, end));
+            self.writer.indent += 1;
+            self.statements(&statement.body.statements)?;
+            self.writer.line(&format!("{} += {}", counter, step));
+            self.writer.indent -= 1;
+            return Ok(());
+        }
+
+        let range = match &statement.step {
+            None => format!("{}, {}", start, self.exclusive_end(&statement.end, 1)?),
+            Some(step) => {
+                let step = step.as_ref().borrow();
+                let text = self.operand(&*step, OR)?;
+                let end = match is_literal(&*step) {
+                    true if text.starts_with('-') => self.exclusive_end(&statement.end, -1)?,
+                    true => self.exclusive_end(&statement.end, 1)?,
+                    false => format!(
+                        "{} + (1 if {} > 0 else -1)",
+                        self.optional(&statement.end, ADDITIVE)?,
+                        text
+                    ),
+                };
+                format!("{}, {}, {}", start, end, text)
+            }
+        };
+        self.block(
+            &format!("for {} in range({})", counter, range),
+            &statement.body,
+        )
+    }
+
+    /// The exclusive end of a `range` stopping at `end` and moving by
+    /// `offset`, which is 1 or -1. The offset is folded into a constant
+    /// term, so `n - 1` becomes `n` rather than `n - 1 + 1`.
+    fn exclusive_end(
+        &mut self,
+        end: &Option<Rc<RefCell<dyn Expression>>>,
+        offset: i64,
+    ) -> Result<String, CodegenError> {
+        if let Some(expression) = end {
+            let expression = expression.as_ref().borrow();
+            let any = expression.as_any();
+            if let Some(literal) = any.downcast_ref::<IntegralLiteral>() {
+                return Ok((&literal.value + &BigInt::from(offset)).to_string());
+            }
+            if let Some(infix) = any.downcast_ref::<InfixExpression>() {
+                let constant = infix.right.as_ref().and_then(|right| {
+                    let right = right.as_ref().borrow();
+                    let literal = right.as_any().downcast_ref::<IntegralLiteral>()?;
+                    match infix.operator.as_str() {
+                        "+" => Some(literal.value.clone()),
+                        "-" => Some(-&literal.value),
+                        _ => None,
+                    }
+                });
+                if let Some(constant) = constant {
+                    let left = self.optional(&infix.left, ADDITIVE)?;
+                    return Ok(match &constant + &BigInt::from(offset) {
+                        term if term.is_zero() => left,
+                        term if term.is_negative() => format!("{} - {}", left, -term),
+                        term => format!("{} + {}", left, term),
+                    });
+                }
+            }
+        }
+
+        let end = self.optional(end, ADDITIVE)?;
+        Ok(match offset > 0 {
+            true => format!("{} + {}", end, offset),
+            false => format!("{} - {}", end, -offset),
+        })
+    }
+
+    fn optional(
+        &mut self,
+        expression: &Option<Rc<RefCell<dyn Expression>>>,
+        precedence: u8,
+    ) -> Result<String, CodegenError> {
+        match expression {
+            Some(expression) => self.operand(&*expression.as_ref().borrow(), precedence),
+            None => Err(CodegenError::Unsupported("missing expression".to_string())),
+        }
+    }
+
+    /// `expression`, parenthesised unless it binds at least as tightly as
+    /// `precedence`.
+    fn operand(
+        &mut self,
+        expression: &dyn Expression,
+        precedence: u8,
+    ) -> Result<String, CodegenError> {
+        let (text, own) = self.expression(expression)?;
+        Ok(match own < precedence {
+            true => format!("({})", text),
+            false => text,
+        })
+    }
+
+    fn expression(&mut self, expression: &dyn Expression) -> Result<(String, u8), CodegenError> {
+        let any = expression.as_any();
+
+        if let Some(literal) = any.downcast_ref::<IntegralLiteral>() {
+            Ok((literal.value.to_string(), ATOM))
+        } else if let Some(literal) = any.downcast_ref::<RealLiteral>() {
+            Ok((literal.token.literal.clone(), ATOM))
+        } else if let Some(literal) = any.downcast_ref::<StringLiteral>() {
+            Ok((string_literal(&literal.value), ATOM))
+        } else if let Some(literal) = any.downcast_ref::<BooleanLiteral>() {
+            let value = match literal.value {
+                true => "True",
+                false => "False",
+            };
+            Ok((value.to_string(), ATOM))
+        } else if let Some(identifier) = any.downcast_ref::<Identifier>() {
+            Ok((identifier.value.clone(), ATOM))
+        } else if let Some(prefix) = any.downcast_ref::<PrefixExpression>() {
+            match prefix.operator.as_str() {
+                "!" => Ok((format!("not {}", self.optional(&prefix.right, NOT)?), NOT)),
+                operator => {
+                    let right = self.optional(&prefix.right, PREFIX)?;
+                    Ok((format!("{}{}", operator, right), PREFIX))
+                }
+            }
+        } else if let Some(integer_part) = any.downcast_ref::<IntegerPartExpression>() {
+            let ty = self.analysis.optional_type(self.scope, &integer_part.value);
+            if ty == Type::Integer {
+                let value = integer_part.value.as_ref();
+                return match value {
+                    Some(value) => self.expression(&*value.as_ref().borrow()),
+                    None => Err(CodegenError::Unsupported(expression.string())),
+                };
+            }
+            self.imports.insert("math");
+            let value = self.optional(&integer_part.value, OR)?;
+            Ok((format!("math.floor({})", value), POSTFIX))
+        } else if let Some(infix) = any.downcast_ref::<InfixExpression>() {
+            self.infix_expression(infix)
+        } else if let Some(array) = any.downcast_ref::<ArrayLiteral>() {
+            let mut elements = Vec::new();
+            for element in &array.elements {
+                elements.push(self.operand(&*element.as_ref().borrow(), OR)?);
+            }
+            Ok((format!("[{}]", elements.join(", ")), ATOM))
+        } else if let Some(map) = any.downcast_ref::<MapLiteral>() {
+            let mut pairs = Vec::new();
+            for (key, value) in &map.pairs {
+                let key = self.operand(&*key.as_ref().borrow(), OR)?;
+                let value = self.operand(&*value.as_ref().borrow(), OR)?;
+                pairs.push(format!("{}: {}", key, value));
+            }
+            Ok((format!("{{{}}}", pairs.join(", ")), ATOM))
+        } else if let Some(index) = any.downcast_ref::<IndexExpression>() {
+            let left = self.optional(&index.left, POSTFIX)?;
+            let is_array = matches!(
+                self.analysis.optional_type(self.scope, &index.left),
+                Type::Array(_)
+            );
+            let index = match self.one_based && is_array {
+                true => format!("{} - 1", self.optional(&index.index, ADDITIVE)?),
+                false => self.optional(&index.index, OR)?,
+            };
+            Ok((format!("{}[{}]", left, index), POSTFIX))
+        } else if let Some(call) = any.downcast_ref::<CallExpression>() {
+            self.call_expression(call)
+        } else {
+            Err(CodegenError::Unsupported(expression.string()))
+        }
+    }
+
+    fn infix_expression(&mut self, infix: &InfixExpression) -> Result<(String, u8), CodegenError> {
+        if matches!(infix.operator.as_str(), "div" | "%") {
+            let helper = match infix.operator.as_str() {
+                "div" => "_div",
+                _ => "_mod",
+            };
+            self.helpers.insert(helper);
+            let left = self.optional(&infix.left, OR)?;
+            let right = self.optional(&infix.right, OR)?;
+            return Ok((format!("{}({}, {})", helper, left, right), POSTFIX));
+        }
+
+        let (operator, precedence) = match infix.operator.as_str() {
+            "||" => ("or", OR),
+            "&&" => ("and", AND),
+            "==" | "!=" | "<" | "<=" | ">" | ">=" => (infix.operator.as_str(), COMPARISON),
+            "+" | "-" => (infix.operator.as_str(), ADDITIVE),
+            "*" | "/" => (infix.operator.as_str(), MULTIPLICATIVE),
+            _ => return Err(CodegenError::Unsupported(infix.string())),
+        };
+
+        // Python chains comparisons, so `(a < b) == c` keeps its
+        // parentheses on both sides.
+        let left_precedence = match precedence {
+            COMPARISON => precedence + 1,
+            _ => precedence,
+        };
+        let left = self.optional(&infix.left, left_precedence)?;
+        let right = self.optional(&infix.right, precedence + 1)?;
+        Ok((format!("{} {} {}", left, operator, right), precedence))
+    }
+
+    fn call_expression(&mut self, call: &CallExpression) -> Result<(String, u8), CodegenError> {
+        let mut arguments = Vec::new();
+        for argument in &call.arguments {
+            arguments.push(self.operand(&*argument.as_ref().borrow(), OR)?);
+        }
+
+        let Some(name) = callee(call) else {
+            return Err(CodegenError::Unsupported(call.string()));
+        };
+        let called = |function: &str| format!("{}({})", function, arguments.join(", "));
+        if self.analysis.function(&name).is_some() || !BUILTINS.contains(&name.as_str()) {
+            return Ok((called(&name), POSTFIX));
+        }
+
+        let call = match (name.as_str(), arguments.as_slice()) {
+            ("push", [_, value]) => {
+                let array = self.operand(&*call.arguments[0].as_ref().borrow(), POSTFIX)?;
+                format!("{}.append({})", array, value)
+            }
+            ("keys", [map]) => format!("sorted({})", map),
+            ("values", [map]) => format!("[{}[k] for k in sorted({})]", map, map),
+            ("sqrt" | "floor" | "ceil", _) => {
+                self.imports.insert("math");
+                called(&format!("math.{}", name))
+            }
+            ("real", _) => called("float"),
+            ("str", [value]) => {
+                let argument = call.arguments[0].as_ref().borrow();
+                match self.analysis.type_of(self.scope, &*argument) {
+                    Type::Boolean => format!("str({}).lower()", value),
+                    Type::Real => {
+                        self.helpers.insert("_real");
+                        format!("_real({})", value)
+                    }
+                    Type::Array(element) if *element != Type::Integer => {
+                        return Err(CodegenError::Unsupported(call.string()));
+                    }
+                    Type::Map(key, value) if *key != Type::Integer || *value != Type::Integer => {
+                        return Err(CodegenError::Unsupported(call.string()));
+                    }
+                    _ => called("str"),
+                }
+            }
+            ("random", arguments) => {
+                self.imports.insert("random");
+                match arguments.len() {
+                    0 => "random.random()".to_string(),
+                    1 => called("random.randrange"),
+                    _ => called("random.randint"),
+                }
+            }
+            ("seed", _) => {
+                self.imports.insert("random");
+                called("random.seed")
+            }
+            ("sort", _) => called("sorted"),
+            _ => called(&name),
+        };
+        Ok((call, POSTFIX))
+    }
+}
+
+fn string_literal(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn is_literal(expression: &dyn Expression) -> bool {
+    let any = expression.as_any();
+    if any.is::<IntegralLiteral>() || any.is::<RealLiteral>() {
+        return true;
+    }
+    any.downcast_ref::<PrefixExpression>()
+        .is_some_and(|prefix| {
+            prefix.operator == "-"
+                && prefix
+                    .right
+                    .as_ref()
+                    .is_some_and(|right| is_literal(&*right.as_ref().borrow()))
+        })
+}
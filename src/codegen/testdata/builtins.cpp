@@ -0,0 +1,77 @@
+#include <algorithm>
+#include <charconv>
+#include <cmath>
+#include <cstdlib>
+#include <iostream>
+#include <random>
+#include <string>
+#include <vector>
+using namespace std;
+
+long long pow_(long long base, long long exponent) {
+    unsigned long long result = 1, factor = base;
+    for (; exponent > 0; exponent /= 2) {
+        if (exponent % 2 == 1) {
+            result *= factor;
+        }
+        factor *= factor;
+    }
+    return (long long)result;
+}
+
+string str_(double value) {
+    char text[400];
+    return string(text, to_chars(text, text + sizeof text, value, chars_format::fixed).ptr);
+}
+
+mt19937_64 rng_(random_device{}());
+
+double random_() {
+    return uniform_real_distribution<double>(0, 1)(rng_);
+}
+
+long long random_(long long n) {
+    return uniform_int_distribution<long long>(0, n - 1)(rng_);
+}
+
+long long random_(long long low, long long high) {
+    return uniform_int_distribution<long long>(low, high)(rng_);
+}
+
+template <typename T>
+vector<T> sorted_(vector<T> values) {
+    sort(values.begin(), values.end());
+    return values;
+}
+
+template <typename T>
+T min_(const vector<T>& values) {
+    return *min_element(values.begin(), values.end());
+}
+
+template <typename T>
+T max_(const vector<T>& values) {
+    return *max_element(values.begin(), values.end());
+}
+
+int main() {
+    long long n;
+    string s;
+    vector<long long> v;
+    vector<long long> w;
+
+    cout << boolalpha;
+    cin >> n;
+    s = " 42";
+    v = {5, 3, 9, 1};
+    w = sorted_(v);
+    cout << w[0] << " " << w[3] << " " << v[0] << endl;
+    cout << max<long long>({n, 0}) << " " << min<long long>({n, 2, 7}) << " " << max_(v) << " " << min_(w) << endl;
+    cout << llabs(n - 10) << " " << str_(fabs(-2.5)) << endl;
+    cout << str_(sqrt(16)) << " " << pow_(2, 40) << " " << str_(pow(2, 0.5)) << endl;
+    cout << (long long)floor(7.9) << " " << (long long)ceil(7.1) << " " << n << " " << (long long)-7.9 << " " << stoll(s) << endl;
+    cout << str_((double)n / 4) << " " << to_string(n) + "!" << " " << str_(2.5) << " " << string(n > 3 ? "true" : "false") << endl;
+    rng_.seed(n);
+    cout << (random_(1, 6) <= 6) << " " << (random_() < 1) << " " << (random_(3) < 3) << endl;
+    return 0;
+}
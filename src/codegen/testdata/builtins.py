@@ -0,0 +1,22 @@
+import math
+import random
+import sys
+
+
+def _real(x):
+    return str(int(x)) if x == int(x) else repr(x)
+
+
+_words = iter(sys.stdin.read().split())
+n = int(next(_words))
+s = " 42"
+v = [5, 3, 9, 1]
+w = sorted(v)
+print(w[0], " ", w[3], " ", v[0], sep="")
+print(max(n, 0), " ", min(n, 2, 7), " ", max(v), " ", min(w), sep="")
+print(abs(n - 10), " ", _real(abs(-2.5)), sep="")
+print(_real(math.sqrt(16)), " ", pow(2, 40), " ", _real(pow(2, 0.5)), sep="")
+print(math.floor(7.9), " ", math.ceil(7.1), " ", math.floor(n), " ", int(-7.9), " ", int(s), sep="")
+print(_real(float(n) / 4), " ", str(n) + "!", " ", _real(2.5), " ", str(n > 3).lower(), sep="")
+random.seed(n)
+print(str(random.randint(1, 6) <= 6).lower(), " ", str(random.random() < 1).lower(), " ", str(random.randrange(3) < 3).lower(), sep="")
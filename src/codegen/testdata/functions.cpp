@@ -0,0 +1,48 @@
+#include <iostream>
+using namespace std;
+
+long long total;
+
+long long fact(long long n);
+bool par(long long n);
+bool impar(long long n);
+void aduna(long long x);
+
+long long fact(long long n) {
+    if (n <= 1) {
+        return 1;
+    }
+    return n * fact(n - 1);
+}
+
+bool par(long long n) {
+    if (n == 0) {
+        return true;
+    }
+    return impar(n - 1);
+}
+
+bool impar(long long n) {
+    if (n == 0) {
+        return false;
+    }
+    return par(n - 1);
+}
+
+void aduna(long long x) {
+    total += x;
+}
+
+int main() {
+    long long k;
+    long long i;
+
+    cout << boolalpha;
+    total = 0;
+    cin >> k;
+    for (i = 1; i <= k; i++) {
+        aduna(fact(i));
+    }
+    cout << total << " " << par(k) << endl;
+    return 0;
+}
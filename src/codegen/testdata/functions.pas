@@ -0,0 +1,53 @@
+program main;
+
+{$mode objfpc}{$H+}
+
+uses SysUtils;
+
+var
+    total: int64;
+    k: int64;
+    i: int64;
+
+function fact(n: int64): int64; forward;
+function par(n: int64): boolean; forward;
+function impar(n: int64): boolean; forward;
+procedure aduna(x: int64); forward;
+
+function fact(n: int64): int64;
+begin
+    if n <= 1 then begin
+        exit(1);
+    end;
+    exit(n * fact(n - 1));
+end;
+
+function par(n: int64): boolean;
+begin
+    if n = 0 then begin
+        exit(True);
+    end;
+    exit(impar(n - 1));
+end;
+
+function impar(n: int64): boolean;
+begin
+    if n = 0 then begin
+        exit(False);
+    end;
+    exit(par(n - 1));
+end;
+
+procedure aduna(x: int64);
+begin
+    total := total + x;
+end;
+
+begin
+    total := 0;
+    read(k);
+    for i := 1 to k do begin
+        aduna(fact(i));
+    end;
+    writeln(total, ' ', LowerCase(BoolToStr(par(k), True)));
+end.
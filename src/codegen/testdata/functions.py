@@ -0,0 +1,32 @@
+import sys
+
+
+def fact(n):
+    if n <= 1:
+        return 1
+    return n * fact(n - 1)
+
+
+def par(n):
+    if n == 0:
+        return True
+    return impar(n - 1)
+
+
+def impar(n):
+    if n == 0:
+        return False
+    return par(n - 1)
+
+
+def aduna(x):
+    global total
+    total += x
+
+
+_words = iter(sys.stdin.read().split())
+total = 0
+k = int(next(_words))
+for i in range(1, k + 1):
+    aduna(fact(i))
+print(total, " ", str(par(k)).lower(), sep="")
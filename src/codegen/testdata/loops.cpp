@@ -0,0 +1,46 @@
+#include <cmath>
+#include <iostream>
+#include <vector>
+using namespace std;
+
+int main() {
+    long long n;
+    vector<long long> v;
+    long long i;
+    long long x;
+    long long j;
+    long long t;
+    long long c;
+    double m;
+
+    cin >> n;
+    v = {};
+    for (i = 1; i <= n; i++) {
+        cin >> x;
+        v.push_back(x);
+    }
+    for (i = 0; i <= (long long)v.size() - 2; i++) {
+        for (j = i + 1; j <= (long long)v.size() - 1; j++) {
+            if (v[i] > v[j]) {
+                t = v[i];
+                v[i] = v[j];
+                v[j] = t;
+            }
+        }
+    }
+    for (i = (long long)v.size() - 1; i >= 0; i -= 1) {
+        cout << v[i] << endl;
+    }
+    c = 0;
+    do {
+        c = c + 1;
+        n = n / 10;
+    } while (!(n == 0));
+    m = 7.5;
+    while (m > 1 && c < 100) {
+        m = m / 2;
+        c += 1;
+    }
+    cout << c << " " << (long long)floor(m * 10) << " " << -(c - 1) * 2 << endl;
+    return 0;
+}
@@ -0,0 +1,51 @@
+program main;
+
+{$mode objfpc}{$H+}
+
+uses Math;
+
+type
+    TInt64Array = array of int64;
+
+var
+    n: int64;
+    v: TInt64Array;
+    i: int64;
+    x: int64;
+    j: int64;
+    t: int64;
+    c: int64;
+    m: real;
+
+begin
+    read(n);
+    SetLength(v, 0);
+    for i := 1 to n do begin
+        read(x);
+        SetLength(v, Length(v) + 1);
+        v[High(v)] := x;
+    end;
+    for i := 0 to Length(v) - 2 do begin
+        for j := i + 1 to Length(v) - 1 do begin
+            if v[i] > v[j] then begin
+                t := v[i];
+                v[i] := v[j];
+                v[j] := t;
+            end;
+        end;
+    end;
+    for i := Length(v) - 1 downto 0 do begin
+        writeln(v[i]);
+    end;
+    c := 0;
+    repeat
+        c := c + 1;
+        n := n div 10;
+    until n = 0;
+    m := 7.5;
+    while (m > 1) and (c < 100) do begin
+        m := m / 2;
+        c := c + 1;
+    end;
+    writeln(c, ' ', Floor(m * 10), ' ', -(c - 1) * 2);
+end.
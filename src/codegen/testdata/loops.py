@@ -0,0 +1,34 @@
+import math
+import sys
+
+
+def _div(a, b):
+    q = abs(a) // abs(b)
+    return q if (a < 0) == (b < 0) else -q
+
+
+_words = iter(sys.stdin.read().split())
+n = int(next(_words))
+v = []
+for i in range(1, n + 1):
+    x = int(next(_words))
+    v.append(x)
+for i in range(0, len(v) - 1):
+    for j in range(i + 1, len(v)):
+        if v[i] > v[j]:
+            t = v[i]
+            v[i] = v[j]
+            v[j] = t
+for i in range(len(v) - 1, -1, -1):
+    print(v[i], sep="")
+c = 0
+while True:
+    c = c + 1
+    n = _div(n, 10)
+    if n == 0:
+        break
+m = 7.5
+while m > 1 and c < 100:
+    m = m / 2
+    c += 1
+print(c, " ", math.floor(m * 10), " ", -(c - 1) * 2, sep="")
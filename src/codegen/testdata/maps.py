@@ -0,0 +1,34 @@
+import sys
+
+
+def _div(a, b):
+    q = abs(a) // abs(b)
+    return q if (a < 0) == (b < 0) else -q
+
+
+def _mod(a, b):
+    return a - b * _div(a, b)
+
+
+def _real(x):
+    return str(int(x)) if x == int(x) else repr(x)
+
+
+_words = iter(sys.stdin.read().split())
+a = int(next(_words))
+b = int(next(_words))
+c = int(next(_words))
+frecv = {}
+for i in range(1, 4):
+    frecv[i] = 0
+frecv[a] += 1
+frecv[b] += 1
+frecv[c] += 1
+print(sorted(frecv), [frecv[k] for k in sorted(frecv)], " ", frecv, sep="")
+x = 0
+while x <= 1:
+    print(_real(x), sep="")
+    x += 0.5
+pas = -2
+for i in range(a - 7, 0 + (1 if pas > 0 else -1), pas):
+    print(_div(-7, 2), " ", _mod(-7, i), " ", str((not a < b) == (b < c)).lower(), sep="")
@@ -0,0 +1,18 @@
+#include <charconv>
+#include <iostream>
+#include <string>
+using namespace std;
+
+string str_(double value) {
+    char text[400];
+    return string(text, to_chars(text, text + sizeof text, value, chars_format::fixed).ptr);
+}
+
+int main() {
+    long long x;
+
+    cin >> x;
+    cout << str_((double)2 / 3) << " " << - -x << " " << str_(- -x * 1.5) << endl;
+    cout << str_((double)x / 4) << " " << str_(0.1 + 0.2) << endl;
+    return 0;
+}
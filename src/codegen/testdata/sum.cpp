@@ -0,0 +1,32 @@
+#include <charconv>
+#include <iostream>
+#include <string>
+using namespace std;
+
+string str_(double value) {
+    char text[400];
+    return string(text, to_chars(text, text + sizeof text, value, chars_format::fixed).ptr);
+}
+
+int main() {
+    long long n;
+    long long s;
+    long long i;
+
+    cout << boolalpha;
+    cin >> n;
+    s = 0;
+    for (i = 1; i <= n; i++) {
+        if (i % 2 == 0) {
+            s = s + i * i;
+        } else if (i % 3 == 0) {
+            s -= i;
+        } else {
+            s += 1;
+        }
+    }
+    cout << "suma = " << s << endl;
+    cout << "media = " << str_((double)s / n) << endl;
+    cout << "par: " << (s % 2 == 0) << endl;
+    return 0;
+}
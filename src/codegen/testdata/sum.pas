@@ -0,0 +1,27 @@
+program main;
+
+{$mode objfpc}{$H+}
+
+uses SysUtils;
+
+var
+    n: int64;
+    s: int64;
+    i: int64;
+
+begin
+    read(n);
+    s := 0;
+    for i := 1 to n do begin
+        if i mod 2 = 0 then begin
+            s := s + i * i;
+        end else if i mod 3 = 0 then begin
+            s := s - i;
+        end else begin
+            s := s + 1;
+        end;
+    end;
+    writeln('suma = ', s);
+    writeln('media = ', FloatToStr(s / n));
+    writeln('par: ', LowerCase(BoolToStr(s mod 2 = 0, True)));
+end.
@@ -0,0 +1,29 @@
+import sys
+
+
+def _div(a, b):
+    q = abs(a) // abs(b)
+    return q if (a < 0) == (b < 0) else -q
+
+
+def _mod(a, b):
+    return a - b * _div(a, b)
+
+
+def _real(x):
+    return str(int(x)) if x == int(x) else repr(x)
+
+
+_words = iter(sys.stdin.read().split())
+n = int(next(_words))
+s = 0
+for i in range(1, n + 1):
+    if _mod(i, 2) == 0:
+        s = s + i * i
+    elif _mod(i, 3) == 0:
+        s -= i
+    else:
+        s += 1
+print("suma = ", s, sep="")
+print("media = ", _real(s / n), sep="")
+print("par: ", str(_mod(s, 2) == 0).lower(), sep="")
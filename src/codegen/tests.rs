@@ -0,0 +1,285 @@
+use std::io::{Cursor, Write};
+use std::process::{Command, Stdio};
+use std::{env, fs};
+
+use crate::evaluator::Evaluator;
+use crate::test_support::{parse, Output};
+
+use super::cpp::Cpp;
+use super::pascal::Pascal;
+use super::python::Python;
+
+fn testdata(name: &str) -> String {
+    let path = format!(
+        "{}/src/codegen/testdata/{}",
+        env!("CARGO_MANIFEST_DIR"),
+        name
+    );
+    std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {}", path, e))
+}
+
+const CPP_SAMPLES: [&str; 5] = ["sum", "functions", "loops", "reals", "builtins"];
+
+#[test]
+fn translates_to_cpp() {
+    for name in CPP_SAMPLES {
+        let program = parse(&testdata(&format!("{}.pseudo", name)));
+        let cpp = Cpp::new().generate(&program).unwrap();
+        assert_eq!(cpp, testdata(&format!("{}.cpp", name)), "{}", name);
+    }
+}
+
+#[test]
+fn cpp_one_based_arrays() {
+    let program = parse("v ← [1, 2]\nv[1] ← v[2]");
+    let cpp = Cpp::new()
+        .one_based_arrays(true)
+        .generate(&program)
+        .unwrap();
+    assert!(cpp.contains("    v[1 - 1] = v[2 - 1];\n"), "{}", cpp);
+}
+
+#[test]
+fn cpp_rejects_what_it_cannot_translate() {
+    let tests = [
+        ("scrie [1, 2]", "cannot translate: scrie [1, 2];"),
+        (
+            "let f = fn(x) { returneaza fn() { returneaza x } }",
+            "cannot translate: fn() { returneaza x; }",
+        ),
+        ("m ← {1: 2}\nk ← keys(m)", "cannot translate: keys(m)"),
+    ];
+
+    for (input, expected) in tests {
+        let error = Cpp::new().generate(&parse(input)).unwrap_err();
+        assert_eq!(error.to_string(), expected, "{}", input);
+    }
+}
+
+const PYTHON_SAMPLES: [&str; 5] = ["sum", "functions", "loops", "maps", "builtins"];
+
+#[test]
+fn translates_to_python() {
+    for name in PYTHON_SAMPLES {
+        let program = parse(&testdata(&format!("{}.pseudo", name)));
+        let python = Python::new().generate(&program).unwrap();
+        assert_eq!(python, testdata(&format!("{}.py", name)), "{}", name);
+    }
+}
+
+/// What the evaluator prints running the sample `name` on `input`.
+fn evaluated(name: &str, input: &str) -> String {
+    let program = parse(&testdata(&format!("{}.pseudo", name)));
+    let output = Output::default();
+    Evaluator::new()
+        .with_io(
+            Box::new(Cursor::new(input.to_string())),
+            Box::new(output.clone()),
+        )
+        .eval_program(&program)
+        .unwrap();
+    output.text()
+}
+
+/// What `command` prints given `input`, after checking it succeeded.
+fn printed(command: &mut Command, input: &str, name: &str) -> String {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    let result = child.wait_with_output().unwrap();
+    assert!(result.status.success(), "{}", name);
+    String::from_utf8(result.stdout).unwrap()
+}
+
+/// Compiles each translated sample with `g++`, when there is one, runs it
+/// and compares what it prints with the evaluator.
+#[test]
+fn cpp_prints_what_the_evaluator_prints() {
+    if Command::new("g++").arg("--version").output().is_err() {
+        eprintln!("g++ not found, skipping");
+        return;
+    }
+
+    let dir = env::temp_dir().join(format!("h95-cpp-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    for name in CPP_SAMPLES {
+        let input = testdata(&format!("{}.in", name));
+        let source = dir.join(format!("{}.cpp", name));
+        let binary = dir.join(name);
+        fs::write(&source, testdata(&format!("{}.cpp", name))).unwrap();
+        let compiled = Command::new("g++")
+            .arg("-std=c++17")
+            .arg("-o")
+            .arg(&binary)
+            .arg(&source)
+            .status()
+            .unwrap();
+        assert!(compiled.success(), "{}", name);
+        let output = printed(&mut Command::new(&binary), &input, name);
+        assert_eq!(output, evaluated(name, &input), "{}", name);
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+/// Runs each translated sample with `python3`, when there is one, and
+/// compares what it prints with the evaluator.
+#[test]
+fn python_prints_what_the_evaluator_prints() {
+    if Command::new("python3").arg("--version").output().is_err() {
+        eprintln!("python3 not found, skipping");
+        return;
+    }
+
+    for name in PYTHON_SAMPLES {
+        let input = testdata(&format!("{}.in", name));
+        let script = testdata(&format!("{}.py", name));
+        let output = printed(Command::new("python3").args(["-c", &script]), &input, name);
+        assert_eq!(output, evaluated(name, &input), "{}", name);
+    }
+}
+
+#[test]
+fn python_one_based_arrays() {
+    let program = parse("v ← [1, 2]\nv[1] ← v[2]");
+    let python = Python::new()
+        .one_based_arrays(true)
+        .generate(&program)
+        .unwrap();
+    assert_eq!(python, "v = [1, 2]\nv[1 - 1] = v[2 - 1]\n");
+}
+
+#[test]
+fn translates_to_pascal() {
+    for name in ["sum", "functions", "loops"] {
+        let program = parse(&testdata(&format!("{}.pseudo", name)));
+        let pascal = Pascal::new().generate(&program).unwrap();
+        assert_eq!(pascal, testdata(&format!("{}.pas", name)), "{}", name);
+    }
+}
+
+#[test]
+fn pascal_loops_without_a_unit_step() {
+    let program = parse(
+        "pentru i ← 10, 1, -3 { scrie i }
+         pentru x ← 0, 1, 0.5 { scrie x }",
+    );
+    let pascal = Pascal::new().name("pasi").generate(&program).unwrap();
+    let expected = "\
+program pasi;
+
+{$mode objfpc}{$H+}
+
+uses SysUtils;
+
+var
+    i: int64;
+    x: real;
+
+begin
+    i := 10;
+    while i >= 1 do begin
+        writeln(i);
+        i := i - 3;
+    end;
+    x := 0;
+    while x <= 1 do begin
+        writeln(FloatToStr(x));
+        x := x + 0.5;
+    end;
+end.
+";
+    assert_eq!(pascal, expected);
+}
+
+#[test]
+fn pascal_builtins() {
+    let program = parse(
+        "citeste n
+         v ← [5, 3, 9, 1]
+         scrie max(n, 0), \" \", min(n, 2, 7), \" \", max(v)
+         scrie pow(n, 2), \" \", pow(2, 0.5), \" \", floor(7.9), \" \", int(-7.9)
+         scrie str(n) + \"!\", \" \", random(1, 6)",
+    );
+    let pascal = Pascal::new().generate(&program).unwrap();
+    let expected = "\
+program main;
+
+{$mode objfpc}{$H+}
+
+uses Math, SysUtils;
+
+type
+    TInt64Array = array of int64;
+
+function IntPow(base, exponent: int64): int64;
+begin
+    result := 1;
+    while exponent > 0 do begin
+        if odd(exponent) then
+            result := result * base;
+        base := base * base;
+        exponent := exponent div 2;
+    end;
+end;
+
+var
+    n: int64;
+    v: TInt64Array;
+
+begin
+    Randomize;
+    read(n);
+    v := [5, 3, 9, 1];
+    writeln(Max(n, 0), ' ', Min(n, Min(2, 7)), ' ', MaxValue(v));
+    writeln(IntPow(n, 2), ' ', FloatToStr(Power(2, 0.5)), ' ', Floor64(7.9), ' ', Trunc(-7.9));
+    writeln(IntToStr(n) + '!', ' ', RandomRange(1, 6 + 1));
+end.
+";
+    assert_eq!(pascal, expected);
+
+    let error = Pascal::new()
+        .generate(&parse("v ← sort([2, 1])"))
+        .unwrap_err();
+    assert_eq!(error.to_string(), "cannot translate: sort([2, 1])");
+}
+
+#[test]
+fn pascal_reads_numbers_word_by_word() {
+    let program = parse(
+        "nume ← \"\"
+         citeste n, nume
+         pentru i ← 1, n { citeste x; scrie nume, x }",
+    );
+    let pascal = Pascal::new().generate(&program).unwrap();
+    assert!(
+        pascal.contains("    read(n);\n    readln(nume);\n"),
+        "{}",
+        pascal
+    );
+    assert!(pascal.contains("        read(x);\n"), "{}", pascal);
+}
+
+#[test]
+fn rejects_integers_beyond_64_bits() {
+    let program = parse("x ← 9223372036854775807\ny ← 9223372036854775808");
+    let expected = "integer 9223372036854775808 does not fit in 64 bits";
+    let error = Cpp::new().generate(&program).unwrap_err();
+    assert_eq!(error.to_string(), expected);
+    let error = Pascal::new().generate(&program).unwrap_err();
+    assert_eq!(error.to_string(), expected);
+    assert!(Python::new().generate(&program).is_ok());
+}
+
+#[test]
+fn pascal_rejects_maps() {
+    let error = Pascal::new().generate(&parse("m ← {1: 2}")).unwrap_err();
+    assert_eq!(error.to_string(), "cannot translate: map");
+}
@@ -0,0 +1,850 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::ast::{
+    ArrayLiteral, AssignStatement, BlockStatement, BooleanLiteral, CallExpression, Expression,
+    ExpressionStatement, ForStatement, FunctionLiteral, Identifier, IfStatement,
+    IndexAssignStatement, IndexExpression, InfixExpression, IntegerPartExpression, IntegralLiteral,
+    LetStatement, MapLiteral, Node, PrefixExpression, Program, ReadStatement, RealLiteral,
+    RepeatStatement, ReturnStatement, Statement, StringLiteral, WhileStatement, WriteStatement,
+};
+use crate::builtins::BUILTINS;
+use crate::code::{self, Instructions, Opcode};
+use crate::lexer::{Span, TokenType};
+use crate::object::{CompiledFunction, Object};
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CompileError {
+    UndefinedVariable(String),
+    UndeclaredAssignment(String),
+    /// Closures capture values, not variables, so a nested function may
+    /// not rebind a local of the function around it.
+    CapturedAssignment(String),
+    /// An operand does not fit its encoding, e.g. more than 65536
+    /// constants or 256 locals in one function.
+    TooMany(&'static str),
+    UnsupportedNode(String),
+}
+
+impl fmt::Display for CompileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CompileError::UndefinedVariable(name) => write!(f, "identifier not found: {}", name),
+            CompileError::UndeclaredAssignment(name) => {
+                write!(f, "assignment to undeclared variable: {}", name)
+            }
+            CompileError::CapturedAssignment(name) => {
+                write!(f, "cannot assign to captured variable: {}", name)
+            }
+            CompileError::TooMany(what) => write!(f, "too many {} in one program", what),
+            CompileError::UnsupportedNode(node) => write!(f, "cannot compile: {}", node),
+        }
+    }
+}
+
+impl std::error::Error for CompileError {}
+
+/// The output of [`Compiler::compile`]: the top-level code and the
+/// constants every function refers to by index.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bytecode {
+    pub main: CompiledFunction,
+    pub constants: Vec<Object>,
+    /// The names of the global variables, by index.
+    pub globals: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolScope {
+    Global,
+    Local,
+    Builtin,
+    Free,
+    /// The function currently being compiled, referring to itself.
+    Function,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+    pub name: String,
+    pub scope: SymbolScope,
+    pub index: usize,
+}
+
+/// The names visible in one function body, chained to the enclosing
+/// function's table. Resolving a local of an enclosing function records it
+/// as a free variable of this one.
+#[derive(Debug, Default)]
+pub struct SymbolTable {
+    outer: Option<Box<SymbolTable>>,
+    store: HashMap<String, Symbol>,
+    num_definitions: usize,
+    pub free_symbols: Vec<Symbol>,
+}
+
+impl SymbolTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn new_enclosed(outer: SymbolTable) -> Self {
+        Self {
+            outer: Some(Box::new(outer)),
+            ..Self::default()
+        }
+    }
+
+    /// Declares `name` in this scope. Declaring a name twice reuses its
+    /// slot.
+    pub fn define(&mut self, name: &str) -> Symbol {
+        if let Some(symbol) = self.store.get(name) {
+            if matches!(symbol.scope, SymbolScope::Global | SymbolScope::Local) {
+                return symbol.clone();
+            }
+        }
+
+        let scope = match self.outer {
+            Some(_) => SymbolScope::Local,
+            None => SymbolScope::Global,
+        };
+        let symbol = Symbol {
+            name: name.to_string(),
+            scope,
+            index: self.num_definitions,
+        };
+        self.num_definitions += 1;
+        self.store.insert(name.to_string(), symbol.clone());
+        symbol
+    }
+
+    pub fn define_builtin(&mut self, index: usize, name: &str) -> Symbol {
+        let symbol = Symbol {
+            name: name.to_string(),
+            scope: SymbolScope::Builtin,
+            index,
+        };
+        self.store.insert(name.to_string(), symbol.clone());
+        symbol
+    }
+
+    pub fn define_function_name(&mut self, name: &str) -> Symbol {
+        let symbol = Symbol {
+            name: name.to_string(),
+            scope: SymbolScope::Function,
+            index: 0,
+        };
+        self.store.insert(name.to_string(), symbol.clone());
+        symbol
+    }
+
+    fn define_free(&mut self, original: Symbol) -> Symbol {
+        let symbol = Symbol {
+            name: original.name.clone(),
+            scope: SymbolScope::Free,
+            index: self.free_symbols.len(),
+        };
+        self.free_symbols.push(original);
+        self.store.insert(symbol.name.clone(), symbol.clone());
+        symbol
+    }
+
+    pub fn resolve(&mut self, name: &str) -> Option<Symbol> {
+        if let Some(symbol) = self.store.get(name) {
+            return Some(symbol.clone());
+        }
+
+        let symbol = self.outer.as_mut()?.resolve(name)?;
+        match symbol.scope {
+            SymbolScope::Global | SymbolScope::Builtin => Some(symbol),
+            _ => Some(self.define_free(symbol)),
+        }
+    }
+
+    /// Like [`SymbolTable::resolve`], but without capturing anything.
+    pub fn lookup(&self, name: &str) -> Option<&Symbol> {
+        match self.store.get(name) {
+            Some(symbol) => Some(symbol),
+            None => self.outer.as_ref()?.lookup(name),
+        }
+    }
+
+    pub fn num_definitions(&self) -> usize {
+        self.num_definitions
+    }
+
+    /// The names declared in this scope, by index.
+    pub fn names(&self) -> Vec<String> {
+        let mut names = vec![String::new(); self.num_definitions];
+        for symbol in self.store.values() {
+            if matches!(symbol.scope, SymbolScope::Global | SymbolScope::Local) {
+                names[symbol.index] = symbol.name.clone();
+            }
+        }
+        names
+    }
+}
+
+#[derive(Debug, Default)]
+struct CompilationScope {
+    instructions: Instructions,
+    positions: Vec<(usize, Span)>,
+}
+
+/// Lowers a [`Program`] to [`Bytecode`] for the [`crate::vm::Vm`].
+///
+/// Variables are resolved at compile time. Names assigned anywhere in a
+/// function body (outside nested functions) are declared on entry, as are
+/// all top-level `let`s, so loops and mutually recursive functions can
+/// refer to them before the assignment. Reading one before it is assigned
+/// is the evaluator's undefined-variable error, raised at run time.
+///
+/// Unlike the evaluator's functions, a closure copies the locals of the
+/// functions around it when it is created, so those must be assigned by
+/// then, later assignments to them go unseen, and the closure itself may
+/// not assign them ([`CompileError::CapturedAssignment`]).
+#[derive(Debug)]
+pub struct Compiler {
+    constants: Vec<Object>,
+    symbol_table: SymbolTable,
+    scopes: Vec<CompilationScope>,
+    strict: bool,
+    loop_temporaries: usize,
+}
+
+impl Default for Compiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Compiler {
+    pub fn new() -> Self {
+        let mut symbol_table = SymbolTable::new();
+        for (index, name) in BUILTINS.iter().enumerate() {
+            symbol_table.define_builtin(index, name);
+        }
+
+        Self {
+            constants: Vec::new(),
+            symbol_table,
+            scopes: vec![CompilationScope::default()],
+            strict: false,
+            loop_temporaries: 0,
+        }
+    }
+
+    /// In strict mode only `let` declares a variable and assigning to
+    /// anything else is an error, as in the evaluator.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    pub fn compile(mut self, program: &Program) -> Result<Bytecode, CompileError> {
+        let mut names = Vec::new();
+        collect_declared(&program.statements, !self.strict, true, &mut names);
+        for name in names {
+            self.symbol_table.define(&name);
+        }
+
+        for statement in &program.statements {
+            self.compile_statement(&*statement.as_ref().borrow())?;
+        }
+
+        let globals = self.symbol_table.names();
+        if globals.len() > u16::MAX as usize + 1 {
+            return Err(CompileError::TooMany("global variables"));
+        }
+
+        let scope = self.scopes.pop().unwrap_or_default();
+        Ok(Bytecode {
+            main: CompiledFunction {
+                name: None,
+                instructions: scope.instructions,
+                locals: Vec::new(),
+                num_parameters: 0,
+                positions: scope.positions,
+            },
+            constants: self.constants,
+            globals,
+        })
+    }
+
+    fn compile_statement(&mut self, statement: &dyn Statement) -> Result<(), CompileError> {
+        let any = statement.as_any();
+        self.record_position(statement.span());
+
+        if let Some(statement) = any.downcast_ref::<ExpressionStatement>() {
+            self.compile_optional(&statement.expression)?;
+            self.emit(Opcode::Pop, &[]);
+        } else if let Some(statement) = any.downcast_ref::<LetStatement>() {
+            let Some(name) = &statement.name else {
+                return Err(CompileError::UnsupportedNode(statement.string()));
+            };
+            match &statement.value {
+                Some(value) => {
+                    self.compile_value(&*value.as_ref().borrow(), Some(&name.value))?;
+                }
+                None => {
+                    self.emit(Opcode::Null, &[]);
+                }
+            }
+            let symbol = self.symbol_table.define(&name.value);
+            self.store_symbol(&symbol)?;
+        } else if let Some(statement) = any.downcast_ref::<AssignStatement>() {
+            let name = &statement.name.value;
+            if let Some(operator) = compound_operator(&statement.token.token_type) {
+                self.load_identifier(name)?;
+                self.compile_optional(&statement.value)?;
+                self.record_position(statement.span());
+                self.emit(operator, &[]);
+            } else {
+                self.compile_optional(&statement.value)?;
+            }
+            self.store_identifier(name)?;
+        } else if let Some(statement) = any.downcast_ref::<IndexAssignStatement>() {
+            self.compile_index_assign_statement(statement)?;
+        } else if let Some(statement) = any.downcast_ref::<ReturnStatement>() {
+            match &statement.return_value {
+                Some(value) => self.compile_expression(&*value.as_ref().borrow())?,
+                None => {
+                    self.emit(Opcode::Null, &[]);
+                }
+            }
+            self.emit(Opcode::ReturnValue, &[]);
+        } else if let Some(block) = any.downcast_ref::<BlockStatement>() {
+            self.compile_block(block)?;
+        } else if let Some(statement) = any.downcast_ref::<IfStatement>() {
+            self.compile_optional(&statement.condition)?;
+            let jump_not_truthy = self.emit(Opcode::JumpNotTruthy, &[0]);
+            self.compile_block(&statement.consequence)?;
+
+            match &statement.alternative {
+                Some(alternative) => {
+                    let jump = self.emit(Opcode::Jump, &[0]);
+                    self.patch_jump(jump_not_truthy)?;
+                    self.compile_block(alternative)?;
+                    self.patch_jump(jump)?;
+                }
+                None => self.patch_jump(jump_not_truthy)?,
+            }
+        } else if let Some(statement) = any.downcast_ref::<WhileStatement>() {
+            let start = self.current_instructions().len();
+            self.compile_optional(&statement.condition)?;
+            let exit = self.emit(Opcode::JumpNotTruthy, &[0]);
+            self.compile_block(&statement.body)?;
+            self.emit_jump(Opcode::Jump, start)?;
+            self.patch_jump(exit)?;
+        } else if let Some(statement) = any.downcast_ref::<RepeatStatement>() {
+            let start = self.current_instructions().len();
+            self.compile_block(&statement.body)?;
+            self.compile_optional(&statement.condition)?;
+            self.emit_jump(Opcode::JumpNotTruthy, start)?;
+        } else if let Some(statement) = any.downcast_ref::<ForStatement>() {
+            self.compile_for_statement(statement)?;
+        } else if let Some(statement) = any.downcast_ref::<ReadStatement>() {
+            for target in &statement.targets {
+                self.compile_read_target(&*target.as_ref().borrow())?;
+            }
+        } else if let Some(statement) = any.downcast_ref::<WriteStatement>() {
+            for argument in &statement.arguments {
+                self.compile_expression(&*argument.as_ref().borrow())?;
+            }
+            if statement.arguments.len() > u8::MAX as usize {
+                return Err(CompileError::TooMany("values to write"));
+            }
+            self.record_position(statement.token.span);
+            self.emit(Opcode::Write, &[statement.arguments.len()]);
+        } else {
+            return Err(CompileError::UnsupportedNode(statement.string()));
+        }
+
+        Ok(())
+    }
+
+    fn compile_block(&mut self, block: &BlockStatement) -> Result<(), CompileError> {
+        for statement in &block.statements {
+            self.compile_statement(&*statement.as_ref().borrow())?;
+        }
+        Ok(())
+    }
+
+    fn compile_index_assign_statement(
+        &mut self,
+        statement: &IndexAssignStatement,
+    ) -> Result<(), CompileError> {
+        let target = statement.target.as_ref().borrow();
+        let Some(target) = target.as_any().downcast_ref::<IndexExpression>() else {
+            return Err(CompileError::UnsupportedNode(statement.string()));
+        };
+
+        self.compile_optional(&target.left)?;
+        self.compile_optional(&target.index)?;
+        if let Some(operator) = compound_operator(&statement.token.token_type) {
+            self.emit(Opcode::DupPair, &[]);
+            self.record_position(target.token.span);
+            self.emit(Opcode::Index, &[]);
+            self.compile_optional(&statement.value)?;
+            self.record_position(target.token.span);
+            self.emit(operator, &[]);
+        } else {
+            self.compile_optional(&statement.value)?;
+        }
+        self.record_position(target.token.span);
+        self.emit(Opcode::SetIndex, &[]);
+        Ok(())
+    }
+
+    /// The bounds and step live in hidden variables so they are evaluated
+    /// once, matching the evaluator.
+    fn compile_for_statement(&mut self, statement: &ForStatement) -> Result<(), CompileError> {
+        self.compile_optional(&statement.start)?;
+        self.compile_optional(&statement.end)?;
+        match &statement.step {
+            Some(step) => self.compile_expression(&*step.as_ref().borrow())?,
+            None => {
+                let constant = self.add_constant(Object::Integer(1))?;
+                self.emit(Opcode::Constant, &[constant]);
+            }
+        }
+
+        self.loop_temporaries += 1;
+        let step = self
+            .symbol_table
+            .define(&format!("pentru#{}#step", self.loop_temporaries));
+        let end = self
+            .symbol_table
+            .define(&format!("pentru#{}#end", self.loop_temporaries));
+        self.store_symbol(&step)?;
+        self.store_symbol(&end)?;
+        let counter = &statement.counter.value;
+        self.store_identifier(counter)?;
+
+        let start = self.current_instructions().len();
+        self.load_identifier(counter)?;
+        self.load_symbol(&end);
+        self.load_symbol(&step);
+        self.record_position(statement.token.span);
+        self.emit(Opcode::ForCondition, &[]);
+        let exit = self.emit(Opcode::JumpNotTruthy, &[0]);
+
+        self.compile_block(&statement.body)?;
+
+        self.load_identifier(counter)?;
+        self.load_symbol(&step);
+        self.record_position(statement.token.span);
+        self.emit(Opcode::Add, &[]);
+        self.store_identifier(counter)?;
+        self.emit_jump(Opcode::Jump, start)?;
+        self.patch_jump(exit)?;
+        Ok(())
+    }
+
+    fn compile_read_target(&mut self, target: &dyn Expression) -> Result<(), CompileError> {
+        let any = target.as_any();
+
+        if let Some(identifier) = any.downcast_ref::<Identifier>() {
+            self.record_position(identifier.token.span);
+            self.emit(Opcode::Read, &[]);
+            self.store_identifier(&identifier.value)
+        } else if let Some(target) = any.downcast_ref::<IndexExpression>() {
+            self.compile_optional(&target.left)?;
+            self.compile_optional(&target.index)?;
+            self.record_position(target.token.span);
+            self.emit(Opcode::Read, &[]);
+            self.record_position(target.token.span);
+            self.emit(Opcode::SetIndex, &[]);
+            Ok(())
+        } else {
+            Err(CompileError::UnsupportedNode(target.string()))
+        }
+    }
+
+    fn compile_optional(
+        &mut self,
+        expression: &Option<Rc<RefCell<dyn Expression>>>,
+    ) -> Result<(), CompileError> {
+        match expression {
+            Some(expression) => self.compile_expression(&*expression.as_ref().borrow()),
+            None => {
+                self.emit(Opcode::Null, &[]);
+                Ok(())
+            }
+        }
+    }
+
+    fn compile_expression(&mut self, expression: &dyn Expression) -> Result<(), CompileError> {
+        self.compile_value(expression, None)
+    }
+
+    /// Compiles `expression`; a function literal bound by `let` is told
+    /// its name so it can call itself.
+    fn compile_value(
+        &mut self,
+        expression: &dyn Expression,
+        binding: Option<&str>,
+    ) -> Result<(), CompileError> {
+        let any = expression.as_any();
+
+        if let Some(literal) = any.downcast_ref::<IntegralLiteral>() {
+            let constant = self.add_constant(Object::from(&literal.value))?;
+            self.emit(Opcode::Constant, &[constant]);
+        } else if let Some(literal) = any.downcast_ref::<RealLiteral>() {
+            let constant = self.add_constant(Object::Real(literal.value))?;
+            self.emit(Opcode::Constant, &[constant]);
+        } else if let Some(literal) = any.downcast_ref::<StringLiteral>() {
+            let constant = self.add_constant(Object::Str(literal.value.clone()))?;
+            self.emit(Opcode::Constant, &[constant]);
+        } else if let Some(literal) = any.downcast_ref::<BooleanLiteral>() {
+            let op = if literal.value {
+                Opcode::True
+            } else {
+                Opcode::False
+            };
+            self.emit(op, &[]);
+        } else if let Some(array) = any.downcast_ref::<ArrayLiteral>() {
+            for element in &array.elements {
+                self.compile_expression(&*element.as_ref().borrow())?;
+            }
+            if array.elements.len() > u16::MAX as usize {
+                return Err(CompileError::TooMany("array elements"));
+            }
+            self.record_position(array.token.span);
+            self.emit(Opcode::Array, &[array.elements.len()]);
+        } else if let Some(map) = any.downcast_ref::<MapLiteral>() {
+            for (key, value) in &map.pairs {
+                self.compile_expression(&*key.as_ref().borrow())?;
+                self.compile_expression(&*value.as_ref().borrow())?;
+            }
+            if map.pairs.len() > u16::MAX as usize {
+                return Err(CompileError::TooMany("map entries"));
+            }
+            self.record_position(map.token.span);
+            self.emit(Opcode::Map, &[map.pairs.len()]);
+        } else if let Some(index) = any.downcast_ref::<IndexExpression>() {
+            self.compile_optional(&index.left)?;
+            self.compile_optional(&index.index)?;
+            self.record_position(index.token.span);
+            self.emit(Opcode::Index, &[]);
+        } else if let Some(literal) = any.downcast_ref::<FunctionLiteral>() {
+            let name = literal.name.as_deref().or(binding);
+            self.compile_function(literal, name)?;
+        } else if let Some(call) = any.downcast_ref::<CallExpression>() {
+            self.compile_optional(&call.function)?;
+            for argument in &call.arguments {
+                self.compile_expression(&*argument.as_ref().borrow())?;
+            }
+            if call.arguments.len() > u8::MAX as usize {
+                return Err(CompileError::TooMany("arguments"));
+            }
+            self.record_position(call.token.span);
+            self.emit(Opcode::Call, &[call.arguments.len()]);
+        } else if let Some(identifier) = any.downcast_ref::<Identifier>() {
+            self.record_position(identifier.token.span);
+            self.load_identifier(&identifier.value)?;
+        } else if let Some(prefix) = any.downcast_ref::<PrefixExpression>() {
+            self.compile_optional(&prefix.right)?;
+            self.record_position(prefix.token.span);
+            match prefix.operator.as_str() {
+                "-" => self.emit(Opcode::Minus, &[]),
+                "!" => self.emit(Opcode::Bang, &[]),
+                _ => return Err(CompileError::UnsupportedNode(expression.string())),
+            };
+        } else if let Some(integer_part) = any.downcast_ref::<IntegerPartExpression>() {
+            self.compile_optional(&integer_part.value)?;
+            self.record_position(integer_part.token.span);
+            self.emit(Opcode::IntegerPart, &[]);
+        } else if let Some(infix) = any.downcast_ref::<InfixExpression>() {
+            match infix.operator.as_str() {
+                "&&" | "||" => self.compile_logical_expression(infix)?,
+                operator => {
+                    let Some(op) = Opcode::from_infix_operator(operator) else {
+                        return Err(CompileError::UnsupportedNode(expression.string()));
+                    };
+                    self.compile_optional(&infix.left)?;
+                    self.compile_optional(&infix.right)?;
+                    self.record_position(infix.token.span);
+                    self.emit(op, &[]);
+                }
+            }
+        } else {
+            return Err(CompileError::UnsupportedNode(expression.string()));
+        }
+
+        Ok(())
+    }
+
+    /// `a && b` and `a || b` jump over `b` when `a` decides the result.
+    /// `b` is normalised to a boolean with a double `Bang`.
+    fn compile_logical_expression(&mut self, infix: &InfixExpression) -> Result<(), CompileError> {
+        self.compile_optional(&infix.left)?;
+        let jump_not_truthy = self.emit(Opcode::JumpNotTruthy, &[0]);
+
+        if infix.operator == "&&" {
+            self.compile_optional(&infix.right)?;
+            self.emit(Opcode::Bang, &[]);
+            self.emit(Opcode::Bang, &[]);
+            let jump = self.emit(Opcode::Jump, &[0]);
+            self.patch_jump(jump_not_truthy)?;
+            self.emit(Opcode::False, &[]);
+            self.patch_jump(jump)?;
+        } else {
+            self.emit(Opcode::True, &[]);
+            let jump = self.emit(Opcode::Jump, &[0]);
+            self.patch_jump(jump_not_truthy)?;
+            self.compile_optional(&infix.right)?;
+            self.emit(Opcode::Bang, &[]);
+            self.emit(Opcode::Bang, &[]);
+            self.patch_jump(jump)?;
+        }
+
+        Ok(())
+    }
+
+    fn compile_function(
+        &mut self,
+        literal: &FunctionLiteral,
+        name: Option<&str>,
+    ) -> Result<(), CompileError> {
+        self.enter_scope();
+
+        // Top-level functions already reach themselves through their
+        // global; nested ones need the closure being run.
+        if let Some(name) = name {
+            let global = matches!(
+                self.symbol_table.lookup(name),
+                Some(Symbol {
+                    scope: SymbolScope::Global,
+                    ..
+                })
+            );
+            if !global {
+                self.symbol_table.define_function_name(name);
+            }
+        }
+        for parameter in &literal.parameters {
+            self.symbol_table.define(&parameter.value);
+        }
+
+        if !self.strict {
+            let mut names = Vec::new();
+            collect_declared(&literal.body.statements, true, false, &mut names);
+            for name in names {
+                let declared = self
+                    .symbol_table
+                    .lookup(&name)
+                    .is_some_and(|symbol| symbol.scope != SymbolScope::Builtin);
+                if !declared {
+                    self.symbol_table.define(&name);
+                }
+            }
+        }
+
+        self.compile_block(&literal.body)?;
+        self.emit(Opcode::Return, &[]);
+
+        let locals = self.symbol_table.names();
+        if locals.len() > u8::MAX as usize + 1 {
+            return Err(CompileError::TooMany("local variables"));
+        }
+        let (scope, free_symbols) = self.leave_scope();
+
+        for symbol in &free_symbols {
+            self.load_symbol(symbol);
+        }
+        if free_symbols.len() > u8::MAX as usize {
+            return Err(CompileError::TooMany("captured variables"));
+        }
+
+        let function = CompiledFunction {
+            name: name.map(str::to_string),
+            instructions: scope.instructions,
+            locals,
+            num_parameters: literal.parameters.len(),
+            positions: scope.positions,
+        };
+        let constant = self.add_constant(Object::CompiledFunction(Rc::new(function)))?;
+        self.emit(Opcode::Closure, &[constant, free_symbols.len()]);
+        Ok(())
+    }
+
+    fn load_identifier(&mut self, name: &str) -> Result<(), CompileError> {
+        let symbol = self
+            .symbol_table
+            .resolve(name)
+            .ok_or_else(|| CompileError::UndefinedVariable(name.to_string()))?;
+        self.load_symbol(&symbol);
+        Ok(())
+    }
+
+    fn load_symbol(&mut self, symbol: &Symbol) {
+        match symbol.scope {
+            SymbolScope::Global => self.emit(Opcode::GetGlobal, &[symbol.index]),
+            SymbolScope::Local => self.emit(Opcode::GetLocal, &[symbol.index]),
+            SymbolScope::Builtin => self.emit(Opcode::GetBuiltin, &[symbol.index]),
+            SymbolScope::Free => self.emit(Opcode::GetFree, &[symbol.index]),
+            SymbolScope::Function => self.emit(Opcode::CurrentClosure, &[]),
+        };
+    }
+
+    fn store_identifier(&mut self, name: &str) -> Result<(), CompileError> {
+        let symbol = match self.symbol_table.resolve(name) {
+            Some(symbol) if symbol.scope != SymbolScope::Builtin => symbol,
+            _ if self.strict => return Err(CompileError::UndeclaredAssignment(name.to_string())),
+            _ => self.symbol_table.define(name),
+        };
+        self.store_symbol(&symbol)
+    }
+
+    fn store_symbol(&mut self, symbol: &Symbol) -> Result<(), CompileError> {
+        match symbol.scope {
+            SymbolScope::Global => self.emit(Opcode::SetGlobal, &[symbol.index]),
+            SymbolScope::Local => self.emit(Opcode::SetLocal, &[symbol.index]),
+            _ => return Err(CompileError::CapturedAssignment(symbol.name.clone())),
+        };
+        Ok(())
+    }
+
+    fn add_constant(&mut self, object: Object) -> Result<usize, CompileError> {
+        if self.constants.len() > u16::MAX as usize {
+            return Err(CompileError::TooMany("constants"));
+        }
+        self.constants.push(object);
+        Ok(self.constants.len() - 1)
+    }
+
+    /// Appends an instruction and returns its offset.
+    fn emit(&mut self, op: Opcode, operands: &[usize]) -> usize {
+        let instruction = code::make(op, operands);
+        let instructions = &mut self.current_scope().instructions;
+        let position = instructions.len();
+        instructions.extend_from_slice(&instruction);
+        position
+    }
+
+    fn emit_jump(&mut self, op: Opcode, target: usize) -> Result<(), CompileError> {
+        let target = jump_target(target)?;
+        self.emit(op, &[target]);
+        Ok(())
+    }
+
+    /// Points the jump at `position` to the next instruction emitted.
+    fn patch_jump(&mut self, position: usize) -> Result<(), CompileError> {
+        let target = jump_target(self.current_instructions().len())?;
+        let operand = (target as u16).to_be_bytes();
+        self.current_scope().instructions[position + 1..position + 3].copy_from_slice(&operand);
+        Ok(())
+    }
+
+    /// Maps the next instruction emitted back to `span`.
+    fn record_position(&mut self, span: Span) {
+        let scope = self.current_scope();
+        let offset = scope.instructions.len();
+        match scope.positions.last_mut() {
+            Some((last, last_span)) if *last == offset => *last_span = span,
+            _ => scope.positions.push((offset, span)),
+        }
+    }
+
+    fn current_scope(&mut self) -> &mut CompilationScope {
+        self.scopes.last_mut().expect("compiler has no scope")
+    }
+
+    fn current_instructions(&self) -> &Instructions {
+        &self
+            .scopes
+            .last()
+            .expect("compiler has no scope")
+            .instructions
+    }
+
+    fn enter_scope(&mut self) {
+        self.scopes.push(CompilationScope::default());
+        let outer = std::mem::take(&mut self.symbol_table);
+        self.symbol_table = SymbolTable::new_enclosed(outer);
+    }
+
+    fn leave_scope(&mut self) -> (CompilationScope, Vec<Symbol>) {
+        let scope = self.scopes.pop().unwrap_or_default();
+        let table = std::mem::take(&mut self.symbol_table);
+        self.symbol_table = table.outer.map(|outer| *outer).unwrap_or_default();
+        (scope, table.free_symbols)
+    }
+}
+
+fn compound_operator(token_type: &TokenType) -> Option<Opcode> {
+    match token_type {
+        TokenType::PlusAssign => Some(Opcode::Add),
+        TokenType::MinusAssign => Some(Opcode::Sub),
+        TokenType::AsteriskAssign => Some(Opcode::Mul),
+        TokenType::SlashAssign => Some(Opcode::Div),
+        _ => None,
+    }
+}
+
+fn jump_target(target: usize) -> Result<usize, CompileError> {
+    if target > u16::MAX as usize {
+        return Err(CompileError::TooMany("instructions"));
+    }
+    Ok(target)
+}
+
+/// Collects the names a body declares, without looking inside nested
+/// functions: assignment targets if `assigned` and `let` names if `lets`.
+fn collect_declared(
+    statements: &[Rc<RefCell<dyn Statement>>],
+    assigned: bool,
+    lets: bool,
+    names: &mut Vec<String>,
+) {
+    fn add(name: &str, names: &mut Vec<String>) {
+        if !names.iter().any(|n| n == name) {
+            names.push(name.to_string());
+        }
+    }
+
+    for statement in statements {
+        let statement = statement.as_ref().borrow();
+        let any = statement.as_any();
+
+        if let Some(statement) = any.downcast_ref::<LetStatement>() {
+            if let (true, Some(name)) = (lets, &statement.name) {
+                add(&name.value, names);
+            }
+        } else if let Some(statement) = any.downcast_ref::<AssignStatement>() {
+            if assigned {
+                add(&statement.name.value, names);
+            }
+        } else if let Some(statement) = any.downcast_ref::<ReadStatement>() {
+            for target in &statement.targets {
+                let target = target.as_ref().borrow();
+                if let (true, Some(identifier)) =
+                    (assigned, target.as_any().downcast_ref::<Identifier>())
+                {
+                    add(&identifier.value, names);
+                }
+            }
+        } else if let Some(block) = any.downcast_ref::<BlockStatement>() {
+            collect_declared(&block.statements, assigned, lets, names);
+        } else if let Some(statement) = any.downcast_ref::<IfStatement>() {
+            collect_declared(&statement.consequence.statements, assigned, lets, names);
+            if let Some(alternative) = &statement.alternative {
+                collect_declared(&alternative.statements, assigned, lets, names);
+            }
+        } else if let Some(statement) = any.downcast_ref::<WhileStatement>() {
+            collect_declared(&statement.body.statements, assigned, lets, names);
+        } else if let Some(statement) = any.downcast_ref::<RepeatStatement>() {
+            collect_declared(&statement.body.statements, assigned, lets, names);
+        } else if let Some(statement) = any.downcast_ref::<ForStatement>() {
+            if assigned {
+                add(&statement.counter.value, names);
+            }
+            collect_declared(&statement.body.statements, assigned, lets, names);
+        }
+    }
+}
@@ -0,0 +1,215 @@
+use crate::code::{disassemble, make, Instructions, Opcode};
+use crate::object::Object;
+use crate::test_support::parse;
+
+use super::{Bytecode, CompileError, Compiler, SymbolScope, SymbolTable};
+
+fn compile_with(compiler: Compiler, input: &str) -> Result<Bytecode, CompileError> {
+    compiler.compile(&parse(input))
+}
+
+fn compile(input: &str) -> Bytecode {
+    compile_with(Compiler::new(), input).unwrap()
+}
+
+fn instructions(parts: &[(Opcode, &[usize])]) -> Instructions {
+    parts
+        .iter()
+        .flat_map(|(op, operands)| make(*op, operands))
+        .collect()
+}
+
+fn assert_instructions(actual: &[u8], expected: &[(Opcode, &[usize])]) {
+    assert_eq!(disassemble(actual), disassemble(&instructions(expected)),);
+}
+
+#[test]
+fn arithmetic_and_constants() {
+    let bytecode = compile("1 + 2 * 3; -4.5; [7]");
+
+    assert_eq!(
+        bytecode.constants,
+        vec![
+            Object::Integer(1),
+            Object::Integer(2),
+            Object::Integer(3),
+            Object::Real(4.5),
+            Object::Integer(7),
+        ]
+    );
+    assert_instructions(
+        &bytecode.main.instructions,
+        &[
+            (Opcode::Constant, &[0]),
+            (Opcode::Constant, &[1]),
+            (Opcode::Constant, &[2]),
+            (Opcode::Mul, &[]),
+            (Opcode::Add, &[]),
+            (Opcode::Pop, &[]),
+            (Opcode::Constant, &[3]),
+            (Opcode::Minus, &[]),
+            (Opcode::Pop, &[]),
+            (Opcode::Constant, &[4]),
+            (Opcode::IntegerPart, &[]),
+            (Opcode::Pop, &[]),
+        ],
+    );
+}
+
+#[test]
+fn globals_and_loops() {
+    let bytecode = compile("x ← 1; cat timp x < 5 { x += 1 }");
+
+    assert_eq!(bytecode.globals, ["x"]);
+    assert_instructions(
+        &bytecode.main.instructions,
+        &[
+            (Opcode::Constant, &[0]),
+            (Opcode::SetGlobal, &[0]),
+            (Opcode::GetGlobal, &[0]),
+            (Opcode::Constant, &[1]),
+            (Opcode::LessThan, &[]),
+            (Opcode::JumpNotTruthy, &[29]),
+            (Opcode::GetGlobal, &[0]),
+            (Opcode::Constant, &[2]),
+            (Opcode::Add, &[]),
+            (Opcode::SetGlobal, &[0]),
+            (Opcode::Jump, &[6]),
+        ],
+    );
+}
+
+#[test]
+fn if_statements() {
+    let bytecode = compile("daca true { 1 } altfel { 2 }");
+
+    assert_instructions(
+        &bytecode.main.instructions,
+        &[
+            (Opcode::True, &[]),
+            (Opcode::JumpNotTruthy, &[11]),
+            (Opcode::Constant, &[0]),
+            (Opcode::Pop, &[]),
+            (Opcode::Jump, &[15]),
+            (Opcode::Constant, &[1]),
+            (Opcode::Pop, &[]),
+        ],
+    );
+}
+
+#[test]
+fn closures_capture_free_variables() {
+    let bytecode = compile("fn(a) { return fn(b) { return a + b; }; }");
+
+    let Object::CompiledFunction(inner) = &bytecode.constants[0] else {
+        panic!(
+            "expected a compiled function, got {:?}",
+            bytecode.constants[0]
+        );
+    };
+    assert_instructions(
+        &inner.instructions,
+        &[
+            (Opcode::GetFree, &[0]),
+            (Opcode::GetLocal, &[0]),
+            (Opcode::Add, &[]),
+            (Opcode::ReturnValue, &[]),
+            (Opcode::Return, &[]),
+        ],
+    );
+
+    let Object::CompiledFunction(outer) = &bytecode.constants[1] else {
+        panic!(
+            "expected a compiled function, got {:?}",
+            bytecode.constants[1]
+        );
+    };
+    assert_eq!(outer.locals, ["a"]);
+    assert_eq!(outer.num_parameters, 1);
+    assert_instructions(
+        &outer.instructions,
+        &[
+            (Opcode::GetLocal, &[0]),
+            (Opcode::Closure, &[0, 1]),
+            (Opcode::ReturnValue, &[]),
+            (Opcode::Return, &[]),
+        ],
+    );
+}
+
+#[test]
+fn nested_functions_call_themselves_through_the_current_closure() {
+    let bytecode = compile("fn() { let f = fn(n) { f(n - 1) }; }");
+
+    let Object::CompiledFunction(f) = &bytecode.constants[1] else {
+        panic!(
+            "expected a compiled function, got {:?}",
+            bytecode.constants[1]
+        );
+    };
+    assert_eq!(f.name.as_deref(), Some("f"));
+    assert_eq!(f.instructions[0], Opcode::CurrentClosure as u8);
+}
+
+#[test]
+fn positions_map_instructions_to_spans() {
+    let bytecode = compile("let a = [1, 2];\nscrie a[5]");
+
+    let lines: Vec<(usize, usize, usize)> = bytecode
+        .main
+        .positions
+        .iter()
+        .map(|(offset, span)| (*offset, span.line, span.column))
+        .collect();
+    assert_eq!(
+        lines,
+        vec![(0, 1, 1), (6, 1, 9), (12, 2, 7), (18, 2, 8), (19, 2, 1)]
+    );
+}
+
+#[test]
+fn compile_errors() {
+    let tests = vec![
+        ("x + 1", CompileError::UndefinedVariable("x".to_string())),
+        (
+            "fn(a) { fn() { a ← 1; } }",
+            CompileError::CapturedAssignment("a".to_string()),
+        ),
+    ];
+
+    for (input, expected) in tests {
+        assert_eq!(
+            compile_with(Compiler::new(), input),
+            Err(expected),
+            "{}",
+            input
+        );
+    }
+
+    assert_eq!(
+        compile_with(Compiler::new().strict(true), "let a = 1; b ← a"),
+        Err(CompileError::UndeclaredAssignment("b".to_string()))
+    );
+}
+
+#[test]
+fn symbol_table_resolves_through_scopes() {
+    let mut global = SymbolTable::new();
+    global.define_builtin(0, "len");
+    let a = global.define("a");
+    assert_eq!(a.scope, SymbolScope::Global);
+    assert_eq!(global.define("a"), a);
+
+    let mut first = SymbolTable::new_enclosed(global);
+    first.define("b");
+    let mut second = SymbolTable::new_enclosed(first);
+    let c = second.define("c");
+    assert_eq!((c.scope, c.index), (SymbolScope::Local, 0));
+
+    let resolved = second.resolve("b").unwrap();
+    assert_eq!((resolved.scope, resolved.index), (SymbolScope::Free, 0));
+    assert_eq!(second.resolve("a").unwrap().scope, SymbolScope::Global);
+    assert_eq!(second.resolve("len").unwrap().scope, SymbolScope::Builtin);
+    assert_eq!(second.free_symbols.len(), 1);
+    assert!(second.resolve("d").is_none());
+}
@@ -0,0 +1,499 @@
+use std::cell::RefCell;
+use std::fs;
+use std::io::{self, BufRead, Cursor, Write};
+use std::mem;
+use std::rc::Rc;
+
+use crate::ast::Program;
+use crate::debugger::{Breakpoints, Controller, Debugger, Reason, Resume, Stop};
+use crate::evaluator::{Evaluator, RuntimeError, STACK_SIZE};
+use crate::json::{read_message, write_message, Json};
+use crate::lexer::Lexer;
+use crate::object::Object;
+use crate::parser::Parser;
+
+#[cfg(test)]
+mod tests;
+
+/// The only thread a program runs on.
+const THREAD: i64 = 1;
+
+/// Serves one debugging session: the client launches a program, sets
+/// breakpoints, and steps through it. `citeste` reads from the `input`
+/// launch argument and `scrie` output is sent as `output` events.
+/// Programs may recurse as deeply as a thread with [`STACK_SIZE`] of stack
+/// allows, so call it on such a thread.
+pub fn run<R, W>(input: R, output: W) -> io::Result<()>
+where
+    R: BufRead + 'static,
+    W: Write + 'static,
+{
+    let session = Rc::new(RefCell::new(Session::new(input, output)));
+    loop {
+        let Some(request) = session.borrow_mut().read()? else {
+            return Ok(());
+        };
+        let mut session_ = session.borrow_mut();
+        let mut breakpoints = mem::take(&mut session_.breakpoints);
+        let action = session_.handle(&request, None, &mut breakpoints);
+        session_.breakpoints = breakpoints;
+        drop(session_);
+
+        match action? {
+            Action::Configured => {
+                if !launch(&session)? {
+                    return Ok(());
+                }
+            }
+            Action::Disconnect => return Ok(()),
+            Action::None | Action::Resume(_) => {}
+        }
+    }
+}
+
+/// What a request asks of the session beyond its response.
+enum Action {
+    None,
+    /// The client is done configuring; the program can run.
+    Configured,
+    Resume(Resume),
+    Disconnect,
+}
+
+struct Session<R, W> {
+    input: R,
+    output: W,
+    seq: i64,
+    launched: Option<Launched>,
+    breakpoints: Breakpoints,
+    /// Variables the client can expand while the program is stopped,
+    /// indexed by `variablesReference - 1`.
+    references: Vec<Vec<(String, Object)>>,
+}
+
+/// The program given by `launch`, waiting for `configurationDone`.
+struct Launched {
+    path: String,
+    program: Option<Program>,
+    input: String,
+    stop_on_entry: bool,
+}
+
+/// Lets the client drive a [`Debugger`] while the program is stopped.
+struct Adapter<R, W>(Rc<RefCell<Session<R, W>>>);
+
+/// Sends what the program writes as `output` events, a line at a time.
+struct ProgramOutput<R, W> {
+    session: Rc<RefCell<Session<R, W>>>,
+    line: Vec<u8>,
+}
+
+/// Runs the launched program to the end, returning `false` if the client
+/// disconnected meanwhile.
+fn launch<R, W>(session: &Rc<RefCell<Session<R, W>>>) -> io::Result<bool>
+where
+    R: BufRead + 'static,
+    W: Write + 'static,
+{
+    let (program, input, stop_on_entry) = {
+        let mut session = session.borrow_mut();
+        let Some(launched) = session.launched.as_mut() else {
+            return Ok(true);
+        };
+        let Some(program) = launched.program.take() else {
+            return Ok(true);
+        };
+        (program, launched.input.clone(), launched.stop_on_entry)
+    };
+
+    let mut debugger = Debugger::new(&program, Adapter(Rc::clone(session)));
+    if !stop_on_entry {
+        debugger = debugger.without_stop_on_entry();
+    }
+    *debugger.breakpoints() = session.borrow().breakpoints.clone();
+    let output = ProgramOutput {
+        session: Rc::clone(session),
+        line: Vec::new(),
+    };
+    let mut evaluator = Evaluator::new()
+        .stack_size(STACK_SIZE)
+        .hook(Rc::new(RefCell::new(debugger)))
+        .with_io(Box::new(Cursor::new(input)), Box::new(output));
+    let result = evaluator.eval_program(&program);
+    drop(evaluator);
+
+    let mut session = session.borrow_mut();
+    let exit_code: i64 = match result {
+        Ok(_) => 0,
+        Err(failure) if failure.error == RuntimeError::Interrupted => return Ok(false),
+        Err(failure) => {
+            session.event(
+                "output",
+                Json::from([
+                    ("category", "stderr".into()),
+                    ("output", format!("{}\n", failure.error).into()),
+                    ("line", failure.span.line.into()),
+                    ("column", failure.span.column.into()),
+                ]),
+            )?;
+            1
+        }
+    };
+    session.event("exited", Json::from([("exitCode", exit_code.into())]))?;
+    session.event("terminated", Json::Object(Vec::new()))?;
+    Ok(true)
+}
+
+impl<R: BufRead, W: Write> Session<R, W> {
+    fn new(input: R, output: W) -> Self {
+        Self {
+            input,
+            output,
+            seq: 0,
+            launched: None,
+            breakpoints: Breakpoints::default(),
+            references: Vec::new(),
+        }
+    }
+
+    fn read(&mut self) -> io::Result<Option<Json>> {
+        read_message(&mut self.input)
+    }
+
+    fn send(&mut self, kind: &str, mut members: Vec<(String, Json)>) -> io::Result<()> {
+        self.seq += 1;
+        members.insert(0, ("seq".to_string(), self.seq.into()));
+        members.insert(1, ("type".to_string(), kind.into()));
+        write_message(&mut self.output, &Json::Object(members))
+    }
+
+    fn event(&mut self, event: &str, body: Json) -> io::Result<()> {
+        self.send(
+            "event",
+            vec![
+                ("event".to_string(), event.into()),
+                ("body".to_string(), body),
+            ],
+        )
+    }
+
+    fn respond(&mut self, request: &Json, result: Result<Json, String>) -> io::Result<()> {
+        let command = request.get("command").cloned().unwrap_or(Json::Null);
+        let request_seq = request.get("seq").cloned().unwrap_or(Json::Null);
+        let mut members = vec![
+            ("request_seq".to_string(), request_seq),
+            ("success".to_string(), result.is_ok().into()),
+            ("command".to_string(), command),
+        ];
+        match result {
+            Ok(Json::Null) => {}
+            Ok(body) => members.push(("body".to_string(), body)),
+            Err(message) => members.push(("message".to_string(), message.into())),
+        }
+        self.send("response", members)
+    }
+
+    /// Answers `request`. `stop` is where the program is stopped, if it is
+    /// running, and `breakpoints` the ones `setBreakpoints` replaces.
+    fn handle(
+        &mut self,
+        request: &Json,
+        stop: Option<&Stop<'_>>,
+        breakpoints: &mut Breakpoints,
+    ) -> io::Result<Action> {
+        let command = request.get("command").and_then(Json::as_str).unwrap_or("");
+        let arguments = request.get("arguments").unwrap_or(&Json::Null);
+        let resume = |resume| Ok((Json::Null, Action::Resume(resume)));
+
+        let result = match (command, stop) {
+            ("initialize", _) => Ok((
+                Json::from([
+                    ("supportsConfigurationDoneRequest", true.into()),
+                    ("supportsEvaluateForHovers", true.into()),
+                ]),
+                Action::None,
+            )),
+            ("launch", _) => self
+                .launch(arguments, breakpoints)
+                .map(|_| (Json::Null, Action::None)),
+            ("setBreakpoints", _) => Ok((set_breakpoints(arguments, breakpoints), Action::None)),
+            ("configurationDone", None) => Ok((Json::Null, Action::Configured)),
+            ("threads", _) => Ok((
+                Json::from([(
+                    "threads",
+                    Json::Array(vec![Json::from([
+                        ("id", THREAD.into()),
+                        ("name", "main".into()),
+                    ])]),
+                )]),
+                Action::None,
+            )),
+            ("disconnect" | "terminate", _) => Ok((Json::Null, Action::Disconnect)),
+            ("stackTrace", Some(stop)) => Ok((self.stack_trace(stop), Action::None)),
+            ("scopes", Some(stop)) => {
+                let frame = arguments.get("frameId").and_then(Json::as_i64).unwrap_or(0);
+                Ok((self.scopes(stop, frame as usize), Action::None))
+            }
+            ("variables", Some(_)) => {
+                let reference = arguments
+                    .get("variablesReference")
+                    .and_then(Json::as_i64)
+                    .unwrap_or(0);
+                Ok((self.variables(reference as usize), Action::None))
+            }
+            ("evaluate", Some(stop)) => {
+                let expression = arguments
+                    .get("expression")
+                    .and_then(Json::as_str)
+                    .unwrap_or("")
+                    .trim();
+                match stop.value(expression) {
+                    Some(value) => Ok((
+                        Json::from([
+                            ("result", display(&value).into()),
+                            ("variablesReference", self.reference(&value).into()),
+                        ]),
+                        Action::None,
+                    )),
+                    None => Err(format!("{} is not defined", expression)),
+                }
+            }
+            ("continue", Some(_)) => Ok((
+                Json::from([("allThreadsContinued", true.into())]),
+                Action::Resume(Resume::Continue),
+            )),
+            ("next", Some(_)) => resume(Resume::StepOver),
+            ("stepIn", Some(_)) => resume(Resume::StepInto),
+            ("stepOut", Some(_)) => resume(Resume::StepOut),
+            (command, _) => Err(format!("unsupported request: {}", command)),
+        };
+
+        match result {
+            Ok((body, action)) => {
+                self.respond(request, Ok(body))?;
+                // Breakpoints can only be placed once the program is known,
+                // so configuration starts after `launch`.
+                if command == "launch" {
+                    self.event("initialized", Json::Object(Vec::new()))?;
+                }
+                Ok(action)
+            }
+            Err(message) => {
+                self.respond(request, Err(message))?;
+                Ok(Action::None)
+            }
+        }
+    }
+
+    fn launch(&mut self, arguments: &Json, breakpoints: &mut Breakpoints) -> Result<(), String> {
+        let path = arguments
+            .get("program")
+            .and_then(Json::as_str)
+            .ok_or("missing program")?;
+        let source = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        let mut parser = Parser::new(Lexer::new(source));
+        let program = parser.parse_program();
+        let errors = parser.errors();
+        let program = match program {
+            Some(program) if errors.is_empty() => program,
+            _ => return Err(format!("{}: {}", path, errors.join("\n"))),
+        };
+
+        *breakpoints = Breakpoints::new(&program);
+        self.launched = Some(Launched {
+            path: path.to_string(),
+            program: Some(program),
+            input: arguments
+                .get("input")
+                .and_then(Json::as_str)
+                .unwrap_or_default()
+                .to_string(),
+            stop_on_entry: arguments
+                .get("stopOnEntry")
+                .and_then(Json::as_bool)
+                .unwrap_or(false),
+        });
+        Ok(())
+    }
+
+    fn stack_trace(&self, stop: &Stop<'_>) -> Json {
+        let path = self.launched.as_ref().map_or("", |launched| &launched.path);
+        let source = Json::from([("path", path.into())]);
+        let frames: Vec<Json> = stop
+            .call_stack()
+            .iter()
+            .enumerate()
+            .map(|(id, entry)| {
+                Json::from([
+                    ("id", id.into()),
+                    ("name", entry.function.as_deref().unwrap_or("main").into()),
+                    ("source", source.clone()),
+                    ("line", entry.line.into()),
+                    ("column", 1i64.into()),
+                ])
+            })
+            .collect();
+        let total = frames.len();
+        Json::from([
+            ("stackFrames", frames.into()),
+            ("totalFrames", total.into()),
+        ])
+    }
+
+    /// The local variables of frame `frame`, counted from the innermost,
+    /// and the globals.
+    fn scopes(&mut self, stop: &Stop<'_>, frame: usize) -> Json {
+        let calls = stop.frames.len();
+        let mut scopes = Vec::new();
+        if frame < calls {
+            let locals = stop.frames[calls - 1 - frame].env.borrow().bindings();
+            scopes.push(("Locals", locals));
+        }
+        scopes.push(("Globals", stop.globals()));
+
+        let scopes: Vec<Json> = scopes
+            .into_iter()
+            .map(|(name, variables)| {
+                self.references.push(variables);
+                Json::from([
+                    ("name", name.into()),
+                    ("variablesReference", self.references.len().into()),
+                    ("expensive", false.into()),
+                ])
+            })
+            .collect();
+        Json::from([("scopes", scopes.into())])
+    }
+
+    fn variables(&mut self, reference: usize) -> Json {
+        let variables = reference
+            .checked_sub(1)
+            .and_then(|index| self.references.get(index))
+            .cloned()
+            .unwrap_or_default();
+        let variables: Vec<Json> = variables
+            .iter()
+            .map(|(name, value)| {
+                Json::from([
+                    ("name", name.as_str().into()),
+                    ("value", display(value).into()),
+                    ("type", value.type_name().into()),
+                    ("variablesReference", self.reference(value).into()),
+                ])
+            })
+            .collect();
+        Json::from([("variables", variables.into())])
+    }
+
+    /// A reference the client can expand an array or map through, or 0
+    /// for any other value.
+    fn reference(&mut self, value: &Object) -> usize {
+        let children = match value {
+            Object::Array(elements) => elements
+                .borrow()
+                .iter()
+                .enumerate()
+                .map(|(index, element)| (format!("[{}]", index), element.clone()))
+                .collect(),
+            Object::Map(pairs) => pairs
+                .borrow()
+                .iter()
+                .map(|(key, value)| {
+                    let key = display(&Object::from(key.clone()));
+                    (format!("[{}]", key), value.clone())
+                })
+                .collect(),
+            _ => return 0,
+        };
+        self.references.push(children);
+        self.references.len()
+    }
+}
+
+/// How the client shows a value: strings quoted, everything else as
+/// `scrie` prints it.
+fn display(value: &Object) -> String {
+    match value {
+        Object::Str(value) => format!("\"{}\"", value),
+        value => value.to_string(),
+    }
+}
+
+/// Replaces the breakpoints with those in `arguments`, reporting the line
+/// each was moved to or that it could not be set.
+fn set_breakpoints(arguments: &Json, breakpoints: &mut Breakpoints) -> Json {
+    breakpoints.clear();
+    let requested = arguments
+        .get("breakpoints")
+        .and_then(Json::as_array)
+        .unwrap_or_default();
+    let set: Vec<Json> = requested
+        .iter()
+        .filter_map(|breakpoint| breakpoint.get("line").and_then(Json::as_i64))
+        .map(|line| match breakpoints.insert(line as usize) {
+            Some(line) => Json::from([("verified", true.into()), ("line", line.into())]),
+            None => Json::from([
+                ("verified", false.into()),
+                ("line", line.into()),
+                ("message", "no statement at or after this line".into()),
+            ]),
+        })
+        .collect();
+    Json::from([("breakpoints", set.into())])
+}
+
+impl<R: BufRead, W: Write> Controller for Adapter<R, W> {
+    fn stopped(&mut self, stop: &Stop<'_>, breakpoints: &mut Breakpoints) -> Resume {
+        let mut session = self.0.borrow_mut();
+        session.references.clear();
+        let reason = match stop.reason {
+            Reason::Entry => "entry",
+            Reason::Breakpoint => "breakpoint",
+            Reason::Step => "step",
+        };
+        let stopped = session.event(
+            "stopped",
+            Json::from([
+                ("reason", reason.into()),
+                ("threadId", THREAD.into()),
+                ("allThreadsStopped", true.into()),
+            ]),
+        );
+        if stopped.is_err() {
+            return Resume::Stop;
+        }
+
+        loop {
+            let request = match session.read() {
+                Ok(Some(request)) => request,
+                Ok(None) | Err(_) => return Resume::Stop,
+            };
+            match session.handle(&request, Some(stop), breakpoints) {
+                Ok(Action::Resume(resume)) => return resume,
+                Ok(Action::Disconnect) | Err(_) => return Resume::Stop,
+                Ok(Action::None | Action::Configured) => {}
+            }
+        }
+    }
+}
+
+impl<R: BufRead, W: Write> Write for ProgramOutput<R, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.line.extend_from_slice(buf);
+        if self.line.ends_with(b"\n") {
+            self.flush()?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.line.is_empty() {
+            return Ok(());
+        }
+        let output = String::from_utf8_lossy(&mem::take(&mut self.line)).into_owned();
+        self.session.borrow_mut().event(
+            "output",
+            Json::from([("category", "stdout".into()), ("output", output.into())]),
+        )
+    }
+}
@@ -0,0 +1,281 @@
+use std::cell::RefCell;
+use std::env;
+use std::fs;
+use std::io::{self, Cursor, Write};
+use std::rc::Rc;
+
+use crate::json::{read_message, write_message, Json};
+
+use super::run;
+
+const PROGRAM: &str = "functie dublu(n) {
+  returneaza 2 * n
+}
+citeste x
+v ← [x, dublu(x)]
+scrie dublu(x)
+scrie v";
+
+/// An output stream the test can still read after `run` consumed it.
+#[derive(Clone, Default)]
+struct Shared(Rc<RefCell<Vec<u8>>>);
+
+impl Write for Shared {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Runs a session with `requests`, given as command and arguments, and
+/// returns everything the adapter sent.
+fn session(name: &str, requests: Vec<(&str, Json)>) -> Vec<Json> {
+    let path = env::temp_dir().join(format!("h95-dap-{}-{}.pseudo", name, std::process::id()));
+    fs::write(&path, PROGRAM).unwrap();
+    let path = path.to_str().unwrap().to_string();
+
+    let mut input = Vec::new();
+    for (seq, (command, arguments)) in requests.into_iter().enumerate() {
+        let arguments = match (command, arguments) {
+            ("launch", Json::Object(mut members)) => {
+                members.push(("program".to_string(), path.as_str().into()));
+                Json::Object(members)
+            }
+            (_, arguments) => arguments,
+        };
+        let request = Json::from([
+            ("seq", (seq + 1).into()),
+            ("type", "request".into()),
+            ("command", command.into()),
+            ("arguments", arguments),
+        ]);
+        write_message(&mut input, &request).unwrap();
+    }
+
+    let output = Shared::default();
+    run(Cursor::new(input), output.clone()).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    let mut output = Cursor::new(output.0.take());
+    let mut messages = Vec::new();
+    while let Some(message) = read_message(&mut output).unwrap() {
+        messages.push(message);
+    }
+    messages
+}
+
+/// Each message as `command` for a response, `!command` for a failed one
+/// and `event:name` for an event.
+fn summary(messages: &[Json]) -> Vec<String> {
+    messages
+        .iter()
+        .map(|message| {
+            let name = |key| message.get(key).and_then(Json::as_str).unwrap_or_default();
+            match name("type") {
+                "event" => format!("event:{}", name("event")),
+                _ if message.get("success") == Some(&Json::Bool(false)) => {
+                    format!("!{}", name("command"))
+                }
+                _ => name("command").to_string(),
+            }
+        })
+        .collect()
+}
+
+fn body<'a>(messages: &'a [Json], command: &str) -> Vec<&'a Json> {
+    messages
+        .iter()
+        .filter(|m| m.get("command").and_then(Json::as_str) == Some(command))
+        .filter_map(|m| m.get("body"))
+        .collect()
+}
+
+fn lines(line: &[usize]) -> Json {
+    Json::from([
+        ("source", Json::from([("path", "ignored".into())])),
+        (
+            "breakpoints",
+            line.iter()
+                .map(|line| Json::from([("line", (*line).into())]))
+                .collect::<Vec<_>>()
+                .into(),
+        ),
+    ])
+}
+
+#[test]
+fn stops_at_breakpoints_and_shows_variables() {
+    let messages = session(
+        "breakpoints",
+        vec![
+            ("initialize", Json::from([("adapterID", "h95".into())])),
+            ("launch", Json::from([("input", "21".into())])),
+            ("setBreakpoints", lines(&[1, 8])),
+            ("configurationDone", Json::Null),
+            ("stackTrace", Json::from([("threadId", 1i64.into())])),
+            ("scopes", Json::from([("frameId", 0i64.into())])),
+            (
+                "variables",
+                Json::from([("variablesReference", 1i64.into())]),
+            ),
+            ("stepOut", Json::Null),
+            ("continue", Json::Null),
+            ("scopes", Json::from([("frameId", 0i64.into())])),
+            (
+                "variables",
+                Json::from([("variablesReference", 1i64.into())]),
+            ),
+            (
+                "variables",
+                Json::from([("variablesReference", 2i64.into())]),
+            ),
+            ("evaluate", Json::from([("expression", "x".into())])),
+            ("continue", Json::Null),
+            ("disconnect", Json::Null),
+        ],
+    );
+
+    assert_eq!(
+        summary(&messages),
+        vec![
+            "initialize",
+            "launch",
+            "event:initialized",
+            "setBreakpoints",
+            "configurationDone",
+            "event:stopped",
+            "stackTrace",
+            "scopes",
+            "variables",
+            "stepOut",
+            "event:stopped",
+            "continue",
+            "event:stopped",
+            "scopes",
+            "variables",
+            "variables",
+            "evaluate",
+            "continue",
+            "event:output",
+            "event:output",
+            "event:exited",
+            "event:terminated",
+            "disconnect",
+        ]
+    );
+    assert_eq!(
+        body(&messages, "setBreakpoints")[0].to_string(),
+        r#"{"breakpoints":[{"verified":true,"line":2},{"verified":false,"line":8,"message":"no statement at or after this line"}]}"#
+    );
+
+    let frames = body(&messages, "stackTrace")[0].get("stackFrames").unwrap();
+    let frames: Vec<(&str, i64)> = frames
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|f| {
+            (
+                f.get("name").and_then(Json::as_str).unwrap(),
+                f.get("line").and_then(Json::as_i64).unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(frames, vec![("dublu", 2), ("main", 5)]);
+
+    let variables = body(&messages, "variables");
+    assert_eq!(
+        variables[0].to_string(),
+        r#"{"variables":[{"name":"n","value":"21","type":"INTEGER","variablesReference":0}]}"#
+    );
+    // The breakpoint on the declaration moved into the body. Stepping out
+    // of the call in line 5 stops at the next line, which calls it again.
+    let stopped: Vec<_> = messages
+        .iter()
+        .filter(|m| m.get("event").and_then(Json::as_str) == Some("stopped"))
+        .map(|m| m.at(&["body", "reason"]).and_then(Json::as_str).unwrap())
+        .collect();
+    assert_eq!(stopped, vec!["breakpoint", "step", "breakpoint"]);
+    assert_eq!(
+        variables[2].to_string(),
+        r#"{"variables":[{"name":"dublu","value":"function dublu","type":"FUNCTION","variablesReference":0},{"name":"v","value":"[21, 42]","type":"ARRAY","variablesReference":3},{"name":"x","value":"21","type":"INTEGER","variablesReference":0}]}"#
+    );
+    assert_eq!(
+        body(&messages, "evaluate")[0].to_string(),
+        r#"{"result":"21","variablesReference":0}"#
+    );
+
+    let output: Vec<_> = messages
+        .iter()
+        .filter_map(|m| m.at(&["body", "output"]).and_then(Json::as_str))
+        .collect();
+    assert_eq!(output, vec!["42\n", "[21, 42]\n"]);
+}
+
+#[test]
+fn steps_from_entry() {
+    let messages = session(
+        "entry",
+        vec![
+            ("initialize", Json::Null),
+            (
+                "launch",
+                Json::from([("input", "1".into()), ("stopOnEntry", true.into())]),
+            ),
+            ("configurationDone", Json::Null),
+            ("next", Json::Null),
+            ("next", Json::Null),
+            ("stepIn", Json::Null),
+            ("stackTrace", Json::Null),
+            ("disconnect", Json::Null),
+        ],
+    );
+
+    let lines: Vec<_> = body(&messages, "stackTrace")[0]
+        .get("stackFrames")
+        .and_then(Json::as_array)
+        .unwrap()
+        .iter()
+        .map(|f| f.get("line").and_then(Json::as_i64).unwrap())
+        .collect();
+    assert_eq!(lines, vec![2, 5]);
+    assert_eq!(summary(&messages).last().unwrap(), "disconnect");
+    assert!(!summary(&messages).contains(&"event:terminated".to_string()));
+}
+
+#[test]
+fn reports_problems() {
+    let messages = session(
+        "errors",
+        vec![
+            ("initialize", Json::Null),
+            ("launch", Json::from([])),
+            ("stackTrace", Json::Null),
+            ("configurationDone", Json::Null),
+        ],
+    );
+    assert_eq!(
+        summary(&messages),
+        vec![
+            "initialize",
+            "launch",
+            "event:initialized",
+            "!stackTrace",
+            "configurationDone",
+            "event:output",
+            "event:exited",
+            "event:terminated",
+        ]
+    );
+    assert_eq!(
+        messages[5].at(&["body", "output"]).and_then(Json::as_str),
+        Some("no more input to read\n")
+    );
+    assert_eq!(
+        messages[6].at(&["body", "exitCode"]).and_then(Json::as_i64),
+        Some(1)
+    );
+}
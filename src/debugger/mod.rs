@@ -0,0 +1,288 @@
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::ops::ControlFlow;
+use std::rc::Rc;
+
+use crate::ast::{
+    BlockStatement, ForStatement, IfStatement, Program, RepeatStatement, Statement, WhileStatement,
+};
+use crate::evaluator::{Frame, Hook};
+use crate::lexer::Span;
+use crate::object::{Environment, Object};
+use crate::types::function_declaration;
+
+#[cfg(test)]
+mod tests;
+
+/// How to go on after the program stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resume {
+    /// Run until the next breakpoint.
+    Continue,
+    /// Stop at the next line, inside a function if one is called.
+    StepInto,
+    /// Stop at the next line of the current function.
+    StepOver,
+    /// Stop once the current function has returned.
+    StepOut,
+    /// End the run with [`RuntimeError::Interrupted`].
+    ///
+    /// [`RuntimeError::Interrupted`]: crate::evaluator::RuntimeError::Interrupted
+    Stop,
+}
+
+/// Why the program stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reason {
+    /// Before the first statement.
+    Entry,
+    Breakpoint,
+    Step,
+}
+
+/// The state of a stopped program.
+#[derive(Debug)]
+pub struct Stop<'a> {
+    pub reason: Reason,
+    /// The statement about to run.
+    pub span: Span,
+    /// The function calls in progress, outermost first.
+    pub frames: &'a [Frame],
+    env: &'a Rc<RefCell<Environment>>,
+}
+
+/// One line of a call stack: where a function, or the main program, is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StackEntry {
+    /// `None` for the main program.
+    pub function: Option<String>,
+    pub line: usize,
+}
+
+/// Decides what a [`Debugger`] does each time the program stops, e.g. by
+/// asking the user.
+pub trait Controller {
+    /// May change `breakpoints` before the program goes on.
+    fn stopped(&mut self, stop: &Stop<'_>, breakpoints: &mut Breakpoints) -> Resume;
+}
+
+/// The lines a program stops at. A breakpoint on a line where no statement
+/// starts moves down to the next one that does.
+#[derive(Debug, Clone, Default)]
+pub struct Breakpoints {
+    lines: BTreeSet<usize>,
+    set: BTreeSet<usize>,
+}
+
+/// A [`Hook`] that stops the program at breakpoints and after steps, and
+/// lets a [`Controller`] look at it.
+#[derive(Debug)]
+pub struct Debugger<C> {
+    controller: C,
+    breakpoints: Breakpoints,
+    mode: Mode,
+    /// Where the last statement reached was and its call depth, so a line
+    /// holding several statements only stops once each time it is run.
+    last: Option<(Span, usize)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Entry,
+    Continue,
+    StepInto,
+    /// Stop at a call depth no deeper than this.
+    StepOver(usize),
+    /// Stop at a call depth shallower than this.
+    StepOut(usize),
+}
+
+impl Stop<'_> {
+    /// The variables of the current scope: the locals and parameters of the
+    /// innermost function, or the globals in the main program.
+    pub fn locals(&self) -> Vec<(String, Object)> {
+        self.env.borrow().bindings()
+    }
+
+    pub fn globals(&self) -> Vec<(String, Object)> {
+        let mut env = Rc::clone(self.env);
+        loop {
+            let outer = env.borrow().outer();
+            match outer {
+                Some(outer) => env = outer,
+                None => return env.borrow().bindings(),
+            }
+        }
+    }
+
+    /// The value `name` has where the program stopped.
+    pub fn value(&self, name: &str) -> Option<Object> {
+        self.env.borrow().get(name)
+    }
+
+    /// The call stack, innermost first, ending with the main program.
+    pub fn call_stack(&self) -> Vec<StackEntry> {
+        let mut line = self.span.line;
+        let mut stack = Vec::new();
+        for frame in self.frames.iter().rev() {
+            stack.push(StackEntry {
+                function: Some(
+                    frame
+                        .function
+                        .clone()
+                        .unwrap_or_else(|| "anonymous function".to_string()),
+                ),
+                line,
+            });
+            line = frame.call.line;
+        }
+        stack.push(StackEntry {
+            function: None,
+            line,
+        });
+        stack
+    }
+}
+
+impl Breakpoints {
+    pub fn new(program: &Program) -> Self {
+        let mut lines = BTreeSet::new();
+        for statement in &program.statements {
+            statement_lines(&*statement.as_ref().borrow(), &mut lines);
+        }
+        Self {
+            lines,
+            set: BTreeSet::new(),
+        }
+    }
+
+    /// Sets a breakpoint on `line` or the first line after it where a
+    /// statement starts, returning that line. `None` if there is none.
+    pub fn insert(&mut self, line: usize) -> Option<usize> {
+        let line = *self.lines.range(line..).next()?;
+        self.set.insert(line);
+        Some(line)
+    }
+
+    pub fn remove(&mut self, line: usize) -> bool {
+        self.set.remove(&line)
+    }
+
+    pub fn clear(&mut self) {
+        self.set.clear();
+    }
+
+    pub fn contains(&self, line: usize) -> bool {
+        self.set.contains(&line)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.set.iter().copied()
+    }
+}
+
+/// Adds the lines statements start on, including those in blocks and
+/// function bodies.
+fn statement_lines(statement: &dyn Statement, lines: &mut BTreeSet<usize>) {
+    let any = statement.as_any();
+    let block = |block: &BlockStatement, lines: &mut BTreeSet<usize>| {
+        for statement in &block.statements {
+            statement_lines(&*statement.as_ref().borrow(), lines);
+        }
+    };
+
+    if let Some(declaration) = function_declaration(statement) {
+        block(&declaration.body, lines);
+        return;
+    }
+    if !any.is::<BlockStatement>() {
+        lines.insert(statement.span().line);
+    }
+    if let Some(statement) = any.downcast_ref::<BlockStatement>() {
+        block(statement, lines);
+    } else if let Some(statement) = any.downcast_ref::<IfStatement>() {
+        block(&statement.consequence, lines);
+        if let Some(alternative) = &statement.alternative {
+            block(alternative, lines);
+        }
+    } else if let Some(statement) = any.downcast_ref::<WhileStatement>() {
+        block(&statement.body, lines);
+    } else if let Some(statement) = any.downcast_ref::<RepeatStatement>() {
+        block(&statement.body, lines);
+        if let Some(condition) = &statement.condition {
+            lines.insert(condition.as_ref().borrow().span().line);
+        }
+    } else if let Some(statement) = any.downcast_ref::<ForStatement>() {
+        block(&statement.body, lines);
+    }
+}
+
+impl<C: Controller> Debugger<C> {
+    /// A debugger for `program` that stops before its first statement.
+    pub fn new(program: &Program, controller: C) -> Self {
+        Self {
+            controller,
+            breakpoints: Breakpoints::new(program),
+            mode: Mode::Entry,
+            last: None,
+        }
+    }
+
+    /// Runs to the first breakpoint instead of stopping on entry.
+    pub fn without_stop_on_entry(mut self) -> Self {
+        self.mode = Mode::Continue;
+        self
+    }
+
+    pub fn breakpoints(&mut self) -> &mut Breakpoints {
+        &mut self.breakpoints
+    }
+
+    pub fn controller(&mut self) -> &mut C {
+        &mut self.controller
+    }
+}
+
+impl<C: Controller> Hook for Debugger<C> {
+    fn statement(
+        &mut self,
+        span: Span,
+        frames: &[Frame],
+        env: &Rc<RefCell<Environment>>,
+    ) -> ControlFlow<()> {
+        let depth = frames.len();
+        // Further along the same line is the same stop, but going back to
+        // an earlier column, as a loop on one line does, runs the line again.
+        let same_run = self.last.is_some_and(|(last, last_depth)| {
+            last_depth == depth && last.line == span.line && last.column < span.column
+        });
+        self.last = Some((span, depth));
+        if same_run {
+            return ControlFlow::Continue(());
+        }
+
+        let reason = match self.mode {
+            Mode::Entry => Reason::Entry,
+            _ if self.breakpoints.contains(span.line) => Reason::Breakpoint,
+            Mode::StepInto => Reason::Step,
+            Mode::StepOver(max) if depth <= max => Reason::Step,
+            Mode::StepOut(above) if depth < above => Reason::Step,
+            _ => return ControlFlow::Continue(()),
+        };
+
+        let stop = Stop {
+            reason,
+            span,
+            frames,
+            env,
+        };
+        self.mode = match self.controller.stopped(&stop, &mut self.breakpoints) {
+            Resume::Continue => Mode::Continue,
+            Resume::StepInto => Mode::StepInto,
+            Resume::StepOver => Mode::StepOver(depth),
+            Resume::StepOut => Mode::StepOut(depth),
+            Resume::Stop => return ControlFlow::Break(()),
+        };
+        ControlFlow::Continue(())
+    }
+}
@@ -0,0 +1,182 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::io::{self, Cursor};
+use std::rc::Rc;
+
+use crate::evaluator::{Evaluator, RuntimeError};
+use crate::test_support::parse;
+
+use super::{Breakpoints, Controller, Debugger, Reason, Resume, StackEntry, Stop};
+
+/// Answers each stop with the next of `script`, continuing once it runs
+/// out, and records where the program stopped.
+#[derive(Default)]
+struct Script {
+    script: VecDeque<Resume>,
+    breakpoints: Vec<usize>,
+    stops: Vec<(Reason, usize)>,
+    stacks: Vec<Vec<StackEntry>>,
+    locals: Vec<String>,
+}
+
+impl Controller for Script {
+    fn stopped(&mut self, stop: &Stop<'_>, breakpoints: &mut Breakpoints) -> Resume {
+        for line in self.breakpoints.drain(..) {
+            breakpoints.insert(line);
+        }
+        self.stops.push((stop.reason, stop.span.line));
+        self.stacks.push(stop.call_stack());
+        let locals: Vec<String> = stop
+            .locals()
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect();
+        self.locals.push(locals.join(" "));
+        self.script.pop_front().unwrap_or(Resume::Continue)
+    }
+}
+
+fn debug(input: &str, script: Script) -> (Script, Result<(), RuntimeError>) {
+    let program = parse(input);
+    let debugger = Rc::new(RefCell::new(Debugger::new(&program, script)));
+    let mut evaluator = Evaluator::new()
+        .hook(debugger.clone())
+        .with_io(Box::new(Cursor::new("")), Box::new(io::sink()));
+    let result = evaluator
+        .eval_program(&program)
+        .map(|_| ())
+        .map_err(|failure| failure.error);
+    let script = std::mem::take(debugger.borrow_mut().controller());
+    (script, result)
+}
+
+const PROGRAM: &str = "functie suma(n) {
+  s ← 0
+  pentru i ← 1, n {
+    s += i
+  }
+  returneaza s
+}
+x ← 2
+cat timp x > 0 {
+  x -= 1
+}
+scrie suma(2)";
+
+#[test]
+fn steps_line_by_line() {
+    let script = Script {
+        script: VecDeque::from(vec![Resume::StepOver; 8]),
+        ..Script::default()
+    };
+    let (script, result) = debug(PROGRAM, script);
+    assert_eq!(result, Ok(()));
+    let lines: Vec<usize> = script.stops.iter().map(|(_, line)| *line).collect();
+    assert_eq!(lines, vec![1, 8, 9, 10, 9, 10, 9, 12]);
+    assert_eq!(script.stops[0].0, Reason::Entry);
+    assert_eq!(script.stops[1].0, Reason::Step);
+    assert_eq!(script.locals[4], "suma=function suma x=1");
+}
+
+#[test]
+fn steps_into_and_out_of_functions() {
+    let script = Script {
+        script: VecDeque::from(vec![
+            Resume::Continue,
+            Resume::StepInto,
+            Resume::StepInto,
+            Resume::StepOut,
+        ]),
+        breakpoints: vec![12],
+        ..Script::default()
+    };
+    let (script, result) = debug(PROGRAM, script);
+    assert_eq!(result, Ok(()));
+    assert_eq!(
+        script.stops,
+        vec![
+            (Reason::Entry, 1),
+            (Reason::Breakpoint, 12),
+            (Reason::Step, 2),
+            (Reason::Step, 3),
+        ]
+    );
+    assert_eq!(
+        script.stacks[3],
+        vec![
+            StackEntry {
+                function: Some("suma".to_string()),
+                line: 3
+            },
+            StackEntry {
+                function: None,
+                line: 12
+            },
+        ]
+    );
+    assert_eq!(script.locals[3], "n=2 s=0");
+}
+
+#[test]
+fn stops_at_breakpoints_in_loops() {
+    let script = Script {
+        breakpoints: vec![4],
+        ..Script::default()
+    };
+    let (script, _) = debug(PROGRAM, script);
+    assert_eq!(
+        script.stops,
+        vec![
+            (Reason::Entry, 1),
+            (Reason::Breakpoint, 4),
+            (Reason::Breakpoint, 4)
+        ]
+    );
+    assert_eq!(script.locals[2], "i=2 n=2 s=1");
+}
+
+#[test]
+fn can_stop_the_program() {
+    let script = Script {
+        script: VecDeque::from(vec![Resume::StepOver, Resume::Stop]),
+        ..Script::default()
+    };
+    let (script, result) = debug(PROGRAM, script);
+    assert_eq!(result, Err(RuntimeError::Interrupted));
+    assert_eq!(script.stops.len(), 2);
+}
+
+#[test]
+fn moves_breakpoints_to_statements() {
+    let mut breakpoints = Breakpoints::new(&parse(PROGRAM));
+    assert_eq!(breakpoints.insert(1), Some(2));
+    assert_eq!(breakpoints.insert(5), Some(6));
+    assert_eq!(breakpoints.insert(11), Some(12));
+    assert_eq!(breakpoints.insert(13), None);
+    assert!(breakpoints.remove(6));
+    assert_eq!(breakpoints.iter().collect::<Vec<_>>(), vec![2, 12]);
+}
+
+#[test]
+fn stops_each_time_a_loop_on_one_line_repeats() {
+    let input = "s ← 0
+pentru i ← 1, 3 { s ← s + i }
+scrie s";
+    let script = Script {
+        breakpoints: vec![2],
+        ..Script::default()
+    };
+    let (script, _) = debug(input, script);
+    let lines: Vec<usize> = script.stops.iter().map(|(_, line)| *line).collect();
+    assert_eq!(lines, vec![1, 2, 2, 2, 2]);
+    assert_eq!(script.locals[1..], ["s=0", "i=2 s=1", "i=3 s=3", "i=4 s=6"]);
+
+    let script = Script {
+        script: VecDeque::from(vec![Resume::StepInto; 3]),
+        ..Script::default()
+    };
+    let (script, _) = debug(input, script);
+    let lines: Vec<usize> = script.stops.iter().map(|(_, line)| *line).collect();
+    assert_eq!(lines, vec![1, 2, 2, 2]);
+    assert_eq!(script.locals[3], "i=3 s=3");
+}
@@ -0,0 +1,1530 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Write};
+use std::ops::ControlFlow;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use crate::ast::{
+    ArrayLiteral, AssignStatement, BlockStatement, BooleanLiteral, CallExpression, Expression,
+    ExpressionStatement, ForStatement, FunctionLiteral, Identifier, IfStatement,
+    IndexAssignStatement, IndexExpression, InfixExpression, IntegerPartExpression, IntegralLiteral,
+    LetStatement, MapLiteral, Node, PrefixExpression, Program, ReadStatement, RealLiteral,
+    RepeatStatement, ReturnStatement, Statement, StringLiteral, WhileStatement, WriteStatement,
+};
+use crate::bigint::BigInt;
+use crate::builtins::Builtins;
+use crate::lexer::{Span, TokenType};
+use crate::messages::{self, Lang};
+use crate::object::{Environment, Function, HashKey, Object};
+use crate::resolver;
+
+#[cfg(test)]
+mod tests;
+
+/// How deeply calls may nest when [`Limits::max_depth`] does not say, the
+/// same as the VM's frame limit.
+const MAX_DEPTH: usize = 1024;
+
+/// The native stack the evaluator assumes its thread has unless told
+/// otherwise with [`Evaluator::stack_size`]: what `std::thread::spawn`
+/// gives a thread.
+const DEFAULT_STACK_SIZE: usize = 2 << 20;
+
+/// The native stack a thread running the evaluator should have so that
+/// [`MAX_DEPTH`] nested calls fit, even in a debug build, where one call
+/// can take tens of kilobytes. Pass it to [`Evaluator::stack_size`] too.
+pub const STACK_SIZE: usize = 64 << 20;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RuntimeError {
+    UndefinedVariable(String),
+    UndeclaredAssignment(String),
+    TypeMismatch {
+        left: &'static str,
+        operator: String,
+        right: &'static str,
+    },
+    UnknownPrefixOperator {
+        operator: String,
+        right: &'static str,
+    },
+    UnknownInfixOperator {
+        left: &'static str,
+        operator: String,
+        right: &'static str,
+    },
+    InvalidOperand {
+        operator: String,
+        operand: &'static str,
+    },
+    DivisionByZero,
+    IntegerOverflow,
+    IndexOutOfRange {
+        index: String,
+        length: usize,
+    },
+    IndexNotSupported {
+        left: &'static str,
+        index: &'static str,
+    },
+    UnhashableKey(&'static str),
+    KeyNotFound(String),
+    NotCallable(&'static str),
+    WrongArgumentCount {
+        function: String,
+        expected: usize,
+        got: usize,
+    },
+    InvalidArgument {
+        function: String,
+        argument: &'static str,
+    },
+    /// An argument of the right type a builtin still cannot use, such as
+    /// the square root of a negative number.
+    InvalidValue {
+        function: String,
+        value: String,
+    },
+    /// A function registered by the program embedding the interpreter
+    /// failed.
+    Native {
+        function: String,
+        message: String,
+    },
+    ZeroForStep,
+    InputExhausted,
+    Io(String),
+    StackOverflow,
+    LimitExceeded(Limit),
+    UnsupportedNode(String),
+    /// A [`Hook`] asked for the run to stop.
+    Interrupted,
+}
+
+/// A [`RuntimeError`] with where it happened: the innermost expression or
+/// statement that failed, and the function calls it was inside of.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Failure {
+    pub error: RuntimeError,
+    pub span: Span,
+    /// Innermost first.
+    pub stack: Vec<StackFrame>,
+}
+
+/// A call to a function that an error escaped from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StackFrame {
+    /// `None` for an anonymous function.
+    pub function: Option<String>,
+    /// Where the function was called from.
+    pub call: Span,
+}
+
+/// Resource limits for running untrusted programs. Limits left as `None`
+/// are not enforced.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Limits {
+    /// The most steps one run may take. A step is a statement or a loop
+    /// test in the evaluator and an instruction in the VM.
+    pub max_steps: Option<u64>,
+    /// The deepest function calls may nest.
+    pub max_depth: Option<usize>,
+    /// The most elements one array or map may hold, or bytes one string or
+    /// integer.
+    pub max_allocation: Option<usize>,
+    /// How long one run may take.
+    pub deadline: Option<Duration>,
+}
+
+/// Which of the [`Limits`] a program ran into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    Steps,
+    Depth,
+    Allocation,
+    Time,
+}
+
+/// Tree-walking evaluator for [`Program`]s.
+///
+/// In strict mode assigning to a name that was never bound with `let`
+/// is an error; otherwise the assignment declares it in the current scope.
+/// With one-based arrays, as school pseudocode assumes, `a[1]` is the first
+/// element and `a[len(a)]` the last.
+pub struct Evaluator {
+    env: Rc<RefCell<Environment>>,
+    strict: bool,
+    one_based: bool,
+    console: Console,
+    builtins: Builtins,
+    meter: Meter,
+    depth: usize,
+    position: Span,
+    frames: Vec<Frame>,
+    /// Where the error being returned happened, once it is known.
+    failure: Option<(Span, Vec<StackFrame>)>,
+    hook: Option<Rc<RefCell<dyn Hook>>>,
+    stack_size: usize,
+    /// The address of the native stack when the program started.
+    stack_base: usize,
+}
+
+/// A function call in progress.
+#[derive(Debug, Clone)]
+pub struct Frame {
+    /// `None` for an anonymous function.
+    pub function: Option<String>,
+    /// Where the function was called from.
+    pub call: Span,
+    /// The scope of the call: its parameters and local variables.
+    pub env: Rc<RefCell<Environment>>,
+}
+
+/// Observes an [`Evaluator`] as it runs, e.g. to build a trace table.
+/// Every method does nothing by default.
+pub trait Hook {
+    /// `target`, a variable name or an element such as `v[2]`, was set to
+    /// `value` by the statement at `span`.
+    fn assignment(&mut self, _span: Span, _target: &str, _value: &Object) {}
+
+    /// The condition of a `daca`, a loop or a `pana cand` was tested.
+    fn condition(&mut self, _span: Span, _condition: &str, _value: bool) {}
+
+    /// `scrie` printed `text`, not counting the line break.
+    fn output(&mut self, _span: Span, _text: &str) {}
+
+    /// The statement at `span` is about to run, or a loop at `span` is about
+    /// to test its condition again. `frames` holds the function calls in
+    /// progress, outermost first, and `env` is the current scope. Breaking
+    /// stops the run with [`RuntimeError::Interrupted`].
+    fn statement(
+        &mut self,
+        _span: Span,
+        _frames: &[Frame],
+        _env: &Rc<RefCell<Environment>>,
+    ) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+}
+
+/// Counts the steps of a run against its [`Limits`].
+#[derive(Debug)]
+pub(crate) struct Meter {
+    limits: Limits,
+    steps: u64,
+    started: Instant,
+}
+
+/// Where `citeste` reads from and `scrie` writes to. Input is consumed one
+/// whitespace-separated word at a time.
+pub(crate) struct Console {
+    input: Box<dyn BufRead>,
+    output: Box<dyn Write>,
+    words: VecDeque<String>,
+}
+
+impl RuntimeError {
+    /// The code the message is kept under in the [`messages`] catalogue.
+    pub fn code(&self) -> &'static str {
+        match self {
+            RuntimeError::UndefinedVariable(_) => "E0201",
+            RuntimeError::UndeclaredAssignment(_) => "E0202",
+            RuntimeError::TypeMismatch { .. } => "E0203",
+            RuntimeError::UnknownPrefixOperator { .. } => "E0204",
+            RuntimeError::UnknownInfixOperator { .. } => "E0205",
+            RuntimeError::InvalidOperand { .. } => "E0206",
+            RuntimeError::DivisionByZero => "E0207",
+            RuntimeError::IntegerOverflow => "E0208",
+            RuntimeError::IndexOutOfRange { .. } => "E0209",
+            RuntimeError::IndexNotSupported { .. } => "E0210",
+            RuntimeError::UnhashableKey(_) => "E0211",
+            RuntimeError::KeyNotFound(_) => "E0212",
+            RuntimeError::NotCallable(_) => "E0213",
+            RuntimeError::WrongArgumentCount { .. } => "E0214",
+            RuntimeError::InvalidArgument { .. } => "E0215",
+            RuntimeError::ZeroForStep => "E0216",
+            RuntimeError::InputExhausted => "E0217",
+            RuntimeError::Io(_) => "E0218",
+            RuntimeError::StackOverflow => "E0219",
+            RuntimeError::LimitExceeded(limit) => match limit {
+                Limit::Steps => "E0220",
+                Limit::Depth => "E0221",
+                Limit::Allocation => "E0222",
+                Limit::Time => "E0223",
+            },
+            RuntimeError::UnsupportedNode(_) => "E0224",
+            RuntimeError::Interrupted => "E0225",
+            RuntimeError::InvalidValue { .. } => "E0226",
+            RuntimeError::Native { .. } => "E0227",
+        }
+    }
+
+    pub fn message(&self, lang: Lang) -> String {
+        let args: Vec<&dyn fmt::Display> = match self {
+            RuntimeError::UndefinedVariable(name) | RuntimeError::UndeclaredAssignment(name) => {
+                vec![name]
+            }
+            RuntimeError::TypeMismatch {
+                left,
+                operator,
+                right,
+            }
+            | RuntimeError::UnknownInfixOperator {
+                left,
+                operator,
+                right,
+            } => vec![left, operator, right],
+            RuntimeError::UnknownPrefixOperator { operator, right } => vec![operator, right],
+            RuntimeError::InvalidOperand { operator, operand } => vec![operator, operand],
+            RuntimeError::IndexOutOfRange { index, length } => vec![index, length],
+            RuntimeError::IndexNotSupported { left, index } => vec![left, index],
+            RuntimeError::UnhashableKey(value) | RuntimeError::NotCallable(value) => vec![value],
+            RuntimeError::KeyNotFound(key) => vec![key],
+            RuntimeError::WrongArgumentCount {
+                function,
+                expected,
+                got,
+            } => vec![function, got, expected],
+            RuntimeError::InvalidArgument { function, argument } => vec![function, argument],
+            RuntimeError::InvalidValue { function, value } => vec![function, value],
+            RuntimeError::Native { function, message } => vec![function, message],
+            RuntimeError::Io(error) => vec![error],
+            RuntimeError::UnsupportedNode(node) => vec![node],
+            RuntimeError::DivisionByZero
+            | RuntimeError::IntegerOverflow
+            | RuntimeError::ZeroForStep
+            | RuntimeError::InputExhausted
+            | RuntimeError::StackOverflow
+            | RuntimeError::LimitExceeded(_)
+            | RuntimeError::Interrupted => Vec::new(),
+        };
+        messages::message(self.code(), lang, &args)
+    }
+}
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message(Lang::En))
+    }
+}
+
+impl std::error::Error for RuntimeError {}
+
+impl Failure {
+    /// The error with the line of `source` it happened on, a caret under
+    /// the failing expression and the calls it happened inside of. A call
+    /// repeated by recursion is shown once with how often it repeats.
+    pub fn render(&self, source: &str, lang: Lang) -> String {
+        let label = format!(
+            "{}[{}]: {}",
+            messages::message("error", lang, &[]),
+            self.error.code(),
+            self.error.message(lang)
+        );
+        let mut text = self.span.annotate(source, &label);
+        let anonymous = messages::message("anonymous-function", lang, &[]);
+        for frames in self.stack.chunk_by(|a, b| a == b) {
+            let frame = &frames[0];
+            let function = frame.function.as_deref().unwrap_or(&anonymous);
+            let call = format!("{}:{}", frame.call.line, frame.call.column);
+            let line = messages::message("called-at", lang, &[&function, &call]);
+            text.push_str(&format!("\n  = {}", line));
+            if frames.len() > 1 {
+                let repeats = frames.len() - 1;
+                let line = messages::message("repeated", lang, &[&repeats]);
+                text.push_str(&format!("\n  = {}", line));
+            }
+        }
+        text
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at {}:{}",
+            self.error, self.span.line, self.span.column
+        )
+    }
+}
+
+impl std::error::Error for Failure {}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Limit::Steps => write!(f, "step limit"),
+            Limit::Depth => write!(f, "recursion depth limit"),
+            Limit::Allocation => write!(f, "allocation limit"),
+            Limit::Time => write!(f, "time limit"),
+        }
+    }
+}
+
+impl Meter {
+    /// How many steps pass between two looks at the clock.
+    const CLOCK_INTERVAL: u64 = 1024;
+
+    /// The size in bytes from which working out a result is slow enough to
+    /// look at the clock first.
+    const LARGE_RESULT: usize = 4096;
+
+    pub(crate) fn new(limits: Limits) -> Self {
+        Self {
+            limits,
+            steps: 0,
+            started: Instant::now(),
+        }
+    }
+
+    /// Resets the step count and the clock for a new run.
+    pub(crate) fn start(&mut self) {
+        self.steps = 0;
+        self.started = Instant::now();
+    }
+
+    #[inline]
+    pub(crate) fn step(&mut self) -> Result<(), Limit> {
+        self.steps += 1;
+        if self.limits.max_steps.is_some_and(|max| self.steps > max) {
+            return Err(Limit::Steps);
+        }
+        if let Some(deadline) = self.limits.deadline {
+            if self.steps.is_multiple_of(Self::CLOCK_INTERVAL) && self.started.elapsed() > deadline
+            {
+                return Err(Limit::Time);
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn check_depth(&self, depth: usize) -> Result<(), Limit> {
+        match self.limits.max_depth {
+            Some(max) if depth > max => Err(Limit::Depth),
+            _ => Ok(()),
+        }
+    }
+
+    /// Rejects a result of at least `bytes` before it is worked out, since
+    /// multiplying integers that large takes long enough to get around the
+    /// time limit. Before a large one the clock is looked at too, as a few
+    /// of them can outlast the deadline between two steps that do.
+    pub(crate) fn check_size(&self, bytes: usize) -> Result<(), Limit> {
+        if self.limits.max_allocation.is_some_and(|max| bytes > max) {
+            return Err(Limit::Allocation);
+        }
+        if let Some(deadline) = self.limits.deadline {
+            if bytes >= Self::LARGE_RESULT && self.started.elapsed() > deadline {
+                return Err(Limit::Time);
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn check_allocation(&self, value: &Object) -> Result<(), Limit> {
+        let Some(max) = self.limits.max_allocation else {
+            return Ok(());
+        };
+        let size = match value {
+            Object::Str(value) => value.len(),
+            Object::BigInteger(value) => value.byte_len(),
+            Object::Array(elements) => elements.borrow().len(),
+            Object::Map(pairs) => pairs.borrow().len(),
+            _ => 0,
+        };
+        if size > max {
+            return Err(Limit::Allocation);
+        }
+        Ok(())
+    }
+}
+
+impl From<io::Error> for RuntimeError {
+    fn from(error: io::Error) -> Self {
+        RuntimeError::Io(error.to_string())
+    }
+}
+
+impl Console {
+    pub(crate) fn new(input: Box<dyn BufRead>, output: Box<dyn Write>) -> Self {
+        Self {
+            input,
+            output,
+            words: VecDeque::new(),
+        }
+    }
+
+    pub(crate) fn stdio() -> Self {
+        Self::new(
+            Box::new(BufReader::new(io::stdin())),
+            Box::new(io::stdout()),
+        )
+    }
+
+    /// The next word of input: an integer or a real if it parses as one,
+    /// otherwise a string.
+    pub(crate) fn read(&mut self) -> Result<Object, RuntimeError> {
+        while self.words.is_empty() {
+            let mut line = String::new();
+            if self.input.read_line(&mut line)? == 0 {
+                return Err(RuntimeError::InputExhausted);
+            }
+            self.words
+                .extend(line.split_whitespace().map(str::to_string));
+        }
+
+        let word = self.words.pop_front().unwrap_or_default();
+        if let Ok(value) = word.parse::<BigInt>() {
+            Ok(Object::from(value))
+        } else if let Ok(value) = word.parse::<f64>() {
+            Ok(Object::Real(value))
+        } else {
+            Ok(Object::Str(word))
+        }
+    }
+
+    /// Writes `values` back to back and ends the line.
+    pub(crate) fn write(&mut self, values: &[Object]) -> Result<(), RuntimeError> {
+        for value in values {
+            write!(self.output, "{}", value)?;
+        }
+        writeln!(self.output)?;
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Console {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Console")
+            .field("words", &self.words)
+            .finish_non_exhaustive()
+    }
+}
+
+impl fmt::Debug for Evaluator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Evaluator")
+            .field("strict", &self.strict)
+            .field("one_based", &self.one_based)
+            .field("meter", &self.meter)
+            .field("depth", &self.depth)
+            .field("position", &self.position)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for Evaluator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Evaluator {
+    pub fn new() -> Self {
+        Self {
+            env: Rc::new(RefCell::new(Environment::new())),
+            strict: false,
+            one_based: false,
+            console: Console::stdio(),
+            builtins: Builtins::new(),
+            meter: Meter::new(Limits::default()),
+            depth: 0,
+            position: Span::default(),
+            frames: Vec::new(),
+            failure: None,
+            hook: None,
+            stack_size: DEFAULT_STACK_SIZE,
+            stack_base: 0,
+        }
+    }
+
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    pub fn one_based_arrays(mut self, one_based: bool) -> Self {
+        self.one_based = one_based;
+        self
+    }
+
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.meter = Meter::new(limits);
+        self
+    }
+
+    /// Tells the evaluator its thread has `bytes` of native stack. Calls
+    /// stop with [`RuntimeError::StackOverflow`] once a program has used
+    /// half of it, leaving the rest for the caller and the call being made.
+    pub fn stack_size(mut self, bytes: usize) -> Self {
+        self.stack_size = bytes;
+        self
+    }
+
+    /// Reports statements, assignments, conditions and output to `hook`.
+    pub fn hook(mut self, hook: Rc<RefCell<dyn Hook>>) -> Self {
+        self.hook = Some(hook);
+        self
+    }
+
+    /// Reads `citeste` input from `input` and writes `scrie` output to
+    /// `output` instead of the standard streams.
+    pub fn with_io(mut self, input: Box<dyn BufRead>, output: Box<dyn Write>) -> Self {
+        self.console = Console::new(input, output);
+        self
+    }
+
+    /// Makes `builtins` callable instead of the standard ones.
+    pub fn builtins(mut self, builtins: Builtins) -> Self {
+        self.builtins = builtins;
+        self
+    }
+
+    pub(crate) fn builtins_mut(&mut self) -> &mut Builtins {
+        &mut self.builtins
+    }
+
+    pub fn env(&self) -> Rc<RefCell<Environment>> {
+        Rc::clone(&self.env)
+    }
+
+    /// Runs `program`. The [`Limits`] apply to each call separately.
+    pub fn eval_program(&mut self, program: &Program) -> Result<Object, Failure> {
+        resolver::resolve(program);
+        self.meter.start();
+        self.depth = 0;
+        self.stack_base = stack_address();
+        self.frames.clear();
+        self.failure = None;
+
+        self.eval_statements(program).map_err(|error| {
+            let (span, stack) = self.failure.take().unwrap_or_default();
+            Failure { error, span, stack }
+        })
+    }
+
+    fn eval_statements(&mut self, program: &Program) -> Result<Object, RuntimeError> {
+        let mut result = Object::Null;
+        for statement in &program.statements {
+            result = self.eval_statement(&*statement.as_ref().borrow())?;
+            if let Object::ReturnValue(value) = result {
+                return Ok(*value);
+            }
+        }
+        Ok(result)
+    }
+
+    /// Remembers `span` as where the error being returned happened, unless
+    /// a node inside it already failed.
+    fn fail_at(&mut self, span: Span) {
+        if self.failure.is_none() {
+            self.failure = Some((span, Vec::new()));
+        }
+    }
+
+    fn eval_statement(&mut self, statement: &dyn Statement) -> Result<Object, RuntimeError> {
+        let result = self.eval_statement_node(statement);
+        if result.is_err() {
+            self.fail_at(statement.span());
+        }
+        result
+    }
+
+    fn eval_statement_node(&mut self, statement: &dyn Statement) -> Result<Object, RuntimeError> {
+        let any = statement.as_any();
+        self.step(statement.span())?;
+        if !any.is::<BlockStatement>() {
+            self.reach(statement.span())?;
+        }
+
+        // Each kind of statement has its own method so that this one, which
+        // every call recurses through, keeps a small stack frame.
+        if let Some(statement) = any.downcast_ref::<ExpressionStatement>() {
+            self.eval_optional(&statement.expression)
+        } else if let Some(statement) = any.downcast_ref::<LetStatement>() {
+            self.eval_let_statement(statement)
+        } else if let Some(statement) = any.downcast_ref::<AssignStatement>() {
+            self.eval_assign_statement(statement)
+        } else if let Some(statement) = any.downcast_ref::<IndexAssignStatement>() {
+            self.eval_index_assign_statement(statement)
+        } else if let Some(statement) = any.downcast_ref::<ReturnStatement>() {
+            self.eval_return_statement(statement)
+        } else if let Some(block) = any.downcast_ref::<BlockStatement>() {
+            self.eval_block(block)
+        } else if let Some(statement) = any.downcast_ref::<IfStatement>() {
+            self.eval_if_statement(statement)
+        } else if let Some(statement) = any.downcast_ref::<WhileStatement>() {
+            self.eval_while_statement(statement)
+        } else if let Some(statement) = any.downcast_ref::<RepeatStatement>() {
+            self.eval_repeat_statement(statement)
+        } else if let Some(statement) = any.downcast_ref::<ForStatement>() {
+            self.eval_for_statement(statement)
+        } else if let Some(statement) = any.downcast_ref::<ReadStatement>() {
+            self.eval_read_statement(statement)
+        } else if let Some(statement) = any.downcast_ref::<WriteStatement>() {
+            self.eval_write_statement(statement)
+        } else {
+            Err(RuntimeError::UnsupportedNode(statement.string()))
+        }
+    }
+
+    fn eval_let_statement(&mut self, statement: &LetStatement) -> Result<Object, RuntimeError> {
+        let value = self.eval_optional(&statement.value)?;
+        if let Some(name) = &statement.name {
+            self.notify(|hook| hook.assignment(statement.span(), &name.value, &value));
+            self.env.borrow_mut().set(name.value.clone(), value);
+        }
+        Ok(Object::Null)
+    }
+
+    fn eval_return_statement(
+        &mut self,
+        statement: &ReturnStatement,
+    ) -> Result<Object, RuntimeError> {
+        let value = self.eval_optional(&statement.return_value)?;
+        Ok(Object::ReturnValue(Box::new(value)))
+    }
+
+    fn eval_if_statement(&mut self, statement: &IfStatement) -> Result<Object, RuntimeError> {
+        if self.eval_condition(&statement.condition)? {
+            self.eval_block(&statement.consequence)
+        } else if let Some(alternative) = &statement.alternative {
+            self.eval_block(alternative)
+        } else {
+            Ok(Object::Null)
+        }
+    }
+
+    fn eval_while_statement(&mut self, statement: &WhileStatement) -> Result<Object, RuntimeError> {
+        while self.eval_condition(&statement.condition)? {
+            self.step(statement.token.span)?;
+            let result = self.eval_block(&statement.body)?;
+            if let Object::ReturnValue(_) = result {
+                return Ok(result);
+            }
+            self.reach(statement.token.span)?;
+        }
+        Ok(Object::Null)
+    }
+
+    fn eval_repeat_statement(
+        &mut self,
+        statement: &RepeatStatement,
+    ) -> Result<Object, RuntimeError> {
+        loop {
+            let result = self.eval_block(&statement.body)?;
+            if let Object::ReturnValue(_) = result {
+                return Ok(result);
+            }
+            self.step(statement.token.span)?;
+            if let Some(condition) = &statement.condition {
+                self.reach(condition.as_ref().borrow().span())?;
+            }
+            if self.eval_condition(&statement.condition)? {
+                return Ok(Object::Null);
+            }
+        }
+    }
+
+    fn eval_read_statement(&mut self, statement: &ReadStatement) -> Result<Object, RuntimeError> {
+        for target in &statement.targets {
+            let target = target.as_ref().borrow();
+            if let Err(error) = self.eval_read_target(&*target) {
+                self.fail_at(target.span());
+                return Err(error);
+            }
+        }
+        Ok(Object::Null)
+    }
+
+    fn eval_write_statement(&mut self, statement: &WriteStatement) -> Result<Object, RuntimeError> {
+        let values = self.eval_expressions(&statement.arguments)?;
+        if self.hook.is_some() {
+            let text: String = values.iter().map(|value| value.to_string()).collect();
+            self.notify(|hook| hook.output(statement.span(), &text));
+        }
+        self.console.write(&values)?;
+        Ok(Object::Null)
+    }
+
+    fn eval_assign_statement(
+        &mut self,
+        statement: &AssignStatement,
+    ) -> Result<Object, RuntimeError> {
+        let name = &statement.name.value;
+        let value = self.eval_optional(&statement.value)?;
+
+        let value = match compound_operator(&statement.token.token_type) {
+            Some(operator) => {
+                let current = self.eval_identifier(name)?;
+                self.infix(operator, current, value)?
+            }
+            None => value,
+        };
+
+        self.check_allocation(&value)?;
+        self.notify(|hook| hook.assignment(statement.span(), name, &value));
+        self.assign(name, value)?;
+        Ok(Object::Null)
+    }
+
+    /// Rebinds `name`, declaring it in the current scope if it is unbound
+    /// and strict mode is off.
+    fn assign(&mut self, name: &str, value: Object) -> Result<(), RuntimeError> {
+        let assigned = self.env.borrow_mut().assign(name, value.clone());
+        if !assigned {
+            if self.strict {
+                return Err(RuntimeError::UndeclaredAssignment(name.to_string()));
+            }
+            self.env.borrow_mut().set(name.to_string(), value);
+        }
+        Ok(())
+    }
+
+    fn eval_index_assign_statement(
+        &mut self,
+        statement: &IndexAssignStatement,
+    ) -> Result<Object, RuntimeError> {
+        let target = statement.target.as_ref().borrow();
+        let Some(target) = target.as_any().downcast_ref::<IndexExpression>() else {
+            return Err(RuntimeError::UnsupportedNode(statement.string()));
+        };
+
+        let left = self.eval_optional(&target.left)?;
+        let index = self.eval_optional(&target.index)?;
+        let value = self.eval_optional(&statement.value)?;
+
+        let value = match compound_operator(&statement.token.token_type) {
+            Some(operator) => {
+                let current = eval_index_expression(left.clone(), index.clone(), self.one_based)?;
+                self.infix(operator, current, value)?
+            }
+            None => value,
+        };
+
+        self.check_allocation(&value)?;
+        if self.hook.is_some() {
+            let element = element_name(target, &index);
+            self.notify(|hook| hook.assignment(statement.span(), &element, &value));
+        }
+        set_index(left.clone(), index, value, self.one_based)?;
+        self.check_allocation(&left)?;
+        Ok(Object::Null)
+    }
+
+    fn notify(&self, event: impl FnOnce(&mut dyn Hook)) {
+        if let Some(hook) = &self.hook {
+            event(&mut *hook.borrow_mut());
+        }
+    }
+
+    /// Evaluates the condition of a branch or loop, reporting it to the
+    /// hook.
+    fn eval_condition(
+        &mut self,
+        condition: &Option<Rc<RefCell<dyn Expression>>>,
+    ) -> Result<bool, RuntimeError> {
+        let value = self.eval_optional(condition)?.is_truthy();
+        if let (Some(_), Some(condition)) = (&self.hook, condition) {
+            let condition = condition.as_ref().borrow();
+            self.notify(|hook| hook.condition(condition.span(), &condition.string(), value));
+        }
+        Ok(value)
+    }
+
+    /// Tells the hook execution has reached `span`, stopping if it says so.
+    fn reach(&mut self, span: Span) -> Result<(), RuntimeError> {
+        let Some(hook) = &self.hook else {
+            return Ok(());
+        };
+        match hook.borrow_mut().statement(span, &self.frames, &self.env) {
+            ControlFlow::Continue(()) => Ok(()),
+            ControlFlow::Break(()) => Err(RuntimeError::Interrupted),
+        }
+    }
+
+    /// Counts one step towards the limits, remembering `span` as where
+    /// execution is.
+    fn step(&mut self, span: Span) -> Result<(), RuntimeError> {
+        self.position = span;
+        self.meter.step().map_err(RuntimeError::LimitExceeded)
+    }
+
+    fn check_allocation(&self, value: &Object) -> Result<(), RuntimeError> {
+        self.meter
+            .check_allocation(value)
+            .map_err(RuntimeError::LimitExceeded)
+    }
+
+    fn eval_block(&mut self, block: &BlockStatement) -> Result<Object, RuntimeError> {
+        for statement in &block.statements {
+            let result = self.eval_statement(&*statement.as_ref().borrow())?;
+            if let Object::ReturnValue(_) = result {
+                return Ok(result);
+            }
+        }
+        Ok(Object::Null)
+    }
+
+    /// `pentru i ← start, end, step` evaluates its bounds once, then runs
+    /// while the counter has not passed `end`. The counter is an ordinary
+    /// variable, so the body may change it.
+    fn eval_for_statement(&mut self, statement: &ForStatement) -> Result<Object, RuntimeError> {
+        let name = &statement.counter.value;
+        let start = self.eval_optional(&statement.start)?;
+        let end = self.eval_optional(&statement.end)?;
+        let step = match &statement.step {
+            Some(step) => self.eval_expression(&*step.as_ref().borrow())?,
+            None => Object::Integer(1),
+        };
+
+        let span = statement.token.span;
+        let condition = match (&self.hook, &statement.end) {
+            (Some(_), Some(end)) => {
+                let operator = for_operator(step.clone())?;
+                format!("{} {} {}", name, operator, end.as_ref().borrow().string())
+            }
+            _ => String::new(),
+        };
+
+        self.notify(|hook| hook.assignment(span, name, &start));
+        self.assign(name, start)?;
+        loop {
+            let value = for_condition(self.eval_identifier(name)?, end.clone(), step.clone())?;
+            self.notify(|hook| hook.condition(span, &condition, value));
+            if !value {
+                break;
+            }
+
+            self.step(span)?;
+            let result = self.eval_block(&statement.body)?;
+            if let Object::ReturnValue(_) = result {
+                return Ok(result);
+            }
+            let next = eval_infix_expression("+", self.eval_identifier(name)?, step.clone())?;
+            self.notify(|hook| hook.assignment(span, name, &next));
+            self.assign(name, next)?;
+            self.reach(span)?;
+        }
+
+        Ok(Object::Null)
+    }
+
+    fn eval_read_target(&mut self, target: &dyn Expression) -> Result<(), RuntimeError> {
+        let any = target.as_any();
+
+        if let Some(identifier) = any.downcast_ref::<Identifier>() {
+            let value = self.console.read()?;
+            self.check_allocation(&value)?;
+            let span = self.position;
+            self.notify(|hook| hook.assignment(span, &identifier.value, &value));
+            self.assign(&identifier.value, value)
+        } else if let Some(target) = any.downcast_ref::<IndexExpression>() {
+            let left = self.eval_optional(&target.left)?;
+            let index = self.eval_optional(&target.index)?;
+            let value = self.console.read()?;
+            self.check_allocation(&value)?;
+            if self.hook.is_some() {
+                let element = element_name(target, &index);
+                let span = self.position;
+                self.notify(|hook| hook.assignment(span, &element, &value));
+            }
+            set_index(left.clone(), index, value, self.one_based)?;
+            self.check_allocation(&left)
+        } else {
+            Err(RuntimeError::UnsupportedNode(target.string()))
+        }
+    }
+
+    fn eval_optional(
+        &mut self,
+        expression: &Option<Rc<RefCell<dyn Expression>>>,
+    ) -> Result<Object, RuntimeError> {
+        match expression {
+            Some(expression) => self.eval_expression(&*expression.as_ref().borrow()),
+            None => Ok(Object::Null),
+        }
+    }
+
+    fn eval_expression(&mut self, expression: &dyn Expression) -> Result<Object, RuntimeError> {
+        let result = self.eval_expression_node(expression);
+        if result.is_err() {
+            self.fail_at(expression.span());
+        }
+        result
+    }
+
+    fn eval_expression_node(
+        &mut self,
+        expression: &dyn Expression,
+    ) -> Result<Object, RuntimeError> {
+        let any = expression.as_any();
+
+        // As with statements, the work happens in one method per kind of
+        // expression to keep this frame small.
+        if let Some(literal) = any.downcast_ref::<IntegralLiteral>() {
+            Ok(Object::from(&literal.value))
+        } else if let Some(literal) = any.downcast_ref::<RealLiteral>() {
+            Ok(Object::Real(literal.value))
+        } else if let Some(literal) = any.downcast_ref::<StringLiteral>() {
+            Ok(Object::Str(literal.value.clone()))
+        } else if let Some(literal) = any.downcast_ref::<BooleanLiteral>() {
+            Ok(Object::Boolean(literal.value))
+        } else if let Some(array) = any.downcast_ref::<ArrayLiteral>() {
+            self.eval_array_literal(array)
+        } else if let Some(map) = any.downcast_ref::<MapLiteral>() {
+            self.eval_map_literal(map)
+        } else if let Some(index) = any.downcast_ref::<IndexExpression>() {
+            self.eval_index_expression(index)
+        } else if let Some(literal) = any.downcast_ref::<FunctionLiteral>() {
+            Ok(self.eval_function_literal(literal))
+        } else if let Some(call) = any.downcast_ref::<CallExpression>() {
+            self.eval_call_expression(call)
+        } else if let Some(identifier) = any.downcast_ref::<Identifier>() {
+            self.eval_resolved_identifier(identifier)
+        } else if let Some(prefix) = any.downcast_ref::<PrefixExpression>() {
+            let right = self.eval_optional(&prefix.right)?;
+            eval_prefix_expression(&prefix.operator, right)
+        } else if let Some(integer_part) = any.downcast_ref::<IntegerPartExpression>() {
+            let value = self.eval_optional(&integer_part.value)?;
+            eval_integer_part(value)
+        } else if let Some(infix) = any.downcast_ref::<InfixExpression>() {
+            self.eval_infix(infix)
+        } else {
+            Err(RuntimeError::UnsupportedNode(expression.string()))
+        }
+    }
+
+    fn eval_array_literal(&mut self, array: &ArrayLiteral) -> Result<Object, RuntimeError> {
+        let elements = self.eval_expressions(&array.elements)?;
+        let array = Object::array(elements);
+        self.check_allocation(&array)?;
+        Ok(array)
+    }
+
+    fn eval_map_literal(&mut self, map: &MapLiteral) -> Result<Object, RuntimeError> {
+        let mut pairs = BTreeMap::new();
+        for (key, value) in &map.pairs {
+            let key = self.eval_expression(&*key.as_ref().borrow())?;
+            let value = self.eval_expression(&*value.as_ref().borrow())?;
+            pairs.insert(map_key(&key)?, value);
+        }
+        let map = Object::map(pairs);
+        self.check_allocation(&map)?;
+        Ok(map)
+    }
+
+    fn eval_index_expression(&mut self, index: &IndexExpression) -> Result<Object, RuntimeError> {
+        let left = self.eval_optional(&index.left)?;
+        let position = self.eval_optional(&index.index)?;
+        eval_index_expression(left, position, self.one_based)
+    }
+
+    fn eval_function_literal(&self, literal: &FunctionLiteral) -> Object {
+        Object::Function(Rc::new(Function {
+            name: literal.name.clone(),
+            parameters: literal.parameters.iter().map(|p| p.value.clone()).collect(),
+            body: Rc::clone(&literal.body),
+            env: self.env(),
+        }))
+    }
+
+    fn eval_call_expression(&mut self, call: &CallExpression) -> Result<Object, RuntimeError> {
+        let function = self.eval_optional(&call.function)?;
+        let arguments = self.eval_expressions(&call.arguments)?;
+        self.apply_function(function, arguments, call.span())
+    }
+
+    /// Looks the identifier up where the resolver found it bound, or by
+    /// name if it did not.
+    fn eval_resolved_identifier(&self, identifier: &Identifier) -> Result<Object, RuntimeError> {
+        let resolved = identifier
+            .depth
+            .get()
+            .and_then(|depth| self.env.borrow().get_at(depth, &identifier.value));
+        match resolved {
+            Some(value) => Ok(value),
+            None => self.eval_identifier(&identifier.value),
+        }
+    }
+
+    fn eval_infix(&mut self, infix: &InfixExpression) -> Result<Object, RuntimeError> {
+        if let "&&" | "||" = infix.operator.as_str() {
+            return self.eval_logical_expression(infix);
+        }
+        let left = self.eval_optional(&infix.left)?;
+        let right = self.eval_optional(&infix.right)?;
+        let result = self.infix(&infix.operator, left, right)?;
+        self.check_allocation(&result)?;
+        Ok(result)
+    }
+
+    /// [`eval_infix_expression`], after checking its result can fit in the
+    /// allocation limit.
+    fn infix(&self, operator: &str, left: Object, right: Object) -> Result<Object, RuntimeError> {
+        self.meter
+            .check_size(result_size(operator, &left, &right))
+            .map_err(RuntimeError::LimitExceeded)?;
+        eval_infix_expression(operator, left, right)
+    }
+
+    /// `&&` and `||` only evaluate their right operand when the left one
+    /// does not already decide the result.
+    fn eval_logical_expression(&mut self, infix: &InfixExpression) -> Result<Object, RuntimeError> {
+        let left = self.eval_optional(&infix.left)?.is_truthy();
+        if left == (infix.operator == "||") {
+            return Ok(Object::Boolean(left));
+        }
+
+        let right = self.eval_optional(&infix.right)?;
+        Ok(Object::Boolean(right.is_truthy()))
+    }
+
+    fn eval_expressions(
+        &mut self,
+        expressions: &[Rc<RefCell<dyn Expression>>],
+    ) -> Result<Vec<Object>, RuntimeError> {
+        expressions
+            .iter()
+            .map(|e| self.eval_expression(&*e.as_ref().borrow()))
+            .collect()
+    }
+
+    fn eval_identifier(&self, name: &str) -> Result<Object, RuntimeError> {
+        if let Some(value) = self.env.borrow().get(name) {
+            return Ok(value);
+        }
+        self.builtins
+            .get(name)
+            .map(|builtin| Object::Builtin(Rc::clone(&builtin.name)))
+            .ok_or_else(|| RuntimeError::UndefinedVariable(name.to_string()))
+    }
+
+    fn apply_function(
+        &mut self,
+        function: Object,
+        arguments: Vec<Object>,
+        call: Span,
+    ) -> Result<Object, RuntimeError> {
+        let function = match function {
+            Object::Function(function) => function,
+            Object::Builtin(name) => {
+                let target = arguments.first().cloned().unwrap_or(Object::Null);
+                self.meter
+                    .check_size(self.builtins.result_size(&name, &arguments))
+                    .map_err(RuntimeError::LimitExceeded)?;
+                let result = self.builtins.call(&name, &arguments)?;
+                self.check_allocation(&target)?;
+                return Ok(result);
+            }
+            function => return Err(RuntimeError::NotCallable(function.type_name())),
+        };
+
+        check_arity(&function.name, function.parameters.len(), arguments.len())?;
+        self.meter
+            .check_depth(self.depth + 1)
+            .map_err(RuntimeError::LimitExceeded)?;
+        if self.depth >= MAX_DEPTH
+            || stack_address().abs_diff(self.stack_base) > self.stack_size / 2
+        {
+            return Err(RuntimeError::StackOverflow);
+        }
+
+        let mut env = Environment::new_enclosed(Rc::clone(&function.env));
+        for (parameter, argument) in function.parameters.iter().zip(arguments) {
+            env.set(parameter.clone(), argument);
+        }
+
+        let env = Rc::new(RefCell::new(env));
+        let caller = std::mem::replace(&mut self.env, Rc::clone(&env));
+        self.depth += 1;
+        if self.hook.is_some() {
+            self.frames.push(Frame {
+                function: function.name.clone(),
+                call,
+                env,
+            });
+        }
+        let result = self.eval_block(&function.body);
+        if self.hook.is_some() {
+            self.frames.pop();
+        }
+        if let (Err(_), Some((_, stack))) = (&result, &mut self.failure) {
+            stack.push(StackFrame {
+                function: function.name.clone(),
+                call,
+            });
+        }
+        self.depth -= 1;
+        self.env = caller;
+
+        match result? {
+            Object::ReturnValue(value) => Ok(*value),
+            _ => Ok(Object::Null),
+        }
+    }
+}
+
+/// Roughly where the native stack is now: the address of a local.
+fn stack_address() -> usize {
+    let marker = 0u8;
+    std::hint::black_box(&marker) as *const u8 as usize
+}
+
+/// The arithmetic operator behind a compound assignment such as `+=`.
+pub(crate) fn compound_operator(token_type: &TokenType) -> Option<&'static str> {
+    match token_type {
+        TokenType::PlusAssign => Some("+"),
+        TokenType::MinusAssign => Some("-"),
+        TokenType::AsteriskAssign => Some("*"),
+        TokenType::SlashAssign => Some("/"),
+        _ => None,
+    }
+}
+
+pub(crate) fn map_key(key: &Object) -> Result<HashKey, RuntimeError> {
+    key.hash_key()
+        .ok_or(RuntimeError::UnhashableKey(key.type_name()))
+}
+
+pub(crate) fn check_arity(
+    name: &Option<String>,
+    expected: usize,
+    got: usize,
+) -> Result<(), RuntimeError> {
+    if expected == got {
+        return Ok(());
+    }
+    Err(RuntimeError::WrongArgumentCount {
+        function: name
+            .clone()
+            .unwrap_or_else(|| "anonymous function".to_string()),
+        expected,
+        got,
+    })
+}
+
+pub(crate) fn eval_index_expression(
+    left: Object,
+    index: Object,
+    one_based: bool,
+) -> Result<Object, RuntimeError> {
+    match left {
+        Object::Array(elements) => {
+            let elements = elements.borrow();
+            let offset = array_offset(&elements, &index, one_based)?;
+            Ok(elements[offset].clone())
+        }
+        Object::Map(pairs) => pairs
+            .borrow()
+            .get(&map_key(&index)?)
+            .cloned()
+            .ok_or_else(|| RuntimeError::KeyNotFound(index.to_string())),
+        left => Err(RuntimeError::IndexNotSupported {
+            left: left.type_name(),
+            index: index.type_name(),
+        }),
+    }
+}
+
+pub(crate) fn set_index(
+    left: Object,
+    index: Object,
+    value: Object,
+    one_based: bool,
+) -> Result<(), RuntimeError> {
+    match left {
+        Object::Array(elements) => {
+            let offset = array_offset(&elements.borrow(), &index, one_based)?;
+            elements.borrow_mut()[offset] = value;
+        }
+        Object::Map(pairs) => {
+            let key = map_key(&index)?;
+            pairs.borrow_mut().insert(key, value);
+        }
+        left => {
+            return Err(RuntimeError::IndexNotSupported {
+                left: left.type_name(),
+                index: index.type_name(),
+            })
+        }
+    }
+    Ok(())
+}
+
+/// Translates a pseudocode index into a position in `elements`,
+/// honouring the one-based setting.
+fn array_offset(
+    elements: &[Object],
+    index: &Object,
+    one_based: bool,
+) -> Result<usize, RuntimeError> {
+    let out_of_range = || RuntimeError::IndexOutOfRange {
+        index: index.to_string(),
+        length: elements.len(),
+    };
+    let index = match *index {
+        Object::Integer(index) => index,
+        Object::BigInteger(_) => return Err(out_of_range()),
+        _ => {
+            return Err(RuntimeError::IndexNotSupported {
+                left: "ARRAY",
+                index: index.type_name(),
+            })
+        }
+    };
+
+    let offset = if one_based {
+        index.checked_sub(1)
+    } else {
+        Some(index)
+    };
+
+    offset
+        .and_then(|offset| usize::try_from(offset).ok())
+        .filter(|&offset| offset < elements.len())
+        .ok_or_else(out_of_range)
+}
+
+/// Whether a `pentru` loop runs again: `counter` has not yet passed `end`
+/// in the direction of `step`.
+pub(crate) fn for_condition(
+    counter: Object,
+    end: Object,
+    step: Object,
+) -> Result<bool, RuntimeError> {
+    let operator = for_operator(step)?;
+    Ok(eval_infix_expression(operator, counter, end)?.is_truthy())
+}
+
+/// The comparison a `pentru` loop with this `step` tests its counter with.
+fn for_operator(step: Object) -> Result<&'static str, RuntimeError> {
+    if eval_infix_expression("==", step.clone(), Object::Integer(0))?.is_truthy() {
+        return Err(RuntimeError::ZeroForStep);
+    }
+
+    if eval_infix_expression(">", step, Object::Integer(0))?.is_truthy() {
+        Ok("<=")
+    } else {
+        Ok(">=")
+    }
+}
+
+/// How a hook names the element `target` refers to, e.g. `v[2]`.
+fn element_name(target: &IndexExpression, index: &Object) -> String {
+    let left = target
+        .left
+        .as_ref()
+        .map(|left| left.as_ref().borrow().string())
+        .unwrap_or_default();
+    format!("{}[{}]", left, index)
+}
+
+pub(crate) fn eval_prefix_expression(
+    operator: &str,
+    right: Object,
+) -> Result<Object, RuntimeError> {
+    match (operator, right) {
+        ("!", right) => Ok(Object::Boolean(!right.is_truthy())),
+        ("-", Object::Integer(value)) => Ok(value
+            .checked_neg()
+            .map(Object::Integer)
+            .unwrap_or_else(|| Object::from(-BigInt::from(value)))),
+        ("-", Object::BigInteger(value)) => Ok(Object::from(-value)),
+        ("-", Object::Real(value)) => Ok(Object::Real(-value)),
+        (operator, right) => Err(RuntimeError::UnknownPrefixOperator {
+            operator: operator.to_string(),
+            right: right.type_name(),
+        }),
+    }
+}
+
+/// `[x]` is the mathematical integer part, so `[-2.5]` is `-3`.
+pub(crate) fn eval_integer_part(value: Object) -> Result<Object, RuntimeError> {
+    match value {
+        Object::Integer(_) | Object::BigInteger(_) => Ok(value),
+        Object::Real(value) => BigInt::from_f64(value.floor())
+            .map(Object::from)
+            .ok_or(RuntimeError::IntegerOverflow),
+        value => Err(RuntimeError::InvalidOperand {
+            operator: "[]".to_string(),
+            operand: value.type_name(),
+        }),
+    }
+}
+
+/// A lower bound on the bytes the result of `left operator right` takes:
+/// a product has at least one bit less than its factors together. Other
+/// results are never much larger than their operands.
+pub(crate) fn result_size(operator: &str, left: &Object, right: &Object) -> usize {
+    match (operator, left.bit_len(), right.bit_len()) {
+        ("*", Some(left), Some(right)) if left > 0 && right > 0 => {
+            BigInt::bytes_for(left + right - 1)
+        }
+        _ => 0,
+    }
+}
+
+pub(crate) fn eval_infix_expression(
+    operator: &str,
+    left: Object,
+    right: Object,
+) -> Result<Object, RuntimeError> {
+    match (left, right) {
+        (Object::Integer(left), Object::Integer(right)) => {
+            eval_integer_infix_expression(operator, left, right)
+        }
+        (Object::BigInteger(left), Object::BigInteger(right)) => {
+            eval_big_integer_infix_expression(operator, &left, &right)
+        }
+        (Object::BigInteger(left), Object::Integer(right)) => {
+            eval_big_integer_infix_expression(operator, &left, &BigInt::from(right))
+        }
+        (Object::Integer(left), Object::BigInteger(right)) => {
+            eval_big_integer_infix_expression(operator, &BigInt::from(left), &right)
+        }
+        (Object::Str(left), Object::Str(right)) => match operator {
+            "+" => Ok(Object::Str(left + &right)),
+            "==" => Ok(Object::Boolean(left == right)),
+            "!=" => Ok(Object::Boolean(left != right)),
+            "<" => Ok(Object::Boolean(left < right)),
+            "<=" => Ok(Object::Boolean(left <= right)),
+            ">" => Ok(Object::Boolean(left > right)),
+            ">=" => Ok(Object::Boolean(left >= right)),
+            _ => Err(RuntimeError::UnknownInfixOperator {
+                left: "STRING",
+                operator: operator.to_string(),
+                right: "STRING",
+            }),
+        },
+        (Object::Boolean(left), Object::Boolean(right)) => match operator {
+            "==" => Ok(Object::Boolean(left == right)),
+            "!=" => Ok(Object::Boolean(left != right)),
+            _ => Err(RuntimeError::UnknownInfixOperator {
+                left: "BOOLEAN",
+                operator: operator.to_string(),
+                right: "BOOLEAN",
+            }),
+        },
+        (left, right) => match (left.as_real(), right.as_real()) {
+            (Some(l), Some(r)) => eval_real_infix_expression(operator, l, r).unwrap_or_else(|| {
+                Err(RuntimeError::UnknownInfixOperator {
+                    left: left.type_name(),
+                    operator: operator.to_string(),
+                    right: right.type_name(),
+                })
+            }),
+            _ if left.type_name() != right.type_name() => Err(RuntimeError::TypeMismatch {
+                left: left.type_name(),
+                operator: operator.to_string(),
+                right: right.type_name(),
+            }),
+            _ => Err(RuntimeError::UnknownInfixOperator {
+                left: left.type_name(),
+                operator: operator.to_string(),
+                right: right.type_name(),
+            }),
+        },
+    }
+}
+
+/// Integer arithmetic follows C++: `div` truncates toward zero and `%`
+/// takes the sign of the dividend, so `-7 div 2` is `-3` and `-7 % 2` is
+/// `-1`. `/` is exact division and only stays integral when the quotient
+/// is a whole number. Results that overflow an `i64` are worked out again
+/// as [`BigInt`]s.
+fn eval_integer_infix_expression(
+    operator: &str,
+    left: i64,
+    right: i64,
+) -> Result<Object, RuntimeError> {
+    let arithmetic = |result: Option<i64>| match result {
+        Some(result) => Ok(Object::Integer(result)),
+        None => {
+            eval_big_integer_infix_expression(operator, &BigInt::from(left), &BigInt::from(right))
+        }
+    };
+
+    match operator {
+        "+" => arithmetic(left.checked_add(right)),
+        "-" => arithmetic(left.checked_sub(right)),
+        "*" => arithmetic(left.checked_mul(right)),
+        "/" | "div" | "%" if right == 0 => Err(RuntimeError::DivisionByZero),
+        "/" => match left.checked_rem(right) {
+            Some(0) | None => arithmetic(left.checked_div(right)),
+            Some(_) => Ok(Object::Real(left as f64 / right as f64)),
+        },
+        "div" => arithmetic(left.checked_div(right)),
+        "%" => Ok(Object::Integer(left.wrapping_rem(right))),
+        "<" => Ok(Object::Boolean(left < right)),
+        "<=" => Ok(Object::Boolean(left <= right)),
+        ">" => Ok(Object::Boolean(left > right)),
+        ">=" => Ok(Object::Boolean(left >= right)),
+        "==" => Ok(Object::Boolean(left == right)),
+        "!=" => Ok(Object::Boolean(left != right)),
+        _ => Err(RuntimeError::UnknownInfixOperator {
+            left: "INTEGER",
+            operator: operator.to_string(),
+            right: "INTEGER",
+        }),
+    }
+}
+
+/// Integer arithmetic where either side is outside the range of `i64`,
+/// with the same meaning as [`eval_integer_infix_expression`].
+fn eval_big_integer_infix_expression(
+    operator: &str,
+    left: &BigInt,
+    right: &BigInt,
+) -> Result<Object, RuntimeError> {
+    match operator {
+        "+" => Ok(Object::from(left + right)),
+        "-" => Ok(Object::from(left - right)),
+        "*" => Ok(Object::from(left * right)),
+        "/" | "div" | "%" => {
+            let (quotient, remainder) = left.div_rem(right).ok_or(RuntimeError::DivisionByZero)?;
+            match operator {
+                "%" => Ok(Object::from(remainder)),
+                "/" if !remainder.is_zero() => Ok(Object::Real(left.to_f64() / right.to_f64())),
+                _ => Ok(Object::from(quotient)),
+            }
+        }
+        "<" => Ok(Object::Boolean(left < right)),
+        "<=" => Ok(Object::Boolean(left <= right)),
+        ">" => Ok(Object::Boolean(left > right)),
+        ">=" => Ok(Object::Boolean(left >= right)),
+        "==" => Ok(Object::Boolean(left == right)),
+        "!=" => Ok(Object::Boolean(left != right)),
+        _ => Err(RuntimeError::UnknownInfixOperator {
+            left: "INTEGER",
+            operator: operator.to_string(),
+            right: "INTEGER",
+        }),
+    }
+}
+
+/// Returns `None` for operators that are not defined on reals.
+fn eval_real_infix_expression(
+    operator: &str,
+    left: f64,
+    right: f64,
+) -> Option<Result<Object, RuntimeError>> {
+    let result = match operator {
+        "+" => Object::Real(left + right),
+        "-" => Object::Real(left - right),
+        "*" => Object::Real(left * right),
+        "/" if right == 0.0 => return Some(Err(RuntimeError::DivisionByZero)),
+        "/" => Object::Real(left / right),
+        "<" => Object::Boolean(left < right),
+        "<=" => Object::Boolean(left <= right),
+        ">" => Object::Boolean(left > right),
+        ">=" => Object::Boolean(left >= right),
+        "==" => Object::Boolean(left == right),
+        "!=" => Object::Boolean(left != right),
+        _ => return None,
+    };
+    Some(Ok(result))
+}
@@ -0,0 +1,743 @@
+use std::io::Cursor;
+use std::thread;
+use std::time::Duration;
+
+use crate::{
+    messages::Lang,
+    object::Object,
+    test_support::{parse, Output},
+};
+
+use super::{Evaluator, Failure, Limit, Limits, RuntimeError, STACK_SIZE};
+
+fn eval_located(evaluator: &mut Evaluator, input: &str) -> Result<Object, Failure> {
+    evaluator.eval_program(&parse(input))
+}
+
+fn eval_with(evaluator: &mut Evaluator, input: &str) -> Result<Object, RuntimeError> {
+    eval_located(evaluator, input).map_err(|failure| failure.error)
+}
+
+fn eval(input: &str) -> Result<Object, RuntimeError> {
+    eval_with(&mut Evaluator::new(), input)
+}
+
+#[test]
+fn integer_expressions() {
+    let tests = vec![
+        ("5", 5),
+        ("-10", -10),
+        ("5 + 5 + 5 + 5 - 10", 10),
+        ("2 * 2 * 2 * 2 * 2", 32),
+        ("-50 + 100 + -50", 0),
+        ("20 + 2 * -10", 0),
+        ("2 * (5 + 10)", 30),
+        ("(5 + 10 * 2 + 15 / 3) * 2 + -10", 50),
+    ];
+
+    for (input, expected) in tests {
+        assert_eq!(eval(input), Ok(Object::Integer(expected)), "{}", input);
+    }
+}
+
+#[test]
+fn boolean_expressions() {
+    let tests = vec![
+        ("true", true),
+        ("!true", false),
+        ("!!5", true),
+        ("1 < 2", true),
+        ("1 >= 2", false),
+        ("2 <= 2", true),
+        ("1 != 2", true),
+        ("true == false", false),
+        ("(1 < 2) == true", true),
+        ("true && false", false),
+        ("true || false", true),
+        ("1 < 2 si 2 < 3", true),
+        ("1 > 2 sau 2 > 3", false),
+        ("nu (1 > 2) si not false", true),
+        ("false || 5", true),
+    ];
+
+    for (input, expected) in tests {
+        assert_eq!(eval(input), Ok(Object::Boolean(expected)), "{}", input);
+    }
+}
+
+#[test]
+fn let_and_return_statements() {
+    let tests = vec![
+        ("let a = 5; a;", 5),
+        ("let a = 5; let b = a * 2; b;", 10),
+        ("return 10; 9;", 10),
+        ("let a = 2; return a * 5; 9;", 10),
+    ];
+
+    for (input, expected) in tests {
+        assert_eq!(eval(input), Ok(Object::Integer(expected)), "{}", input);
+    }
+}
+
+#[test]
+fn assign_statements() {
+    let tests = vec![
+        ("let x = 1; x ← x + 1; x;", 2),
+        ("let x = 1; x = 7; x;", 7),
+        ("let x = 10; x += 5; x;", 15),
+        ("let x = 10; x -= 5; x;", 5),
+        ("let x = 10; x *= 5; x;", 50),
+        ("let x = 10; x /= 5; x;", 2),
+        ("s ← 0; s += 4; s *= s; s;", 16),
+    ];
+
+    for (input, expected) in tests {
+        assert_eq!(eval(input), Ok(Object::Integer(expected)), "{}", input);
+    }
+}
+
+#[test]
+fn strict_mode_rejects_undeclared_assignment() {
+    let mut evaluator = Evaluator::new().strict(true);
+    assert_eq!(
+        eval_with(&mut evaluator, "x ← 1;"),
+        Err(RuntimeError::UndeclaredAssignment("x".to_string()))
+    );
+
+    let mut evaluator = Evaluator::new().strict(true);
+    assert_eq!(
+        eval_with(&mut evaluator, "let x = 1; x ← 2; x;"),
+        Ok(Object::Integer(2))
+    );
+}
+
+#[test]
+fn runtime_errors() {
+    let tests = vec![
+        ("y += 1;", "identifier not found: y"),
+        ("5 + true;", "type mismatch: INTEGER + BOOLEAN"),
+        ("-true", "unknown operator: -BOOLEAN"),
+        ("true + false;", "unknown operator: BOOLEAN + BOOLEAN"),
+        ("let x = 1; x /= 0;", "division by zero"),
+    ];
+
+    for (input, expected) in tests {
+        let error = eval(input).unwrap_err();
+        assert_eq!(error.to_string(), expected, "{}", input);
+    }
+}
+
+#[test]
+fn logical_operators_short_circuit() {
+    let tests = vec![
+        ("false && 1 / 0 == 0", false),
+        ("true || 1 / 0 == 0", true),
+        ("let x = 0; x != 0 si 10 / x > 1", false),
+        ("let x = 0; x == 0 sau 10 / x > 1", true),
+    ];
+
+    for (input, expected) in tests {
+        assert_eq!(eval(input), Ok(Object::Boolean(expected)), "{}", input);
+    }
+
+    assert_eq!(
+        eval("true && 1 / 0 == 0"),
+        Err(RuntimeError::DivisionByZero)
+    );
+    assert_eq!(
+        eval("false || 1 / 0 == 0"),
+        Err(RuntimeError::DivisionByZero)
+    );
+}
+
+#[test]
+fn division_and_integer_part() {
+    let tests = vec![
+        ("7 div 2", Object::Integer(3)),
+        ("-7 div 2", Object::Integer(-3)),
+        ("7 div -2", Object::Integer(-3)),
+        ("7 % 3", Object::Integer(1)),
+        ("-7 % 3", Object::Integer(-1)),
+        ("7 mod -3", Object::Integer(1)),
+        ("(-9223372036854775807 - 1) % -1", Object::Integer(0)),
+        ("12 / 4", Object::Integer(3)),
+        ("7 / 2", Object::Real(3.5)),
+        ("-7 / 2", Object::Real(-3.5)),
+        ("[7 / 2]", Object::Integer(3)),
+        ("[-7 / 2]", Object::Integer(-4)),
+        ("[-2.5]", Object::Integer(-3)),
+        ("[4]", Object::Integer(4)),
+        ("[0.999] + 1", Object::Integer(1)),
+        ("1.5 + 1", Object::Real(2.5)),
+        ("2 * 0.25", Object::Real(0.5)),
+        ("-0.5", Object::Real(-0.5)),
+        ("1 / 4 == 0.25", Object::Boolean(true)),
+        ("3 < 3.5", Object::Boolean(true)),
+        ("2.0 == 2", Object::Boolean(true)),
+        ("let x = 9; x /= 2; x;", Object::Real(4.5)),
+    ];
+
+    for (input, expected) in tests {
+        assert_eq!(eval(input), Ok(expected), "{}", input);
+    }
+}
+
+#[test]
+fn division_errors() {
+    let tests = vec![
+        ("1 / 0", "division by zero"),
+        ("1 div 0", "division by zero"),
+        ("1 % 0", "division by zero"),
+        ("1 mod 0", "division by zero"),
+        ("1.5 / 0", "division by zero"),
+        ("1 / 0.0", "division by zero"),
+        ("7.5 div 2", "unknown operator: REAL div INTEGER"),
+        ("7 % 2.5", "unknown operator: INTEGER % REAL"),
+        ("[true]", "invalid operand for []: BOOLEAN"),
+        ("100000000000000000000 div 0", "division by zero"),
+        ("[-pow(10, 400.0)]", "integer overflow"),
+    ];
+
+    for (input, expected) in tests {
+        let error = eval(input).unwrap_err();
+        assert_eq!(error.to_string(), expected, "{}", input);
+    }
+}
+
+#[test]
+fn integers_grow_past_i64() {
+    let tests = vec![
+        ("9223372036854775807 + 1", "9223372036854775808"),
+        ("-9223372036854775807 - 2", "-9223372036854775809"),
+        ("-(-9223372036854775807 - 1)", "9223372036854775808"),
+        ("(-9223372036854775807 - 1) div -1", "9223372036854775808"),
+        ("(-9223372036854775807 - 1) / -1", "9223372036854775808"),
+        (
+            "let f = 1; pentru i ← 1, 30 { f *= i }; f",
+            "265252859812191058636308480000000",
+        ),
+        (
+            "let a = 0; let b = 1; pentru i ← 1, 100 { t ← a + b; a ← b; b ← t }; a",
+            "354224848179261915075",
+        ),
+        ("123456789012345678901234567890 % 1000", "890"),
+        ("-100000000000000000000 div 7", "-14285714285714285714"),
+        ("-100000000000000000000 % 7", "-2"),
+        ("100000000000000000000 / 8", "12500000000000000000"),
+        ("100000000000000000000 / 16", "6250000000000000000"),
+        ("100000000000000000000 > 9223372036854775807", "true"),
+        ("-100000000000000000000 < 5", "true"),
+        ("100000000000000000000 == 100000000000000000000.0", "true"),
+        ("100000000000000000000 + 0.5", "100000000000000000000"),
+        ("[100000000000000000000.0]", "100000000000000000000"),
+        (
+            "{100000000000000000000: 1, 5: 2, -100000000000000000000: 3}",
+            "{-100000000000000000000: 3, 5: 2, 100000000000000000000: 1}",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let value = eval(input).map(|value| value.to_string());
+        assert_eq!(value, Ok(expected.to_string()), "{}", input);
+    }
+
+    // Results back in range are ordinary integers again.
+    assert_eq!(
+        eval("(9223372036854775807 + 1) - 1"),
+        Ok(Object::Integer(i64::MAX))
+    );
+    assert_eq!(
+        eval("100000000000000000000 div 100000000000000000000"),
+        Ok(Object::Integer(1))
+    );
+    assert_eq!(
+        eval_located(&mut Evaluator::new(), "[1, 2][100000000000000000000]")
+            .unwrap_err()
+            .to_string(),
+        "index 100000000000000000000 out of range for array of length 2 at 1:7"
+    );
+}
+
+#[test]
+fn array_literals_and_indexing() {
+    let tests = vec![
+        ("[1, 2 * 2, 3 + 3]", "[1, 4, 6]"),
+        ("[]", "[]"),
+        ("[7,]", "[7]"),
+        ("[1, 2, 3][0]", "1"),
+        ("[1, 2, 3][1 + 1]", "3"),
+        ("let i = 0; [1,][i];", "1"),
+        ("[5 / 2]", "2"),
+        ("let a = [1, 2, 3]; a[0] + a[1] + a[2];", "6"),
+        ("let a = [[1, 2], [3, 4]]; a[1][0];", "3"),
+        ("let a = [1, 2, 3]; a[1] ← 20; a;", "[1, 20, 3]"),
+        ("let a = [1, 2, 3]; a[2] += 10; a[2];", "13"),
+        (
+            "let m = [[0, 0], [0, 0]]; m[1][0] = 5; m;",
+            "[[0, 0], [5, 0]]",
+        ),
+        ("let a = [1, 2]; let b = a; b[0] = 9; a;", "[9, 2]"),
+    ];
+
+    for (input, expected) in tests {
+        assert_eq!(eval(input).unwrap().to_string(), expected, "{}", input);
+    }
+}
+
+#[test]
+fn len_and_push_builtins() {
+    let tests = vec![
+        ("len([])", "0"),
+        ("len([1, 2, 3])", "3"),
+        ("let a = []; push(a, 1); push(a, 2); a;", "[1, 2]"),
+        ("let a = [1,]; push(a, [2,]); len(a);", "2"),
+    ];
+
+    for (input, expected) in tests {
+        assert_eq!(eval(input).unwrap().to_string(), expected, "{}", input);
+    }
+
+    let tests = vec![
+        ("len(1)", "argument to `len` not supported, got INTEGER"),
+        (
+            "len([], [])",
+            "wrong number of arguments to `len`: got=2, want=1",
+        ),
+        (
+            "push([])",
+            "wrong number of arguments to `push`: got=1, want=2",
+        ),
+        ("let f = 3; f(1);", "not a function: INTEGER"),
+    ];
+
+    for (input, expected) in tests {
+        let error = eval(input).unwrap_err();
+        assert_eq!(error.to_string(), expected, "{}", input);
+    }
+}
+
+#[test]
+fn one_based_arrays() {
+    let input = "let a = [10, 20, 30]; a[1] ← a[1] + a[len(a)]; a;";
+
+    let mut evaluator = Evaluator::new().one_based_arrays(true);
+    assert_eq!(
+        eval_with(&mut evaluator, input).unwrap().to_string(),
+        "[40, 20, 30]"
+    );
+
+    let mut evaluator = Evaluator::new().one_based_arrays(true);
+    let error = eval_located(&mut evaluator, "let a = [1, 2];\na[0];").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "index 0 out of range for array of length 2 at 2:2"
+    );
+}
+
+#[test]
+fn index_errors() {
+    let tests = vec![
+        (
+            "[1, 2, 3][3]",
+            "index 3 out of range for array of length 3 at 1:10",
+        ),
+        (
+            "let a = [1,];\n  a[-1] ← 0;",
+            "index -1 out of range for array of length 1 at 2:4",
+        ),
+        (
+            "[1, 2][true]",
+            "index operator not supported: ARRAY[BOOLEAN] at 1:7",
+        ),
+        (
+            "let x = 1; x[0];",
+            "index operator not supported: INTEGER[INTEGER] at 1:13",
+        ),
+        (
+            "5[0] ← 1;",
+            "index operator not supported: INTEGER[INTEGER] at 1:2",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let failure = eval_located(&mut Evaluator::new(), input).unwrap_err();
+        assert_eq!(failure.to_string(), expected, "{}", input);
+    }
+}
+
+#[test]
+fn strings() {
+    let tests = vec![
+        (r#""ana""#, "ana"),
+        (r#""ana" + " are " + "mere""#, "ana are mere"),
+        (r#""abc" == "abc""#, "true"),
+        (r#""abc" < "abd""#, "true"),
+        (r#"len("ăsta")"#, "4"),
+    ];
+
+    for (input, expected) in tests {
+        assert_eq!(eval(input).unwrap().to_string(), expected, "{}", input);
+    }
+}
+
+#[test]
+fn map_literals_and_indexing() {
+    let tests = vec![
+        ("{}", "{}"),
+        (r#"{"b": 2, "a": 1}"#, "{a: 1, b: 2}"),
+        (r#"{"a": 1}["a"]"#, "1"),
+        ("{1 + 1: true, true: 1}[2]", "true"),
+        ("{true: 1, 2: 0}[1 < 2]", "1"),
+        (
+            r#"let m = {}; m["x"] ← 5; m["y"] = 6; m["x"] += 10; m;"#,
+            "{x: 15, y: 6}",
+        ),
+        (r#"let m = {"a": [1, 2]}; m["a"][1] = 3; m;"#, "{a: [1, 3]}"),
+        ("let m = {3: 30, 1: 10, 2: 20}; keys(m);", "[1, 2, 3]"),
+        ("let m = {3: 30, 1: 10, 2: 20}; values(m);", "[10, 20, 30]"),
+        ("let m = {1: 1, 2: 2}; len(m);", "2"),
+        ("let m = {1: 1}; let n = m; n[2] = 2; m;", "{1: 1, 2: 2}"),
+    ];
+
+    for (input, expected) in tests {
+        assert_eq!(eval(input).unwrap().to_string(), expected, "{}", input);
+    }
+}
+
+#[test]
+fn map_errors() {
+    let tests = vec![
+        (r#"{"a": 1}["b"]"#, "key not found: b"),
+        ("{[1, 2]: 1}", "unusable as map key: ARRAY"),
+        ("{1: 2}[{}]", "unusable as map key: MAP"),
+        ("let m = {}; m[1.5] = 1;", "unusable as map key: REAL"),
+        ("let m = {}; m[1] += 1;", "key not found: 1"),
+        (
+            "keys([1, 2])",
+            "argument to `keys` not supported, got ARRAY",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let error = eval(input).unwrap_err();
+        assert_eq!(error.to_string(), expected, "{}", input);
+    }
+}
+
+#[test]
+fn control_flow_statements() {
+    let tests = vec![
+        (
+            "let x = 0; daca 1 < 2 atunci { x ← 1 } altfel { x ← 2 }; x",
+            "1",
+        ),
+        (
+            "let x = 0; daca 1 > 2 { x ← 1 } altfel daca true { x ← 3 }; x",
+            "3",
+        ),
+        ("let x = 5; daca false { x ← 1 }; x", "5"),
+        (
+            "let s = 0; let i = 1; cat timp i <= 10 executa { s += i; i += 1 }; s",
+            "55",
+        ),
+        (
+            "let n = 1234; let c = 0; repeta { c += 1; n ← n div 10 } pana cand n == 0; c",
+            "4",
+        ),
+        ("let c = 0; repeta { c += 1 } pana cand true; c", "1"),
+        ("let s = 0; pentru i ← 1, 4 executa { s += i }; s", "10"),
+        (
+            "let s = 0; pentru i ← 10, 1, -3 { s = s * 100 + i }; s",
+            "10070401",
+        ),
+        ("let s = 0; pentru i ← 5, 1 { s += 1 }; s", "0"),
+        ("pentru i ← 1, 3 { }; i", "4"),
+        (
+            "let n = 3; let s = 0; pentru i ← 1, n { n ← 10; s += 1 }; s",
+            "3",
+        ),
+        ("let s = 0; pentru i ← 0, 1, 0.25 { s += i }; s", "2.5"),
+    ];
+
+    for (input, expected) in tests {
+        assert_eq!(eval(input).unwrap().to_string(), expected, "{}", input);
+    }
+}
+
+#[test]
+fn functions_and_closures() {
+    let tests = vec![
+        ("let f = fn(x) { x * 2 }; f(4)", "null"),
+        ("let f = fn(x) { return x * 2; }; f(4)", "8"),
+        (
+            "functie fact(n) { daca n <= 1 { returneaza 1 } returneaza n * fact(n - 1) } fact(10)",
+            "3628800",
+        ),
+        (
+            "let add = fn(a) { fn(b) { return a + b; } }; add(2)",
+            "null",
+        ),
+        (
+            "let add = fn(a) { return fn(b) { return a + b; }; }; add(2)(3)",
+            "5",
+        ),
+        (
+            "let c = 0; let inc = fn() { c += 1; }; inc(); inc(); c",
+            "2",
+        ),
+        ("let f = fn() { x ← 1; return x; }; f()", "1"),
+        (
+            "functie par(n) { daca n == 0 { returneaza true } returneaza impar(n - 1) } \
+             functie impar(n) { daca n == 0 { returneaza false } returneaza par(n - 1) } par(10)",
+            "true",
+        ),
+        (
+            "functie f() { pentru i ← 1, 10 { daca i == 3 { returneaza i } } } f()",
+            "3",
+        ),
+        ("functie f() { returneaza } f()", "null"),
+        ("functie f(n) { } f", "function f"),
+    ];
+
+    for (input, expected) in tests {
+        assert_eq!(eval(input).unwrap().to_string(), expected, "{}", input);
+    }
+
+    assert_eq!(
+        eval("let f = fn() { y ← 1; }; f(); y"),
+        Err(RuntimeError::UndefinedVariable("y".to_string()))
+    );
+}
+
+#[test]
+fn function_errors() {
+    let tests = vec![
+        (
+            "functie f(a, b) { } f(1)",
+            "wrong number of arguments to `f`: got=1, want=2",
+        ),
+        (
+            "fn(a) { }()",
+            "wrong number of arguments to `anonymous function`: got=0, want=1",
+        ),
+        ("5(1)", "not a function: INTEGER"),
+        (
+            "pentru i ← 1, 2, 0 { }",
+            "step of for loop must not be zero",
+        ),
+        (
+            "pentru i ← 1, 2, true { }",
+            "type mismatch: BOOLEAN == INTEGER",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let error = eval(input).unwrap_err();
+        assert_eq!(error.to_string(), expected, "{}", input);
+    }
+}
+
+#[test]
+fn errors_know_where_they_happened() {
+    let input = "functie imparte(a, b) {
+  returneaza a div b
+}
+functie medie(v) {
+  returneaza imparte(v[0] + v[1], 0)
+}
+scrie medie([4, 6,])";
+    let failure = Evaluator::new().eval_program(&parse(input)).unwrap_err();
+
+    assert_eq!(failure.error, RuntimeError::DivisionByZero);
+    assert_eq!((failure.span.line, failure.span.column), (2, 16));
+    let stack: Vec<_> = failure
+        .stack
+        .iter()
+        .map(|frame| (frame.function.as_deref(), frame.call.line))
+        .collect();
+    assert_eq!(stack, vec![(Some("imparte"), 5), (Some("medie"), 7)]);
+    assert_eq!(failure.to_string(), "division by zero at 2:16");
+    assert_eq!(
+        failure.render(input, Lang::En),
+        "error[E0207]: division by zero
+ --> 2:16
+  |
+2 |   returneaza a div b
+  |                ^^^
+  = in `imparte`, called at 5:21
+  = in `medie`, called at 7:12"
+    );
+    assert_eq!(
+        failure.render(input, Lang::Ro).lines().last(),
+        Some("  = în `medie`, apelată la 7:12")
+    );
+}
+
+#[test]
+fn recursion_shows_each_call_once() {
+    let input = "functie f(n) {
+  daca n == 0 { returneaza 1 div n }
+  returneaza f(n - 1)
+}
+scrie f(3)";
+    let failure = eval_located(&mut Evaluator::new(), input).unwrap_err();
+    assert_eq!(failure.stack.len(), 4);
+    assert_eq!(
+        failure.render(input, Lang::En),
+        "error[E0207]: division by zero
+ --> 2:30
+  |
+2 |   daca n == 0 { returneaza 1 div n }
+  |                              ^^^
+  = in `f`, called at 3:15
+  = … repeated 2 more times
+  = in `f`, called at 5:8"
+    );
+    assert_eq!(
+        failure.render(input, Lang::Ro).lines().nth(6),
+        Some("  = … repetat de încă 2 ori")
+    );
+}
+
+fn run(input: &str, stdin: &str) -> Result<String, RuntimeError> {
+    let output = Output::default();
+    let mut evaluator = Evaluator::new().with_io(
+        Box::new(Cursor::new(stdin.to_string())),
+        Box::new(output.clone()),
+    );
+    eval_with(&mut evaluator, input)?;
+    Ok(output.text())
+}
+
+#[test]
+fn read_and_write_statements() {
+    let tests = vec![
+        (r#"scrie "a", 1, 2.5, true"#, "", "a12.5true\n"),
+        ("citeste a, b; scrie a + b", "3\n  4\n", "7\n"),
+        ("citeste x; scrie x * 2", "1.25", "2.5\n"),
+        ("citeste s; scrie len(s)", "abc def", "3\n"),
+        (
+            "let v = [0, 0, 0]; pentru i ← 0, 2 { citeste v[i] }; scrie v",
+            "1 2\n3",
+            "[1, 2, 3]\n",
+        ),
+    ];
+
+    for (input, stdin, expected) in tests {
+        assert_eq!(run(input, stdin).unwrap(), expected, "{}", input);
+    }
+
+    assert_eq!(run("citeste a, b", "1"), Err(RuntimeError::InputExhausted));
+}
+
+#[test]
+fn deep_recursion_overflows_the_stack() {
+    let tests = [
+        "functie f(n) { returneaza f(n + 1) } f(0)",
+        "functie f(n) { cat timp n >= 0 { daca n >= 0 { pentru i ← 1, 2 { \
+         returneaza 1 + 2 * (3 - [f(n + 1) / 2]) } } } } f(0)",
+    ];
+    for input in tests {
+        assert_eq!(eval(input), Err(RuntimeError::StackOverflow), "{}", input);
+    }
+
+    let result = thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(|| {
+            let mut evaluator = Evaluator::new().stack_size(STACK_SIZE);
+            let deep =
+                "functie f(n) { daca n == 0 { returneaza 0 } returneaza 1 + f(n - 1) } f(1000)";
+            let runaway = "functie f(n) { returneaza f(n + 1) } f(0)";
+            (
+                eval_with(&mut evaluator, deep).map(|value| value.to_string()),
+                eval_with(&mut evaluator, runaway).err(),
+            )
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+    assert_eq!(
+        result,
+        (Ok("1000".to_string()), Some(RuntimeError::StackOverflow))
+    );
+}
+
+#[test]
+fn limits_stop_runaway_programs() {
+    let limited = |limits: Limits, input: &str| {
+        let mut evaluator = Evaluator::new().limits(limits);
+        match eval_located(&mut evaluator, input) {
+            Err(Failure {
+                error: RuntimeError::LimitExceeded(limit),
+                span,
+                ..
+            }) => (limit, span.line, span.column),
+            result => panic!("{}: expected a limit, got {:?}", input, result),
+        }
+    };
+
+    let steps = Limits {
+        max_steps: Some(100),
+        ..Limits::default()
+    };
+    assert_eq!(
+        limited(steps, "x ← 0\ncat timp true { }"),
+        (Limit::Steps, 2, 1)
+    );
+    let odd_steps = Limits {
+        max_steps: Some(101),
+        ..Limits::default()
+    };
+    assert_eq!(
+        limited(odd_steps, "x ← 0\ncat timp true {\n  x += 1\n}"),
+        (Limit::Steps, 3, 3)
+    );
+
+    let depth = Limits {
+        max_depth: Some(20),
+        ..Limits::default()
+    };
+    assert_eq!(
+        limited(depth, "functie f(n) {\n  returneaza f(n + 1)\n}\nf(0)"),
+        (Limit::Depth, 2, 15)
+    );
+
+    let allocation = Limits {
+        max_allocation: Some(8),
+        ..Limits::default()
+    };
+    assert_eq!(
+        limited(allocation, "a ← []\npentru i ← 1, 100 { push(a, i) }"),
+        (Limit::Allocation, 2, 25)
+    );
+    assert_eq!(
+        limited(allocation, "s ← \"abc\"\ns += s\ns += s"),
+        (Limit::Allocation, 3, 1)
+    );
+    // Products and powers too large for the limit are not worked out.
+    let kibibyte = Limits {
+        max_allocation: Some(1024),
+        ..Limits::default()
+    };
+    assert_eq!(
+        limited(kibibyte, "x ← 2\npentru i ← 1, 40 { x ← x * x }"),
+        (Limit::Allocation, 2, 26)
+    );
+    assert_eq!(
+        limited(kibibyte, "x ← 3\nx *= pow(3, 20000000)"),
+        (Limit::Allocation, 2, 9)
+    );
+
+    let deadline = Limits {
+        deadline: Some(Duration::from_millis(20)),
+        ..Limits::default()
+    };
+    assert_eq!(limited(deadline, "cat timp true { }").0, Limit::Time);
+
+    let mut evaluator = Evaluator::new().limits(Limits {
+        max_steps: Some(10),
+        ..Limits::default()
+    });
+    assert_eq!(eval_with(&mut evaluator, "1; 2; 3"), Ok(Object::Integer(3)));
+    assert_eq!(eval_with(&mut evaluator, "4; 5; 6"), Ok(Object::Integer(6)));
+}
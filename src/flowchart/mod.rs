@@ -0,0 +1,390 @@
+use std::cell::RefCell;
+use std::fmt::Write;
+use std::rc::Rc;
+
+use crate::ast::{
+    strip_parentheses, AssignStatement, BlockStatement, Expression, ExpressionStatement,
+    ForStatement, FunctionLiteral, IfStatement, IndexAssignStatement, IntegralLiteral,
+    LetStatement, PrefixExpression, Program, ReadStatement, RealLiteral, RepeatStatement,
+    ReturnStatement, Statement, StringLiteral, WhileStatement, WriteStatement,
+};
+use crate::lexer::TokenType;
+
+#[cfg(test)]
+mod tests;
+
+/// The shape a [`Node`] is drawn with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeKind {
+    Start,
+    Stop,
+    Process,
+    Input,
+    Output,
+    Decision,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    pub id: usize,
+    pub kind: NodeKind,
+    pub label: String,
+}
+
+/// An arrow between two nodes. Arrows leaving a decision are labelled `DA`
+/// or `NU`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Edge {
+    pub from: usize,
+    pub to: usize,
+    pub label: Option<String>,
+}
+
+/// The flowchart ("schema logica") of a program: the main program from
+/// `START` to `STOP`, followed by one such component per function.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Flowchart {
+    pub nodes: Vec<Node>,
+    pub edges: Vec<Edge>,
+}
+
+/// The arrows still waiting for the node that comes next.
+type Exits = Vec<(usize, Option<String>)>;
+
+#[derive(Default)]
+struct Builder {
+    chart: Flowchart,
+    /// `returneaza` nodes of the component being built, joined to its `STOP`.
+    returns: Exits,
+    functions: Vec<(String, Rc<BlockStatement>)>,
+}
+
+impl Flowchart {
+    pub fn from_program(program: &Program) -> Self {
+        let mut builder = Builder::default();
+        builder.component("START".to_string(), &program.statements);
+
+        let mut built = 0;
+        while let Some((label, body)) = builder.functions.get(built).cloned() {
+            builder.component(label, &body.statements);
+            built += 1;
+        }
+        builder.chart
+    }
+
+    /// The flowchart as a Graphviz `digraph`.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph flowchart {\n");
+        for node in &self.nodes {
+            let shape = match node.kind {
+                NodeKind::Start | NodeKind::Stop => "ellipse",
+                NodeKind::Process => "box",
+                NodeKind::Input | NodeKind::Output => "parallelogram",
+                NodeKind::Decision => "diamond",
+            };
+            let label = node.label.replace('\\', "\\\\").replace('"', "\\\"");
+            let _ = writeln!(
+                out,
+                "    n{} [label=\"{}\", shape={}];",
+                node.id, label, shape
+            );
+        }
+        for edge in &self.edges {
+            match &edge.label {
+                Some(label) => {
+                    let _ = writeln!(
+                        out,
+                        "    n{} -> n{} [label=\"{}\"];",
+                        edge.from, edge.to, label
+                    );
+                }
+                None => {
+                    let _ = writeln!(out, "    n{} -> n{};", edge.from, edge.to);
+                }
+            }
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// The flowchart as a Mermaid `flowchart TD` diagram.
+    pub fn to_mermaid(&self) -> String {
+        let mut out = String::from("flowchart TD\n");
+        for node in &self.nodes {
+            let label = node.label.replace('"', "#quot;");
+            let (open, close) = match node.kind {
+                NodeKind::Start | NodeKind::Stop => ("([", "])"),
+                NodeKind::Process => ("[", "]"),
+                NodeKind::Input | NodeKind::Output => ("[/", "/]"),
+                NodeKind::Decision => ("{", "}"),
+            };
+            let _ = writeln!(out, "    n{}{}\"{}\"{}", node.id, open, label, close);
+        }
+        for edge in &self.edges {
+            match &edge.label {
+                Some(label) => {
+                    let _ = writeln!(out, "    n{} -->|{}| n{}", edge.from, label, edge.to);
+                }
+                None => {
+                    let _ = writeln!(out, "    n{} --> n{}", edge.from, edge.to);
+                }
+            }
+        }
+        out
+    }
+}
+
+impl Builder {
+    /// Draws `statements` between a start node labelled `label` and a `STOP`.
+    fn component(&mut self, label: String, statements: &[Rc<RefCell<dyn Statement>>]) {
+        let start = self.node(NodeKind::Start, label, Vec::new());
+        let mut exits = self.statements(statements, vec![(start, None)]);
+        exits.append(&mut self.returns);
+        self.node(NodeKind::Stop, "STOP".to_string(), exits);
+    }
+
+    fn node(&mut self, kind: NodeKind, label: String, entries: Exits) -> usize {
+        let id = self.chart.nodes.len();
+        self.chart.nodes.push(Node { id, kind, label });
+        self.connect(entries, id);
+        id
+    }
+
+    fn connect(&mut self, exits: Exits, to: usize) {
+        for (from, label) in exits {
+            self.chart.edges.push(Edge { from, to, label });
+        }
+    }
+
+    fn statements(&mut self, statements: &[Rc<RefCell<dyn Statement>>], entries: Exits) -> Exits {
+        statements.iter().fold(entries, |exits, statement| {
+            self.statement(&*statement.as_ref().borrow(), exits)
+        })
+    }
+
+    fn statement(&mut self, statement: &dyn Statement, entries: Exits) -> Exits {
+        let any = statement.as_any();
+
+        if let Some(statement) = any.downcast_ref::<ExpressionStatement>() {
+            match &statement.expression {
+                Some(expression) => {
+                    let label = label(&*expression.as_ref().borrow());
+                    vec![(self.node(NodeKind::Process, label, entries), None)]
+                }
+                None => entries,
+            }
+        } else if let Some(statement) = any.downcast_ref::<LetStatement>() {
+            self.let_statement(statement, entries)
+        } else if let Some(statement) = any.downcast_ref::<AssignStatement>() {
+            let label = format!(
+                "{} {} {}",
+                statement.name.value,
+                statement.token.literal,
+                optional_label(&statement.value)
+            );
+            vec![(self.node(NodeKind::Process, label, entries), None)]
+        } else if let Some(statement) = any.downcast_ref::<IndexAssignStatement>() {
+            let label = format!(
+                "{} {} {}",
+                label(&*statement.target.as_ref().borrow()),
+                statement.token.literal,
+                optional_label(&statement.value)
+            );
+            vec![(self.node(NodeKind::Process, label, entries), None)]
+        } else if let Some(statement) = any.downcast_ref::<ReturnStatement>() {
+            let label = match &statement.return_value {
+                Some(_) => format!(
+                    "{} {}",
+                    statement.token.literal,
+                    optional_label(&statement.return_value)
+                ),
+                None => statement.token.literal.clone(),
+            };
+            let id = self.node(NodeKind::Process, label, entries);
+            self.returns.push((id, None));
+            Vec::new()
+        } else if let Some(block) = any.downcast_ref::<BlockStatement>() {
+            self.statements(&block.statements, entries)
+        } else if let Some(statement) = any.downcast_ref::<IfStatement>() {
+            let condition = optional_label(&statement.condition);
+            let decision = self.node(NodeKind::Decision, condition, entries);
+            let mut exits = self.statements(
+                &statement.consequence.statements,
+                vec![(decision, Some("DA".to_string()))],
+            );
+            let otherwise = vec![(decision, Some("NU".to_string()))];
+            match &statement.alternative {
+                Some(alternative) => {
+                    exits.extend(self.statements(&alternative.statements, otherwise))
+                }
+                None => exits.extend(otherwise),
+            }
+            exits
+        } else if let Some(statement) = any.downcast_ref::<WhileStatement>() {
+            let condition = optional_label(&statement.condition);
+            let decision = self.node(NodeKind::Decision, condition, entries);
+            let body = self.statements(
+                &statement.body.statements,
+                vec![(decision, Some("DA".to_string()))],
+            );
+            self.connect(body, decision);
+            vec![(decision, Some("NU".to_string()))]
+        } else if let Some(statement) = any.downcast_ref::<RepeatStatement>() {
+            let first = self.chart.nodes.len();
+            let body = self.statements(&statement.body.statements, entries);
+            let condition = optional_label(&statement.condition);
+            let decision = self.node(NodeKind::Decision, condition, body);
+            self.chart.edges.push(Edge {
+                from: decision,
+                to: first,
+                label: Some("NU".to_string()),
+            });
+            vec![(decision, Some("DA".to_string()))]
+        } else if let Some(statement) = any.downcast_ref::<ForStatement>() {
+            self.for_statement(statement, entries)
+        } else if let Some(statement) = any.downcast_ref::<ReadStatement>() {
+            let label = format!("{} {}", statement.token.literal, labels(&statement.targets));
+            vec![(self.node(NodeKind::Input, label, entries), None)]
+        } else if let Some(statement) = any.downcast_ref::<WriteStatement>() {
+            let label = format!(
+                "{} {}",
+                statement.token.literal,
+                labels(&statement.arguments)
+            );
+            vec![(self.node(NodeKind::Output, label, entries), None)]
+        } else {
+            entries
+        }
+    }
+
+    /// A function declaration gets a component of its own and no node in
+    /// the flow it is declared in.
+    fn let_statement(&mut self, statement: &LetStatement, entries: Exits) -> Exits {
+        let name = statement.name.as_ref().map(|name| name.value.as_str());
+        let Some(value) = &statement.value else {
+            let label = format!("{} ← null", name.unwrap_or_default());
+            return vec![(self.node(NodeKind::Process, label, entries), None)];
+        };
+
+        let value = value.as_ref().borrow();
+        if let Some(function) = value.as_any().downcast_ref::<FunctionLiteral>() {
+            let name = function.name.as_deref().or(name).unwrap_or_default();
+            let parameters: Vec<&str> = function
+                .parameters
+                .iter()
+                .map(|p| p.value.as_str())
+                .collect();
+            let signature = format!(
+                "{} {}({})",
+                function.token.literal,
+                name,
+                parameters.join(", ")
+            );
+            self.functions.push((signature, Rc::clone(&function.body)));
+            if statement.token.token_type == TokenType::Function {
+                return entries;
+            }
+        }
+
+        let label = format!("{} ← {}", name.unwrap_or_default(), label(&*value));
+        vec![(self.node(NodeKind::Process, label, entries), None)]
+    }
+
+    /// `pentru i ← a, b, p` is drawn as the loop it stands for: `i ← a`,
+    /// then `i <= b` (or `i >= b` for a negative step) guarding the body
+    /// and `i ← i + p`. A step whose sign is only known at run time picks
+    /// the comparison as the loop does, `p > 0 ? i <= b : i >= b`.
+    fn for_statement(&mut self, statement: &ForStatement, entries: Exits) -> Exits {
+        let counter = &statement.counter.value;
+        let init = format!("{} ← {}", counter, optional_label(&statement.start));
+        let init = self.node(NodeKind::Process, init, entries);
+
+        let end = optional_label(&statement.end);
+        let step = statement.step.as_ref().map(|step| step.as_ref().borrow());
+        let (condition, increment) = match step.as_deref().map(|step| (step, sign(step))) {
+            None => (
+                format!("{} <= {}", counter, end),
+                format!("{} ← {} + 1", counter, counter),
+            ),
+            Some((step, Some(Sign::Negative))) => (
+                format!("{} >= {}", counter, end),
+                format!(
+                    "{} ← {} - {}",
+                    counter,
+                    counter,
+                    label(step).trim_start_matches('-')
+                ),
+            ),
+            Some((step, Some(Sign::Positive))) => (
+                format!("{} <= {}", counter, end),
+                format!("{} ← {} + {}", counter, counter, label(step)),
+            ),
+            Some((step, None)) => (
+                format!(
+                    "{} > 0 ? {} <= {} : {} >= {}",
+                    step.string(),
+                    counter,
+                    end,
+                    counter,
+                    end
+                ),
+                format!("{} ← {} + {}", counter, counter, step.string()),
+            ),
+        };
+
+        let decision = self.node(NodeKind::Decision, condition, vec![(init, None)]);
+        let body = self.statements(
+            &statement.body.statements,
+            vec![(decision, Some("DA".to_string()))],
+        );
+        let increment = self.node(NodeKind::Process, increment, body);
+        self.connect(vec![(increment, None)], decision);
+        vec![(decision, Some("NU".to_string()))]
+    }
+}
+
+enum Sign {
+    Positive,
+    Negative,
+}
+
+/// The sign of a literal `pentru` step such as `2` or `-1`, or `None` if it
+/// is only known at run time.
+fn sign(step: &dyn Expression) -> Option<Sign> {
+    let is_literal = |expression: &dyn Expression| {
+        expression.as_any().is::<IntegralLiteral>() || expression.as_any().is::<RealLiteral>()
+    };
+    if is_literal(step) {
+        return Some(Sign::Positive);
+    }
+    let prefix = step.as_any().downcast_ref::<PrefixExpression>()?;
+    let right = prefix.right.as_ref()?.as_ref().borrow();
+    match prefix.operator == "-" && is_literal(&*right) {
+        true => Some(Sign::Negative),
+        false => None,
+    }
+}
+
+/// How an expression reads in a box: string literals keep their quotes and
+/// the outer parentheses of an operation are dropped.
+fn label(expression: &dyn Expression) -> String {
+    if let Some(literal) = expression.as_any().downcast_ref::<StringLiteral>() {
+        return format!("\"{}\"", literal.value);
+    }
+    strip_parentheses(&expression.string()).to_string()
+}
+
+fn optional_label(expression: &Option<Rc<RefCell<dyn Expression>>>) -> String {
+    expression
+        .as_ref()
+        .map(|e| label(&*e.as_ref().borrow()))
+        .unwrap_or_default()
+}
+
+fn labels(expressions: &[Rc<RefCell<dyn Expression>>]) -> String {
+    let labels: Vec<String> = expressions
+        .iter()
+        .map(|e| label(&*e.as_ref().borrow()))
+        .collect();
+    labels.join(", ")
+}
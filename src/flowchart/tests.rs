@@ -0,0 +1,189 @@
+use crate::test_support::parse;
+
+use super::{Edge, Flowchart, NodeKind};
+
+fn flowchart(input: &str) -> Flowchart {
+    Flowchart::from_program(&parse(input))
+}
+
+fn nodes(chart: &Flowchart) -> Vec<(NodeKind, &str)> {
+    chart
+        .nodes
+        .iter()
+        .map(|node| (node.kind, node.label.as_str()))
+        .collect()
+}
+
+fn edges(chart: &Flowchart) -> Vec<(usize, usize, Option<&str>)> {
+    chart
+        .edges
+        .iter()
+        .map(|Edge { from, to, label }| (*from, *to, label.as_deref()))
+        .collect()
+}
+
+#[test]
+fn builds_nodes_and_edges() {
+    let chart = flowchart(
+        "citeste n
+s ← 0
+pentru i ← 1, n { s += i }
+daca s > 2 atunci { scrie \"s=\", s } altfel { scrie \"mic\" }",
+    );
+
+    assert_eq!(
+        nodes(&chart),
+        vec![
+            (NodeKind::Start, "START"),
+            (NodeKind::Input, "citeste n"),
+            (NodeKind::Process, "s ← 0"),
+            (NodeKind::Process, "i ← 1"),
+            (NodeKind::Decision, "i <= n"),
+            (NodeKind::Process, "s += i"),
+            (NodeKind::Process, "i ← i + 1"),
+            (NodeKind::Decision, "s > 2"),
+            (NodeKind::Output, "scrie \"s=\", s"),
+            (NodeKind::Output, "scrie \"mic\""),
+            (NodeKind::Stop, "STOP"),
+        ]
+    );
+    assert_eq!(
+        edges(&chart),
+        vec![
+            (0, 1, None),
+            (1, 2, None),
+            (2, 3, None),
+            (3, 4, None),
+            (4, 5, Some("DA")),
+            (5, 6, None),
+            (6, 4, None),
+            (4, 7, Some("NU")),
+            (7, 8, Some("DA")),
+            (7, 9, Some("NU")),
+            (8, 10, None),
+            (9, 10, None),
+        ]
+    );
+}
+
+#[test]
+fn loops_and_functions() {
+    let chart = flowchart(
+        "functie f(n) {
+  daca n <= 1 { returneaza 1 }
+  returneaza n * f(n - 1)
+}
+k ← 3
+repeta { k -= 1 } pana cand k == 0
+cat timp k < 2 { k += 1 }
+pentru j ← 5, 1, -2 { }
+scrie f(k)",
+    );
+
+    assert_eq!(
+        nodes(&chart),
+        vec![
+            (NodeKind::Start, "START"),
+            (NodeKind::Process, "k ← 3"),
+            (NodeKind::Process, "k -= 1"),
+            (NodeKind::Decision, "k == 0"),
+            (NodeKind::Decision, "k < 2"),
+            (NodeKind::Process, "k += 1"),
+            (NodeKind::Process, "j ← 5"),
+            (NodeKind::Decision, "j >= 1"),
+            (NodeKind::Process, "j ← j - 2"),
+            (NodeKind::Output, "scrie f(k)"),
+            (NodeKind::Stop, "STOP"),
+            (NodeKind::Start, "functie f(n)"),
+            (NodeKind::Decision, "n <= 1"),
+            (NodeKind::Process, "returneaza 1"),
+            (NodeKind::Process, "returneaza n * f((n - 1))"),
+            (NodeKind::Stop, "STOP"),
+        ]
+    );
+    assert_eq!(
+        edges(&chart),
+        vec![
+            (0, 1, None),
+            (1, 2, None),
+            (2, 3, None),
+            (3, 2, Some("NU")),
+            (3, 4, Some("DA")),
+            (4, 5, Some("DA")),
+            (5, 4, None),
+            (4, 6, Some("NU")),
+            (6, 7, None),
+            (7, 8, Some("DA")),
+            (8, 7, None),
+            (7, 9, Some("NU")),
+            (9, 10, None),
+            (11, 12, None),
+            (12, 13, Some("DA")),
+            (12, 14, Some("NU")),
+            (13, 15, None),
+            (14, 15, None),
+        ]
+    );
+}
+
+#[test]
+fn steps_known_only_at_run_time() {
+    let chart = flowchart("citeste p\npentru i ← 1, 10, p { }\npentru j ← 9, 0, -p { }");
+
+    assert_eq!(
+        nodes(&chart)[2..8],
+        [
+            (NodeKind::Process, "i ← 1"),
+            (NodeKind::Decision, "p > 0 ? i <= 10 : i >= 10"),
+            (NodeKind::Process, "i ← i + p"),
+            (NodeKind::Process, "j ← 9"),
+            (NodeKind::Decision, "(-p) > 0 ? j <= 0 : j >= 0"),
+            (NodeKind::Process, "j ← j + (-p)"),
+        ]
+    );
+}
+
+fn sample() -> Flowchart {
+    flowchart("citeste x\ncat timp x > 0 { x ← x - 1 }\nscrie \"gata\"")
+}
+
+#[test]
+fn renders_dot() {
+    let expected = "\
+digraph flowchart {
+    n0 [label=\"START\", shape=ellipse];
+    n1 [label=\"citeste x\", shape=parallelogram];
+    n2 [label=\"x > 0\", shape=diamond];
+    n3 [label=\"x ← x - 1\", shape=box];
+    n4 [label=\"scrie \\\"gata\\\"\", shape=parallelogram];
+    n5 [label=\"STOP\", shape=ellipse];
+    n0 -> n1;
+    n1 -> n2;
+    n2 -> n3 [label=\"DA\"];
+    n3 -> n2;
+    n2 -> n4 [label=\"NU\"];
+    n4 -> n5;
+}
+";
+    assert_eq!(sample().to_dot(), expected);
+}
+
+#[test]
+fn renders_mermaid() {
+    let expected = "\
+flowchart TD
+    n0([\"START\"])
+    n1[/\"citeste x\"/]
+    n2{\"x > 0\"}
+    n3[\"x ← x - 1\"]
+    n4[/\"scrie #quot;gata#quot;\"/]
+    n5([\"STOP\"])
+    n0 --> n1
+    n1 --> n2
+    n2 -->|DA| n3
+    n3 --> n2
+    n2 -->|NU| n4
+    n4 --> n5
+";
+    assert_eq!(sample().to_mermaid(), expected);
+}
@@ -0,0 +1,205 @@
+use std::fmt::Write;
+
+use crate::lexer::{Lexer, Span, Token, TokenType};
+
+#[cfg(test)]
+mod tests;
+
+/// What a token is coloured as. Everything between tokens is whitespace
+/// and is left as it is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Keyword,
+    Identifier,
+    Number,
+    String,
+    Operator,
+    /// Brackets, commas, semicolons and colons.
+    Punctuation,
+    /// `//` up to the end of the line.
+    Comment,
+    /// A character the lexer does not accept, or an unterminated string.
+    Illegal,
+}
+
+impl Kind {
+    /// The CSS class of the `<span>` the HTML renderer wraps the token in.
+    pub fn class(self) -> &'static str {
+        match self {
+            Kind::Keyword => "kw",
+            Kind::Identifier => "id",
+            Kind::Number => "num",
+            Kind::String => "str",
+            Kind::Operator => "op",
+            Kind::Punctuation => "punct",
+            Kind::Comment => "com",
+            Kind::Illegal => "err",
+        }
+    }
+
+    /// The SGR parameters the terminal renderer colours the token with.
+    fn ansi(self) -> Option<&'static str> {
+        match self {
+            Kind::Keyword => Some("1;35"),
+            Kind::Number => Some("36"),
+            Kind::String => Some("32"),
+            Kind::Operator => Some("33"),
+            Kind::Comment => Some("90"),
+            Kind::Illegal => Some("4;31"),
+            Kind::Identifier | Kind::Punctuation => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Highlight {
+    pub kind: Kind,
+    pub span: Span,
+}
+
+pub fn classify(token: &Token) -> Kind {
+    match &token.token_type {
+        TokenType::Ident(_) => Kind::Identifier,
+        TokenType::Int(_) | TokenType::Float(_) => Kind::Number,
+        TokenType::Str(_) => Kind::String,
+        TokenType::Comment => Kind::Comment,
+        TokenType::Illegal | TokenType::Eof => Kind::Illegal,
+        TokenType::Comma
+        | TokenType::Semicolon
+        | TokenType::Colon
+        | TokenType::LParen
+        | TokenType::RParen
+        | TokenType::LBrace
+        | TokenType::RBrace
+        | TokenType::LBracket
+        | TokenType::RBracket => Kind::Punctuation,
+        TokenType::Function
+        | TokenType::Let
+        | TokenType::True
+        | TokenType::False
+        | TokenType::If
+        | TokenType::Then
+        | TokenType::Else
+        | TokenType::Return
+        | TokenType::While
+        | TokenType::Do
+        | TokenType::For
+        | TokenType::Repeat
+        | TokenType::Until
+        | TokenType::Read
+        | TokenType::Write => Kind::Keyword,
+        // `si`, `sau`, `nu`, `div` and `mod` read as keywords, their
+        // symbolic spellings as operators.
+        TokenType::And | TokenType::Or | TokenType::Not | TokenType::Div | TokenType::Mod
+            if token.literal.starts_with(|c: char| c.is_alphabetic()) =>
+        {
+            Kind::Keyword
+        }
+        TokenType::Eq
+        | TokenType::NotEq
+        | TokenType::And
+        | TokenType::Or
+        | TokenType::Not
+        | TokenType::Assign
+        | TokenType::LeftArrow
+        | TokenType::PlusAssign
+        | TokenType::MinusAssign
+        | TokenType::AsteriskAssign
+        | TokenType::SlashAssign
+        | TokenType::Plus
+        | TokenType::Minus
+        | TokenType::Bang
+        | TokenType::Asterisk
+        | TokenType::Slash
+        | TokenType::Percent
+        | TokenType::Div
+        | TokenType::Mod
+        | TokenType::Lt
+        | TokenType::Lte
+        | TokenType::Gt
+        | TokenType::Gte => Kind::Operator,
+    }
+}
+
+/// The tokens of `source` in order, with what each is coloured as.
+pub fn highlight(source: &str) -> Vec<Highlight> {
+    tokens(source)
+        .iter()
+        .map(|token| Highlight {
+            kind: classify(token),
+            span: token.span,
+        })
+        .collect()
+}
+
+pub(crate) fn tokens(source: &str) -> Vec<Token> {
+    let mut lexer = Lexer::new(source.to_string()).keep_comments();
+    let mut tokens = Vec::new();
+    loop {
+        let token = lexer.next_token();
+        if token.token_type == TokenType::Eof {
+            break;
+        }
+        tokens.push(token);
+    }
+    tokens
+}
+
+/// Walks `source`, handing each stretch of text to `write` with its kind,
+/// or `None` for the whitespace between tokens.
+fn render(source: &str, mut write: impl FnMut(Option<Kind>, &str)) {
+    let mut position = 0;
+    for highlight in highlight(source) {
+        if position < highlight.span.start {
+            write(None, &source[position..highlight.span.start]);
+        }
+        write(
+            Some(highlight.kind),
+            &source[highlight.span.start..highlight.span.end],
+        );
+        position = highlight.span.end;
+    }
+    if position < source.len() {
+        write(None, &source[position..]);
+    }
+}
+
+fn escape(text: &str, output: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => output.push_str("&amp;"),
+            '<' => output.push_str("&lt;"),
+            '>' => output.push_str("&gt;"),
+            '"' => output.push_str("&quot;"),
+            c => output.push(c),
+        }
+    }
+}
+
+/// `source` as a `<pre class="pseudocode">` block with each token in a
+/// `<span>` classed by [`Kind::class`], for handouts and the web grader to
+/// style.
+pub fn html(source: &str) -> String {
+    let mut output = String::from("<pre class=\"pseudocode\">");
+    render(source, |kind, text| match kind {
+        Some(kind) => {
+            write!(output, "<span class=\"{}\">", kind.class()).unwrap();
+            escape(text, &mut output);
+            output.push_str("</span>");
+        }
+        None => escape(text, &mut output),
+    });
+    output.push_str("</pre>");
+    output
+}
+
+/// `source` with ANSI escape codes colouring its tokens, for printing to a
+/// terminal.
+pub fn ansi(source: &str) -> String {
+    let mut output = String::new();
+    render(source, |kind, text| match kind.and_then(Kind::ansi) {
+        Some(code) => write!(output, "\x1b[{}m{}\x1b[0m", code, text).unwrap(),
+        None => output.push_str(text),
+    });
+    output
+}
@@ -0,0 +1,111 @@
+use super::{ansi, highlight, html, Kind};
+
+#[test]
+fn classifies_tokens() {
+    let source = "daca x mod 2 == 0 si nu gata { s ← s + \"par\"; v[1] -= 1.5 } @";
+    let kinds: Vec<_> = highlight(source)
+        .iter()
+        .map(|h| (h.kind, &source[h.span.start..h.span.end]))
+        .collect();
+
+    assert_eq!(
+        kinds,
+        vec![
+            (Kind::Keyword, "daca"),
+            (Kind::Identifier, "x"),
+            (Kind::Keyword, "mod"),
+            (Kind::Number, "2"),
+            (Kind::Operator, "=="),
+            (Kind::Number, "0"),
+            (Kind::Keyword, "si"),
+            (Kind::Keyword, "nu"),
+            (Kind::Identifier, "gata"),
+            (Kind::Punctuation, "{"),
+            (Kind::Identifier, "s"),
+            (Kind::Operator, "←"),
+            (Kind::Identifier, "s"),
+            (Kind::Operator, "+"),
+            (Kind::String, "\"par\""),
+            (Kind::Punctuation, ";"),
+            (Kind::Identifier, "v"),
+            (Kind::Punctuation, "["),
+            (Kind::Number, "1"),
+            (Kind::Punctuation, "]"),
+            (Kind::Operator, "-="),
+            (Kind::Number, "1.5"),
+            (Kind::Punctuation, "}"),
+            (Kind::Illegal, "@"),
+        ]
+    );
+}
+
+#[test]
+fn keeps_multi_word_keywords_whole() {
+    let source = "cat  timp a && b executa\nrepeta {} pana cand \"x";
+    let kinds: Vec<_> = highlight(source)
+        .iter()
+        .map(|h| (h.kind, &source[h.span.start..h.span.end], h.span.line))
+        .collect();
+
+    assert_eq!(
+        kinds,
+        vec![
+            (Kind::Keyword, "cat  timp", 1),
+            (Kind::Identifier, "a", 1),
+            (Kind::Operator, "&&", 1),
+            (Kind::Identifier, "b", 1),
+            (Kind::Keyword, "executa", 1),
+            (Kind::Keyword, "repeta", 2),
+            (Kind::Punctuation, "{", 2),
+            (Kind::Punctuation, "}", 2),
+            (Kind::Keyword, "pana cand", 2),
+            (Kind::Illegal, "\"x", 2),
+        ]
+    );
+}
+
+#[test]
+fn colours_comments() {
+    let source = "x ← 1 // unu\n// doi";
+    let kinds: Vec<_> = highlight(source)
+        .iter()
+        .map(|h| (h.kind, &source[h.span.start..h.span.end]))
+        .collect();
+
+    assert_eq!(
+        kinds,
+        vec![
+            (Kind::Identifier, "x"),
+            (Kind::Operator, "←"),
+            (Kind::Number, "1"),
+            (Kind::Comment, "// unu"),
+            (Kind::Comment, "// doi"),
+        ]
+    );
+    assert_eq!(
+        html("1 // <"),
+        "<pre class=\"pseudocode\"><span class=\"num\">1</span> \
+         <span class=\"com\">// &lt;</span></pre>"
+    );
+}
+
+#[test]
+fn renders_html() {
+    assert_eq!(
+        html("daca a < 1 {\n  scrie \"<b>\"\n}"),
+        "<pre class=\"pseudocode\">\
+         <span class=\"kw\">daca</span> <span class=\"id\">a</span> \
+         <span class=\"op\">&lt;</span> <span class=\"num\">1</span> \
+         <span class=\"punct\">{</span>\n  \
+         <span class=\"kw\">scrie</span> <span class=\"str\">&quot;&lt;b&gt;&quot;</span>\n\
+         <span class=\"punct\">}</span></pre>"
+    );
+}
+
+#[test]
+fn renders_ansi() {
+    assert_eq!(
+        ansi("scrie x + 1, \"a\" "),
+        "\x1b[1;35mscrie\x1b[0m x \x1b[33m+\x1b[0m \x1b[36m1\x1b[0m, \x1b[32m\"a\"\x1b[0m "
+    );
+}
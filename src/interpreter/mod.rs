@@ -0,0 +1,213 @@
+use std::fmt;
+use std::io::{BufRead, Write};
+use std::rc::Rc;
+
+use crate::builtins::Arity;
+use crate::evaluator::{Evaluator, Failure, Limits, RuntimeError};
+use crate::lexer::Lexer;
+use crate::messages::Lang;
+use crate::object::Object;
+use crate::parser::{ParseError, Parser};
+
+#[cfg(test)]
+mod tests;
+
+/// An interpreter for embedding in Rust programs: it runs pseudocode with
+/// globals and functions the host provides, and keeps the globals the
+/// pseudocode leaves behind for the host to read.
+///
+/// Runaway recursion stops with [`RuntimeError::StackOverflow`] before it
+/// overflows the native stack of the thread calling [`Interpreter::run`].
+/// That thread is assumed to have the 2 MiB `std::thread::spawn` gives;
+/// hosts with a bigger stack say so with [`Interpreter::stack_size`] to
+/// allow deeper recursion.
+pub struct Interpreter {
+    evaluator: Evaluator,
+}
+
+/// Why [`Interpreter::run`] failed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    Parse(Vec<ParseError>),
+    Runtime(Failure),
+}
+
+/// A Rust closure callable from pseudocode. Implemented for closures of up
+/// to four arguments that convert from [`Object`] with `TryFrom`, returning
+/// a [`NativeResult`].
+pub trait NativeFunction<Args>: 'static {
+    const ARITY: usize;
+
+    fn call(&self, function: &str, arguments: &[Object]) -> Result<Object, RuntimeError>;
+}
+
+/// What a [`NativeFunction`] can return: a value converting into an
+/// [`Object`], or a `Result` of one whose error ends the program.
+pub trait NativeResult {
+    fn into_result(self, function: &str) -> Result<Object, RuntimeError>;
+}
+
+impl<T: Into<Object>> NativeResult for T {
+    fn into_result(self, _: &str) -> Result<Object, RuntimeError> {
+        Ok(self.into())
+    }
+}
+
+impl<T: Into<Object>, E: fmt::Display> NativeResult for Result<T, E> {
+    fn into_result(self, function: &str) -> Result<Object, RuntimeError> {
+        self.map(Into::into).map_err(|error| RuntimeError::Native {
+            function: function.to_string(),
+            message: error.to_string(),
+        })
+    }
+}
+
+/// Converts the argument of `function` to the type its closure takes.
+fn argument<T: TryFrom<Object>>(function: &str, argument: &Object) -> Result<T, RuntimeError> {
+    T::try_from(argument.clone()).map_err(|_| RuntimeError::InvalidArgument {
+        function: function.to_string(),
+        argument: argument.type_name(),
+    })
+}
+
+macro_rules! native_function {
+    ($arity:expr; $($arg:ident),*) => {
+        impl<F, R, $($arg),*> NativeFunction<($($arg,)*)> for F
+        where
+            F: Fn($($arg),*) -> R + 'static,
+            R: NativeResult,
+            $($arg: TryFrom<Object>,)*
+        {
+            const ARITY: usize = $arity;
+
+            #[allow(non_snake_case, unused_variables, unused_mut)]
+            fn call(&self, function: &str, arguments: &[Object]) -> Result<Object, RuntimeError> {
+                let mut arguments = arguments.iter();
+                $(let $arg = argument::<$arg>(function, arguments.next().unwrap())?;)*
+                self($($arg),*).into_result(function)
+            }
+        }
+    };
+}
+
+native_function!(0;);
+native_function!(1; A);
+native_function!(2; A, B);
+native_function!(3; A, B, C);
+native_function!(4; A, B, C, D);
+
+impl Interpreter {
+    pub fn new() -> Self {
+        Self {
+            evaluator: Evaluator::new(),
+        }
+    }
+
+    /// Reads `citeste` input from `input` and writes `scrie` output to
+    /// `output` instead of the standard streams.
+    pub fn with_io(mut self, input: Box<dyn BufRead>, output: Box<dyn Write>) -> Self {
+        self.evaluator = self.evaluator.with_io(input, output);
+        self
+    }
+
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.evaluator = self.evaluator.limits(limits);
+        self
+    }
+
+    /// Tells the interpreter the thread calling [`Interpreter::run`] has
+    /// `bytes` of native stack, such as
+    /// [`STACK_SIZE`](crate::evaluator::STACK_SIZE).
+    pub fn stack_size(mut self, bytes: usize) -> Self {
+        self.evaluator = self.evaluator.stack_size(bytes);
+        self
+    }
+
+    /// Makes `name` call `function`, e.g.
+    /// `register_fn("verifica", |x: i64| x % 2 == 0)`. Arguments that do
+    /// not convert to the types the closure takes are runtime errors.
+    pub fn register_fn<Args, F: NativeFunction<Args>>(
+        &mut self,
+        name: impl Into<String>,
+        function: F,
+    ) {
+        let name: Rc<str> = name.into().into();
+        let arity = Arity::Exactly(F::ARITY);
+        self.evaluator
+            .builtins_mut()
+            .register(Rc::clone(&name), arity, move |arguments, _| {
+                function.call(&name, arguments)
+            });
+    }
+
+    pub fn set_global(&mut self, name: &str, value: impl Into<Object>) {
+        self.evaluator
+            .env()
+            .borrow_mut()
+            .set(name.to_string(), value.into());
+    }
+
+    /// The global `name` as a `T`, or `None` if it is not set or does not
+    /// convert. `T` may be [`Object`] itself.
+    pub fn get_global<T: TryFrom<Object>>(&self, name: &str) -> Option<T> {
+        let value = self.evaluator.env().borrow().get(name)?;
+        T::try_from(value).ok()
+    }
+
+    /// Restarts the numbers `random` returns from `seed`.
+    pub fn seed(&mut self, seed: u64) {
+        self.evaluator.builtins_mut().seed(seed);
+    }
+
+    /// Runs `source`, returning the value of its last statement. Globals
+    /// persist from one run to the next.
+    pub fn run(&mut self, source: &str) -> Result<Object, Error> {
+        let mut parser = Parser::new(Lexer::new(source.to_string()));
+        let program = parser.parse_program();
+        match program {
+            Some(program) if parser.parse_errors().is_empty() => self
+                .evaluator
+                .eval_program(&program)
+                .map_err(Error::Runtime),
+            _ => Err(Error::Parse(parser.parse_errors().to_vec())),
+        }
+    }
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Error {
+    /// Each error with the line of `source` it is on, as the command line
+    /// shows them.
+    pub fn render(&self, source: &str, lang: Lang) -> String {
+        match self {
+            Error::Parse(errors) => errors
+                .iter()
+                .map(|error| error.render(source, lang))
+                .collect::<Vec<_>>()
+                .join("\n\n"),
+            Error::Runtime(failure) => failure.render(source, lang),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Parse(errors) => {
+                let errors: Vec<String> = errors
+                    .iter()
+                    .map(|error| format!("{} at {}:{}", error, error.span.line, error.span.column))
+                    .collect();
+                write!(f, "{}", errors.join("\n"))
+            }
+            Error::Runtime(failure) => write!(f, "{}", failure),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
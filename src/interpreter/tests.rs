@@ -0,0 +1,187 @@
+use std::io::Cursor;
+use std::thread;
+
+use crate::bigint::BigInt;
+use crate::evaluator::{RuntimeError, STACK_SIZE};
+use crate::messages::Lang;
+use crate::object::{ConversionError, Object};
+use crate::test_support::Output;
+
+use super::{Error, Interpreter};
+
+#[test]
+fn calls_registered_functions() {
+    let mut interpreter = Interpreter::new();
+    interpreter.register_fn("verifica", |x: i64| x % 2 == 0);
+    interpreter.register_fn("medie", |a: f64, b: f64| (a + b) / 2.0);
+    interpreter.register_fn("saluta", |name: String| format!("Salut, {}!", name));
+    interpreter.register_fn("nota", || 10);
+    interpreter.register_fn("suma", |v: Vec<i64>| v.iter().sum::<i64>());
+    for (prefix, n) in [("du", 2), ("tri", 3)] {
+        interpreter.register_fn(format!("{}plu", prefix), move |x: i64| x * n);
+    }
+
+    let tests = [
+        ("verifica(4)", Object::Boolean(true)),
+        ("verifica(3)", Object::Boolean(false)),
+        ("medie(1, 2.5)", Object::Real(1.75)),
+        ("saluta(\"Ana\")", Object::Str("Salut, Ana!".to_string())),
+        ("nota() + 1", Object::Integer(11)),
+        ("suma([1, 2, 3])", Object::Integer(6)),
+        ("duplu(5) + triplu(5)", Object::Integer(25)),
+    ];
+    for (input, expected) in tests {
+        assert_eq!(interpreter.run(input), Ok(expected), "{}", input);
+    }
+}
+
+#[test]
+fn checks_native_arguments() {
+    let mut interpreter = Interpreter::new();
+    interpreter.register_fn("verifica", |x: i64| x > 0);
+
+    let error = |interpreter: &mut Interpreter, input| match interpreter.run(input) {
+        Err(Error::Runtime(failure)) => failure.error,
+        result => panic!("{}: {:?}", input, result),
+    };
+    assert_eq!(
+        error(&mut interpreter, "verifica(\"5\")").to_string(),
+        "argument to `verifica` not supported, got STRING"
+    );
+    assert_eq!(
+        error(&mut interpreter, "verifica()").to_string(),
+        "wrong number of arguments to `verifica`: got=0, want=1"
+    );
+
+    interpreter.register_fn("radical", |x: f64| {
+        if x < 0.0 {
+            Err(format!("{} is negative", x))
+        } else {
+            Ok(x.sqrt())
+        }
+    });
+    assert_eq!(interpreter.run("radical(9)"), Ok(Object::Real(3.0)));
+    assert_eq!(
+        error(&mut interpreter, "radical(-1)"),
+        RuntimeError::Native {
+            function: "radical".to_string(),
+            message: "-1 is negative".to_string(),
+        }
+    );
+}
+
+#[test]
+fn shares_globals_with_the_host() {
+    let mut interpreter = Interpreter::new();
+    interpreter.set_global("n", 5);
+    interpreter.set_global("nume", "Ana");
+    interpreter.set_global("note", vec![7, 9]);
+
+    interpreter
+        .run("s ← 0\npentru i ← 1, n { s += i }\nok ← len(nume) == 3\nnote[0] ← 10")
+        .unwrap();
+    assert_eq!(interpreter.get_global::<i64>("s"), Some(15));
+    assert_eq!(interpreter.get_global::<bool>("ok"), Some(true));
+    assert_eq!(
+        interpreter.get_global::<Vec<i64>>("note"),
+        Some(vec![10, 9])
+    );
+    assert_eq!(interpreter.get_global::<String>("s"), None);
+    assert_eq!(interpreter.get_global::<i64>("lipsa"), None);
+    assert_eq!(
+        interpreter.get_global::<Object>("nume"),
+        Some(Object::Str("Ana".to_string()))
+    );
+
+    // Globals persist from one run to the next.
+    assert_eq!(interpreter.run("s * 2"), Ok(Object::Integer(30)));
+}
+
+#[test]
+fn runs_with_the_hosts_input_and_output() {
+    let output = Output::default();
+    let mut interpreter =
+        Interpreter::new().with_io(Box::new(Cursor::new("3 4")), Box::new(output.clone()));
+    interpreter.run("citeste a, b\nscrie a * b").unwrap();
+    assert_eq!(output.text(), "12\n");
+}
+
+#[test]
+fn reports_errors() {
+    let mut interpreter = Interpreter::new();
+    let source = "x ← (1";
+    let error = interpreter.run(source).unwrap_err();
+    assert!(matches!(&error, Error::Parse(errors) if errors.len() == 1));
+    assert_eq!(
+        error.to_string(),
+        "expected `)`, got end of input instead at 1:7"
+    );
+
+    let source = "scrie 1 div 0";
+    let error = interpreter.run(source).unwrap_err();
+    assert_eq!(error.to_string(), "division by zero at 1:9");
+    assert_eq!(
+        error.render(source, Lang::Ro),
+        "eroare[E0207]: împărțire la zero\n --> 1:9\n  |\n1 | scrie 1 div 0\n  |         ^^^"
+    );
+}
+
+#[test]
+fn stops_runaway_recursion() {
+    let recurse = |interpreter: Interpreter| {
+        let mut interpreter = interpreter;
+        match interpreter.run("functie f(n) { returneaza f(n + 1) }\nf(0)") {
+            Err(Error::Runtime(failure)) => Some(failure.error),
+            _ => None,
+        }
+    };
+
+    let error = thread::spawn(move || recurse(Interpreter::new()))
+        .join()
+        .unwrap();
+    assert_eq!(error, Some(RuntimeError::StackOverflow));
+
+    let error = thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(move || recurse(Interpreter::new().stack_size(STACK_SIZE)))
+        .unwrap()
+        .join()
+        .unwrap();
+    assert_eq!(error, Some(RuntimeError::StackOverflow));
+}
+
+#[test]
+fn converts_values() {
+    assert_eq!(Object::from(3), Object::Integer(3));
+    assert_eq!(Object::from("a"), Object::Str("a".to_string()));
+    assert_eq!(Object::from(Some(1.5)), Object::Real(1.5));
+    assert_eq!(Object::from(None::<i64>), Object::Null);
+    assert_eq!(Object::from(vec![true]).to_string(), "[true]");
+
+    assert_eq!(f64::try_from(Object::Integer(2)), Ok(2.0));
+    assert_eq!(
+        i64::try_from(Object::Real(2.0)),
+        Err(ConversionError::WrongType {
+            expected: "INTEGER",
+            got: "REAL",
+        })
+    );
+    let big = BigInt::from(i64::MAX).pow(2);
+    assert_eq!(
+        i64::try_from(Object::BigInteger(big.clone())),
+        Err(ConversionError::Overflow(big.clone()))
+    );
+    assert_eq!(
+        i64::try_from(Object::BigInteger(big))
+            .unwrap_err()
+            .to_string(),
+        "integer overflow: 85070591730234615847396907784232501249 does not fit in 64 bits"
+    );
+    assert_eq!(
+        Vec::<String>::try_from(Object::from(vec![1])),
+        Err(ConversionError::WrongType {
+            expected: "STRING",
+            got: "INTEGER",
+        })
+    );
+}
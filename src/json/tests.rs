@@ -0,0 +1,64 @@
+use super::{Json, JsonError};
+
+#[test]
+fn parses_and_prints() {
+    let text =
+        r#" {"id": 1, "params": {"text": "a\n\"b\" ă😀", "ok": [true, false, null, -2.5e1]}} "#;
+    let value = Json::parse(text).unwrap();
+
+    assert_eq!(value.get("id").and_then(Json::as_i64), Some(1));
+    assert_eq!(
+        value.at(&["params", "text"]).and_then(Json::as_str),
+        Some("a\n\"b\" ă😀")
+    );
+    assert_eq!(
+        value.at(&["params", "ok"]).and_then(Json::as_array),
+        Some(
+            &[
+                Json::Bool(true),
+                Json::Bool(false),
+                Json::Null,
+                Json::Number(-25.0)
+            ][..]
+        )
+    );
+    assert_eq!(
+        value.to_string(),
+        r#"{"id":1,"params":{"text":"a\n\"b\" ă😀","ok":[true,false,null,-25]}}"#
+    );
+}
+
+#[test]
+fn builds_objects() {
+    let value = Json::from([
+        ("line", 3usize.into()),
+        ("name", "x".into()),
+        ("value", Json::from(Some(0.5))),
+        ("missing", Json::from(None::<bool>)),
+    ]);
+    assert_eq!(
+        value.to_string(),
+        r#"{"line":3,"name":"x","value":0.5,"missing":null}"#
+    );
+}
+
+#[test]
+fn rejects_malformed_text() {
+    let tests = [
+        ("", 0, "unexpected end of input"),
+        ("[1, 2", 5, "expected `,` or `]`"),
+        ("{\"a\" 1}", 5, "expected `:`"),
+        ("\"abc", 4, "unterminated string"),
+        ("tru", 0, "unexpected character"),
+        ("1 2", 2, "trailing characters"),
+    ];
+
+    for (text, position, message) in tests {
+        assert_eq!(
+            Json::parse(text),
+            Err(JsonError { position, message }),
+            "{}",
+            text
+        );
+    }
+}
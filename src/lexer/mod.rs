@@ -1,197 +1,549 @@
 use std::fmt::Display;
 
+#[cfg(test)]
 mod tests;
 
-#[derive(Debug, PartialEq, Clone, Eq, Hash)]
-pub enum TokenType {
+#[derive(Debug, PartialEq, Clone, Copy, Eq, Hash)]
+pub enum TokenType<S = String> {
     Illegal,
     Eof,
+    /// `//` up to the end of the line. Only a lexer made to keep comments
+    /// yields these; otherwise they are skipped like whitespace.
+    Comment,
 
-    Ident(String),
-    Int(String),
+    Ident(S),
+    Int(S),
+    Float(S),
+    Str(S),
 
     Comma,
     Semicolon,
+    Colon,
 
     LParen,
     RParen,
     LBrace,
     RBrace,
+    LBracket,
+    RBracket,
 
     Function,
     Let,
     True,
     False,
     If,
+    Then,
     Else,
     Return,
+    While,
+    Do,
+    For,
+    Repeat,
+    Until,
+    Read,
+    Write,
 
     Eq,
     NotEq,
 
+    And,
+    Or,
+    Not,
+
     Assign,
+    LeftArrow,
+    PlusAssign,
+    MinusAssign,
+    AsteriskAssign,
+    SlashAssign,
     Plus,
     Minus,
     Bang,
     Asterisk,
     Slash,
+    Percent,
+    Div,
+    Mod,
     Lt,
     Lte,
     Gt,
     Gte,
 }
 
+/// Location of a token in the source: byte offsets plus the 1-based
+/// line and column of its first character.
+#[derive(Debug, PartialEq, Clone, Copy, Eq, Hash, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
 #[derive(Debug, Clone)]
 pub struct Token {
     pub token_type: TokenType,
     pub literal: String,
+    pub span: Span,
 }
 
-#[derive(Debug)]
-pub struct Lexer {
-    input: String,
+/// A token whose literal and payload are slices of the lexed source,
+/// so producing one never allocates.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct BorrowedToken<'src> {
+    pub token_type: TokenType<&'src str>,
+    pub literal: &'src str,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Cursor {
     position: usize,
     read_position: usize,
     ch: char,
+    line: usize,
+    column: usize,
+}
+
+/// Lexer over a borrowed source, yielding [`BorrowedToken`]s.
+#[derive(Debug, Clone)]
+pub struct BorrowedLexer<'src> {
+    input: &'src str,
+    cursor: Cursor,
+    comments: bool,
+}
+
+/// Owning lexer, yielding [`Token`]s that outlive the input.
+#[derive(Debug)]
+pub struct Lexer {
+    input: String,
+    cursor: Cursor,
+    comments: bool,
+}
+
+impl Span {
+    /// `message` followed by the line of `source` the span starts on, with
+    /// carets under the span:
+    ///
+    /// ```text
+    /// error: division by zero
+    ///  --> 2:16
+    ///   |
+    /// 2 |   returneaza n / 0
+    ///   |                ^
+    /// ```
+    pub fn annotate(&self, source: &str, message: &str) -> String {
+        let text = source
+            .lines()
+            .nth(self.line.saturating_sub(1))
+            .unwrap_or("");
+        let gutter = " ".repeat(self.line.to_string().len());
+
+        // Keep tabs so the carets line up however wide they are shown.
+        let before: String = text
+            .chars()
+            .take(self.column.saturating_sub(1))
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let rest = text
+            .chars()
+            .count()
+            .saturating_sub(self.column.saturating_sub(1));
+        let width = source
+            .get(self.start..self.end)
+            .map_or(1, |text| text.chars().take_while(|&c| c != '\n').count())
+            .clamp(1, rest.max(1));
+
+        format!(
+            "{message}\n{gutter}--> {line}:{column}\n{gutter} |\n{line} | {text}\n{gutter} | {before}{carets}",
+            line = self.line,
+            column = self.column,
+            carets = "^".repeat(width),
+        )
+    }
+}
+
+impl<S> TokenType<S> {
+    pub fn map<T, F>(self, f: F) -> TokenType<T>
+    where
+        F: FnOnce(S) -> T,
+    {
+        match self {
+            TokenType::Illegal => TokenType::Illegal,
+            TokenType::Eof => TokenType::Eof,
+            TokenType::Comment => TokenType::Comment,
+            TokenType::Ident(s) => TokenType::Ident(f(s)),
+            TokenType::Int(s) => TokenType::Int(f(s)),
+            TokenType::Float(s) => TokenType::Float(f(s)),
+            TokenType::Str(s) => TokenType::Str(f(s)),
+            TokenType::Comma => TokenType::Comma,
+            TokenType::Semicolon => TokenType::Semicolon,
+            TokenType::Colon => TokenType::Colon,
+            TokenType::LParen => TokenType::LParen,
+            TokenType::RParen => TokenType::RParen,
+            TokenType::LBrace => TokenType::LBrace,
+            TokenType::RBrace => TokenType::RBrace,
+            TokenType::LBracket => TokenType::LBracket,
+            TokenType::RBracket => TokenType::RBracket,
+            TokenType::Function => TokenType::Function,
+            TokenType::Let => TokenType::Let,
+            TokenType::True => TokenType::True,
+            TokenType::False => TokenType::False,
+            TokenType::If => TokenType::If,
+            TokenType::Then => TokenType::Then,
+            TokenType::Else => TokenType::Else,
+            TokenType::Return => TokenType::Return,
+            TokenType::While => TokenType::While,
+            TokenType::Do => TokenType::Do,
+            TokenType::For => TokenType::For,
+            TokenType::Repeat => TokenType::Repeat,
+            TokenType::Until => TokenType::Until,
+            TokenType::Read => TokenType::Read,
+            TokenType::Write => TokenType::Write,
+            TokenType::Eq => TokenType::Eq,
+            TokenType::NotEq => TokenType::NotEq,
+            TokenType::And => TokenType::And,
+            TokenType::Or => TokenType::Or,
+            TokenType::Not => TokenType::Not,
+            TokenType::Assign => TokenType::Assign,
+            TokenType::LeftArrow => TokenType::LeftArrow,
+            TokenType::PlusAssign => TokenType::PlusAssign,
+            TokenType::MinusAssign => TokenType::MinusAssign,
+            TokenType::AsteriskAssign => TokenType::AsteriskAssign,
+            TokenType::SlashAssign => TokenType::SlashAssign,
+            TokenType::Plus => TokenType::Plus,
+            TokenType::Minus => TokenType::Minus,
+            TokenType::Bang => TokenType::Bang,
+            TokenType::Asterisk => TokenType::Asterisk,
+            TokenType::Slash => TokenType::Slash,
+            TokenType::Percent => TokenType::Percent,
+            TokenType::Div => TokenType::Div,
+            TokenType::Mod => TokenType::Mod,
+            TokenType::Lt => TokenType::Lt,
+            TokenType::Lte => TokenType::Lte,
+            TokenType::Gt => TokenType::Gt,
+            TokenType::Gte => TokenType::Gte,
+        }
+    }
 }
 
 impl Token {
-    fn new<T: Into<String> + Display>(token_type: TokenType, literal: T) -> Self {
+    fn new<T: Into<String> + Display>(token_type: TokenType, literal: T, span: Span) -> Self {
         Self {
             token_type,
             literal: literal.into(),
+            span,
         }
     }
 
-    fn lookup_ident(literal: &str) -> TokenType {
+    fn lookup_ident(literal: &str) -> TokenType<&str> {
         match literal {
-            "fn" => TokenType::Function,
+            "fn" | "functie" => TokenType::Function,
             "let" => TokenType::Let,
-            "true" => TokenType::True,
-            "false" => TokenType::False,
-            "if" => TokenType::If,
-            "else" => TokenType::Else,
-            "return" => TokenType::Return,
-            _ => TokenType::Ident(literal.to_string()),
+            "true" | "adevarat" => TokenType::True,
+            "false" | "fals" => TokenType::False,
+            "if" | "daca" => TokenType::If,
+            "atunci" => TokenType::Then,
+            "else" | "altfel" => TokenType::Else,
+            "return" | "returneaza" => TokenType::Return,
+            "while" => TokenType::While,
+            "executa" => TokenType::Do,
+            "for" | "pentru" => TokenType::For,
+            "repeta" => TokenType::Repeat,
+            "citeste" => TokenType::Read,
+            "scrie" => TokenType::Write,
+            "si" => TokenType::And,
+            "sau" => TokenType::Or,
+            "not" | "nu" => TokenType::Not,
+            "div" => TokenType::Div,
+            "mod" => TokenType::Mod,
+            _ => TokenType::Ident(literal),
         }
     }
 }
 
-impl Lexer {
-    pub fn new(input: String) -> Self {
+impl BorrowedToken<'_> {
+    pub fn into_owned(self) -> Token {
+        Token::new(self.token_type.map(str::to_string), self.literal, self.span)
+    }
+}
+
+impl<'src> BorrowedLexer<'src> {
+    pub fn new(input: &'src str) -> Self {
         let mut lexer = Self {
             input,
-            position: 0,
-            read_position: 0,
-            ch: char::default(),
+            cursor: Cursor {
+                position: 0,
+                read_position: 0,
+                ch: char::default(),
+                line: 1,
+                column: 0,
+            },
+            comments: false,
         };
         lexer.read_char();
         lexer
     }
 
+    /// Yields comments as [`TokenType::Comment`] tokens instead of skipping
+    /// them, for highlighting.
+    pub fn keep_comments(mut self) -> Self {
+        self.comments = true;
+        self
+    }
+
     fn read_char(&mut self) {
-        if self.read_position >= self.input.len() {
-            self.ch = char::default();
+        if self.cursor.ch == '\n' {
+            self.cursor.line += 1;
+            self.cursor.column = 1;
         } else {
-            self.ch = self.input.chars().nth(self.read_position).unwrap();
+            self.cursor.column += 1;
+        }
+        self.cursor.position = self.cursor.read_position;
+        match self.input[self.cursor.read_position..].chars().next() {
+            Some(ch) => {
+                self.cursor.ch = ch;
+                self.cursor.read_position += ch.len_utf8();
+            }
+            None => self.cursor.ch = char::default(),
         }
-        self.position = self.read_position;
-        self.read_position += 1;
     }
 
     fn peek_char(&self) -> char {
-        if self.read_position > self.input.len() {
-            char::default()
-        } else {
-            self.input.chars().nth(self.read_position).unwrap()
-        }
+        self.input[self.cursor.read_position..]
+            .chars()
+            .next()
+            .unwrap_or_default()
     }
 
-    pub fn next_token(&mut self) -> Token {
+    pub fn next_token(&mut self) -> BorrowedToken<'src> {
         self.skip_whitespace();
-        let token = match self.ch {
+        let start = self.cursor;
+        let token_type = match self.cursor.ch {
             '=' => match self.peek_char() {
                 '=' => {
                     self.read_char();
-                    Token::new(TokenType::Eq, "==")
+                    TokenType::Eq
                 }
-                _ => Token::new(TokenType::Assign, self.ch),
+                _ => TokenType::Assign,
+            },
+            ';' => TokenType::Semicolon,
+            ':' => TokenType::Colon,
+            '"' => match self.read_string() {
+                Some(string) => TokenType::Str(string),
+                None => TokenType::Illegal,
+            },
+            '(' => TokenType::LParen,
+            ')' => TokenType::RParen,
+            '{' => TokenType::LBrace,
+            '}' => TokenType::RBrace,
+            '[' => TokenType::LBracket,
+            ']' => TokenType::RBracket,
+            '%' => TokenType::Percent,
+            ',' => TokenType::Comma,
+            '+' => match self.peek_char() {
+                '=' => {
+                    self.read_char();
+                    TokenType::PlusAssign
+                }
+                _ => TokenType::Plus,
+            },
+            '-' => match self.peek_char() {
+                '=' => {
+                    self.read_char();
+                    TokenType::MinusAssign
+                }
+                _ => TokenType::Minus,
             },
-            ';' => Token::new(TokenType::Semicolon, self.ch),
-            '(' => Token::new(TokenType::LParen, self.ch),
-            ')' => Token::new(TokenType::RParen, self.ch),
-            '{' => Token::new(TokenType::LBrace, self.ch),
-            '}' => Token::new(TokenType::RBrace, self.ch),
-            ',' => Token::new(TokenType::Comma, self.ch),
-            '+' => Token::new(TokenType::Plus, self.ch),
-            '-' => Token::new(TokenType::Minus, self.ch),
             '!' => match self.peek_char() {
                 '=' => {
                     self.read_char();
-                    Token::new(TokenType::NotEq, "!=")
+                    TokenType::NotEq
                 }
-                _ => Token::new(TokenType::Bang, self.ch),
+                _ => TokenType::Bang,
             },
-            '*' => Token::new(TokenType::Asterisk, self.ch),
-            '/' => Token::new(TokenType::Slash, self.ch),
-            '<' => match self.peek_char() {
+            '*' => match self.peek_char() {
                 '=' => {
                     self.read_char();
-                    Token::new(TokenType::Lte, "<=")
+                    TokenType::AsteriskAssign
                 }
-                _ => Token::new(TokenType::Lt, self.ch),
+                _ => TokenType::Asterisk,
             },
-            '>' => match self.peek_char() {
+            '/' => match self.peek_char() {
                 '=' => {
                     self.read_char();
-                    Token::new(TokenType::Gte, ">=")
+                    TokenType::SlashAssign
+                }
+                '/' => {
+                    self.read_comment();
+                    return self.token(TokenType::Comment, start);
                 }
-                _ => Token::new(TokenType::Gt, self.ch),
+                _ => TokenType::Slash,
             },
-            '\0' => Token::new(TokenType::Eof, self.ch),
-            _ => match (self.ch.is_ascii_alphabetic(), self.ch.is_ascii_digit()) {
-                (true, _) => {
-                    let ident = self.read_identifier();
-                    let token_type = Token::lookup_ident(ident);
-                    Token::new(token_type, ident)
+            '←' => TokenType::LeftArrow,
+            '&' => match self.peek_char() {
+                '&' => {
+                    self.read_char();
+                    TokenType::And
                 }
-                (_, true) => {
-                    let token_number = self.read_number();
-                    Token::new(
-                        TokenType::Int(token_number.to_string().clone()),
-                        token_number,
-                    )
+                _ => TokenType::Illegal,
+            },
+            '|' => match self.peek_char() {
+                '|' => {
+                    self.read_char();
+                    TokenType::Or
+                }
+                _ => TokenType::Illegal,
+            },
+            '<' => match self.peek_char() {
+                '=' => {
+                    self.read_char();
+                    TokenType::Lte
                 }
-                _ => Token::new(TokenType::Illegal, self.ch),
+                _ => TokenType::Lt,
             },
+            '>' => match self.peek_char() {
+                '=' => {
+                    self.read_char();
+                    TokenType::Gte
+                }
+                _ => TokenType::Gt,
+            },
+            '\0' => TokenType::Eof,
+            ch if ch.is_ascii_alphabetic() => {
+                let ident = self.read_identifier();
+                let token_type = match ident {
+                    "cat" if self.skip_word("timp") => TokenType::While,
+                    "pana" if self.skip_word("cand") => TokenType::Until,
+                    _ => Token::lookup_ident(ident),
+                };
+                return self.token(token_type, start);
+            }
+            ch if ch.is_ascii_digit() => {
+                let number = self.read_number();
+                if self.cursor.ch == '.' && self.peek_char().is_ascii_digit() {
+                    self.read_char();
+                    self.read_number();
+                    let number = &self.input[start.position..self.cursor.position];
+                    return self.token(TokenType::Float(number), start);
+                }
+                return self.token(TokenType::Int(number), start);
+            }
+            _ => TokenType::Illegal,
         };
         self.read_char();
-        token
+        self.token(token_type, start)
+    }
+
+    fn token(&self, token_type: TokenType<&'src str>, start: Cursor) -> BorrowedToken<'src> {
+        BorrowedToken {
+            token_type,
+            literal: &self.input[start.position..self.cursor.position],
+            span: Span {
+                start: start.position,
+                end: self.cursor.position,
+                line: start.line,
+                column: start.column,
+            },
+        }
     }
 
-    fn read_while<F>(&mut self, condition: F) -> &str
+    fn read_while<F>(&mut self, condition: F) -> &'src str
     where
         F: Fn(char) -> bool,
     {
-        let position = self.position;
-        while condition(self.ch) {
+        let position = self.cursor.position;
+        while condition(self.cursor.ch) {
             self.read_char();
         }
-        self.read_position -= 1;
-        &self.input[position..self.read_position]
+        &self.input[position..self.cursor.position]
     }
 
-    fn read_identifier(&mut self) -> &str {
+    fn read_identifier(&mut self) -> &'src str {
         self.read_while(|ch| ch.is_ascii_alphabetic())
     }
 
-    fn read_number(&mut self) -> &str {
+    /// Reads up to the closing quote, leaving it as the current character.
+    /// Returns `None` if the input ends first.
+    fn read_string(&mut self) -> Option<&'src str> {
+        self.read_char();
+        let string = self.read_while(|ch| ch != '"' && ch != '\0');
+        match self.cursor.ch {
+            '"' => Some(string),
+            _ => None,
+        }
+    }
+
+    fn read_number(&mut self) -> &'src str {
         self.read_while(|ch| ch.is_ascii_digit())
     }
 
+    /// Consumes `word` if it is the next identifier, so that two-word
+    /// keywords such as `cat timp` lex as a single token.
+    fn skip_word(&mut self, word: &str) -> bool {
+        let cursor = self.cursor;
+        self.skip_whitespace();
+        if self.cursor.ch.is_ascii_alphabetic() && self.read_identifier() == word {
+            return true;
+        }
+        self.cursor = cursor;
+        false
+    }
+
     fn skip_whitespace(&mut self) {
-        while self.ch.is_ascii_whitespace() {
-            self.read_char();
+        loop {
+            while self.cursor.ch.is_ascii_whitespace() {
+                self.read_char();
+            }
+            if self.comments || self.cursor.ch != '/' || self.peek_char() != '/' {
+                return;
+            }
+            self.read_comment();
         }
     }
+
+    fn read_comment(&mut self) {
+        self.read_while(|ch| ch != '\n' && ch != '\0');
+    }
+}
+
+impl<'src> Iterator for BorrowedLexer<'src> {
+    type Item = BorrowedToken<'src>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let token = self.next_token();
+        match token.token_type {
+            TokenType::Eof => None,
+            _ => Some(token),
+        }
+    }
+}
+
+impl Lexer {
+    pub fn new(input: String) -> Self {
+        let cursor = BorrowedLexer::new(&input).cursor;
+        Self {
+            input,
+            cursor,
+            comments: false,
+        }
+    }
+
+    /// Yields comments as [`TokenType::Comment`] tokens instead of skipping
+    /// them, for highlighting.
+    pub fn keep_comments(mut self) -> Self {
+        self.comments = true;
+        self
+    }
+
+    pub fn next_token(&mut self) -> Token {
+        let mut lexer = BorrowedLexer {
+            input: &self.input,
+            cursor: self.cursor,
+            comments: self.comments,
+        };
+        let token = lexer.next_token().into_owned();
+        self.cursor = lexer.cursor;
+        token
+    }
 }
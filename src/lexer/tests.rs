@@ -1,4 +1,4 @@
-use super::{Lexer, TokenType};
+use super::{BorrowedLexer, Lexer, Span, TokenType};
 
 #[test]
 fn simple_lexer() {
@@ -137,3 +137,342 @@ fn complex_lexer() {
         assert_eq!(test, token.token_type);
     }
 }
+
+#[test]
+fn borrowed_lexer_matches_owned() {
+    let input = r#"
+                let add = fn(x, y) { x + y; };
+                if (add(1, 22) >= 23) { return true; } else { return false; }
+            "#;
+
+    let mut lexer = Lexer::new(input.to_string());
+    for borrowed in BorrowedLexer::new(input) {
+        let owned = lexer.next_token();
+        assert_eq!(owned.token_type, borrowed.token_type.map(str::to_string));
+        assert_eq!(owned.literal, borrowed.literal);
+        assert_eq!(owned.span, borrowed.span);
+        assert_eq!(
+            &input[borrowed.span.start..borrowed.span.end],
+            borrowed.literal
+        );
+    }
+    assert_eq!(lexer.next_token().token_type, TokenType::Eof);
+}
+
+#[test]
+fn token_spans() {
+    let input = "let x = 5;\n  x >= 10";
+    let tests = vec![
+        ("let", 0, 1, 1),
+        ("x", 4, 1, 5),
+        ("=", 6, 1, 7),
+        ("5", 8, 1, 9),
+        (";", 9, 1, 10),
+        ("x", 13, 2, 3),
+        (">=", 15, 2, 5),
+        ("10", 18, 2, 8),
+    ];
+
+    let mut lexer = BorrowedLexer::new(input);
+
+    for (literal, start, line, column) in tests {
+        let token = lexer.next_token();
+        assert_eq!(token.literal, literal);
+        assert_eq!(
+            token.span,
+            Span {
+                start,
+                end: start + literal.len(),
+                line,
+                column,
+            }
+        );
+    }
+    assert_eq!(lexer.next_token().token_type, TokenType::Eof);
+}
+
+#[test]
+fn non_ascii_input() {
+    let input = "x ă 1";
+    let tests = vec![
+        (TokenType::Ident("x"), "x"),
+        (TokenType::Illegal, "ă"),
+        (TokenType::Int("1"), "1"),
+        (TokenType::Eof, ""),
+    ];
+
+    let mut lexer = BorrowedLexer::new(input);
+
+    for (token_type, literal) in tests {
+        let token = lexer.next_token();
+        assert_eq!(token.token_type, token_type);
+        assert_eq!(token.literal, literal);
+    }
+}
+
+#[test]
+fn assignment_operators() {
+    let input = "x ← x + 1; x += 2; x -= 3; x *= 4; x /= 5; x = 6;";
+    let tests = vec![
+        TokenType::Ident("x"),
+        TokenType::LeftArrow,
+        TokenType::Ident("x"),
+        TokenType::Plus,
+        TokenType::Int("1"),
+        TokenType::Semicolon,
+        TokenType::Ident("x"),
+        TokenType::PlusAssign,
+        TokenType::Int("2"),
+        TokenType::Semicolon,
+        TokenType::Ident("x"),
+        TokenType::MinusAssign,
+        TokenType::Int("3"),
+        TokenType::Semicolon,
+        TokenType::Ident("x"),
+        TokenType::AsteriskAssign,
+        TokenType::Int("4"),
+        TokenType::Semicolon,
+        TokenType::Ident("x"),
+        TokenType::SlashAssign,
+        TokenType::Int("5"),
+        TokenType::Semicolon,
+        TokenType::Ident("x"),
+        TokenType::Assign,
+        TokenType::Int("6"),
+        TokenType::Semicolon,
+        TokenType::Eof,
+    ];
+
+    let mut lexer = BorrowedLexer::new(input);
+
+    for test in tests {
+        let token = lexer.next_token();
+        assert_eq!(test, token.token_type);
+    }
+}
+
+#[test]
+fn logical_operators() {
+    let input = "a && b || !c; daca x si y sau not z nu w; a & b | c";
+    let tests = vec![
+        (TokenType::Ident("a"), "a"),
+        (TokenType::And, "&&"),
+        (TokenType::Ident("b"), "b"),
+        (TokenType::Or, "||"),
+        (TokenType::Bang, "!"),
+        (TokenType::Ident("c"), "c"),
+        (TokenType::Semicolon, ";"),
+        (TokenType::If, "daca"),
+        (TokenType::Ident("x"), "x"),
+        (TokenType::And, "si"),
+        (TokenType::Ident("y"), "y"),
+        (TokenType::Or, "sau"),
+        (TokenType::Not, "not"),
+        (TokenType::Ident("z"), "z"),
+        (TokenType::Not, "nu"),
+        (TokenType::Ident("w"), "w"),
+        (TokenType::Semicolon, ";"),
+        (TokenType::Ident("a"), "a"),
+        (TokenType::Illegal, "&"),
+        (TokenType::Ident("b"), "b"),
+        (TokenType::Illegal, "|"),
+        (TokenType::Ident("c"), "c"),
+        (TokenType::Eof, ""),
+    ];
+
+    let mut lexer = BorrowedLexer::new(input);
+
+    for (token_type, literal) in tests {
+        let token = lexer.next_token();
+        assert_eq!(token.token_type, token_type);
+        assert_eq!(token.literal, literal);
+    }
+}
+
+#[test]
+fn division_operators_and_reals() {
+    let input = "a % b; a div b mod c; [x / 2.5]; 3.; 0.125";
+    let tests = vec![
+        (TokenType::Ident("a"), "a"),
+        (TokenType::Percent, "%"),
+        (TokenType::Ident("b"), "b"),
+        (TokenType::Semicolon, ";"),
+        (TokenType::Ident("a"), "a"),
+        (TokenType::Div, "div"),
+        (TokenType::Ident("b"), "b"),
+        (TokenType::Mod, "mod"),
+        (TokenType::Ident("c"), "c"),
+        (TokenType::Semicolon, ";"),
+        (TokenType::LBracket, "["),
+        (TokenType::Ident("x"), "x"),
+        (TokenType::Slash, "/"),
+        (TokenType::Float("2.5"), "2.5"),
+        (TokenType::RBracket, "]"),
+        (TokenType::Semicolon, ";"),
+        (TokenType::Int("3"), "3"),
+        (TokenType::Illegal, "."),
+        (TokenType::Semicolon, ";"),
+        (TokenType::Float("0.125"), "0.125"),
+        (TokenType::Eof, ""),
+    ];
+
+    let mut lexer = BorrowedLexer::new(input);
+
+    for (token_type, literal) in tests {
+        let token = lexer.next_token();
+        assert_eq!(token.token_type, token_type);
+        assert_eq!(token.literal, literal);
+    }
+}
+
+#[test]
+fn comments() {
+    let input = "x ← 1 // unu / doi\n// gol\ny ← x / 2 //";
+    let skipped = vec![
+        (TokenType::Ident("x"), "x"),
+        (TokenType::LeftArrow, "←"),
+        (TokenType::Int("1"), "1"),
+        (TokenType::Ident("y"), "y"),
+        (TokenType::LeftArrow, "←"),
+        (TokenType::Ident("x"), "x"),
+        (TokenType::Slash, "/"),
+        (TokenType::Int("2"), "2"),
+        (TokenType::Eof, ""),
+    ];
+
+    let mut lexer = BorrowedLexer::new(input);
+
+    for (token_type, literal) in skipped {
+        let token = lexer.next_token();
+        assert_eq!(token.token_type, token_type);
+        assert_eq!(token.literal, literal);
+    }
+
+    let mut lexer = Lexer::new(input.to_string()).keep_comments();
+    let comments: Vec<_> = std::iter::from_fn(|| {
+        let token = lexer.next_token();
+        (token.token_type != TokenType::Eof).then_some(token)
+    })
+    .filter(|token| token.token_type == TokenType::Comment)
+    .map(|token| (token.literal, token.span.line, token.span.column))
+    .collect();
+    assert_eq!(
+        comments,
+        vec![
+            ("// unu / doi".to_string(), 1, 7),
+            ("// gol".to_string(), 2, 1),
+            ("//".to_string(), 3, 11),
+        ]
+    );
+}
+
+#[test]
+fn strings_and_colons() {
+    let input = r#"{"unu": 1, "": x} "ăâ"; "open"#;
+    let tests = vec![
+        (TokenType::LBrace, "{"),
+        (TokenType::Str("unu"), "\"unu\""),
+        (TokenType::Colon, ":"),
+        (TokenType::Int("1"), "1"),
+        (TokenType::Comma, ","),
+        (TokenType::Str(""), "\"\""),
+        (TokenType::Colon, ":"),
+        (TokenType::Ident("x"), "x"),
+        (TokenType::RBrace, "}"),
+        (TokenType::Str("ăâ"), "\"ăâ\""),
+        (TokenType::Semicolon, ";"),
+        (TokenType::Illegal, "\"open"),
+        (TokenType::Eof, ""),
+    ];
+
+    let mut lexer = BorrowedLexer::new(input);
+
+    for (token_type, literal) in tests {
+        let token = lexer.next_token();
+        assert_eq!(token.token_type, token_type);
+        assert_eq!(token.literal, literal);
+    }
+}
+
+#[test]
+fn pseudocode_keywords() {
+    let input = r#"
+        citeste n
+        daca n > 0 atunci { scrie n } altfel { scrie 0 }
+        cat  timp n > 0 executa { n ← n - 1 }
+        pentru i ← 1, n executa { }
+        repeta { } pana
+        cand adevarat sau fals
+        functie f(x) { returneaza x }
+        cat + timp; pana
+    "#;
+    let tests = vec![
+        (TokenType::Read, "citeste"),
+        (TokenType::Ident("n"), "n"),
+        (TokenType::If, "daca"),
+        (TokenType::Ident("n"), "n"),
+        (TokenType::Gt, ">"),
+        (TokenType::Int("0"), "0"),
+        (TokenType::Then, "atunci"),
+        (TokenType::LBrace, "{"),
+        (TokenType::Write, "scrie"),
+        (TokenType::Ident("n"), "n"),
+        (TokenType::RBrace, "}"),
+        (TokenType::Else, "altfel"),
+        (TokenType::LBrace, "{"),
+        (TokenType::Write, "scrie"),
+        (TokenType::Int("0"), "0"),
+        (TokenType::RBrace, "}"),
+        (TokenType::While, "cat  timp"),
+        (TokenType::Ident("n"), "n"),
+        (TokenType::Gt, ">"),
+        (TokenType::Int("0"), "0"),
+        (TokenType::Do, "executa"),
+        (TokenType::LBrace, "{"),
+        (TokenType::Ident("n"), "n"),
+        (TokenType::LeftArrow, "←"),
+        (TokenType::Ident("n"), "n"),
+        (TokenType::Minus, "-"),
+        (TokenType::Int("1"), "1"),
+        (TokenType::RBrace, "}"),
+        (TokenType::For, "pentru"),
+        (TokenType::Ident("i"), "i"),
+        (TokenType::LeftArrow, "←"),
+        (TokenType::Int("1"), "1"),
+        (TokenType::Comma, ","),
+        (TokenType::Ident("n"), "n"),
+        (TokenType::Do, "executa"),
+        (TokenType::LBrace, "{"),
+        (TokenType::RBrace, "}"),
+        (TokenType::Repeat, "repeta"),
+        (TokenType::LBrace, "{"),
+        (TokenType::RBrace, "}"),
+        (TokenType::Until, "pana\n        cand"),
+        (TokenType::True, "adevarat"),
+        (TokenType::Or, "sau"),
+        (TokenType::False, "fals"),
+        (TokenType::Function, "functie"),
+        (TokenType::Ident("f"), "f"),
+        (TokenType::LParen, "("),
+        (TokenType::Ident("x"), "x"),
+        (TokenType::RParen, ")"),
+        (TokenType::LBrace, "{"),
+        (TokenType::Return, "returneaza"),
+        (TokenType::Ident("x"), "x"),
+        (TokenType::RBrace, "}"),
+        (TokenType::Ident("cat"), "cat"),
+        (TokenType::Plus, "+"),
+        (TokenType::Ident("timp"), "timp"),
+        (TokenType::Semicolon, ";"),
+        (TokenType::Ident("pana"), "pana"),
+        (TokenType::Eof, ""),
+    ];
+
+    let mut lexer = BorrowedLexer::new(input);
+
+    for (token_type, literal) in tests {
+        let token = lexer.next_token();
+        assert_eq!(token.token_type, token_type);
+        assert_eq!(token.literal, literal);
+    }
+}
@@ -1,3 +1,25 @@
 pub mod ast;
+pub mod bigint;
+pub mod builtins;
+pub mod code;
+pub mod codegen;
+pub mod compiler;
+pub mod dap;
+pub mod debugger;
+pub mod evaluator;
+pub mod flowchart;
+pub mod highlight;
+pub mod interpreter;
+pub mod json;
 pub mod lexer;
+pub mod lint;
+pub mod lsp;
+pub mod messages;
+pub mod object;
 pub mod parser;
+pub mod resolver;
+#[cfg(test)]
+mod test_support;
+pub mod trace;
+pub mod types;
+pub mod vm;
@@ -0,0 +1,512 @@
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::ast::{
+    ArrayLiteral, AssignStatement, BlockStatement, BooleanLiteral, CallExpression, Expression,
+    ExpressionStatement, ForStatement, FunctionLiteral, Identifier, IfStatement,
+    IndexAssignStatement, IndexExpression, InfixExpression, IntegerPartExpression, IntegralLiteral,
+    LetStatement, MapLiteral, Node, PrefixExpression, Program, ReadStatement, RealLiteral,
+    RepeatStatement, ReturnStatement, Statement, StringLiteral, WhileStatement, WriteStatement,
+};
+use crate::evaluator::{eval_infix_expression, eval_integer_part, eval_prefix_expression};
+use crate::lexer::{Span, TokenType};
+use crate::messages::{self, Lang};
+use crate::object::Object;
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Rule {
+    /// `daca`, `cat timp` or `pana cand` on a condition that never changes.
+    ConstantCondition,
+    /// A `pentru` counter assigned inside the loop body.
+    CounterModified,
+    /// Statements after a `returneaza` that always runs.
+    UnreachableCode,
+    /// `x ← x`, `x += 0` and the like.
+    UselessAssignment,
+    /// `x == x`, `v[i] < v[i]` and the like.
+    SelfComparison,
+}
+
+impl Rule {
+    pub const ALL: [Rule; 5] = [
+        Rule::ConstantCondition,
+        Rule::CounterModified,
+        Rule::UnreachableCode,
+        Rule::UselessAssignment,
+        Rule::SelfComparison,
+    ];
+
+    /// The name the rule goes by in the configuration and in reports.
+    pub fn name(self) -> &'static str {
+        match self {
+            Rule::ConstantCondition => "constant-condition",
+            Rule::CounterModified => "counter-modified",
+            Rule::UnreachableCode => "unreachable-code",
+            Rule::UselessAssignment => "useless-assignment",
+            Rule::SelfComparison => "self-comparison",
+        }
+    }
+
+    /// The code the rule's message is kept under in the [`messages`]
+    /// catalogue.
+    pub fn code(self) -> &'static str {
+        match self {
+            Rule::ConstantCondition => "W0101",
+            Rule::CounterModified => "W0102",
+            Rule::UnreachableCode => "W0103",
+            Rule::UselessAssignment => "W0104",
+            Rule::SelfComparison => "W0105",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Rule> {
+        Rule::ALL.into_iter().find(|rule| rule.name() == name)
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Which rules run. All of them do unless turned off.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Config {
+    disabled: BTreeSet<Rule>,
+}
+
+impl Config {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads a configuration of one `rule = true|false` per line; blank
+    /// lines and lines starting with `#` are skipped.
+    pub fn parse(text: &str) -> Result<Self, ConfigError> {
+        let mut config = Config::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |message: String| ConfigError {
+                line: index + 1,
+                message,
+            };
+
+            let (name, value) = line
+                .split_once('=')
+                .ok_or_else(|| error(format!("expected `rule = true|false`, got `{}`", line)))?;
+            let (name, value) = (name.trim(), value.trim());
+            let rule =
+                Rule::from_name(name).ok_or_else(|| error(format!("unknown rule `{}`", name)))?;
+            let enabled = match value {
+                "true" => true,
+                "false" => false,
+                _ => return Err(error(format!("expected true or false, got `{}`", value))),
+            };
+            config = config.rule(rule, enabled);
+        }
+        Ok(config)
+    }
+
+    pub fn rule(mut self, rule: Rule, enabled: bool) -> Self {
+        if enabled {
+            self.disabled.remove(&rule);
+        } else {
+            self.disabled.insert(rule);
+        }
+        self
+    }
+
+    pub fn enabled(&self, rule: Rule) -> bool {
+        !self.disabled.contains(&rule)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Warning {
+    pub rule: Rule,
+    /// What the rule's message is filled in with.
+    pub arguments: Vec<String>,
+    pub span: Span,
+}
+
+impl Warning {
+    pub fn message(&self, lang: Lang) -> String {
+        let mut arguments = self.arguments.clone();
+        // The value a constant condition always has is a catalogue key.
+        if let (Rule::ConstantCondition, Some(value)) = (self.rule, arguments.get_mut(1)) {
+            *value = messages::message(value, lang, &[]);
+        }
+        let arguments: Vec<&dyn fmt::Display> = arguments
+            .iter()
+            .map(|argument| argument as &dyn fmt::Display)
+            .collect();
+        messages::message(self.rule.code(), lang, &arguments)
+    }
+
+    /// The warning as one line: where it is, the message and the rule.
+    pub fn render(&self, lang: Lang) -> String {
+        format!(
+            "{}:{}: {} [{}]",
+            self.span.line,
+            self.span.column,
+            self.message(lang),
+            self.rule
+        )
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.render(Lang::En))
+    }
+}
+
+/// Runs the rules `config` enables over `program`, function bodies
+/// included. Warnings come in source order.
+pub fn lint(program: &Program, config: &Config) -> Vec<Warning> {
+    let mut linter = Linter {
+        config,
+        counters: Vec::new(),
+        warnings: Vec::new(),
+    };
+    linter.statements(&program.statements);
+
+    let mut warnings = linter.warnings;
+    warnings.sort_by_key(|warning| (warning.span.line, warning.span.column));
+    warnings
+}
+
+/// The value of an expression made only of literals, folded with the
+/// evaluator's own operators.
+fn constant(expression: &dyn Expression) -> Option<Object> {
+    let any = expression.as_any();
+    let operand = |operand: &Option<Rc<RefCell<dyn Expression>>>| {
+        constant(&*operand.as_ref()?.as_ref().borrow())
+    };
+
+    if let Some(literal) = any.downcast_ref::<IntegralLiteral>() {
+        Some(Object::from(&literal.value))
+    } else if let Some(literal) = any.downcast_ref::<RealLiteral>() {
+        Some(Object::Real(literal.value))
+    } else if let Some(literal) = any.downcast_ref::<StringLiteral>() {
+        Some(Object::Str(literal.value.clone()))
+    } else if let Some(literal) = any.downcast_ref::<BooleanLiteral>() {
+        Some(Object::Boolean(literal.value))
+    } else if let Some(prefix) = any.downcast_ref::<PrefixExpression>() {
+        eval_prefix_expression(&prefix.operator, operand(&prefix.right)?).ok()
+    } else if let Some(integer_part) = any.downcast_ref::<IntegerPartExpression>() {
+        eval_integer_part(operand(&integer_part.value)?).ok()
+    } else if let Some(infix) = any.downcast_ref::<InfixExpression>() {
+        let left = operand(&infix.left)?;
+        match infix.operator.as_str() {
+            "&&" if !left.is_truthy() => Some(Object::Boolean(false)),
+            "||" if left.is_truthy() => Some(Object::Boolean(true)),
+            "&&" | "||" => Some(Object::Boolean(operand(&infix.right)?.is_truthy())),
+            operator => eval_infix_expression(operator, left, operand(&infix.right)?).ok(),
+        }
+    } else {
+        None
+    }
+}
+
+/// Whether evaluating `expression` twice gives the same value: it reads
+/// variables but calls nothing.
+fn is_pure(expression: &dyn Expression) -> bool {
+    let any = expression.as_any();
+    let operand = |operand: &Option<Rc<RefCell<dyn Expression>>>| {
+        operand
+            .as_ref()
+            .is_some_and(|operand| is_pure(&*operand.as_ref().borrow()))
+    };
+
+    if let Some(prefix) = any.downcast_ref::<PrefixExpression>() {
+        operand(&prefix.right)
+    } else if let Some(integer_part) = any.downcast_ref::<IntegerPartExpression>() {
+        operand(&integer_part.value)
+    } else if let Some(infix) = any.downcast_ref::<InfixExpression>() {
+        operand(&infix.left) && operand(&infix.right)
+    } else if let Some(index) = any.downcast_ref::<IndexExpression>() {
+        operand(&index.left) && operand(&index.index)
+    } else {
+        any.is::<Identifier>() || constant(expression).is_some()
+    }
+}
+
+/// Whether running `statement` always ends in a `returneaza`.
+fn always_returns(statement: &dyn Statement) -> bool {
+    let any = statement.as_any();
+
+    if any.is::<ReturnStatement>() {
+        true
+    } else if let Some(block) = any.downcast_ref::<BlockStatement>() {
+        block_returns(block)
+    } else if let Some(statement) = any.downcast_ref::<IfStatement>() {
+        block_returns(&statement.consequence)
+            && statement.alternative.as_ref().is_some_and(block_returns)
+    } else {
+        false
+    }
+}
+
+fn block_returns(block: &BlockStatement) -> bool {
+    block
+        .statements
+        .iter()
+        .any(|statement| always_returns(&*statement.as_ref().borrow()))
+}
+
+struct Linter<'a> {
+    config: &'a Config,
+    /// The counters of the `pentru` loops being walked, innermost last.
+    counters: Vec<String>,
+    warnings: Vec<Warning>,
+}
+
+impl Linter<'_> {
+    fn warn(&mut self, rule: Rule, span: Span, arguments: Vec<String>) {
+        if self.config.enabled(rule) {
+            self.warnings.push(Warning {
+                rule,
+                arguments,
+                span,
+            });
+        }
+    }
+
+    /// Walks a statement list, flagging only the first of any statements
+    /// after one that always returns.
+    fn statements(&mut self, statements: &[Rc<RefCell<dyn Statement>>]) {
+        let mut returned = false;
+        let mut reported = false;
+        for statement in statements {
+            let statement = statement.as_ref().borrow();
+            if returned && !reported {
+                self.warn(Rule::UnreachableCode, statement.span(), Vec::new());
+                reported = true;
+            }
+            self.statement(&*statement);
+            returned |= always_returns(&*statement);
+        }
+    }
+
+    fn condition(&mut self, condition: &Option<Rc<RefCell<dyn Expression>>>) {
+        let Some(condition) = condition else {
+            return;
+        };
+        let condition = condition.as_ref().borrow();
+        if let Some(value) = constant(&*condition) {
+            let value = if value.is_truthy() { "true" } else { "false" };
+            self.warn(
+                Rule::ConstantCondition,
+                condition.span(),
+                vec![condition.string(), value.to_string()],
+            );
+        }
+        self.expression(&*condition);
+    }
+
+    /// Flags an assignment to the counter of an enclosing `pentru`.
+    fn assigned(&mut self, identifier: &Identifier) {
+        if self.counters.contains(&identifier.value) {
+            self.warn(
+                Rule::CounterModified,
+                identifier.token.span,
+                vec![identifier.value.clone()],
+            );
+        }
+    }
+
+    fn statement(&mut self, statement: &dyn Statement) {
+        let any = statement.as_any();
+
+        if let Some(statement) = any.downcast_ref::<ExpressionStatement>() {
+            self.optional_expression(&statement.expression);
+        } else if let Some(statement) = any.downcast_ref::<LetStatement>() {
+            self.optional_expression(&statement.value);
+        } else if let Some(statement) = any.downcast_ref::<AssignStatement>() {
+            self.optional_expression(&statement.value);
+            self.assigned(&statement.name);
+            self.assignment(statement);
+        } else if let Some(statement) = any.downcast_ref::<IndexAssignStatement>() {
+            self.expression(&*statement.target.as_ref().borrow());
+            self.optional_expression(&statement.value);
+        } else if let Some(statement) = any.downcast_ref::<ReturnStatement>() {
+            self.optional_expression(&statement.return_value);
+        } else if let Some(block) = any.downcast_ref::<BlockStatement>() {
+            self.statements(&block.statements);
+        } else if let Some(statement) = any.downcast_ref::<IfStatement>() {
+            self.condition(&statement.condition);
+            self.statements(&statement.consequence.statements);
+            if let Some(alternative) = &statement.alternative {
+                self.statements(&alternative.statements);
+            }
+        } else if let Some(statement) = any.downcast_ref::<WhileStatement>() {
+            self.condition(&statement.condition);
+            self.statements(&statement.body.statements);
+        } else if let Some(statement) = any.downcast_ref::<RepeatStatement>() {
+            self.statements(&statement.body.statements);
+            self.condition(&statement.condition);
+        } else if let Some(statement) = any.downcast_ref::<ForStatement>() {
+            for bound in [&statement.start, &statement.end, &statement.step] {
+                self.optional_expression(bound);
+            }
+            self.assigned(&statement.counter);
+            self.counters.push(statement.counter.value.clone());
+            self.statements(&statement.body.statements);
+            self.counters.pop();
+        } else if let Some(statement) = any.downcast_ref::<ReadStatement>() {
+            for target in &statement.targets {
+                let target = target.as_ref().borrow();
+                match target.as_any().downcast_ref::<Identifier>() {
+                    Some(identifier) => self.assigned(identifier),
+                    None => self.expression(&*target),
+                }
+            }
+        } else if let Some(statement) = any.downcast_ref::<WriteStatement>() {
+            for argument in &statement.arguments {
+                self.expression(&*argument.as_ref().borrow());
+            }
+        }
+    }
+
+    /// Flags `x ← x`, `x ← x + 0` and the like, and compound assignments
+    /// of their identity element.
+    fn assignment(&mut self, statement: &AssignStatement) {
+        let Some(value) = &statement.value else {
+            return;
+        };
+        let value = value.as_ref().borrow();
+        let name = &statement.name.value;
+        let is_name = |expression: &dyn Expression| {
+            expression
+                .as_any()
+                .downcast_ref::<Identifier>()
+                .is_some_and(|identifier| identifier.value == *name)
+        };
+        let is_integer =
+            |expression: &dyn Expression, n| constant(expression) == Some(Object::Integer(n));
+
+        let useless = match statement.token.token_type {
+            TokenType::PlusAssign | TokenType::MinusAssign => is_integer(&*value, 0),
+            TokenType::AsteriskAssign | TokenType::SlashAssign => is_integer(&*value, 1),
+            _ => match value.as_any().downcast_ref::<InfixExpression>() {
+                Some(InfixExpression {
+                    left: Some(left),
+                    operator,
+                    right: Some(right),
+                    ..
+                }) => {
+                    let (left, right) = (&*left.as_ref().borrow(), &*right.as_ref().borrow());
+                    match operator.as_str() {
+                        "+" => {
+                            is_name(left) && is_integer(right, 0)
+                                || is_integer(left, 0) && is_name(right)
+                        }
+                        "*" => {
+                            is_name(left) && is_integer(right, 1)
+                                || is_integer(left, 1) && is_name(right)
+                        }
+                        "-" => is_name(left) && is_integer(right, 0),
+                        _ => false,
+                    }
+                }
+                _ => is_name(&*value),
+            },
+        };
+        if useless {
+            self.warn(
+                Rule::UselessAssignment,
+                statement.span(),
+                vec![format!(
+                    "{} {} {}",
+                    name,
+                    statement.token.literal,
+                    value.string()
+                )],
+            );
+        }
+    }
+
+    fn optional_expression(&mut self, expression: &Option<Rc<RefCell<dyn Expression>>>) {
+        if let Some(expression) = expression {
+            self.expression(&*expression.as_ref().borrow());
+        }
+    }
+
+    fn expression(&mut self, expression: &dyn Expression) {
+        let any = expression.as_any();
+
+        if let Some(prefix) = any.downcast_ref::<PrefixExpression>() {
+            self.optional_expression(&prefix.right);
+        } else if let Some(integer_part) = any.downcast_ref::<IntegerPartExpression>() {
+            self.optional_expression(&integer_part.value);
+        } else if let Some(infix) = any.downcast_ref::<InfixExpression>() {
+            self.comparison(infix);
+            self.optional_expression(&infix.left);
+            self.optional_expression(&infix.right);
+        } else if let Some(array) = any.downcast_ref::<ArrayLiteral>() {
+            for element in &array.elements {
+                self.expression(&*element.as_ref().borrow());
+            }
+        } else if let Some(map) = any.downcast_ref::<MapLiteral>() {
+            for (key, value) in &map.pairs {
+                self.expression(&*key.as_ref().borrow());
+                self.expression(&*value.as_ref().borrow());
+            }
+        } else if let Some(index) = any.downcast_ref::<IndexExpression>() {
+            self.optional_expression(&index.left);
+            self.optional_expression(&index.index);
+        } else if let Some(call) = any.downcast_ref::<CallExpression>() {
+            self.optional_expression(&call.function);
+            for argument in &call.arguments {
+                self.expression(&*argument.as_ref().borrow());
+            }
+        } else if let Some(function) = any.downcast_ref::<FunctionLiteral>() {
+            // A function body runs on its own, outside any enclosing loop.
+            let counters = std::mem::take(&mut self.counters);
+            self.statements(&function.body.statements);
+            self.counters = counters;
+        }
+    }
+
+    /// Flags a comparison of an expression with itself, as long as it
+    /// reads a variable; one of literals only is a constant condition.
+    fn comparison(&mut self, infix: &InfixExpression) {
+        if !matches!(
+            infix.operator.as_str(),
+            "==" | "!=" | "<" | "<=" | ">" | ">="
+        ) {
+            return;
+        }
+        let (Some(left), Some(right)) = (&infix.left, &infix.right) else {
+            return;
+        };
+        let (left, right) = (left.as_ref().borrow(), right.as_ref().borrow());
+        if is_pure(&*left) && constant(&*left).is_none() && left.string() == right.string() {
+            self.warn(Rule::SelfComparison, infix.span(), vec![left.string()]);
+        }
+    }
+}
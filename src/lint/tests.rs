@@ -0,0 +1,138 @@
+use crate::messages::Lang;
+use crate::test_support::parse;
+
+use super::{lint, Config, ConfigError, Rule};
+
+fn warnings(input: &str, config: &Config) -> Vec<String> {
+    lint(&parse(input), config)
+        .iter()
+        .map(|warning| warning.to_string())
+        .collect()
+}
+
+#[test]
+fn flags_common_mistakes() {
+    let tests = [
+        (
+            "daca 1 < 2 { scrie 1 }",
+            vec!["1:8: condition `(1 < 2)` is always true [constant-condition]"],
+        ),
+        (
+            "cat timp fals si x { scrie 1 }",
+            vec!["1:15: condition `(fals && x)` is always false [constant-condition]"],
+        ),
+        (
+            "pentru i ← 1, 10 { daca i % 2 == 0 { i += 1 } }",
+            vec!["1:38: counter `i` is modified inside its `pentru` loop [counter-modified]"],
+        ),
+        (
+            "functie f(n) {\n  returneaza n\n  scrie n\n}",
+            vec!["3:3: unreachable code after `returneaza` [unreachable-code]"],
+        ),
+        (
+            "functie f(n) {\n  daca n > 0 { returneaza 1 } altfel { returneaza 2 }\n  scrie n\n}",
+            vec!["3:3: unreachable code after `returneaza` [unreachable-code]"],
+        ),
+        (
+            "x ← 1\nx ← x\nx += 0\nx *= 1",
+            vec![
+                "2:1: `x ← x` has no effect [useless-assignment]",
+                "3:1: `x += 0` has no effect [useless-assignment]",
+                "4:1: `x *= 1` has no effect [useless-assignment]",
+            ],
+        ),
+        (
+            "x ← 1\nx ← x + 0\nx ← x * 1\nx ← x - 0\nx ← 1 * x\nx ← 0 - x\nx ← x + 1",
+            vec![
+                "2:1: `x ← (x + 0)` has no effect [useless-assignment]",
+                "3:1: `x ← (x * 1)` has no effect [useless-assignment]",
+                "4:1: `x ← (x - 0)` has no effect [useless-assignment]",
+                "5:1: `x ← (1 * x)` has no effect [useless-assignment]",
+            ],
+        ),
+        (
+            "citeste a\nscrie a == a, a[0] < a[0], f(a) == f(a)",
+            vec![
+                "2:9: `a` is compared with itself [self-comparison]",
+                "2:20: `(a[0])` is compared with itself [self-comparison]",
+            ],
+        ),
+    ];
+
+    for (input, expected) in tests {
+        assert_eq!(warnings(input, &Config::new()), expected, "{}", input);
+    }
+}
+
+#[test]
+fn warns_in_romanian() {
+    let warnings: Vec<_> = lint(&parse("daca 1 < 2 { x ← x }"), &Config::new())
+        .iter()
+        .map(|warning| warning.render(Lang::Ro))
+        .collect();
+    assert_eq!(
+        warnings,
+        vec![
+            "1:8: condiția `(1 < 2)` este mereu adevărat [constant-condition]",
+            "1:14: `x ← x` nu are niciun efect [useless-assignment]",
+        ]
+    );
+}
+
+#[test]
+fn leaves_ordinary_code_alone() {
+    let input = "citeste n
+s ← 0
+pentru i ← 1, n { s += i; j ← i }
+daca s > 10 { returneaza s }
+repeta { n -= 1 } pana cand n <= 0
+scrie s";
+    assert!(warnings(input, &Config::new()).is_empty());
+}
+
+#[test]
+fn rules_can_be_turned_off() {
+    let input = "x ← 1\ndaca adevarat { x ← x }";
+    let config = Config::parse(
+        "# style rules
+constant-condition = false
+
+useless-assignment = true",
+    )
+    .unwrap();
+    assert!(!config.enabled(Rule::ConstantCondition));
+    assert_eq!(
+        warnings(input, &config),
+        vec!["2:17: `x ← x` has no effect [useless-assignment]"]
+    );
+
+    let config = config.rule(Rule::UselessAssignment, false);
+    assert!(warnings(input, &config).is_empty());
+}
+
+#[test]
+fn rejects_bad_configuration() {
+    let tests = [
+        ("no-such-rule = false", 1, "unknown rule `no-such-rule`"),
+        (
+            "\nself-comparison = off",
+            2,
+            "expected true or false, got `off`",
+        ),
+        (
+            "unreachable-code",
+            1,
+            "expected `rule = true|false`, got `unreachable-code`",
+        ),
+    ];
+
+    for (input, line, message) in tests {
+        assert_eq!(
+            Config::parse(input),
+            Err(ConfigError {
+                line,
+                message: message.to_string()
+            })
+        );
+    }
+}
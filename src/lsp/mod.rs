@@ -0,0 +1,671 @@
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+
+use crate::ast::Program;
+use crate::builtins::BUILTINS;
+use crate::highlight::{self, Kind};
+use crate::json::{read_message, write_message, Json};
+use crate::lexer::{Lexer, Span, Token, TokenType};
+use crate::messages::Lang;
+use crate::parser::{ParseError, Parser};
+use crate::resolver::{self, BindingKind, Resolution, Severity};
+use crate::types::{self, Analysis};
+
+#[cfg(test)]
+mod tests;
+
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_REQUEST: i64 = -32600;
+const INVALID_PARAMS: i64 = -32602;
+
+/// The token types `textDocument/semanticTokens/full` reports, by index.
+const TOKEN_TYPES: [&str; 8] = [
+    "keyword",
+    "function",
+    "variable",
+    "parameter",
+    "number",
+    "string",
+    "operator",
+    "comment",
+];
+
+const SYMBOL_FUNCTION: i64 = 12;
+const SYMBOL_VARIABLE: i64 = 13;
+
+/// Serves one client until it sends `exit` or closes the input.
+pub fn run(mut input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+    let mut server = Server::new();
+    while let Some(message) = read_message(&mut input)? {
+        for reply in server.handle(&message) {
+            write_message(&mut output, &reply)?;
+        }
+        if server.exited() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// The state of a session: the open documents, the language diagnostics
+/// are shown in and whether the client has asked to shut down.
+#[derive(Debug, Default)]
+pub struct Server {
+    documents: HashMap<String, String>,
+    /// Romanian if the client's `locale` is, otherwise English.
+    lang: Lang,
+    shutdown: bool,
+    exited: bool,
+}
+
+impl Server {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn exited(&self) -> bool {
+        self.exited
+    }
+
+    /// Handles one message from the client, returning what to send back:
+    /// the response to a request, and any notifications it causes.
+    pub fn handle(&mut self, message: &Json) -> Vec<Json> {
+        let Some(method) = message.get("method").and_then(Json::as_str) else {
+            return Vec::new();
+        };
+        let params = message.get("params").unwrap_or(&Json::Null);
+
+        let Some(id) = message.get("id") else {
+            return self.notification(method, params);
+        };
+        let response = match self.request(method, params) {
+            Ok(result) => Json::from([
+                ("jsonrpc", "2.0".into()),
+                ("id", id.clone()),
+                ("result", result),
+            ]),
+            Err((code, message)) => Json::from([
+                ("jsonrpc", "2.0".into()),
+                ("id", id.clone()),
+                (
+                    "error",
+                    Json::from([("code", code.into()), ("message", message.into())]),
+                ),
+            ]),
+        };
+        vec![response]
+    }
+
+    fn notification(&mut self, method: &str, params: &Json) -> Vec<Json> {
+        let uri = params
+            .at(&["textDocument", "uri"])
+            .and_then(Json::as_str)
+            .map(str::to_string);
+
+        match (method, uri) {
+            ("exit", _) => {
+                self.exited = true;
+                Vec::new()
+            }
+            ("textDocument/didOpen", Some(uri)) => {
+                let text = params.at(&["textDocument", "text"]).and_then(Json::as_str);
+                self.documents
+                    .insert(uri.clone(), text.unwrap_or_default().to_string());
+                vec![self.publish_diagnostics(&uri)]
+            }
+            ("textDocument/didChange", Some(uri)) => {
+                // Changes come whole, as `initialize` asks for full sync.
+                let text = params
+                    .get("contentChanges")
+                    .and_then(Json::as_array)
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change.get("text"))
+                    .and_then(Json::as_str);
+                if let Some(text) = text {
+                    self.documents.insert(uri.clone(), text.to_string());
+                }
+                vec![self.publish_diagnostics(&uri)]
+            }
+            ("textDocument/didClose", Some(uri)) => {
+                self.documents.remove(&uri);
+                vec![notification(
+                    "textDocument/publishDiagnostics",
+                    Json::from([
+                        ("uri", uri.into()),
+                        ("diagnostics", Json::Array(Vec::new())),
+                    ]),
+                )]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn request(&mut self, method: &str, params: &Json) -> Result<Json, (i64, String)> {
+        if self.shutdown {
+            return Err((INVALID_REQUEST, "the server is shutting down".to_string()));
+        }
+
+        if method == "initialize" {
+            let locale = params.get("locale").and_then(Json::as_str);
+            if locale.is_some_and(|locale| locale.starts_with("ro")) {
+                self.lang = Lang::Ro;
+            }
+        }
+
+        match method {
+            "initialize" => Ok(Json::from([
+                (
+                    "capabilities",
+                    Json::from([
+                        ("textDocumentSync", 1i64.into()),
+                        ("hoverProvider", true.into()),
+                        ("definitionProvider", true.into()),
+                        ("documentSymbolProvider", true.into()),
+                        (
+                            "semanticTokensProvider",
+                            Json::from([
+                                (
+                                    "legend",
+                                    Json::from([
+                                        (
+                                            "tokenTypes",
+                                            TOKEN_TYPES
+                                                .iter()
+                                                .map(|t| (*t).into())
+                                                .collect::<Vec<_>>()
+                                                .into(),
+                                        ),
+                                        ("tokenModifiers", Json::Array(Vec::new())),
+                                    ]),
+                                ),
+                                ("full", true.into()),
+                            ]),
+                        ),
+                    ]),
+                ),
+                (
+                    "serverInfo",
+                    Json::from([("name", env!("CARGO_PKG_NAME").into())]),
+                ),
+            ])),
+            "shutdown" => {
+                self.shutdown = true;
+                Ok(Json::Null)
+            }
+            "textDocument/hover"
+            | "textDocument/definition"
+            | "textDocument/documentSymbol"
+            | "textDocument/semanticTokens/full" => {
+                let uri = params
+                    .at(&["textDocument", "uri"])
+                    .and_then(Json::as_str)
+                    .ok_or((INVALID_PARAMS, "missing textDocument.uri".to_string()))?;
+                let Some(text) = self.documents.get(uri) else {
+                    return Ok(Json::Null);
+                };
+                let document = Document::new(text);
+                let position = params.get("position").and_then(|position| {
+                    let line = position.get("line")?.as_i64()?;
+                    let character = position.get("character")?.as_i64()?;
+                    Some((line as usize, character as usize))
+                });
+
+                Ok(match (method, position) {
+                    ("textDocument/hover", Some(position)) => document.hover(position),
+                    ("textDocument/definition", Some(position)) => {
+                        document.definition(uri, position)
+                    }
+                    ("textDocument/documentSymbol", _) => document.symbols(),
+                    ("textDocument/semanticTokens/full", _) => document.semantic_tokens(),
+                    _ => return Err((INVALID_PARAMS, "missing position".to_string())),
+                })
+            }
+            _ => Err((METHOD_NOT_FOUND, format!("unknown method: {}", method))),
+        }
+    }
+
+    fn publish_diagnostics(&self, uri: &str) -> Json {
+        let text = self.documents.get(uri).map(String::as_str).unwrap_or("");
+        let document = Document::new(text);
+        notification(
+            "textDocument/publishDiagnostics",
+            Json::from([
+                ("uri", uri.into()),
+                ("diagnostics", document.diagnostics(self.lang).into()),
+            ]),
+        )
+    }
+}
+
+fn notification(method: &str, params: Json) -> Json {
+    Json::from([
+        ("jsonrpc", "2.0".into()),
+        ("method", method.into()),
+        ("params", params),
+    ])
+}
+
+fn position(line: usize, character: usize) -> Json {
+    Json::from([("line", line.into()), ("character", character.into())])
+}
+
+/// A function body in the source, from its `functie` or `fn` to its
+/// closing brace.
+struct FunctionRange {
+    name: String,
+    start: Span,
+    end: Span,
+}
+
+/// A parsed open document.
+struct Document<'a> {
+    text: &'a str,
+    tokens: Vec<Token>,
+    program: Option<Program>,
+    errors: Vec<ParseError>,
+    functions: Vec<FunctionRange>,
+}
+
+impl<'a> Document<'a> {
+    fn new(text: &'a str) -> Self {
+        let tokens = highlight::tokens(text);
+        let mut parser = Parser::new(Lexer::new(text.to_string()));
+        let program = parser.parse_program();
+        let errors = parser.parse_errors().to_vec();
+        let functions = function_ranges(&tokens);
+        Document {
+            text,
+            tokens,
+            program,
+            errors,
+            functions,
+        }
+    }
+
+    /// The length of a token in UTF-16 code units, which is how LSP counts
+    /// characters.
+    fn length(&self, span: Span) -> usize {
+        self.text
+            .get(span.start..span.end)
+            .map_or(0, |text| text.encode_utf16().count())
+    }
+
+    /// How far into its line a token starts, in UTF-16 code units.
+    fn character(&self, span: Span) -> usize {
+        let start = span.start.min(self.text.len());
+        let line_start = self.text[..start].rfind('\n').map_or(0, |i| i + 1);
+        self.text
+            .get(line_start..start)
+            .map_or(0, |text| text.encode_utf16().count())
+    }
+
+    fn range(&self, span: Span) -> Json {
+        let line = span.line.saturating_sub(1);
+        let character = self.character(span);
+        Json::from([
+            ("start", position(line, character)),
+            ("end", position(line, character + self.length(span))),
+        ])
+    }
+
+    /// The identifier under the cursor.
+    fn identifier_at(&self, (line, character): (usize, usize)) -> Option<(&str, Span)> {
+        self.tokens.iter().find_map(|token| {
+            let TokenType::Ident(name) = &token.token_type else {
+                return None;
+            };
+            let start = self.character(token.span);
+            let covers = token.span.line == line + 1
+                && start <= character
+                && character < start + self.length(token.span);
+            covers.then_some((name.as_str(), token.span))
+        })
+    }
+
+    /// The innermost named function the source at `span` is in.
+    fn function_at(&self, span: Span) -> Option<&str> {
+        self.functions
+            .iter()
+            .filter(|function| function.start.start <= span.start && span.start < function.end.end)
+            .max_by_key(|function| function.start.start)
+            .map(|function| function.name.as_str())
+    }
+
+    fn resolution(&self) -> Option<Resolution> {
+        match &self.program {
+            Some(program) if self.errors.is_empty() => Some(resolver::resolve(program)),
+            _ => None,
+        }
+    }
+
+    /// The scope of `resolution` the source at `span` is in.
+    fn scope_at(&self, resolution: &Resolution, span: Span) -> usize {
+        self.function_at(span)
+            .and_then(|name| {
+                resolution
+                    .scopes
+                    .iter()
+                    .position(|scope| scope.function.as_deref() == Some(name))
+            })
+            .unwrap_or(0)
+    }
+
+    fn diagnostics(&self, lang: Lang) -> Vec<Json> {
+        let diagnostic = |span: Span, severity: i64, code: &str, message: String| {
+            Json::from([
+                ("range", self.range(span)),
+                ("severity", severity.into()),
+                ("code", code.into()),
+                ("source", env!("CARGO_PKG_NAME").into()),
+                ("message", message.into()),
+            ])
+        };
+
+        let mut diagnostics: Vec<Json> = self
+            .errors
+            .iter()
+            .map(|error| diagnostic(error.span, 1, error.code(), error.message(lang)))
+            .collect();
+        if let (Some(program), Some(resolution)) = (&self.program, self.resolution()) {
+            for problem in &resolution.diagnostics {
+                let severity = match problem.severity() {
+                    Severity::Error => 1,
+                    Severity::Warning => 2,
+                };
+                diagnostics.push(diagnostic(
+                    problem.span,
+                    severity,
+                    problem.problem.code(),
+                    problem.problem.message(lang),
+                ));
+            }
+            for error in types::check(program) {
+                diagnostics.push(diagnostic(
+                    error.span,
+                    1,
+                    error.error.code(),
+                    error.error.message(lang),
+                ));
+            }
+        }
+        diagnostics
+    }
+
+    /// The inferred type of the variable under the cursor, or the
+    /// signature of the function.
+    fn hover(&self, position: (usize, usize)) -> Json {
+        let (Some(program), Some((name, span))) = (&self.program, self.identifier_at(position))
+        else {
+            return Json::Null;
+        };
+        let analysis = Analysis::new(program);
+
+        let text = match analysis.function(name) {
+            Some((_, function)) => {
+                let parameters: Vec<String> = function
+                    .parameters
+                    .iter()
+                    .map(|(name, ty)| format!("{}: {}", name, ty))
+                    .collect();
+                let returns = function
+                    .returns
+                    .as_ref()
+                    .map(|ty| format!(": {}", ty))
+                    .unwrap_or_default();
+                format!("functie {}({}){}", name, parameters.join(", "), returns)
+            }
+            None => {
+                let scope = self
+                    .function_at(span)
+                    .and_then(|function| analysis.function(function))
+                    .map(|(index, _)| index);
+                match analysis.variable(scope, name) {
+                    types::Type::Unknown if BUILTINS.contains(&name) => {
+                        format!("{}: builtin function", name)
+                    }
+                    types::Type::Unknown => return Json::Null,
+                    ty => format!("{}: {}", name, ty),
+                }
+            }
+        };
+
+        Json::from([
+            (
+                "contents",
+                Json::from([
+                    ("kind", "markdown".into()),
+                    ("value", format!("```\n{}\n```", text).into()),
+                ]),
+            ),
+            ("range", self.range(span)),
+        ])
+    }
+
+    /// Where the name under the cursor is first given a value in the scope
+    /// it resolves to.
+    fn definition(&self, uri: &str, position: (usize, usize)) -> Json {
+        let (Some(resolution), Some((name, span))) =
+            (self.resolution(), self.identifier_at(position))
+        else {
+            return Json::Null;
+        };
+
+        let mut scope = Some(self.scope_at(&resolution, span));
+        while let Some(index) = scope {
+            let scope_ = &resolution.scopes[index];
+            if let Some(binding) = scope_.bindings.iter().find(|b| b.name == name) {
+                return Json::from([("uri", uri.into()), ("range", self.range(binding.span))]);
+            }
+            scope = scope_.parent;
+        }
+        Json::Null
+    }
+
+    /// The functions and top-level variables, with the parameters and
+    /// locals of each function nested under it.
+    fn symbols(&self) -> Json {
+        let Some(resolution) = self.resolution() else {
+            return Json::Array(Vec::new());
+        };
+        let symbol = |name: &str, kind: i64, range: Json, selection: Json, children: Vec<Json>| {
+            Json::from([
+                ("name", name.into()),
+                ("kind", kind.into()),
+                ("range", range),
+                ("selectionRange", selection),
+                ("children", children.into()),
+            ])
+        };
+
+        let mut symbols = Vec::new();
+        for binding in &resolution.scopes[0].bindings {
+            let selection = self.range(binding.span);
+            if binding.kind != BindingKind::Function {
+                symbols.push(symbol(
+                    &binding.name,
+                    SYMBOL_VARIABLE,
+                    selection.clone(),
+                    selection,
+                    Vec::new(),
+                ));
+                continue;
+            }
+
+            let children = resolution
+                .scopes
+                .iter()
+                .find(|scope| {
+                    scope.parent == Some(0) && scope.function.as_ref() == Some(&binding.name)
+                })
+                .map(|scope| {
+                    scope
+                        .bindings
+                        .iter()
+                        .map(|local| {
+                            let range = self.range(local.span);
+                            symbol(
+                                &local.name,
+                                SYMBOL_VARIABLE,
+                                range.clone(),
+                                range,
+                                Vec::new(),
+                            )
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            let range = self
+                .functions
+                .iter()
+                .find(|function| function.name == binding.name)
+                .map(|function| {
+                    let end = self.range(function.end);
+                    Json::from([
+                        (
+                            "start",
+                            self.range(function.start).get("start").cloned().into(),
+                        ),
+                        ("end", end.get("end").cloned().into()),
+                    ])
+                })
+                .unwrap_or_else(|| selection.clone());
+            symbols.push(symbol(
+                &binding.name,
+                SYMBOL_FUNCTION,
+                range,
+                selection,
+                children,
+            ));
+        }
+        Json::Array(symbols)
+    }
+
+    /// Every token worth colouring, as the relative offsets LSP expects:
+    /// line delta, start delta, length, type and no modifiers.
+    fn semantic_tokens(&self) -> Json {
+        let resolution = self.resolution();
+        let mut data = Vec::new();
+        let (mut line, mut start) = (0, 0);
+
+        for token in &self.tokens {
+            let Some(kind) = self.classify(token, resolution.as_ref()) else {
+                continue;
+            };
+            let token_line = token.span.line.saturating_sub(1);
+            let token_start = self.character(token.span);
+            if self.text[token.span.start..token.span.end].contains('\n') {
+                continue;
+            }
+            let delta_start = if token_line == line {
+                token_start - start
+            } else {
+                token_start
+            };
+            data.extend([
+                token_line - line,
+                delta_start,
+                self.length(token.span),
+                kind,
+                0,
+            ]);
+            (line, start) = (token_line, token_start);
+        }
+
+        Json::from([(
+            "data",
+            data.into_iter().map(Json::from).collect::<Vec<_>>().into(),
+        )])
+    }
+
+    /// The index into `TOKEN_TYPES` of how `token` is coloured.
+    fn classify(&self, token: &Token, resolution: Option<&Resolution>) -> Option<usize> {
+        let kind = |name: &str| TOKEN_TYPES.iter().position(|t| *t == name);
+
+        match highlight::classify(token) {
+            Kind::Keyword => kind("keyword"),
+            Kind::Number => kind("number"),
+            Kind::String => kind("string"),
+            Kind::Operator => kind("operator"),
+            Kind::Comment => kind("comment"),
+            Kind::Punctuation | Kind::Illegal => None,
+            Kind::Identifier => {
+                let Some(resolution) = resolution else {
+                    return kind("variable");
+                };
+                let name = token.literal.as_str();
+                let scope = &resolution.scopes[self.scope_at(resolution, token.span)];
+                let is_function = resolution.scopes[0]
+                    .bindings
+                    .iter()
+                    .any(|b| b.name == name && b.kind == BindingKind::Function);
+                if is_function || BUILTINS.contains(&name) {
+                    kind("function")
+                } else if scope
+                    .bindings
+                    .iter()
+                    .any(|b| b.name == name && b.kind == BindingKind::Parameter)
+                {
+                    kind("parameter")
+                } else {
+                    kind("variable")
+                }
+            }
+        }
+    }
+}
+
+/// Finds the named functions: `functie f(...) { ... }` and
+/// `let f = fn(...) { ... }`.
+fn function_ranges(tokens: &[Token]) -> Vec<FunctionRange> {
+    let tokens: Vec<&Token> = tokens
+        .iter()
+        .filter(|token| token.token_type != TokenType::Comment)
+        .collect();
+    let mut functions = Vec::new();
+    for (index, token) in tokens.iter().enumerate() {
+        if token.token_type != TokenType::Function {
+            continue;
+        }
+        let name = match (tokens.get(index + 1), index.checked_sub(3)) {
+            (
+                Some(Token {
+                    token_type: TokenType::Ident(name),
+                    ..
+                }),
+                _,
+            ) => name.clone(),
+            (_, Some(before)) => match &tokens[before..index] {
+                [Token {
+                    token_type: TokenType::Let,
+                    ..
+                }, Token {
+                    token_type: TokenType::Ident(name),
+                    ..
+                }, Token {
+                    token_type: TokenType::Assign,
+                    ..
+                }] => name.clone(),
+                _ => continue,
+            },
+            _ => continue,
+        };
+
+        let mut depth = 0;
+        let end = tokens[index..].iter().find(|token| {
+            match token.token_type {
+                TokenType::LBrace => depth += 1,
+                TokenType::RBrace => depth -= 1,
+                _ => return false,
+            }
+            depth == 0
+        });
+        if let Some(end) = end {
+            functions.push(FunctionRange {
+                name,
+                start: token.span,
+                end: end.span,
+            });
+        }
+    }
+    functions
+}
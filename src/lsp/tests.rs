@@ -0,0 +1,309 @@
+use std::io::Cursor;
+
+use crate::json::Json;
+
+use super::{read_message, run, write_message, Server};
+
+const URI: &str = "file:///suma.pseudo";
+
+fn request(id: i64, method: &str, params: Json) -> Json {
+    Json::from([
+        ("jsonrpc", "2.0".into()),
+        ("id", id.into()),
+        ("method", method.into()),
+        ("params", params),
+    ])
+}
+
+fn notification(method: &str, params: Json) -> Json {
+    Json::from([
+        ("jsonrpc", "2.0".into()),
+        ("method", method.into()),
+        ("params", params),
+    ])
+}
+
+fn open(server: &mut Server, text: &str) -> Vec<Json> {
+    server.handle(&notification(
+        "textDocument/didOpen",
+        Json::from([(
+            "textDocument",
+            Json::from([
+                ("uri", URI.into()),
+                ("languageId", "pseudocode".into()),
+                ("version", 1i64.into()),
+                ("text", text.into()),
+            ]),
+        )]),
+    ))
+}
+
+fn at(id: i64, method: &str, line: usize, character: usize) -> Json {
+    request(
+        id,
+        method,
+        Json::from([
+            ("textDocument", Json::from([("uri", URI.into())])),
+            (
+                "position",
+                Json::from([("line", line.into()), ("character", character.into())]),
+            ),
+        ]),
+    )
+}
+
+fn result(mut replies: Vec<Json>) -> Json {
+    assert_eq!(replies.len(), 1);
+    let reply = replies.remove(0);
+    reply.get("result").cloned().unwrap_or(Json::Null)
+}
+
+#[test]
+fn publishes_diagnostics() {
+    let mut server = Server::new();
+    let replies = open(&mut server, "x ← 1\nscrie x, y");
+    assert_eq!(
+        replies[0].to_string(),
+        format!(
+            r#"{{"jsonrpc":"2.0","method":"textDocument/publishDiagnostics","params":{{"uri":"{}","diagnostics":[{}]}}}}"#,
+            URI,
+            r#"{"range":{"start":{"line":1,"character":9},"end":{"line":1,"character":10}},"severity":1,"code":"E0301","source":"h95-lang","message":"identifier not found: y"}"#
+        )
+    );
+
+    let replies = server.handle(&notification(
+        "textDocument/didChange",
+        Json::from([
+            ("textDocument", Json::from([("uri", URI.into())])),
+            (
+                "contentChanges",
+                Json::Array(vec![Json::from([("text", "x ← (1 + 2\nscrie x".into())])]),
+            ),
+        ]),
+    ));
+    let diagnostics = replies[0].at(&["params", "diagnostics"]).unwrap();
+    let messages: Vec<_> = diagnostics
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|d| d.get("message").and_then(Json::as_str).unwrap())
+        .collect();
+    assert_eq!(messages, vec!["expected `)`, got `scrie` instead"]);
+    assert_eq!(
+        diagnostics.as_array().unwrap()[0]
+            .at(&["range", "start"])
+            .unwrap()
+            .to_string(),
+        r#"{"line":1,"character":0}"#
+    );
+
+    let replies = server.handle(&notification(
+        "textDocument/didChange",
+        Json::from([
+            ("textDocument", Json::from([("uri", URI.into())])),
+            (
+                "contentChanges",
+                Json::Array(vec![Json::from([("text", "scrie \"a\" - 1".into())])]),
+            ),
+        ]),
+    ));
+    assert_eq!(
+        replies[0]
+            .at(&["params", "diagnostics"])
+            .unwrap()
+            .to_string(),
+        r#"[{"range":{"start":{"line":0,"character":10},"end":{"line":0,"character":11}},"severity":1,"code":"E0203","source":"h95-lang","message":"type mismatch: STRING - INTEGER"}]"#
+    );
+}
+
+#[test]
+fn publishes_diagnostics_in_the_client_locale() {
+    let mut server = Server::new();
+    server.handle(&request(
+        1,
+        "initialize",
+        Json::from([("locale", "ro-RO".into())]),
+    ));
+    let replies = open(&mut server, "scrie x ← 1");
+    let diagnostics = replies[0].at(&["params", "diagnostics"]).unwrap();
+    let messages: Vec<_> = diagnostics
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|d| d.get("message").and_then(Json::as_str).unwrap())
+        .collect();
+    assert_eq!(messages, vec!["nu se aștepta `←` aici"]);
+}
+
+#[test]
+fn answers_questions_about_the_code() {
+    let text = "functie suma(n) {\n  s ← 0\n  pentru i ← 1, n { s += i }\n  returneaza s\n}\nx ← suma(10)\nscrie x";
+    let mut server = Server::new();
+    open(&mut server, text);
+
+    let hover = result(server.handle(&at(1, "textDocument/hover", 5, 5)));
+    assert_eq!(
+        hover.at(&["contents", "value"]).and_then(Json::as_str),
+        Some("```\nfunctie suma(n: INTEGER): INTEGER\n```")
+    );
+    let hover = result(server.handle(&at(2, "textDocument/hover", 3, 13)));
+    assert_eq!(
+        hover.at(&["contents", "value"]).and_then(Json::as_str),
+        Some("```\ns: INTEGER\n```")
+    );
+    let hover = result(server.handle(&at(3, "textDocument/hover", 6, 0)));
+    assert_eq!(hover, Json::Null);
+
+    let definition = result(server.handle(&at(4, "textDocument/definition", 3, 13)));
+    assert_eq!(
+        definition.to_string(),
+        format!(
+            r#"{{"uri":"{}","range":{{"start":{{"line":1,"character":2}},"end":{{"line":1,"character":3}}}}}}"#,
+            URI
+        )
+    );
+    let definition = result(server.handle(&at(5, "textDocument/definition", 5, 5)));
+    assert_eq!(
+        definition.at(&["range", "start"]).unwrap().to_string(),
+        r#"{"line":0,"character":8}"#
+    );
+
+    let symbols = result(server.handle(&request(
+        6,
+        "textDocument/documentSymbol",
+        Json::from([("textDocument", Json::from([("uri", URI.into())]))]),
+    )));
+    let symbols = symbols.as_array().unwrap();
+    let names: Vec<_> = symbols
+        .iter()
+        .map(|s| s.get("name").and_then(Json::as_str).unwrap())
+        .collect();
+    assert_eq!(names, vec!["suma", "x"]);
+    assert_eq!(
+        symbols[0].get("range").unwrap().to_string(),
+        r#"{"start":{"line":0,"character":0},"end":{"line":4,"character":1}}"#
+    );
+    let children: Vec<_> = symbols[0]
+        .get("children")
+        .and_then(Json::as_array)
+        .unwrap()
+        .iter()
+        .map(|s| s.get("name").and_then(Json::as_str).unwrap())
+        .collect();
+    assert_eq!(children, vec!["n", "s", "i"]);
+}
+
+#[test]
+fn encodes_semantic_tokens() {
+    let mut server = Server::new();
+    open(&mut server, "functie f(n) {\n  scrie n + 1\n}");
+    let tokens = result(server.handle(&request(
+        1,
+        "textDocument/semanticTokens/full",
+        Json::from([("textDocument", Json::from([("uri", URI.into())]))]),
+    )));
+    let data: Vec<_> = tokens
+        .get("data")
+        .and_then(Json::as_array)
+        .unwrap()
+        .iter()
+        .map(|n| n.as_i64().unwrap())
+        .collect();
+    #[rustfmt::skip]
+    assert_eq!(data, vec![
+        0, 0, 7, 0, 0, // functie
+        0, 8, 1, 1, 0, // f
+        0, 2, 1, 3, 0, // n
+        1, 2, 5, 0, 0, // scrie
+        0, 6, 1, 3, 0, // n
+        0, 2, 1, 6, 0, // +
+        0, 2, 1, 4, 0, // 1
+    ]);
+}
+
+#[test]
+fn counts_columns_in_utf16() {
+    let text = "x ← \"a\"\ns ← \"😀\" + x";
+    let mut server = Server::new();
+    open(&mut server, text);
+
+    let definition = result(server.handle(&at(1, "textDocument/definition", 1, 11)));
+    assert_eq!(
+        definition.at(&["range"]).unwrap().to_string(),
+        r#"{"start":{"line":0,"character":0},"end":{"line":0,"character":1}}"#
+    );
+    let hover = result(server.handle(&at(2, "textDocument/hover", 1, 11)));
+    assert_eq!(
+        hover.at(&["contents", "value"]).and_then(Json::as_str),
+        Some("```\nx: STRING\n```")
+    );
+
+    let tokens = result(server.handle(&request(
+        3,
+        "textDocument/semanticTokens/full",
+        Json::from([("textDocument", Json::from([("uri", URI.into())]))]),
+    )));
+    let data: Vec<_> = tokens
+        .get("data")
+        .and_then(Json::as_array)
+        .unwrap()
+        .iter()
+        .map(|n| n.as_i64().unwrap())
+        .collect();
+    #[rustfmt::skip]
+    assert_eq!(data[25..], [
+        0, 2, 4, 5, 0, // "😀"
+        0, 5, 1, 6, 0, // +
+        0, 2, 1, 2, 0, // x
+    ]);
+}
+
+#[test]
+fn follows_the_lifecycle() {
+    let mut server = Server::new();
+    let capabilities = result(server.handle(&request(1, "initialize", Json::from([]))));
+    assert_eq!(
+        capabilities
+            .at(&["capabilities", "hoverProvider"])
+            .and_then(Json::as_bool),
+        Some(true)
+    );
+
+    let reply = server.handle(&request(2, "textDocument/rename", Json::from([])));
+    assert_eq!(
+        reply[0].at(&["error", "code"]).and_then(Json::as_i64),
+        Some(-32601)
+    );
+
+    assert_eq!(
+        result(server.handle(&request(3, "shutdown", Json::Null))),
+        Json::Null
+    );
+    let reply = server.handle(&request(4, "initialize", Json::from([])));
+    assert_eq!(
+        reply[0].at(&["error", "code"]).and_then(Json::as_i64),
+        Some(-32600)
+    );
+
+    assert!(server.handle(&notification("exit", Json::Null)).is_empty());
+    assert!(server.exited());
+}
+
+#[test]
+fn frames_messages() {
+    let mut input = Vec::new();
+    write_message(&mut input, &request(1, "initialize", Json::from([]))).unwrap();
+    write_message(&mut input, &request(2, "shutdown", Json::Null)).unwrap();
+    write_message(&mut input, &notification("exit", Json::Null)).unwrap();
+    write_message(&mut input, &request(3, "shutdown", Json::Null)).unwrap();
+
+    let mut output = Vec::new();
+    run(Cursor::new(input), &mut output).unwrap();
+
+    let mut output = Cursor::new(output);
+    let mut ids = Vec::new();
+    while let Some(message) = read_message(&mut output).unwrap() {
+        ids.push(message.get("id").and_then(Json::as_i64).unwrap());
+    }
+    assert_eq!(ids, vec![1, 2]);
+}
@@ -0,0 +1,363 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::lexer::TokenType;
+
+#[cfg(test)]
+mod tests;
+
+/// The language diagnostics are shown in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Lang {
+    #[default]
+    En,
+    Ro,
+}
+
+impl FromStr for Lang {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "en" => Ok(Lang::En),
+            "ro" => Ok(Lang::Ro),
+            _ => Err(format!("unknown language `{}`, expected ro or en", s)),
+        }
+    }
+}
+
+impl fmt::Display for Lang {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Lang::En => write!(f, "en"),
+            Lang::Ro => write!(f, "ro"),
+        }
+    }
+}
+
+/// Every message by its code, in English and Romanian. `{0}`, `{1}`, ...
+/// stand for the arguments. Codes starting with E01 are syntax errors,
+/// E02 runtime errors, E03 errors in names found without running the
+/// program, W01 lint warnings and W02 warnings about names; the rest label
+/// parts of a diagnostic.
+const CATALOGUE: &[(&str, &str, &str)] = &[
+    (
+        "E0101",
+        "expected {0}, got {1} instead",
+        "se aștepta {0}, dar urmează {1}",
+    ),
+    ("E0102", "unexpected {0}", "nu se aștepta {0} aici"),
+    (
+        "E0103",
+        "invalid assignment target: {0}",
+        "țintă nevalidă pentru atribuire: {0}",
+    ),
+    (
+        "E0104",
+        "invalid read target: {0}",
+        "țintă nevalidă pentru citire: {0}",
+    ),
+    (
+        "E0201",
+        "identifier not found: {0}",
+        "identificator necunoscut: {0}",
+    ),
+    (
+        "E0202",
+        "assignment to undeclared variable: {0}",
+        "atribuire unei variabile nedeclarate: {0}",
+    ),
+    (
+        "E0203",
+        "type mismatch: {0} {1} {2}",
+        "tipuri incompatibile: {0} {1} {2}",
+    ),
+    (
+        "E0204",
+        "unknown operator: {0}{1}",
+        "operator necunoscut: {0}{1}",
+    ),
+    (
+        "E0205",
+        "unknown operator: {0} {1} {2}",
+        "operator necunoscut: {0} {1} {2}",
+    ),
+    (
+        "E0206",
+        "invalid operand for {0}: {1}",
+        "operand nevalid pentru {0}: {1}",
+    ),
+    ("E0207", "division by zero", "împărțire la zero"),
+    (
+        "E0208",
+        "integer overflow",
+        "depășirea domeniului întregilor",
+    ),
+    (
+        "E0209",
+        "index {0} out of range for array of length {1}",
+        "indicele {0} este în afara tabloului de lungime {1}",
+    ),
+    (
+        "E0210",
+        "index operator not supported: {0}[{1}]",
+        "indexarea nu este permisă: {0}[{1}]",
+    ),
+    (
+        "E0211",
+        "unusable as map key: {0}",
+        "nu poate fi cheie de dicționar: {0}",
+    ),
+    ("E0212", "key not found: {0}", "cheie negăsită: {0}"),
+    ("E0213", "not a function: {0}", "nu este o funcție: {0}"),
+    (
+        "E0214",
+        "wrong number of arguments to `{0}`: got={1}, want={2}",
+        "număr greșit de argumente pentru `{0}`: primite={1}, așteptate={2}",
+    ),
+    (
+        "E0215",
+        "argument to `{0}` not supported, got {1}",
+        "argument nepermis pentru `{0}`: {1}",
+    ),
+    (
+        "E0216",
+        "step of for loop must not be zero",
+        "pasul buclei pentru nu poate fi zero",
+    ),
+    (
+        "E0217",
+        "no more input to read",
+        "nu mai sunt date de citit",
+    ),
+    ("E0218", "i/o error: {0}", "eroare de intrare/ieșire: {0}"),
+    ("E0219", "stack overflow", "depășirea stivei"),
+    (
+        "E0220",
+        "step limit exceeded",
+        "limita de pași a fost depășită",
+    ),
+    (
+        "E0221",
+        "recursion depth limit exceeded",
+        "limita de adâncime a recursivității a fost depășită",
+    ),
+    (
+        "E0222",
+        "allocation limit exceeded",
+        "limita de memorie a fost depășită",
+    ),
+    (
+        "E0223",
+        "time limit exceeded",
+        "limita de timp a fost depășită",
+    ),
+    ("E0224", "cannot evaluate: {0}", "nu se poate evalua: {0}"),
+    ("E0225", "execution interrupted", "execuție întreruptă"),
+    (
+        "E0226",
+        "invalid argument to `{0}`: {1}",
+        "argument nevalid pentru `{0}`: {1}",
+    ),
+    ("E0227", "`{0}` failed: {1}", "`{0}` a eșuat: {1}"),
+    (
+        "E0301",
+        "identifier not found: {0}",
+        "identificator necunoscut: {0}",
+    ),
+    (
+        "E0302",
+        "`{0}` is used before it is given a value",
+        "`{0}` este folosit înainte să primească o valoare",
+    ),
+    (
+        "E0303",
+        "`{0}` is already declared in this scope",
+        "`{0}` este deja declarat în acest domeniu",
+    ),
+    (
+        "W0101",
+        "condition `{0}` is always {1}",
+        "condiția `{0}` este mereu {1}",
+    ),
+    (
+        "W0102",
+        "counter `{0}` is modified inside its `pentru` loop",
+        "contorul `{0}` este modificat în propria buclă `pentru`",
+    ),
+    (
+        "W0103",
+        "unreachable code after `returneaza`",
+        "cod inaccesibil după `returneaza`",
+    ),
+    ("W0104", "`{0}` has no effect", "`{0}` nu are niciun efect"),
+    (
+        "W0105",
+        "`{0}` is compared with itself",
+        "`{0}` este comparat cu el însuși",
+    ),
+    (
+        "W0201",
+        "`{0}` shadows an outer declaration",
+        "`{0}` ascunde o declarație exterioară",
+    ),
+    (
+        "W0202",
+        "`{0}` is given a value but never used",
+        "`{0}` primește o valoare, dar nu este folosit",
+    ),
+    ("error", "error", "eroare"),
+    ("warning", "warning", "avertisment"),
+    ("located", "{0} at {1}:{2}", "{0} la {1}:{2}"),
+    ("true", "true", "adevărat"),
+    ("false", "false", "fals"),
+    (
+        "called-at",
+        "in `{0}`, called at {1}",
+        "în `{0}`, apelată la {1}",
+    ),
+    (
+        "anonymous-function",
+        "anonymous function",
+        "funcție anonimă",
+    ),
+    (
+        "repeated",
+        "… repeated {0} more times",
+        "… repetat de încă {0} ori",
+    ),
+];
+
+/// The message for `code` in `lang`, with `args` filled in. A code the
+/// catalogue does not have comes out as itself followed by the arguments.
+pub fn message(code: &str, lang: Lang, args: &[&dyn fmt::Display]) -> String {
+    let Some(&(_, en, ro)) = CATALOGUE.iter().find(|(key, _, _)| *key == code) else {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        return match args.is_empty() {
+            true => code.to_string(),
+            false => format!("{}: {}", code, args.join(", ")),
+        };
+    };
+    let template = match lang {
+        Lang::En => en,
+        Lang::Ro => ro,
+    };
+
+    // One pass over the template, so that an argument containing `{1}` is
+    // left as it is.
+    let mut text = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        text.push_str(&rest[..open]);
+        rest = &rest[open..];
+        let arg = rest.find('}').and_then(|close| {
+            let index: usize = rest[1..close].parse().ok()?;
+            Some((args.get(index)?, close))
+        });
+        match arg {
+            Some((arg, close)) => {
+                text.push_str(&arg.to_string());
+                rest = &rest[close + 1..];
+            }
+            None => {
+                text.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    text.push_str(rest);
+    text
+}
+
+/// How a token is called in a diagnostic: keywords and symbols as they are
+/// written, the rest by what they are, with their text if it is known.
+pub fn token_name(token_type: &TokenType, lang: Lang) -> String {
+    let (en, ro) = match token_type {
+        TokenType::Illegal => ("an invalid token".into(), "un simbol nevalid".into()),
+        TokenType::Eof => ("end of input".into(), "sfârșitul programului".into()),
+        TokenType::Comment => ("a comment".into(), "un comentariu".into()),
+        TokenType::Ident(name) if name.is_empty() => {
+            ("an identifier".into(), "un identificator".into())
+        }
+        TokenType::Ident(name) => (
+            format!("identifier `{}`", name),
+            format!("identificatorul `{}`", name),
+        ),
+        TokenType::Int(digits) | TokenType::Float(digits) if digits.is_empty() => {
+            ("a number".into(), "un număr".into())
+        }
+        TokenType::Int(digits) | TokenType::Float(digits) => (
+            format!("number `{}`", digits),
+            format!("numărul `{}`", digits),
+        ),
+        TokenType::Str(string) => (
+            format!("string {:?}", string),
+            format!("șirul {:?}", string),
+        ),
+        _ => return format!("`{}`", spelling(token_type)),
+    };
+    match lang {
+        Lang::En => en,
+        Lang::Ro => ro,
+    }
+}
+
+/// How a keyword or symbol is written.
+fn spelling(token_type: &TokenType) -> &'static str {
+    match token_type {
+        TokenType::Illegal
+        | TokenType::Eof
+        | TokenType::Comment
+        | TokenType::Ident(_)
+        | TokenType::Int(_)
+        | TokenType::Float(_)
+        | TokenType::Str(_) => unreachable!("{:?} has no fixed spelling", token_type),
+        TokenType::Comma => ",",
+        TokenType::Semicolon => ";",
+        TokenType::Colon => ":",
+        TokenType::LParen => "(",
+        TokenType::RParen => ")",
+        TokenType::LBrace => "{",
+        TokenType::RBrace => "}",
+        TokenType::LBracket => "[",
+        TokenType::RBracket => "]",
+        TokenType::Function => "functie",
+        TokenType::Let => "let",
+        TokenType::True => "adevarat",
+        TokenType::False => "fals",
+        TokenType::If => "daca",
+        TokenType::Then => "atunci",
+        TokenType::Else => "altfel",
+        TokenType::Return => "returneaza",
+        TokenType::While => "cat timp",
+        TokenType::Do => "executa",
+        TokenType::For => "pentru",
+        TokenType::Repeat => "repeta",
+        TokenType::Until => "pana cand",
+        TokenType::Read => "citeste",
+        TokenType::Write => "scrie",
+        TokenType::Eq => "==",
+        TokenType::NotEq => "!=",
+        TokenType::And => "si",
+        TokenType::Or => "sau",
+        TokenType::Not => "nu",
+        TokenType::Assign => "=",
+        TokenType::LeftArrow => "←",
+        TokenType::PlusAssign => "+=",
+        TokenType::MinusAssign => "-=",
+        TokenType::AsteriskAssign => "*=",
+        TokenType::SlashAssign => "/=",
+        TokenType::Plus => "+",
+        TokenType::Minus => "-",
+        TokenType::Bang => "!",
+        TokenType::Asterisk => "*",
+        TokenType::Slash => "/",
+        TokenType::Percent => "%",
+        TokenType::Div => "div",
+        TokenType::Mod => "mod",
+        TokenType::Lt => "<",
+        TokenType::Lte => "<=",
+        TokenType::Gt => ">",
+        TokenType::Gte => ">=",
+    }
+}
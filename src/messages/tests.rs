@@ -0,0 +1,98 @@
+use crate::lexer::TokenType;
+
+use super::{message, token_name, Lang, CATALOGUE};
+
+#[test]
+fn translations_take_the_same_arguments() {
+    let placeholders = |text: &str| -> Vec<usize> {
+        (0..4)
+            .filter(|i| text.contains(&format!("{{{}}}", i)))
+            .collect()
+    };
+    for (code, en, ro) in CATALOGUE {
+        assert_eq!(placeholders(en), placeholders(ro), "{}", code);
+        assert!(
+            CATALOGUE.iter().filter(|(key, _, _)| key == code).count() == 1,
+            "{} is in the catalogue more than once",
+            code
+        );
+    }
+}
+
+#[test]
+fn codes_are_in_order() {
+    let codes: Vec<&str> = CATALOGUE
+        .iter()
+        .map(|(code, _, _)| *code)
+        .filter(|code| code.starts_with(|ch: char| ch.is_ascii_uppercase()))
+        .collect();
+    let mut sorted = codes.clone();
+    sorted.sort_by_key(|code| (code.starts_with('W'), *code));
+    assert_eq!(codes, sorted);
+}
+
+#[test]
+fn fills_in_arguments() {
+    assert_eq!(
+        message("E0214", Lang::En, &[&"f", &1, &2]),
+        "wrong number of arguments to `f`: got=1, want=2"
+    );
+    assert_eq!(
+        message("E0214", Lang::Ro, &[&"f", &1, &2]),
+        "număr greșit de argumente pentru `f`: primite=1, așteptate=2"
+    );
+}
+
+#[test]
+fn fills_in_arguments_once() {
+    assert_eq!(
+        message("E0203", Lang::En, &[&"{1}", &"+", &"{2}"]),
+        "type mismatch: {1} + {2}"
+    );
+    assert_eq!(
+        message("E0101", Lang::En, &[&"`{`"]),
+        "expected `{`, got {1} instead"
+    );
+}
+
+#[test]
+fn falls_back_to_the_code() {
+    assert_eq!(message("E9999", Lang::Ro, &[]), "E9999");
+    assert_eq!(message("E9999", Lang::En, &[&"a", &1]), "E9999: a, 1");
+}
+
+#[test]
+fn names_tokens() {
+    let tests = vec![
+        (TokenType::Assign, "`=`", "`=`"),
+        (TokenType::While, "`cat timp`", "`cat timp`"),
+        (TokenType::Eof, "end of input", "sfârșitul programului"),
+        (
+            TokenType::Ident(String::new()),
+            "an identifier",
+            "un identificator",
+        ),
+        (
+            TokenType::Ident("x".to_string()),
+            "identifier `x`",
+            "identificatorul `x`",
+        ),
+        (TokenType::Int("5".to_string()), "number `5`", "numărul `5`"),
+        (
+            TokenType::Str("a".to_string()),
+            "string \"a\"",
+            "șirul \"a\"",
+        ),
+    ];
+    for (token_type, en, ro) in tests {
+        assert_eq!(token_name(&token_type, Lang::En), en);
+        assert_eq!(token_name(&token_type, Lang::Ro), ro);
+    }
+}
+
+#[test]
+fn parses_languages() {
+    assert_eq!("ro".parse(), Ok(Lang::Ro));
+    assert_eq!("en".parse(), Ok(Lang::En));
+    assert!("fr".parse::<Lang>().is_err());
+}
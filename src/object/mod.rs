@@ -0,0 +1,493 @@
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::rc::Rc;
+
+use crate::ast::BlockStatement;
+use crate::bigint::BigInt;
+use crate::code::Instructions;
+use crate::lexer::Span;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Object {
+    Integer(i64),
+    /// An integer outside the range of `i64`. Integers that fit are always
+    /// [`Object::Integer`], so each value has one representation.
+    BigInteger(BigInt),
+    Real(f64),
+    Boolean(bool),
+    Str(String),
+    Array(Rc<RefCell<Vec<Object>>>),
+    Map(Rc<RefCell<BTreeMap<HashKey, Object>>>),
+    Builtin(Rc<str>),
+    Function(Rc<Function>),
+    CompiledFunction(Rc<CompiledFunction>),
+    Closure(Rc<Closure>),
+    ReturnValue(Box<Object>),
+    Null,
+}
+
+/// The runtime values usable as map keys. Maps are ordered by key so
+/// iterating one is deterministic, with integers in numeric order.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum HashKey {
+    Integer(i64),
+    BigInteger(BigInt),
+    Boolean(bool),
+    Str(String),
+}
+
+/// A function value created by the tree-walking evaluator. It keeps the
+/// environment it was defined in alive so it can close over it.
+pub struct Function {
+    pub name: Option<String>,
+    pub parameters: Vec<String>,
+    pub body: Rc<BlockStatement>,
+    pub env: Rc<RefCell<Environment>>,
+}
+
+/// The bytecode of one function body. `positions` maps instruction offsets
+/// to the source span they were compiled from, in increasing offset order.
+/// `locals` names the local slots, parameters first.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CompiledFunction {
+    pub name: Option<String>,
+    pub instructions: Instructions,
+    pub locals: Vec<String>,
+    pub num_parameters: usize,
+    pub positions: Vec<(usize, Span)>,
+}
+
+/// A compiled function together with the values of the variables it
+/// captured when it was created.
+#[derive(Debug)]
+pub struct Closure {
+    pub function: Rc<CompiledFunction>,
+    pub free: Vec<Object>,
+}
+
+#[derive(Debug, Default)]
+pub struct Environment {
+    store: HashMap<String, Object>,
+    outer: Option<Rc<RefCell<Environment>>>,
+}
+
+impl Object {
+    pub fn array(elements: Vec<Object>) -> Self {
+        Object::Array(Rc::new(RefCell::new(elements)))
+    }
+
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Object::Integer(_) | Object::BigInteger(_) => "INTEGER",
+            Object::Real(_) => "REAL",
+            Object::Boolean(_) => "BOOLEAN",
+            Object::Str(_) => "STRING",
+            Object::Array(_) => "ARRAY",
+            Object::Map(_) => "MAP",
+            Object::Builtin(_) => "BUILTIN",
+            Object::Function(_) | Object::Closure(_) => "FUNCTION",
+            Object::CompiledFunction(_) => "COMPILED_FUNCTION",
+            Object::ReturnValue(_) => "RETURN_VALUE",
+            Object::Null => "NULL",
+        }
+    }
+
+    pub fn map(pairs: BTreeMap<HashKey, Object>) -> Self {
+        Object::Map(Rc::new(RefCell::new(pairs)))
+    }
+
+    /// The key under which this value is stored in a map, or `None` if it
+    /// cannot be used as one.
+    pub fn hash_key(&self) -> Option<HashKey> {
+        match self {
+            Object::Integer(value) => Some(HashKey::Integer(*value)),
+            Object::BigInteger(value) => Some(HashKey::BigInteger(value.clone())),
+            Object::Boolean(value) => Some(HashKey::Boolean(*value)),
+            Object::Str(value) => Some(HashKey::Str(value.clone())),
+            _ => None,
+        }
+    }
+
+    /// The numeric value of an integer or real, widened to `f64`.
+    pub fn as_real(&self) -> Option<f64> {
+        match self {
+            Object::Integer(value) => Some(*value as f64),
+            Object::BigInteger(value) => Some(value.to_f64()),
+            Object::Real(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// How many bits the magnitude of an integer takes up, or `None` if
+    /// this is not one.
+    pub fn bit_len(&self) -> Option<usize> {
+        match self {
+            Object::Integer(value) => Some(64 - value.unsigned_abs().leading_zeros() as usize),
+            Object::BigInteger(value) => Some(value.bit_len()),
+            _ => None,
+        }
+    }
+
+    pub fn is_truthy(&self) -> bool {
+        !matches!(self, Object::Boolean(false) | Object::Null)
+    }
+}
+
+impl fmt::Display for Object {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Object::Integer(value) => write!(f, "{}", value),
+            Object::BigInteger(value) => write!(f, "{}", value),
+            Object::Real(value) => write!(f, "{}", value),
+            Object::Boolean(value) => write!(f, "{}", value),
+            Object::Str(value) => write!(f, "{}", value),
+            Object::Array(elements) => {
+                let elements: Vec<String> =
+                    elements.borrow().iter().map(|e| e.to_string()).collect();
+                write!(f, "[{}]", elements.join(", "))
+            }
+            Object::Map(pairs) => {
+                let pairs: Vec<String> = pairs
+                    .borrow()
+                    .iter()
+                    .map(|(key, value)| format!("{}: {}", Object::from(key.clone()), value))
+                    .collect();
+                write!(f, "{{{}}}", pairs.join(", "))
+            }
+            Object::Builtin(name) => write!(f, "builtin function {}", name),
+            Object::Function(function) => write_function(f, &function.name),
+            Object::CompiledFunction(function) => write_function(f, &function.name),
+            Object::Closure(closure) => write_function(f, &closure.function.name),
+            Object::ReturnValue(value) => write!(f, "{}", value),
+            Object::Null => write!(f, "null"),
+        }
+    }
+}
+
+fn write_function(f: &mut fmt::Formatter<'_>, name: &Option<String>) -> fmt::Result {
+    match name {
+        Some(name) => write!(f, "function {}", name),
+        None => write!(f, "function"),
+    }
+}
+
+// A function's environment usually contains the function itself, so the
+// derived implementations would recurse forever.
+impl fmt::Debug for Function {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Function")
+            .field("name", &self.name)
+            .field("parameters", &self.parameters)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for Function {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl PartialEq for Closure {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl HashKey {
+    /// Where keys of this kind sort relative to keys of other kinds.
+    fn rank(&self) -> u8 {
+        match self {
+            HashKey::Integer(_) | HashKey::BigInteger(_) => 0,
+            HashKey::Boolean(_) => 1,
+            HashKey::Str(_) => 2,
+        }
+    }
+}
+
+impl Ord for HashKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (HashKey::Integer(a), HashKey::Integer(b)) => a.cmp(b),
+            (HashKey::BigInteger(a), HashKey::BigInteger(b)) => a.cmp(b),
+            // A big integer is outside the range of every small one.
+            (HashKey::Integer(_), HashKey::BigInteger(big)) => match big.is_negative() {
+                true => Ordering::Greater,
+                false => Ordering::Less,
+            },
+            (HashKey::BigInteger(big), HashKey::Integer(_)) => match big.is_negative() {
+                true => Ordering::Less,
+                false => Ordering::Greater,
+            },
+            (HashKey::Boolean(a), HashKey::Boolean(b)) => a.cmp(b),
+            (HashKey::Str(a), HashKey::Str(b)) => a.cmp(b),
+            (a, b) => a.rank().cmp(&b.rank()),
+        }
+    }
+}
+
+impl PartialOrd for HashKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl From<HashKey> for Object {
+    fn from(key: HashKey) -> Self {
+        match key {
+            HashKey::Integer(value) => Object::Integer(value),
+            HashKey::BigInteger(value) => Object::BigInteger(value),
+            HashKey::Boolean(value) => Object::Boolean(value),
+            HashKey::Str(value) => Object::Str(value),
+        }
+    }
+}
+
+impl From<i64> for Object {
+    fn from(value: i64) -> Self {
+        Object::Integer(value)
+    }
+}
+
+impl From<i32> for Object {
+    fn from(value: i32) -> Self {
+        Object::Integer(value.into())
+    }
+}
+
+/// An [`Object::Integer`] if `value` fits in one.
+impl From<BigInt> for Object {
+    fn from(value: BigInt) -> Self {
+        match value.to_i64() {
+            Some(value) => Object::Integer(value),
+            None => Object::BigInteger(value),
+        }
+    }
+}
+
+impl From<&BigInt> for Object {
+    fn from(value: &BigInt) -> Self {
+        match value.to_i64() {
+            Some(value) => Object::Integer(value),
+            None => Object::BigInteger(value.clone()),
+        }
+    }
+}
+
+impl From<f64> for Object {
+    fn from(value: f64) -> Self {
+        Object::Real(value)
+    }
+}
+
+impl From<bool> for Object {
+    fn from(value: bool) -> Self {
+        Object::Boolean(value)
+    }
+}
+
+impl From<String> for Object {
+    fn from(value: String) -> Self {
+        Object::Str(value)
+    }
+}
+
+impl From<&str> for Object {
+    fn from(value: &str) -> Self {
+        Object::Str(value.to_string())
+    }
+}
+
+impl From<()> for Object {
+    fn from(_: ()) -> Self {
+        Object::Null
+    }
+}
+
+impl<T: Into<Object>> From<Vec<T>> for Object {
+    fn from(elements: Vec<T>) -> Self {
+        Object::array(elements.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<T: Into<Object>> From<Option<T>> for Object {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Object::Null, Into::into)
+    }
+}
+
+/// Why a value cannot become the Rust value it was asked for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConversionError {
+    /// The value does not have the type asked for.
+    WrongType {
+        expected: &'static str,
+        got: &'static str,
+    },
+    /// An integer too large for the Rust integer type.
+    Overflow(BigInt),
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConversionError::WrongType { expected, got } => {
+                write!(f, "expected {}, got {}", expected, got)
+            }
+            ConversionError::Overflow(value) => {
+                write!(f, "integer overflow: {} does not fit in 64 bits", value)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConversionError {}
+
+impl TryFrom<Object> for i64 {
+    type Error = ConversionError;
+
+    fn try_from(value: Object) -> Result<Self, Self::Error> {
+        match value {
+            Object::Integer(value) => Ok(value),
+            Object::BigInteger(value) => Err(ConversionError::Overflow(value)),
+            value => Err(ConversionError::WrongType {
+                expected: "INTEGER",
+                got: value.type_name(),
+            }),
+        }
+    }
+}
+
+impl TryFrom<Object> for BigInt {
+    type Error = ConversionError;
+
+    fn try_from(value: Object) -> Result<Self, Self::Error> {
+        match value {
+            Object::Integer(value) => Ok(BigInt::from(value)),
+            Object::BigInteger(value) => Ok(value),
+            value => Err(ConversionError::WrongType {
+                expected: "INTEGER",
+                got: value.type_name(),
+            }),
+        }
+    }
+}
+
+/// Integers widen to reals, as in arithmetic.
+impl TryFrom<Object> for f64 {
+    type Error = ConversionError;
+
+    fn try_from(value: Object) -> Result<Self, Self::Error> {
+        value.as_real().ok_or(ConversionError::WrongType {
+            expected: "REAL",
+            got: value.type_name(),
+        })
+    }
+}
+
+impl TryFrom<Object> for bool {
+    type Error = ConversionError;
+
+    fn try_from(value: Object) -> Result<Self, Self::Error> {
+        match value {
+            Object::Boolean(value) => Ok(value),
+            value => Err(ConversionError::WrongType {
+                expected: "BOOLEAN",
+                got: value.type_name(),
+            }),
+        }
+    }
+}
+
+impl TryFrom<Object> for String {
+    type Error = ConversionError;
+
+    fn try_from(value: Object) -> Result<Self, Self::Error> {
+        match value {
+            Object::Str(value) => Ok(value),
+            value => Err(ConversionError::WrongType {
+                expected: "STRING",
+                got: value.type_name(),
+            }),
+        }
+    }
+}
+
+impl<T: TryFrom<Object, Error = ConversionError>> TryFrom<Object> for Vec<T> {
+    type Error = ConversionError;
+
+    fn try_from(value: Object) -> Result<Self, Self::Error> {
+        match value {
+            Object::Array(elements) => elements.borrow().iter().cloned().map(T::try_from).collect(),
+            value => Err(ConversionError::WrongType {
+                expected: "ARRAY",
+                got: value.type_name(),
+            }),
+        }
+    }
+}
+
+impl Environment {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn new_enclosed(outer: Rc<RefCell<Environment>>) -> Self {
+        Self {
+            store: HashMap::new(),
+            outer: Some(outer),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<Object> {
+        match self.store.get(name) {
+            Some(value) => Some(value.clone()),
+            None => self.outer.as_ref()?.borrow().get(name),
+        }
+    }
+
+    /// Looks `name` up only in the scope `depth` levels out, where the
+    /// resolver found it bound.
+    pub fn get_at(&self, depth: usize, name: &str) -> Option<Object> {
+        if depth == 0 {
+            return self.store.get(name).cloned();
+        }
+        self.outer.as_ref()?.borrow().get_at(depth - 1, name)
+    }
+
+    /// The enclosing scope, or `None` for the global one.
+    pub fn outer(&self) -> Option<Rc<RefCell<Environment>>> {
+        self.outer.clone()
+    }
+
+    /// The names bound in this scope itself, in alphabetical order.
+    pub fn bindings(&self) -> Vec<(String, Object)> {
+        let mut bindings: Vec<_> = self
+            .store
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        bindings.sort_by(|a, b| a.0.cmp(&b.0));
+        bindings
+    }
+
+    /// Binds `name` in this scope, shadowing any outer binding.
+    pub fn set(&mut self, name: String, value: Object) {
+        self.store.insert(name, value);
+    }
+
+    /// Rebinds `name` in the nearest scope that declares it. Returns
+    /// `false` if no scope does.
+    pub fn assign(&mut self, name: &str, value: Object) -> bool {
+        if let Some(slot) = self.store.get_mut(name) {
+            *slot = value;
+            return true;
+        }
+        match &self.outer {
+            Some(outer) => outer.borrow_mut().assign(name, value),
+            None => false,
+        }
+    }
+}
@@ -1,38 +1,142 @@
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
+use std::fmt;
+use std::mem::{self, Discriminant};
 use std::rc::Rc;
 
 use crate::ast::{
-    Expression, ExpressionStatement, Identifier, IntegralLiteral, LetStatement, Program,
-    ReturnStatement, Statement,
+    ArrayLiteral, AssignStatement, BlockStatement, BooleanLiteral, CallExpression, Expression,
+    ExpressionStatement, ForStatement, FunctionLiteral, Identifier, IfStatement,
+    IndexAssignStatement, IndexExpression, InfixExpression, IntegerPartExpression, IntegralLiteral,
+    LetStatement, MapLiteral, PrefixExpression, Program, ReadStatement, RealLiteral,
+    RepeatStatement, ReturnStatement, Statement, StringLiteral, WhileStatement, WriteStatement,
 };
-use crate::lexer::{Lexer, Token, TokenType};
+use crate::bigint::BigInt;
+use crate::lexer::{Lexer, Span, Token, TokenType};
+use crate::messages::{self, token_name, Lang};
 
+#[cfg(test)]
 mod tests;
 
+type PrefixParseFn = fn(&mut Parser) -> Option<Rc<RefCell<dyn Expression>>>;
+type InfixParseFn =
+    fn(&mut Parser, Rc<RefCell<dyn Expression>>) -> Option<Rc<RefCell<dyn Expression>>>;
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
 enum Precedence {
     Lowest,
+    LogicalOr,
+    LogicalAnd,
     Equals,
     LessGreater,
     Sum,
     Product,
     Prefix,
     Call,
+    Index,
+}
+
+impl Precedence {
+    fn of(token_type: &TokenType) -> Self {
+        match token_type {
+            TokenType::Or => Precedence::LogicalOr,
+            TokenType::And => Precedence::LogicalAnd,
+            TokenType::Eq | TokenType::NotEq => Precedence::Equals,
+            TokenType::Lt | TokenType::Lte | TokenType::Gt | TokenType::Gte => {
+                Precedence::LessGreater
+            }
+            TokenType::Plus | TokenType::Minus => Precedence::Sum,
+            TokenType::Asterisk
+            | TokenType::Slash
+            | TokenType::Percent
+            | TokenType::Div
+            | TokenType::Mod => Precedence::Product,
+            TokenType::LParen => Precedence::Call,
+            TokenType::LBracket => Precedence::Index,
+            _ => Precedence::Lowest,
+        }
+    }
+}
+
+/// A syntax error with the token it was found at.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseErrorKind {
+    UnexpectedToken {
+        expected: TokenType,
+        got: TokenType,
+    },
+    /// A token no expression can start with.
+    NoPrefixParseFn(TokenType),
+    InvalidAssignmentTarget(String),
+    InvalidReadTarget(String),
+}
+
+impl ParseError {
+    /// The code the message is kept under in the [`messages`] catalogue.
+    pub fn code(&self) -> &'static str {
+        match self.kind {
+            ParseErrorKind::UnexpectedToken { .. } => "E0101",
+            ParseErrorKind::NoPrefixParseFn(_) => "E0102",
+            ParseErrorKind::InvalidAssignmentTarget(_) => "E0103",
+            ParseErrorKind::InvalidReadTarget(_) => "E0104",
+        }
+    }
+
+    pub fn message(&self, lang: Lang) -> String {
+        let code = self.code();
+        match &self.kind {
+            ParseErrorKind::UnexpectedToken { expected, got } => messages::message(
+                code,
+                lang,
+                &[&token_name(expected, lang), &token_name(got, lang)],
+            ),
+            ParseErrorKind::NoPrefixParseFn(token_type) => {
+                messages::message(code, lang, &[&token_name(token_type, lang)])
+            }
+            ParseErrorKind::InvalidAssignmentTarget(target)
+            | ParseErrorKind::InvalidReadTarget(target) => messages::message(code, lang, &[target]),
+        }
+    }
+
+    /// The error with the line of `source` it is on and a caret under the
+    /// token.
+    pub fn render(&self, source: &str, lang: Lang) -> String {
+        let label = format!(
+            "{}[{}]: {}",
+            messages::message("error", lang, &[]),
+            self.code(),
+            self.message(lang)
+        );
+        self.span.annotate(source, &label)
+    }
 }
 
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message(Lang::En))
+    }
+}
+
+impl std::error::Error for ParseError {}
+
 #[derive(Debug)]
-struct Parser {
+pub struct Parser {
     lexer: Rc<RefCell<Lexer>>,
-    errors: Vec<String>,
+    errors: Vec<ParseError>,
     current_token: Option<Token>,
     peek_token: Option<Token>,
-    prefix_parse_fns: HashMap<TokenType, fn(&Parser) -> Option<Rc<RefCell<dyn Expression>>>>,
-    infix_parse_fns:
-        HashMap<TokenType, fn(Rc<RefCell<dyn Expression>>) -> Option<Rc<RefCell<dyn Expression>>>>,
+    prefix_parse_fns: HashMap<Discriminant<TokenType>, PrefixParseFn>,
+    infix_parse_fns: HashMap<Discriminant<TokenType>, InfixParseFn>,
 }
 
 impl Parser {
-    fn new(lexer: Lexer) -> Self {
+    pub fn new(lexer: Lexer) -> Self {
         let mut parser = Self {
             lexer: Rc::new(RefCell::new(lexer)),
             errors: Vec::new(),
@@ -50,21 +154,68 @@ impl Parser {
             TokenType::Int(String::new()),
             Parser::parse_integral_literal,
         );
+        parser.register_prefix(TokenType::Float(String::new()), Parser::parse_real_literal);
+        parser.register_prefix(TokenType::Str(String::new()), Parser::parse_string_literal);
+        parser.register_prefix(TokenType::True, Parser::parse_boolean_literal);
+        parser.register_prefix(TokenType::False, Parser::parse_boolean_literal);
+        parser.register_prefix(TokenType::Bang, Parser::parse_prefix_expression);
+        parser.register_prefix(TokenType::Not, Parser::parse_prefix_expression);
+        parser.register_prefix(TokenType::Minus, Parser::parse_prefix_expression);
+        parser.register_prefix(TokenType::LParen, Parser::parse_grouped_expression);
+        parser.register_prefix(TokenType::LBracket, Parser::parse_bracket_expression);
+        parser.register_prefix(TokenType::LBrace, Parser::parse_map_literal);
+        parser.register_prefix(TokenType::Function, Parser::parse_function_literal);
+
+        for token_type in [
+            TokenType::Plus,
+            TokenType::Minus,
+            TokenType::Asterisk,
+            TokenType::Slash,
+            TokenType::Percent,
+            TokenType::Div,
+            TokenType::Mod,
+            TokenType::Eq,
+            TokenType::NotEq,
+            TokenType::Lt,
+            TokenType::Lte,
+            TokenType::Gt,
+            TokenType::Gte,
+            TokenType::And,
+            TokenType::Or,
+        ] {
+            parser.register_infix(token_type, Parser::parse_infix_expression);
+        }
+        parser.register_infix(TokenType::LParen, Parser::parse_call_expression);
+        parser.register_infix(TokenType::LBracket, Parser::parse_index_expression);
 
         parser
     }
 
-    fn errors(&self) -> Vec<String> {
-        self.errors.clone()
+    pub fn errors(&self) -> Vec<String> {
+        self.errors.iter().map(ParseError::to_string).collect()
+    }
+
+    pub fn parse_errors(&self) -> &[ParseError] {
+        &self.errors
+    }
+
+    fn error(&mut self, span: Span, kind: ParseErrorKind) {
+        self.errors.push(ParseError { kind, span });
     }
 
     fn peek_error(&mut self, token_type: TokenType) {
-        let msg = format!(
-            "expected next token to be {:?}, got {:?} instead",
-            token_type,
-            self.peek_token.as_ref().unwrap().token_type
-        );
-        self.errors.push(msg);
+        let peek = self.peek_token.as_ref().unwrap();
+        let kind = ParseErrorKind::UnexpectedToken {
+            expected: token_type,
+            got: peek.token_type.clone(),
+        };
+        self.error(peek.span, kind);
+    }
+
+    fn no_prefix_parse_fn_error(&mut self) {
+        let current = self.current_token.as_ref().unwrap();
+        let kind = ParseErrorKind::NoPrefixParseFn(current.token_type.clone());
+        self.error(current.span, kind);
     }
 
     fn next_token(&mut self) {
@@ -72,7 +223,7 @@ impl Parser {
         self.peek_token = Some(self.lexer.borrow_mut().next_token());
     }
 
-    fn parse_program(&mut self) -> Option<Program> {
+    pub fn parse_program(&mut self) -> Option<Program> {
         let mut program = Program {
             statements: Vec::new(),
         };
@@ -89,8 +240,20 @@ impl Parser {
 
     fn parse_statement(&mut self) -> Option<Rc<RefCell<dyn Statement>>> {
         match self.current_token.as_ref().unwrap().token_type {
+            // An empty statement, e.g. after the closing brace of a loop.
+            TokenType::Semicolon => None,
             TokenType::Let => self.parse_let_statement(),
             TokenType::Return => self.parse_return_statement(),
+            TokenType::If => self.parse_if_statement(),
+            TokenType::While => self.parse_while_statement(),
+            TokenType::For => self.parse_for_statement(),
+            TokenType::Repeat => self.parse_repeat_statement(),
+            TokenType::Read => self.parse_read_statement(),
+            TokenType::Write => self.parse_write_statement(),
+            TokenType::Function if matches!(self.peek_token_type(), TokenType::Ident(_)) => {
+                self.parse_function_declaration()
+            }
+            TokenType::Ident(_) if self.peek_token_is_assignment() => self.parse_assign_statement(),
             _ => self.parse_expression_statement(),
         }
     }
@@ -109,28 +272,378 @@ impl Parser {
         statement.name = Some(Identifier {
             token: self.current_token.as_ref().unwrap().clone(),
             value: self.current_token.as_ref().unwrap().literal.clone(),
+            depth: Cell::default(),
         });
 
         if !self.expect_peek(TokenType::Assign) {
             return None;
         }
 
-        while !self.current_token_is(TokenType::Semicolon) {
+        self.next_token();
+        statement.value = self.parse_expression(Precedence::Lowest);
+
+        if self.peek_token_is(TokenType::Semicolon) {
+            self.next_token();
+        }
+
+        Some(Rc::new(RefCell::new(statement)))
+    }
+
+    fn parse_assign_statement(&mut self) -> Option<Rc<RefCell<dyn Statement>>> {
+        let name = Identifier {
+            token: self.current_token.as_ref().unwrap().clone(),
+            value: self.current_token.as_ref().unwrap().literal.clone(),
+            depth: Cell::default(),
+        };
+
+        self.next_token();
+        let token = self.current_token.as_ref().unwrap().clone();
+
+        self.next_token();
+        let statement = AssignStatement {
+            token,
+            name,
+            value: self.parse_expression(Precedence::Lowest),
+        };
+
+        if self.peek_token_is(TokenType::Semicolon) {
             self.next_token();
         }
 
         Some(Rc::new(RefCell::new(statement)))
     }
 
+    fn parse_index_assign_statement(
+        &mut self,
+        target: Rc<RefCell<dyn Expression>>,
+    ) -> Option<Rc<RefCell<dyn Statement>>> {
+        self.next_token();
+        let token = self.current_token.as_ref().unwrap().clone();
+
+        self.next_token();
+        let value = self.parse_expression(Precedence::Lowest);
+
+        if self.peek_token_is(TokenType::Semicolon) {
+            self.next_token();
+        }
+
+        let is_index = target.as_ref().borrow().as_any().is::<IndexExpression>();
+        if !is_index {
+            let kind = ParseErrorKind::InvalidAssignmentTarget(target.as_ref().borrow().string());
+            self.error(target.as_ref().borrow().span(), kind);
+            return None;
+        }
+
+        Some(Rc::new(RefCell::new(IndexAssignStatement {
+            token,
+            target,
+            value,
+        })))
+    }
+
+    fn parse_block_statement(&mut self) -> Option<BlockStatement> {
+        let mut block = BlockStatement {
+            token: self.current_token.as_ref().unwrap().clone(),
+            statements: Vec::new(),
+        };
+
+        self.next_token();
+
+        while !self.current_token_is(TokenType::RBrace) {
+            if self.current_token_is(TokenType::Eof) {
+                let span = self.current_token.as_ref().unwrap().span;
+                let kind = ParseErrorKind::UnexpectedToken {
+                    expected: TokenType::RBrace,
+                    got: TokenType::Eof,
+                };
+                self.error(span, kind);
+                return None;
+            }
+            if let Some(statement) = self.parse_statement() {
+                block.statements.push(statement);
+            }
+            self.next_token();
+        }
+
+        Some(block)
+    }
+
+    /// Parses the body of a compound statement, skipping the optional
+    /// `keyword` (`atunci`, `executa`) that may introduce it.
+    fn parse_body(&mut self, keyword: TokenType) -> Option<BlockStatement> {
+        if self.peek_token_is(keyword) {
+            self.next_token();
+        }
+
+        if !self.expect_peek(TokenType::LBrace) {
+            return None;
+        }
+
+        self.parse_block_statement()
+    }
+
+    fn parse_if_statement(&mut self) -> Option<Rc<RefCell<dyn Statement>>> {
+        Some(Rc::new(RefCell::new(self.parse_if()?)))
+    }
+
+    fn parse_if(&mut self) -> Option<IfStatement> {
+        let token = self.current_token.as_ref().unwrap().clone();
+
+        self.next_token();
+        let condition = self.parse_expression(Precedence::Lowest);
+        let consequence = self.parse_body(TokenType::Then)?;
+
+        let mut alternative = None;
+        if self.peek_token_is(TokenType::Else) {
+            self.next_token();
+
+            if self.peek_token_is(TokenType::If) {
+                self.next_token();
+                let block_token = self.current_token.as_ref().unwrap().clone();
+                let nested: Rc<RefCell<dyn Statement>> = Rc::new(RefCell::new(self.parse_if()?));
+                alternative = Some(BlockStatement {
+                    token: block_token,
+                    statements: vec![nested],
+                });
+            } else {
+                if !self.expect_peek(TokenType::LBrace) {
+                    return None;
+                }
+                alternative = Some(self.parse_block_statement()?);
+            }
+        }
+
+        Some(IfStatement {
+            token,
+            condition,
+            consequence,
+            alternative,
+        })
+    }
+
+    fn parse_while_statement(&mut self) -> Option<Rc<RefCell<dyn Statement>>> {
+        let token = self.current_token.as_ref().unwrap().clone();
+
+        self.next_token();
+        let condition = self.parse_expression(Precedence::Lowest);
+        let body = self.parse_body(TokenType::Do)?;
+
+        Some(Rc::new(RefCell::new(WhileStatement {
+            token,
+            condition,
+            body,
+        })))
+    }
+
+    fn parse_for_statement(&mut self) -> Option<Rc<RefCell<dyn Statement>>> {
+        let token = self.current_token.as_ref().unwrap().clone();
+
+        if !self.expect_peek(TokenType::Ident(String::new())) {
+            return None;
+        }
+        let counter = Identifier {
+            token: self.current_token.as_ref().unwrap().clone(),
+            value: self.current_token.as_ref().unwrap().literal.clone(),
+            depth: Cell::default(),
+        };
+
+        if self.peek_token_is(TokenType::Assign) || self.peek_token_is(TokenType::LeftArrow) {
+            self.next_token();
+        } else {
+            self.peek_error(TokenType::LeftArrow);
+            return None;
+        }
+
+        self.next_token();
+        let start = self.parse_expression(Precedence::Lowest);
+
+        if !self.expect_peek(TokenType::Comma) {
+            return None;
+        }
+        self.next_token();
+        let end = self.parse_expression(Precedence::Lowest);
+
+        let mut step = None;
+        if self.peek_token_is(TokenType::Comma) {
+            self.next_token();
+            self.next_token();
+            step = self.parse_expression(Precedence::Lowest);
+        }
+
+        let body = self.parse_body(TokenType::Do)?;
+
+        Some(Rc::new(RefCell::new(ForStatement {
+            token,
+            counter,
+            start,
+            end,
+            step,
+            body,
+        })))
+    }
+
+    fn parse_repeat_statement(&mut self) -> Option<Rc<RefCell<dyn Statement>>> {
+        let token = self.current_token.as_ref().unwrap().clone();
+
+        if !self.expect_peek(TokenType::LBrace) {
+            return None;
+        }
+        let body = self.parse_block_statement()?;
+
+        if !self.expect_peek(TokenType::Until) {
+            return None;
+        }
+        self.next_token();
+        let condition = self.parse_expression(Precedence::Lowest);
+
+        if self.peek_token_is(TokenType::Semicolon) {
+            self.next_token();
+        }
+
+        Some(Rc::new(RefCell::new(RepeatStatement {
+            token,
+            body,
+            condition,
+        })))
+    }
+
+    fn parse_read_statement(&mut self) -> Option<Rc<RefCell<dyn Statement>>> {
+        let token = self.current_token.as_ref().unwrap().clone();
+        let targets = self.parse_statement_arguments()?;
+
+        for target in &targets {
+            let target = target.as_ref().borrow();
+            let any = target.as_any();
+            if !any.is::<Identifier>() && !any.is::<IndexExpression>() {
+                let kind = ParseErrorKind::InvalidReadTarget(target.string());
+                self.error(target.span(), kind);
+                return None;
+            }
+        }
+
+        Some(Rc::new(RefCell::new(ReadStatement { token, targets })))
+    }
+
+    fn parse_write_statement(&mut self) -> Option<Rc<RefCell<dyn Statement>>> {
+        let token = self.current_token.as_ref().unwrap().clone();
+        let arguments = self.parse_statement_arguments()?;
+
+        Some(Rc::new(RefCell::new(WriteStatement { token, arguments })))
+    }
+
+    /// The comma-separated expressions following `citeste` or `scrie`.
+    fn parse_statement_arguments(&mut self) -> Option<Vec<Rc<RefCell<dyn Expression>>>> {
+        self.next_token();
+        let mut arguments = vec![self.parse_expression(Precedence::Lowest)?];
+
+        while self.peek_token_is(TokenType::Comma) {
+            self.next_token();
+            self.next_token();
+            arguments.push(self.parse_expression(Precedence::Lowest)?);
+        }
+
+        if self.peek_token_is(TokenType::Semicolon) {
+            self.next_token();
+        }
+
+        Some(arguments)
+    }
+
+    /// `functie f(a) { ... }` binds `f` like `let f = functie(a) { ... }`.
+    fn parse_function_declaration(&mut self) -> Option<Rc<RefCell<dyn Statement>>> {
+        let token = self.current_token.as_ref().unwrap().clone();
+
+        self.next_token();
+        let name = Identifier {
+            token: self.current_token.as_ref().unwrap().clone(),
+            value: self.current_token.as_ref().unwrap().literal.clone(),
+            depth: Cell::default(),
+        };
+
+        let function = self.parse_function(token.clone(), Some(name.value.clone()))?;
+
+        Some(Rc::new(RefCell::new(LetStatement {
+            token,
+            name: Some(name),
+            value: Some(Rc::new(RefCell::new(function))),
+        })))
+    }
+
+    fn parse_function_literal(&mut self) -> Option<Rc<RefCell<dyn Expression>>> {
+        let token = self.current_token.as_ref().unwrap().clone();
+        Some(Rc::new(RefCell::new(self.parse_function(token, None)?)))
+    }
+
+    fn parse_function(&mut self, token: Token, name: Option<String>) -> Option<FunctionLiteral> {
+        if !self.expect_peek(TokenType::LParen) {
+            return None;
+        }
+
+        let parameters = self.parse_function_parameters()?;
+
+        if !self.expect_peek(TokenType::LBrace) {
+            return None;
+        }
+
+        let body = self.parse_block_statement()?;
+
+        Some(FunctionLiteral {
+            token,
+            name,
+            parameters,
+            body: Rc::new(body),
+        })
+    }
+
+    fn parse_function_parameters(&mut self) -> Option<Vec<Identifier>> {
+        let mut parameters = Vec::new();
+
+        if self.peek_token_is(TokenType::RParen) {
+            self.next_token();
+            return Some(parameters);
+        }
+
+        loop {
+            if !self.expect_peek(TokenType::Ident(String::new())) {
+                return None;
+            }
+            parameters.push(Identifier {
+                token: self.current_token.as_ref().unwrap().clone(),
+                value: self.current_token.as_ref().unwrap().literal.clone(),
+                depth: Cell::default(),
+            });
+
+            if !self.peek_token_is(TokenType::Comma) {
+                break;
+            }
+            self.next_token();
+        }
+
+        if !self.expect_peek(TokenType::RParen) {
+            return None;
+        }
+
+        Some(parameters)
+    }
+
     fn parse_return_statement(&mut self) -> Option<Rc<RefCell<dyn Statement>>> {
         let mut statement = ReturnStatement {
             token: self.current_token.as_ref().unwrap().clone(),
             return_value: None,
         };
 
+        if self.peek_token_is(TokenType::RBrace) {
+            return Some(Rc::new(RefCell::new(statement)));
+        }
+        if self.peek_token_is(TokenType::Semicolon) {
+            self.next_token();
+            return Some(Rc::new(RefCell::new(statement)));
+        }
+
         self.next_token();
+        statement.return_value = self.parse_expression(Precedence::Lowest);
 
-        while !self.current_token_is(TokenType::Semicolon) {
+        if self.peek_token_is(TokenType::Semicolon) {
             self.next_token();
         }
 
@@ -143,6 +656,10 @@ impl Parser {
             expression: self.parse_expression(Precedence::Lowest),
         };
 
+        if self.peek_token_is_assignment() {
+            return self.parse_index_assign_statement(statement.expression?);
+        }
+
         if self.peek_token_is(TokenType::Semicolon) {
             self.next_token();
         }
@@ -150,35 +667,258 @@ impl Parser {
         Some(Rc::new(RefCell::new(statement)))
     }
 
-    fn parse_expression(&self, precedence: Precedence) -> Option<Rc<RefCell<dyn Expression>>> {
+    fn parse_expression(&mut self, precedence: Precedence) -> Option<Rc<RefCell<dyn Expression>>> {
         let prefix = self
             .prefix_parse_fns
-            .get(&self.current_token.as_ref().unwrap().token_type);
+            .get(&mem::discriminant(
+                &self.current_token.as_ref().unwrap().token_type,
+            ))
+            .copied();
 
-        if prefix.is_none() {
+        let Some(prefix) = prefix else {
+            self.no_prefix_parse_fn_error();
             return None;
-        }
+        };
+
+        let mut left_expression = prefix(self)?;
+
+        while !self.peek_token_is(TokenType::Semicolon) && precedence < self.peek_precedence() {
+            let infix = self
+                .infix_parse_fns
+                .get(&mem::discriminant(
+                    &self.peek_token.as_ref().unwrap().token_type,
+                ))
+                .copied();
 
-        let left_expression = prefix.unwrap()(self);
+            let Some(infix) = infix else {
+                return Some(left_expression);
+            };
 
-        left_expression
+            self.next_token();
+            left_expression = infix(self, left_expression)?;
+        }
+
+        Some(left_expression)
     }
 
-    fn parse_identifier(&self) -> Option<Rc<RefCell<dyn Expression>>> {
+    fn parse_identifier(&mut self) -> Option<Rc<RefCell<dyn Expression>>> {
         Some(Rc::new(RefCell::new(Identifier {
             token: self.current_token.as_ref().unwrap().clone(),
             value: self.current_token.as_ref().unwrap().literal.clone(),
+            depth: Cell::default(),
         })))
     }
 
-    fn parse_integral_literal(&self) -> Option<Rc<RefCell<dyn Expression>>> {
+    fn parse_integral_literal(&mut self) -> Option<Rc<RefCell<dyn Expression>>> {
         let token = self.current_token.as_ref()?;
         Some(Rc::new(RefCell::new(IntegralLiteral {
             token: token.clone(),
-            value: token.literal.parse::<i64>().unwrap_or(0),
+            value: token.literal.parse::<BigInt>().unwrap_or_default(),
+        })))
+    }
+
+    fn parse_real_literal(&mut self) -> Option<Rc<RefCell<dyn Expression>>> {
+        let token = self.current_token.as_ref()?;
+        Some(Rc::new(RefCell::new(RealLiteral {
+            token: token.clone(),
+            value: token.literal.parse::<f64>().unwrap_or(0.0),
+        })))
+    }
+
+    fn parse_string_literal(&mut self) -> Option<Rc<RefCell<dyn Expression>>> {
+        let token = self.current_token.as_ref()?;
+        let value = match &token.token_type {
+            TokenType::Str(value) => value.clone(),
+            _ => String::new(),
+        };
+        Some(Rc::new(RefCell::new(StringLiteral {
+            token: token.clone(),
+            value,
+        })))
+    }
+
+    fn parse_boolean_literal(&mut self) -> Option<Rc<RefCell<dyn Expression>>> {
+        Some(Rc::new(RefCell::new(BooleanLiteral {
+            token: self.current_token.as_ref().unwrap().clone(),
+            value: self.current_token_is(TokenType::True),
         })))
     }
 
+    fn parse_prefix_expression(&mut self) -> Option<Rc<RefCell<dyn Expression>>> {
+        let token = self.current_token.as_ref().unwrap().clone();
+        let operator = operator_of(&token);
+
+        self.next_token();
+
+        Some(Rc::new(RefCell::new(PrefixExpression {
+            token,
+            operator,
+            right: Some(self.parse_expression(Precedence::Prefix)?),
+        })))
+    }
+
+    fn parse_infix_expression(
+        &mut self,
+        left: Rc<RefCell<dyn Expression>>,
+    ) -> Option<Rc<RefCell<dyn Expression>>> {
+        let token = self.current_token.as_ref().unwrap().clone();
+        let operator = operator_of(&token);
+        let precedence = self.current_precedence();
+
+        self.next_token();
+
+        Some(Rc::new(RefCell::new(InfixExpression {
+            token,
+            left: Some(left),
+            operator,
+            right: Some(self.parse_expression(precedence)?),
+        })))
+    }
+
+    fn parse_grouped_expression(&mut self) -> Option<Rc<RefCell<dyn Expression>>> {
+        self.next_token();
+
+        let expression = self.parse_expression(Precedence::Lowest);
+
+        if !self.expect_peek(TokenType::RParen) {
+            return None;
+        }
+
+        expression
+    }
+
+    /// `[]` and `[a, b]` are array literals while `[a]` is the integer
+    /// part of `a`; a one-element array is written `[a,]`.
+    fn parse_bracket_expression(&mut self) -> Option<Rc<RefCell<dyn Expression>>> {
+        let token = self.current_token.as_ref().unwrap().clone();
+
+        if self.peek_token_is(TokenType::RBracket) {
+            self.next_token();
+            return Some(Rc::new(RefCell::new(ArrayLiteral {
+                token,
+                elements: Vec::new(),
+            })));
+        }
+
+        self.next_token();
+        let first = self.parse_expression(Precedence::Lowest)?;
+
+        if self.peek_token_is(TokenType::RBracket) {
+            self.next_token();
+            return Some(Rc::new(RefCell::new(IntegerPartExpression {
+                token,
+                value: Some(first),
+            })));
+        }
+
+        let mut elements = vec![first];
+        while self.peek_token_is(TokenType::Comma) {
+            self.next_token();
+            if self.peek_token_is(TokenType::RBracket) {
+                break;
+            }
+            self.next_token();
+            elements.push(self.parse_expression(Precedence::Lowest)?);
+        }
+
+        if !self.expect_peek(TokenType::RBracket) {
+            return None;
+        }
+
+        Some(Rc::new(RefCell::new(ArrayLiteral { token, elements })))
+    }
+
+    /// In expression position `{` always opens a map literal; blocks are
+    /// only parsed where a statement body is expected.
+    fn parse_map_literal(&mut self) -> Option<Rc<RefCell<dyn Expression>>> {
+        let token = self.current_token.as_ref().unwrap().clone();
+        let mut pairs = Vec::new();
+
+        while !self.peek_token_is(TokenType::RBrace) {
+            self.next_token();
+            let key = self.parse_expression(Precedence::Lowest)?;
+
+            if !self.expect_peek(TokenType::Colon) {
+                return None;
+            }
+
+            self.next_token();
+            let value = self.parse_expression(Precedence::Lowest)?;
+            pairs.push((key, value));
+
+            if !self.peek_token_is(TokenType::RBrace) && !self.expect_peek(TokenType::Comma) {
+                return None;
+            }
+        }
+
+        if !self.expect_peek(TokenType::RBrace) {
+            return None;
+        }
+
+        Some(Rc::new(RefCell::new(MapLiteral { token, pairs })))
+    }
+
+    fn parse_index_expression(
+        &mut self,
+        left: Rc<RefCell<dyn Expression>>,
+    ) -> Option<Rc<RefCell<dyn Expression>>> {
+        let token = self.current_token.as_ref().unwrap().clone();
+
+        self.next_token();
+        let index = self.parse_expression(Precedence::Lowest);
+
+        if !self.expect_peek(TokenType::RBracket) {
+            return None;
+        }
+
+        Some(Rc::new(RefCell::new(IndexExpression {
+            token,
+            left: Some(left),
+            index,
+        })))
+    }
+
+    fn parse_call_expression(
+        &mut self,
+        function: Rc<RefCell<dyn Expression>>,
+    ) -> Option<Rc<RefCell<dyn Expression>>> {
+        let token = self.current_token.as_ref().unwrap().clone();
+        let arguments = self.parse_expression_list(TokenType::RParen)?;
+
+        Some(Rc::new(RefCell::new(CallExpression {
+            token,
+            function: Some(function),
+            arguments,
+        })))
+    }
+
+    fn parse_expression_list(
+        &mut self,
+        end: TokenType,
+    ) -> Option<Vec<Rc<RefCell<dyn Expression>>>> {
+        let mut list = Vec::new();
+
+        if self.peek_token_is(end.clone()) {
+            self.next_token();
+            return Some(list);
+        }
+
+        self.next_token();
+        list.push(self.parse_expression(Precedence::Lowest)?);
+
+        while self.peek_token_is(TokenType::Comma) {
+            self.next_token();
+            self.next_token();
+            list.push(self.parse_expression(Precedence::Lowest)?);
+        }
+
+        if !self.expect_peek(end) {
+            return None;
+        }
+
+        Some(list)
+    }
+
     fn current_token_is(&self, token_type: TokenType) -> bool {
         self.current_token.as_ref().unwrap().token_type == token_type
     }
@@ -187,6 +927,30 @@ impl Parser {
         self.peek_token.as_ref().unwrap().token_type == token_type
     }
 
+    fn peek_token_type(&self) -> &TokenType {
+        &self.peek_token.as_ref().unwrap().token_type
+    }
+
+    fn peek_token_is_assignment(&self) -> bool {
+        matches!(
+            self.peek_token.as_ref().unwrap().token_type,
+            TokenType::Assign
+                | TokenType::LeftArrow
+                | TokenType::PlusAssign
+                | TokenType::MinusAssign
+                | TokenType::AsteriskAssign
+                | TokenType::SlashAssign
+        )
+    }
+
+    fn peek_precedence(&self) -> Precedence {
+        Precedence::of(&self.peek_token.as_ref().unwrap().token_type)
+    }
+
+    fn current_precedence(&self) -> Precedence {
+        Precedence::of(&self.current_token.as_ref().unwrap().token_type)
+    }
+
     fn expect_peek(&mut self, token_type: TokenType) -> bool {
         match self.peek_token {
             Some(ref t) => match (&t.token_type, &token_type) {
@@ -210,19 +974,26 @@ impl Parser {
         }
     }
 
-    fn register_prefix(
-        &mut self,
-        token_type: TokenType,
-        func: fn(&Parser) -> Option<Rc<RefCell<dyn Expression>>>,
-    ) {
-        self.prefix_parse_fns.insert(token_type, func);
+    fn register_prefix(&mut self, token_type: TokenType, func: PrefixParseFn) {
+        self.prefix_parse_fns
+            .insert(mem::discriminant(&token_type), func);
     }
 
-    fn register_infix(
-        &mut self,
-        token_type: TokenType,
-        func: fn(Rc<RefCell<dyn Expression>>) -> Option<Rc<RefCell<dyn Expression>>>,
-    ) {
-        self.infix_parse_fns.insert(token_type, func);
+    fn register_infix(&mut self, token_type: TokenType, func: InfixParseFn) {
+        self.infix_parse_fns
+            .insert(mem::discriminant(&token_type), func);
+    }
+}
+
+/// The spelling used for an operator in the AST, so that `si`/`&&`,
+/// `sau`/`||`, `not`/`nu`/`!` and `mod`/`%` are indistinguishable past
+/// the parser.
+fn operator_of(token: &Token) -> String {
+    match token.token_type {
+        TokenType::Mod => "%".to_string(),
+        TokenType::And => "&&".to_string(),
+        TokenType::Or => "||".to_string(),
+        TokenType::Not => "!".to_string(),
+        _ => token.literal.clone(),
     }
 }
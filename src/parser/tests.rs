@@ -1,16 +1,10 @@
-use std::{
-    borrow::Borrow,
-    cell::{Ref, RefCell},
-    ops::Deref,
-    rc::Rc,
-};
-
 use crate::{
     ast::{
-        ExpressionStatement, IntegralLiteral, LetStatement, Node, Program, ReturnStatement,
-        Statement,
+        AssignStatement, ExpressionStatement, IndexAssignStatement, IntegralLiteral, LetStatement,
+        Node, ReturnStatement,
     },
-    lexer::Lexer,
+    lexer::{Lexer, TokenType},
+    messages::Lang,
 };
 
 use super::Parser;
@@ -34,7 +28,7 @@ fn test_one() {
 
     // println!("{:?}", program.statements.len());
 
-    let tests = vec!["x".to_string(), "y".to_string(), "foobar".to_string()];
+    let tests = ["x".to_string(), "y".to_string(), "z".to_string()];
     for (i, tt) in tests.iter().enumerate() {
         let stmt = program.statements.get(i).unwrap();
         let stmt = stmt.as_ref().borrow();
@@ -61,7 +55,7 @@ fn return_statements() {
 
     assert_eq!(program.statements.len(), 3);
 
-    let tests = vec![5, 10, 9993322];
+    let tests = [5, 10, 9993322];
     for (i, _) in tests.iter().enumerate() {
         let stmt = program.statements.get(i).unwrap();
         let stmt = stmt.as_ref().borrow();
@@ -82,9 +76,289 @@ fn test_identifier_expression() {
 
     assert_eq!(program.statements.len(), 1);
 
-    let stmt = program.statements.get(0).unwrap();
+    let stmt = program.statements.first().unwrap();
     let stmt = stmt.as_ref().borrow();
     let expression_stmt = stmt.as_any().downcast_ref::<IntegralLiteral>();
 
     dbg!(expression_stmt);
 }
+
+#[test]
+fn integral_literals_of_any_length() {
+    let input = "123456789012345678901234567890;".to_string();
+    let mut p = Parser::new(Lexer::new(input));
+    let program = p.parse_program().unwrap();
+    assert!(p.errors().is_empty(), "{:?}", p.errors());
+
+    let stmt = program.statements.first().unwrap().as_ref().borrow();
+    let stmt = stmt.as_any().downcast_ref::<ExpressionStatement>().unwrap();
+    let expression = stmt.expression.as_ref().unwrap().as_ref().borrow();
+    let literal = expression
+        .as_any()
+        .downcast_ref::<IntegralLiteral>()
+        .unwrap();
+    assert_eq!(literal.value.to_string(), "123456789012345678901234567890");
+}
+
+#[test]
+fn operator_precedence() {
+    let tests = vec![
+        ("-a * b", "((-a) * b)"),
+        ("!-a", "(!(-a))"),
+        ("a + b + c", "((a + b) + c)"),
+        ("a + b * c", "(a + (b * c))"),
+        ("a * b / c", "((a * b) / c)"),
+        ("5 > 4 == 3 < 4", "((5 > 4) == (3 < 4))"),
+        (
+            "3 + 4 * 5 == 3 * 1 + 4 * 5",
+            "((3 + (4 * 5)) == ((3 * 1) + (4 * 5)))",
+        ),
+        ("a <= b != true", "((a <= b) != true)"),
+        ("(5 + 5) * 2", "((5 + 5) * 2)"),
+        ("-(5 + 5)", "(-(5 + 5))"),
+        ("a || b && c", "(a || (b && c))"),
+        ("a && b || c", "((a && b) || c)"),
+        ("x > 0 si x < 10", "((x > 0) && (x < 10))"),
+        ("a == b sau nu c", "((a == b) || (!c))"),
+        ("not a && !b", "((!a) && (!b))"),
+        ("a div b % c", "((a div b) % c)"),
+        ("a + b mod c", "(a + (b % c))"),
+        ("[x / 2] * 2 + 0.5", "(([(x / 2)] * 2) + 0.5)"),
+        ("-[-2.5]", "(-[(-2.5)])"),
+        (
+            "a * [1, 2, 3, 4][b * c] * d",
+            "((a * ([1, 2, 3, 4][(b * c)])) * d)",
+        ),
+        (
+            "add(a * b[2], b[1], 2 * [1, 2][1])",
+            "add((a * (b[2])), (b[1]), (2 * ([1, 2][1])))",
+        ),
+        ("a + add(b * c) + d", "((a + add((b * c))) + d)"),
+        ("len([])", "len([])"),
+        ("[x,]", "[x,]"),
+        ("[x, y,]", "[x, y]"),
+        ("[[x]]", "[[x]]"),
+        ("{}", "{}"),
+        (r#"{"a": 1, 2: b + c,}"#, r#"{"a": 1, 2: (b + c)}"#),
+        (r#"{true: [1, 2]}["k"][0]"#, r#"(({true: [1, 2]}["k"])[0])"#),
+    ];
+
+    for (input, expected) in tests {
+        let lexer = Lexer::new(input.to_string());
+        let mut p = Parser::new(lexer);
+        let program = p.parse_program().unwrap();
+        assert!(p.errors().is_empty(), "{:?}", p.errors());
+        assert_eq!(program.string(), expected);
+    }
+}
+
+#[test]
+fn assign_statements() {
+    let input = r#"
+    x ← x + 1;
+    y = 2
+    z += 3;
+    z -= 4;
+    z *= y;
+    z /= 2 * y;
+  "#
+    .to_string();
+
+    let lexer = Lexer::new(input);
+    let mut p = Parser::new(lexer);
+    let program = p.parse_program().unwrap();
+    assert!(p.errors().is_empty(), "{:?}", p.errors());
+
+    let tests = vec![
+        ("x", TokenType::LeftArrow, "x ← (x + 1);"),
+        ("y", TokenType::Assign, "y = 2;"),
+        ("z", TokenType::PlusAssign, "z += 3;"),
+        ("z", TokenType::MinusAssign, "z -= 4;"),
+        ("z", TokenType::AsteriskAssign, "z *= y;"),
+        ("z", TokenType::SlashAssign, "z /= (2 * y);"),
+    ];
+
+    assert_eq!(program.statements.len(), tests.len());
+
+    for (i, (name, token_type, string)) in tests.into_iter().enumerate() {
+        let stmt = program.statements.get(i).unwrap();
+        let stmt = stmt.as_ref().borrow();
+        let assign_stmt = stmt.as_any().downcast_ref::<AssignStatement>().unwrap();
+        assert_eq!(assign_stmt.name.value, name);
+        assert_eq!(assign_stmt.token.token_type, token_type);
+        assert_eq!(assign_stmt.string(), string);
+    }
+}
+
+#[test]
+fn let_statement_values() {
+    let input = "let x = 5 * 2; return x == 10;".to_string();
+    let lexer = Lexer::new(input);
+    let mut p = Parser::new(lexer);
+    let program = p.parse_program().unwrap();
+    assert!(p.errors().is_empty(), "{:?}", p.errors());
+
+    assert_eq!(program.string(), "let x = (5 * 2);return (x == 10);");
+}
+
+#[test]
+fn index_assign_statements() {
+    let input = r#"
+    a[0] ← 1;
+    a[i + 1] += a[i];
+    m[1][2] = 3
+  "#
+    .to_string();
+
+    let lexer = Lexer::new(input);
+    let mut p = Parser::new(lexer);
+    let program = p.parse_program().unwrap();
+    assert!(p.errors().is_empty(), "{:?}", p.errors());
+
+    let tests = vec![
+        (TokenType::LeftArrow, "(a[0]) ← 1;"),
+        (TokenType::PlusAssign, "(a[(i + 1)]) += (a[i]);"),
+        (TokenType::Assign, "((m[1])[2]) = 3;"),
+    ];
+
+    assert_eq!(program.statements.len(), tests.len());
+
+    for (i, (token_type, string)) in tests.into_iter().enumerate() {
+        let stmt = program.statements.get(i).unwrap();
+        let stmt = stmt.as_ref().borrow();
+        let assign_stmt = stmt
+            .as_any()
+            .downcast_ref::<IndexAssignStatement>()
+            .unwrap();
+        assert_eq!(assign_stmt.token.token_type, token_type);
+        assert_eq!(assign_stmt.string(), string);
+    }
+}
+
+#[test]
+fn invalid_assignment_target() {
+    let lexer = Lexer::new("f(x) ← 1;".to_string());
+    let mut p = Parser::new(lexer);
+    p.parse_program().unwrap();
+
+    assert_eq!(
+        p.errors(),
+        vec!["invalid assignment target: f(x)".to_string()]
+    );
+    let span = p.parse_errors()[0].span;
+    assert_eq!((span.line, span.column), (1, 2));
+    assert_eq!(
+        p.parse_errors()[0].render("f(x) ← 1;", Lang::En),
+        "error[E0103]: invalid assignment target: f(x)
+ --> 1:2
+  |
+1 | f(x) ← 1;
+  |  ^"
+    );
+    assert_eq!(
+        p.parse_errors()[0].message(Lang::Ro),
+        "țintă nevalidă pentru atribuire: f(x)"
+    );
+}
+
+#[test]
+fn map_literal_errors() {
+    let tests = vec![
+        ("{1 2}", "expected `:`, got number `2` instead"),
+        ("{1: 2 3: 4}", "expected `,`, got number `3` instead"),
+    ];
+
+    for (input, expected) in tests {
+        let lexer = Lexer::new(input.to_string());
+        let mut p = Parser::new(lexer);
+        p.parse_program().unwrap();
+        assert_eq!(p.errors().first().unwrap(), expected, "{}", input);
+    }
+}
+
+fn parse(input: &str) -> String {
+    let lexer = Lexer::new(input.to_string());
+    let mut p = Parser::new(lexer);
+    let program = p.parse_program().unwrap();
+    assert!(p.errors().is_empty(), "{}: {:?}", input, p.errors());
+    program.string()
+}
+
+#[test]
+fn control_flow_statements() {
+    let tests = vec![
+        (
+            "daca x > 1 atunci { y ← 1 } altfel { y ← 2 }",
+            "daca (x > 1) { y ← 1; } altfel { y ← 2; }",
+        ),
+        ("if (x) { return; }", "if x { return ; }"),
+        (
+            "daca a { } altfel daca b { } altfel { c }",
+            "daca a { } altfel { daca b { } altfel { c } }",
+        ),
+        (
+            "cat timp n > 0 executa { n -= 1; }",
+            "cat timp (n > 0) { n -= 1; }",
+        ),
+        ("while x { }", "while x { }"),
+        ("while x { }; y", "while x { }y"),
+        (
+            "pentru i ← 1, n executa { s += i }",
+            "pentru i ← 1, n { s += i; }",
+        ),
+        ("pentru i = n, 1, -1 { }", "pentru i ← n, 1, (-1) { }"),
+        (
+            "repeta { x ← x div 10 } pana cand x == 0",
+            "repeta { x ← (x div 10); } pana cand (x == 0);",
+        ),
+        ("citeste n, v[i]", "citeste n, (v[i]);"),
+        (r#"scrie "s=", s + 1"#, r#"scrie "s=", (s + 1);"#),
+    ];
+
+    for (input, expected) in tests {
+        assert_eq!(parse(input), expected, "{}", input);
+    }
+}
+
+#[test]
+fn function_literals_and_declarations() {
+    let tests =
+        vec![
+        ("fn(x, y) { x + y; }", "fn(x, y) { (x + y) }"),
+        ("let f = functie() { returneaza 1 };", "let f = functie() { returneaza 1; };"),
+        (
+            "functie fact(n) { daca n <= 1 { returneaza 1 } returneaza n * fact(n - 1) }",
+            "functie fact(n) { daca (n <= 1) { returneaza 1; } returneaza (n * fact((n - 1))); }",
+        ),
+        ("functie(a) { }(5)", "functie(a) { }(5)"),
+    ];
+
+    for (input, expected) in tests {
+        assert_eq!(parse(input), expected, "{}", input);
+    }
+}
+
+#[test]
+fn control_flow_errors() {
+    let tests = vec![
+        ("daca x { y", "expected `}`, got end of input instead"),
+        ("cat timp x y", "expected `{`, got identifier `y` instead"),
+        ("pentru i 1, 2 { }", "expected `←`, got number `1` instead"),
+        (
+            "repeta { } x",
+            "expected `pana cand`, got identifier `x` instead",
+        ),
+        ("citeste 5", "invalid read target: 5"),
+        (
+            "functie f(1) { }",
+            "expected an identifier, got number `1` instead",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let lexer = Lexer::new(input.to_string());
+        let mut p = Parser::new(lexer);
+        p.parse_program().unwrap();
+        assert_eq!(p.errors().first().unwrap(), expected, "{}", input);
+    }
+}
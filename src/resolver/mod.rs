@@ -0,0 +1,578 @@
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::mem;
+use std::rc::Rc;
+
+use crate::ast::{
+    ArrayLiteral, AssignStatement, BlockStatement, CallExpression, Expression, ExpressionStatement,
+    ForStatement, FunctionLiteral, Identifier, IfStatement, IndexAssignStatement, IndexExpression,
+    InfixExpression, IntegerPartExpression, LetStatement, MapLiteral, PrefixExpression, Program,
+    ReadStatement, RepeatStatement, ReturnStatement, Statement, WhileStatement, WriteStatement,
+};
+use crate::builtins::BUILTINS;
+use crate::evaluator::compound_operator;
+use crate::lexer::{Span, TokenType};
+use crate::messages::{self, Lang};
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BindingKind {
+    Variable,
+    Parameter,
+    Function,
+    /// The counter of a `pentru` loop.
+    Counter,
+}
+
+/// A name a scope gives a value to, with where it first does so.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Binding {
+    pub name: String,
+    pub kind: BindingKind,
+    pub span: Span,
+}
+
+/// The program itself or the body of a function.
+///
+/// Blocks do not open a scope of their own: as in the evaluator, a variable
+/// assigned inside `daca` is still there after it. Assigning a name an
+/// enclosing scope already has changes that binding rather than adding one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scope {
+    /// `None` for the program.
+    pub parent: Option<usize>,
+    /// The name of the function, if it has one.
+    pub function: Option<String>,
+    pub bindings: Vec<Binding>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Problem {
+    /// A name no scope ever binds.
+    Undefined(String),
+    /// A name read before the first statement of its scope binding it.
+    UsedBeforeDefinition(String),
+    /// A second `let` or `functie` of the same name in one scope.
+    DuplicateDeclaration(String),
+    /// A declaration hiding a name of an enclosing scope or a builtin.
+    Shadowing(String),
+    /// A variable that is given a value but never read.
+    Unused(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub problem: Problem,
+    pub span: Span,
+}
+
+impl Diagnostic {
+    pub fn severity(&self) -> Severity {
+        match self.problem {
+            Problem::Undefined(_)
+            | Problem::UsedBeforeDefinition(_)
+            | Problem::DuplicateDeclaration(_) => Severity::Error,
+            Problem::Shadowing(_) | Problem::Unused(_) => Severity::Warning,
+        }
+    }
+
+    /// The diagnostic as one line: its severity, the message and where it
+    /// is.
+    pub fn render(&self, lang: Lang) -> String {
+        let severity = match self.severity() {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        let message = messages::message(
+            "located",
+            lang,
+            &[
+                &self.problem.message(lang),
+                &self.span.line,
+                &self.span.column,
+            ],
+        );
+        format!("{}: {}", messages::message(severity, lang, &[]), message)
+    }
+}
+
+impl Problem {
+    /// The code the message is kept under in the [`messages`] catalogue.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Problem::Undefined(_) => "E0301",
+            Problem::UsedBeforeDefinition(_) => "E0302",
+            Problem::DuplicateDeclaration(_) => "E0303",
+            Problem::Shadowing(_) => "W0201",
+            Problem::Unused(_) => "W0202",
+        }
+    }
+
+    pub fn message(&self, lang: Lang) -> String {
+        let (Problem::Undefined(name)
+        | Problem::UsedBeforeDefinition(name)
+        | Problem::DuplicateDeclaration(name)
+        | Problem::Shadowing(name)
+        | Problem::Unused(name)) = self;
+        messages::message(self.code(), lang, &[name])
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message(Lang::En))
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.render(Lang::En))
+    }
+}
+
+/// The scope tree of a program and what is wrong with the names in it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Resolution {
+    /// The program is scope 0; functions follow in the order their bodies
+    /// are resolved.
+    pub scopes: Vec<Scope>,
+    /// Ordered by position in the source.
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl Resolution {
+    pub fn errors(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.severity() == Severity::Error)
+    }
+}
+
+/// Resolves every name in `program` to the scope that binds it, and sets
+/// `Identifier::depth` to how many function scopes out that is, unless a
+/// scope in between assigns the name too: run before the outer assignment,
+/// that binds it in the scope in between instead.
+///
+/// A function body may use any name of an enclosing scope, wherever it is
+/// assigned there, since it only runs once called. Within one scope names
+/// must be assigned before they are read, except inside a loop whose body
+/// assigns them on an earlier iteration.
+pub fn resolve(program: &Program) -> Resolution {
+    let mut resolver = Resolver {
+        scopes: Vec::new(),
+        names: Vec::new(),
+        assigned: Vec::new(),
+        used: BTreeSet::new(),
+        frame: Frame::default(),
+        diagnostics: Vec::new(),
+    };
+    resolver.scope(None, None, &[], &program.statements);
+
+    for (index, scope) in resolver.scopes.iter().enumerate() {
+        for binding in &scope.bindings {
+            if binding.kind == BindingKind::Variable
+                && !resolver.used.contains(&(index, binding.name.clone()))
+            {
+                resolver.diagnostics.push(Diagnostic {
+                    problem: Problem::Unused(binding.name.clone()),
+                    span: binding.span,
+                });
+            }
+        }
+    }
+
+    let mut diagnostics = resolver.diagnostics;
+    diagnostics.sort_by_key(|diagnostic| (diagnostic.span.line, diagnostic.span.column));
+    Resolution {
+        scopes: resolver.scopes,
+        diagnostics,
+    }
+}
+
+/// What is known while walking the statements of one scope.
+#[derive(Default)]
+struct Frame {
+    scope: usize,
+    /// Names given a value so far.
+    bound: BTreeSet<String>,
+    /// Names declared by `let`, `functie` or as a parameter so far.
+    declared: BTreeSet<String>,
+    /// Names assigned in the bodies of the loops being walked.
+    loops: Vec<Vec<String>>,
+    /// Functions met in this scope, resolved once all of it is known.
+    functions: Vec<Pending>,
+}
+
+/// A function literal, with the name it is declared under if any.
+struct Pending {
+    name: Option<String>,
+    literal: Rc<RefCell<dyn Expression>>,
+}
+
+struct Resolver {
+    scopes: Vec<Scope>,
+    /// Every name each scope binds, wherever it does so.
+    names: Vec<BTreeSet<String>>,
+    /// Every name each scope assigns, including those an enclosing scope
+    /// binds.
+    assigned: Vec<BTreeSet<String>>,
+    used: BTreeSet<(usize, String)>,
+    frame: Frame,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Resolver {
+    fn scope(
+        &mut self,
+        parent: Option<usize>,
+        function: Option<String>,
+        parameters: &[Identifier],
+        statements: &[Rc<RefCell<dyn Statement>>],
+    ) {
+        let index = self.scopes.len();
+        self.scopes.push(Scope {
+            parent,
+            function,
+            bindings: Vec::new(),
+        });
+
+        let mut names: BTreeSet<String> = parameters.iter().map(|p| p.value.clone()).collect();
+        let mut assigned = Vec::new();
+        for statement in statements {
+            bound_names(&*statement.as_ref().borrow(), &mut names, &mut assigned);
+        }
+        for name in &assigned {
+            if self.enclosing(parent, name).is_none() {
+                names.insert(name.clone());
+            }
+        }
+        self.names.push(names);
+        self.assigned.push(assigned.into_iter().collect());
+
+        let outer = mem::replace(
+            &mut self.frame,
+            Frame {
+                scope: index,
+                ..Frame::default()
+            },
+        );
+        for parameter in parameters {
+            self.declare(parameter, BindingKind::Parameter);
+        }
+        self.statements(statements);
+
+        for Pending { name, literal } in mem::take(&mut self.frame.functions) {
+            let function = literal.as_ref().borrow();
+            if let Some(function) = function.as_any().downcast_ref::<FunctionLiteral>() {
+                let name = function.name.clone().or(name);
+                self.scope(
+                    Some(index),
+                    name,
+                    &function.parameters,
+                    &function.body.statements,
+                );
+            }
+        }
+        self.frame = outer;
+    }
+
+    /// The nearest scope from `scope` outwards binding `name`, and how many
+    /// scopes out it is.
+    fn enclosing(&self, mut scope: Option<usize>, name: &str) -> Option<(usize, usize)> {
+        let mut depth = 0;
+        while let Some(index) = scope {
+            if self.names[index].contains(name) {
+                return Some((index, depth));
+            }
+            scope = self.scopes[index].parent;
+            depth += 1;
+        }
+        None
+    }
+
+    /// `depth`, the scopes out from the current one that bind `name`, if
+    /// none of the scopes in between assigns it.
+    fn fixed_depth(&self, name: &str, depth: usize) -> Option<usize> {
+        let mut scope = self.frame.scope;
+        for _ in 0..depth {
+            if self.assigned[scope].contains(name) {
+                return None;
+            }
+            scope = self.scopes[scope].parent?;
+        }
+        Some(depth)
+    }
+
+    fn report(&mut self, problem: Problem, span: Span) {
+        self.diagnostics.push(Diagnostic { problem, span });
+    }
+
+    /// Records a `let`, `functie` or parameter, which always binds the name
+    /// in the current scope.
+    fn declare(&mut self, identifier: &Identifier, kind: BindingKind) {
+        let name = &identifier.value;
+        if !self.frame.declared.insert(name.clone()) {
+            self.report(
+                Problem::DuplicateDeclaration(name.clone()),
+                identifier.token.span,
+            );
+        } else if self.frame.bound.contains(name) {
+            // Already assigned here, so nothing outer is hidden.
+        } else if BUILTINS.contains(&name.as_str())
+            || self
+                .enclosing(self.scopes[self.frame.scope].parent, name)
+                .is_some()
+        {
+            self.report(Problem::Shadowing(name.clone()), identifier.token.span);
+        }
+        self.bind(identifier, kind);
+    }
+
+    /// Records an assignment to `identifier`, which binds it in the current
+    /// scope unless an enclosing one already has it.
+    fn bind(&mut self, identifier: &Identifier, kind: BindingKind) {
+        let name = &identifier.value;
+        let Some((scope, depth)) = self.enclosing(Some(self.frame.scope), name) else {
+            return;
+        };
+        identifier.depth.set(self.fixed_depth(name, depth));
+        if scope != self.frame.scope || !self.frame.bound.insert(name.clone()) {
+            return;
+        }
+        if kind == BindingKind::Variable && BUILTINS.contains(&name.as_str()) {
+            self.report(Problem::Shadowing(name.clone()), identifier.token.span);
+        }
+        self.scopes[scope].bindings.push(Binding {
+            name: name.clone(),
+            kind,
+            span: identifier.token.span,
+        });
+    }
+
+    fn read(&mut self, identifier: &Identifier) {
+        let name = &identifier.value;
+        match self.enclosing(Some(self.frame.scope), name) {
+            Some((scope, depth)) => {
+                identifier.depth.set(self.fixed_depth(name, depth));
+                self.used.insert((scope, name.clone()));
+                let assigned_earlier = self.frame.bound.contains(name)
+                    || self.frame.loops.iter().flatten().any(|n| n == name);
+                if scope == self.frame.scope && !assigned_earlier {
+                    self.report(
+                        Problem::UsedBeforeDefinition(name.clone()),
+                        identifier.token.span,
+                    );
+                }
+            }
+            None if BUILTINS.contains(&name.as_str()) => {}
+            None => self.report(Problem::Undefined(name.clone()), identifier.token.span),
+        }
+    }
+
+    /// Walks a loop body knowing that what it assigns may already be there
+    /// from an earlier iteration.
+    fn looped(&mut self, body: &BlockStatement) {
+        let mut names = BTreeSet::new();
+        let mut assigned = Vec::new();
+        for statement in &body.statements {
+            bound_names(&*statement.as_ref().borrow(), &mut names, &mut assigned);
+        }
+        assigned.extend(names);
+        self.frame.loops.push(assigned);
+        self.statements(&body.statements);
+        self.frame.loops.pop();
+    }
+
+    fn statements(&mut self, statements: &[Rc<RefCell<dyn Statement>>]) {
+        for statement in statements {
+            self.statement(&*statement.as_ref().borrow());
+        }
+    }
+
+    fn statement(&mut self, statement: &dyn Statement) {
+        let any = statement.as_any();
+
+        if let Some(statement) = any.downcast_ref::<ExpressionStatement>() {
+            self.optional_expression(&statement.expression);
+        } else if let Some(statement) = any.downcast_ref::<LetStatement>() {
+            let Some(name) = &statement.name else {
+                return;
+            };
+            let function = match &statement.value {
+                Some(value) if value.as_ref().borrow().as_any().is::<FunctionLiteral>() => {
+                    self.frame.functions.push(Pending {
+                        name: Some(name.value.clone()),
+                        literal: Rc::clone(value),
+                    });
+                    true
+                }
+                value => {
+                    self.optional_expression(value);
+                    false
+                }
+            };
+            let kind = match (function, &statement.token.token_type) {
+                (true, TokenType::Function) => BindingKind::Function,
+                _ => BindingKind::Variable,
+            };
+            self.declare(name, kind);
+        } else if let Some(statement) = any.downcast_ref::<AssignStatement>() {
+            self.optional_expression(&statement.value);
+            if compound_operator(&statement.token.token_type).is_some() {
+                self.read(&statement.name);
+            }
+            self.bind(&statement.name, BindingKind::Variable);
+        } else if let Some(statement) = any.downcast_ref::<IndexAssignStatement>() {
+            self.optional_expression(&statement.value);
+            self.expression(&statement.target);
+        } else if let Some(statement) = any.downcast_ref::<ReturnStatement>() {
+            self.optional_expression(&statement.return_value);
+        } else if let Some(block) = any.downcast_ref::<BlockStatement>() {
+            self.statements(&block.statements);
+        } else if let Some(statement) = any.downcast_ref::<IfStatement>() {
+            self.optional_expression(&statement.condition);
+            self.statements(&statement.consequence.statements);
+            if let Some(alternative) = &statement.alternative {
+                self.statements(&alternative.statements);
+            }
+        } else if let Some(statement) = any.downcast_ref::<WhileStatement>() {
+            self.optional_expression(&statement.condition);
+            self.looped(&statement.body);
+        } else if let Some(statement) = any.downcast_ref::<RepeatStatement>() {
+            self.looped(&statement.body);
+            self.optional_expression(&statement.condition);
+        } else if let Some(statement) = any.downcast_ref::<ForStatement>() {
+            for bound in [&statement.start, &statement.end, &statement.step] {
+                self.optional_expression(bound);
+            }
+            self.bind(&statement.counter, BindingKind::Counter);
+            if let Some((scope, _)) =
+                self.enclosing(Some(self.frame.scope), &statement.counter.value)
+            {
+                self.used.insert((scope, statement.counter.value.clone()));
+            }
+            self.looped(&statement.body);
+        } else if let Some(statement) = any.downcast_ref::<ReadStatement>() {
+            for target in &statement.targets {
+                let identifier = {
+                    let target = target.as_ref().borrow();
+                    if let Some(identifier) = target.as_any().downcast_ref::<Identifier>() {
+                        self.bind(identifier, BindingKind::Variable);
+                        true
+                    } else {
+                        false
+                    }
+                };
+                if !identifier {
+                    self.expression(target);
+                }
+            }
+        } else if let Some(statement) = any.downcast_ref::<WriteStatement>() {
+            for argument in &statement.arguments {
+                self.expression(argument);
+            }
+        }
+    }
+
+    fn optional_expression(&mut self, expression: &Option<Rc<RefCell<dyn Expression>>>) {
+        if let Some(expression) = expression {
+            self.expression(expression);
+        }
+    }
+
+    fn expression(&mut self, expression: &Rc<RefCell<dyn Expression>>) {
+        let node = expression.as_ref().borrow();
+        let any = node.as_any();
+
+        if let Some(identifier) = any.downcast_ref::<Identifier>() {
+            self.read(identifier);
+        } else if let Some(prefix) = any.downcast_ref::<PrefixExpression>() {
+            self.optional_expression(&prefix.right);
+        } else if let Some(integer_part) = any.downcast_ref::<IntegerPartExpression>() {
+            self.optional_expression(&integer_part.value);
+        } else if let Some(infix) = any.downcast_ref::<InfixExpression>() {
+            self.optional_expression(&infix.left);
+            self.optional_expression(&infix.right);
+        } else if let Some(array) = any.downcast_ref::<ArrayLiteral>() {
+            for element in &array.elements {
+                self.expression(element);
+            }
+        } else if let Some(map) = any.downcast_ref::<MapLiteral>() {
+            for (key, value) in &map.pairs {
+                self.expression(key);
+                self.expression(value);
+            }
+        } else if let Some(index) = any.downcast_ref::<IndexExpression>() {
+            self.optional_expression(&index.left);
+            self.optional_expression(&index.index);
+        } else if let Some(call) = any.downcast_ref::<CallExpression>() {
+            self.optional_expression(&call.function);
+            for argument in &call.arguments {
+                self.expression(argument);
+            }
+        } else if any.is::<FunctionLiteral>() {
+            self.frame.functions.push(Pending {
+                name: None,
+                literal: Rc::clone(expression),
+            });
+        }
+    }
+}
+
+/// Collects the names `statement` declares with `let` or `functie` into
+/// `declared`, and those it assigns otherwise into `assigned`, without
+/// entering nested functions.
+fn bound_names(
+    statement: &dyn Statement,
+    declared: &mut BTreeSet<String>,
+    assigned: &mut Vec<String>,
+) {
+    let any = statement.as_any();
+    let mut blocks: Vec<&BlockStatement> = Vec::new();
+
+    if let Some(statement) = any.downcast_ref::<LetStatement>() {
+        if let Some(name) = &statement.name {
+            declared.insert(name.value.clone());
+        }
+    } else if let Some(statement) = any.downcast_ref::<AssignStatement>() {
+        assigned.push(statement.name.value.clone());
+    } else if let Some(statement) = any.downcast_ref::<ReadStatement>() {
+        for target in &statement.targets {
+            if let Some(identifier) = target
+                .as_ref()
+                .borrow()
+                .as_any()
+                .downcast_ref::<Identifier>()
+            {
+                assigned.push(identifier.value.clone());
+            }
+        }
+    } else if let Some(statement) = any.downcast_ref::<ForStatement>() {
+        assigned.push(statement.counter.value.clone());
+        blocks.push(&statement.body);
+    } else if let Some(block) = any.downcast_ref::<BlockStatement>() {
+        blocks.push(block);
+    } else if let Some(statement) = any.downcast_ref::<IfStatement>() {
+        blocks.push(&statement.consequence);
+        blocks.extend(statement.alternative.as_ref());
+    } else if let Some(statement) = any.downcast_ref::<WhileStatement>() {
+        blocks.push(&statement.body);
+    } else if let Some(statement) = any.downcast_ref::<RepeatStatement>() {
+        blocks.push(&statement.body);
+    }
+
+    for block in blocks {
+        for statement in &block.statements {
+            bound_names(&*statement.as_ref().borrow(), declared, assigned);
+        }
+    }
+}
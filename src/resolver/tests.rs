@@ -0,0 +1,209 @@
+use crate::ast::{Identifier, WriteStatement};
+use crate::evaluator::Evaluator;
+use crate::messages::Lang;
+use crate::test_support::parse;
+use crate::types::function_declaration;
+
+use super::{resolve, Binding, BindingKind, Scope};
+
+fn diagnostics(input: &str) -> Vec<String> {
+    resolve(&parse(input))
+        .diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.to_string())
+        .collect()
+}
+
+#[test]
+fn reports_problems_with_names() {
+    let tests = [
+        ("scrie x", vec!["error: identifier not found: x at 1:7"]),
+        (
+            "scrie x\nx ← 1\nscrie x",
+            vec!["error: `x` is used before it is given a value at 1:7"],
+        ),
+        (
+            "let a = 1; let a = 2; scrie a",
+            vec!["error: `a` is already declared in this scope at 1:16"],
+        ),
+        (
+            "x ← 1\nfunctie f(x) { returneaza x }\nscrie f(x)",
+            vec!["warning: `x` shadows an outer declaration at 2:11"],
+        ),
+        (
+            "len ← 2\nscrie len",
+            vec!["warning: `len` shadows an outer declaration at 1:1"],
+        ),
+        (
+            "citeste a, b\nscrie a",
+            vec!["warning: `b` is given a value but never used at 1:12"],
+        ),
+        (
+            "functie f() { t ← 1; returneaza 2 }\nscrie f()",
+            vec!["warning: `t` is given a value but never used at 1:15"],
+        ),
+    ];
+
+    for (input, expected) in tests {
+        assert_eq!(diagnostics(input), expected, "{}", input);
+    }
+}
+
+#[test]
+fn reports_problems_in_romanian() {
+    let resolution = resolve(&parse("citeste a, b\nscrie a, c"));
+    let diagnostics: Vec<_> = resolution
+        .diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.render(Lang::Ro))
+        .collect();
+    assert_eq!(
+        diagnostics,
+        vec![
+            "avertisment: `b` primește o valoare, dar nu este folosit la 1:12",
+            "eroare: identificator necunoscut: c la 2:10",
+        ]
+    );
+}
+
+#[test]
+fn accepts_what_runs() {
+    let tests = [
+        // A function may use what the program assigns after it.
+        "functie f() { returneaza n * 2 }\nn ← 3\nscrie f()",
+        "functie f(n) { daca n <= 1 { returneaza 1 }; returneaza n * f(n - 1) }\nscrie f(5)",
+        // A loop body may read what an earlier iteration assigned.
+        "pentru i ← 1, 3 { daca i > 1 { scrie prev }; prev ← i }",
+        "s ← 0\npentru i ← 1, 3 { s += i }\nscrie s, len([1, 2])",
+        "c ← 0\nfunctie inc() { c += 1 }\ninc()\nscrie c",
+        "let add = fn(a) { return fn(b) { return a + b; }; }; scrie add(2)(3)",
+        "daca adevarat { x ← 1 } altfel { x ← 2 }\nscrie x",
+    ];
+
+    for input in tests {
+        assert_eq!(diagnostics(input), Vec::<String>::new(), "{}", input);
+    }
+}
+
+#[test]
+fn builds_the_scope_tree() {
+    let resolution = resolve(&parse(
+        "n ← 2
+functie f(a) { b ← a + n; returneaza fn(c) { returneaza b + c } }
+pentru i ← 1, n { scrie f(i)(1) }",
+    ));
+
+    let binding = |name: &str, kind, line, column| (name.to_string(), kind, line, column);
+    let scopes: Vec<(Option<usize>, Option<String>, Vec<_>)> = resolution
+        .scopes
+        .iter()
+        .map(
+            |Scope {
+                 parent,
+                 function,
+                 bindings,
+             }| {
+                let bindings = bindings
+                    .iter()
+                    .map(|Binding { name, kind, span }| {
+                        (name.clone(), *kind, span.line, span.column)
+                    })
+                    .collect();
+                (*parent, function.clone(), bindings)
+            },
+        )
+        .collect();
+
+    assert_eq!(
+        scopes,
+        vec![
+            (
+                None,
+                None,
+                vec![
+                    binding("n", BindingKind::Variable, 1, 1),
+                    binding("f", BindingKind::Function, 2, 9),
+                    binding("i", BindingKind::Counter, 3, 8),
+                ]
+            ),
+            (
+                Some(0),
+                Some("f".to_string()),
+                vec![
+                    binding("a", BindingKind::Parameter, 2, 11),
+                    binding("b", BindingKind::Variable, 2, 16),
+                ]
+            ),
+            (
+                Some(1),
+                None,
+                vec![binding("c", BindingKind::Parameter, 2, 41)]
+            ),
+        ]
+    );
+    assert!(resolution.diagnostics.is_empty());
+}
+
+#[test]
+fn annotates_binding_depth() {
+    let program = parse("x ← 1\nfunctie f(a) { scrie a, x, len(a) }\nscrie x");
+    resolve(&program);
+
+    let depths = |statement: &WriteStatement| -> Vec<Option<usize>> {
+        statement
+            .arguments
+            .iter()
+            .map(|argument| {
+                let argument = argument.as_ref().borrow();
+                argument
+                    .as_any()
+                    .downcast_ref::<Identifier>()
+                    .and_then(|identifier| identifier.depth.get())
+            })
+            .collect()
+    };
+
+    let last = program.statements[2].as_ref().borrow();
+    let last = last.as_any().downcast_ref::<WriteStatement>().unwrap();
+    assert_eq!(depths(last), vec![Some(0)]);
+
+    let declaration = function_declaration(&*program.statements[1].borrow()).unwrap();
+    let body = declaration.body.statements[0].as_ref().borrow();
+    let body = body.as_any().downcast_ref::<WriteStatement>().unwrap();
+    // `len(a)` is a call, not an identifier.
+    assert_eq!(depths(body), vec![Some(0), Some(1), None]);
+}
+
+#[test]
+fn resolved_programs_evaluate_the_same() {
+    let tests = [
+        (
+            "let c = 0; let inc = fn() { c += 1; }; inc(); inc(); c",
+            "2",
+        ),
+        (
+            "let add = fn(a) { return fn(b) { return a + b; }; }; add(2)(3)",
+            "5",
+        ),
+        (
+            "functie f(n) { daca n <= 1 { returneaza 1 }; returneaza n * f(n - 1) }\nf(5)",
+            "120",
+        ),
+        // `x` is assigned in `f` before the program has one, so the call
+        // binds it in `f` and the program never sees it.
+        ("functie f() { x ← 1; returneaza x }\nf()", "1"),
+        // The same holds for a closure `f` returns, even once the program
+        // has an `x` of its own.
+        (
+            "functie f() { x ← 1; returneaza fn() { returneaza x } }\ng ← f()\nx ← 2\ng()",
+            "1",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let program = parse(input);
+        resolve(&program);
+        let result = Evaluator::new().eval_program(&program).unwrap();
+        assert_eq!(result.to_string(), expected, "{}", input);
+    }
+}
@@ -0,0 +1,40 @@
+//! Helpers the unit tests of several modules share.
+
+use std::cell::RefCell;
+use std::io::{self, Write};
+use std::rc::Rc;
+
+use crate::ast::Program;
+use crate::lexer::Lexer;
+use crate::parser::Parser;
+
+/// Parses `input`, failing the test if it is not a valid program.
+pub fn parse(input: &str) -> Program {
+    let lexer = Lexer::new(input.to_string());
+    let mut p = Parser::new(lexer);
+    let program = p.parse_program().unwrap();
+    assert!(p.errors().is_empty(), "{:?}", p.errors());
+    program
+}
+
+/// A writer whose clones all write to one buffer, to keep a handle on what
+/// an evaluator or VM given one prints.
+#[derive(Debug, Clone, Default)]
+pub struct Output(Rc<RefCell<Vec<u8>>>);
+
+impl Output {
+    /// Everything written so far.
+    pub fn text(&self) -> String {
+        String::from_utf8(self.0.borrow().clone()).unwrap()
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,198 @@
+use std::fmt::Write;
+
+use crate::ast::strip_parentheses;
+use crate::evaluator::Hook;
+use crate::lexer::Span;
+use crate::object::Object;
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    Assignment { target: String, value: String },
+    Condition { condition: String, value: bool },
+    Output(String),
+}
+
+/// One row of a trace: what happened and on which source line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step {
+    pub line: usize,
+    pub event: Event,
+}
+
+/// A [`Hook`] recording the steps of a run, from which the classic trace
+/// table ("tabel de urmarire") is built.
+#[derive(Debug, Default)]
+pub struct Trace {
+    steps: Vec<Step>,
+}
+
+/// A trace laid out as a table: a step number and a line number, one
+/// column per variable in the order they were first assigned, then the
+/// conditions and the output if there were any. A row only fills the cell
+/// of what changed in that step.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceTable {
+    pub header: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Trace {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+
+    pub fn table(&self) -> TraceTable {
+        let mut variables: Vec<&str> = Vec::new();
+        let mut conditions = false;
+        let mut output = false;
+
+        for step in &self.steps {
+            match &step.event {
+                Event::Assignment { target, .. } => {
+                    if !variables.contains(&target.as_str()) {
+                        variables.push(target);
+                    }
+                }
+                Event::Condition { .. } => conditions = true,
+                Event::Output(_) => output = true,
+            }
+        }
+
+        let mut header = vec!["step".to_string(), "line".to_string()];
+        header.extend(variables.iter().map(|name| name.to_string()));
+        let condition_column = header.len();
+        if conditions {
+            header.push("condition".to_string());
+        }
+        let output_column = header.len();
+        if output {
+            header.push("output".to_string());
+        }
+
+        let rows = self
+            .steps
+            .iter()
+            .enumerate()
+            .map(|(number, step)| {
+                let mut row = vec![String::new(); header.len()];
+                row[0] = (number + 1).to_string();
+                row[1] = step.line.to_string();
+                match &step.event {
+                    Event::Assignment { target, value } => {
+                        let column = variables.iter().position(|name| name == target);
+                        row[2 + column.unwrap_or_default()] = value.clone();
+                    }
+                    Event::Condition { condition, value } => {
+                        row[condition_column] = format!("{}: {}", condition, value);
+                    }
+                    Event::Output(text) => row[output_column] = text.clone(),
+                }
+                row
+            })
+            .collect();
+
+        TraceTable { header, rows }
+    }
+
+    fn record(&mut self, span: Span, event: Event) {
+        self.steps.push(Step {
+            line: span.line,
+            event,
+        });
+    }
+}
+
+impl Hook for Trace {
+    fn assignment(&mut self, span: Span, target: &str, value: &Object) {
+        let value = match value {
+            Object::Str(value) => format!("\"{}\"", value),
+            value => value.to_string(),
+        };
+        let target = target.to_string();
+        self.record(span, Event::Assignment { target, value });
+    }
+
+    fn condition(&mut self, span: Span, condition: &str, value: bool) {
+        let condition = strip_parentheses(condition).to_string();
+        self.record(span, Event::Condition { condition, value });
+    }
+
+    fn output(&mut self, span: Span, text: &str) {
+        self.record(span, Event::Output(text.to_string()));
+    }
+}
+
+impl TraceTable {
+    /// Columns padded to a common width and separated by `|`.
+    pub fn to_text(&self) -> String {
+        let mut widths: Vec<usize> = self.header.iter().map(|h| h.chars().count()).collect();
+        for row in &self.rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        let line = |cells: &[String]| {
+            let padded: Vec<String> = cells
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+                .collect();
+            padded.join(" | ").trim_end().to_string()
+        };
+
+        let mut out = String::new();
+        let _ = writeln!(out, "{}", line(&self.header));
+        let rule: Vec<String> = widths.iter().map(|width| "-".repeat(*width)).collect();
+        let _ = writeln!(out, "{}", rule.join("-+-"));
+        for row in &self.rows {
+            let _ = writeln!(out, "{}", line(row));
+        }
+        out
+    }
+
+    /// A GitHub-flavoured Markdown table.
+    pub fn to_markdown(&self) -> String {
+        let line = |cells: &[String]| {
+            let cells: Vec<String> = cells.iter().map(|c| c.replace('|', "\\|")).collect();
+            format!("| {} |\n", cells.join(" | "))
+        };
+
+        let mut out = line(&self.header);
+        let rule = vec!["---".to_string(); self.header.len()];
+        let _ = writeln!(out, "|{}|", rule.join("|"));
+        for row in &self.rows {
+            out.push_str(&line(row));
+        }
+        out
+    }
+
+    /// Comma-separated values, quoted as RFC 4180 requires.
+    pub fn to_csv(&self) -> String {
+        let line = |cells: &[String]| {
+            let cells: Vec<String> = cells.iter().map(|cell| csv_field(cell)).collect();
+            format!("{}\n", cells.join(","))
+        };
+
+        let mut out = line(&self.header);
+        for row in &self.rows {
+            out.push_str(&line(row));
+        }
+        out
+    }
+}
+
+fn csv_field(cell: &str) -> String {
+    if cell.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
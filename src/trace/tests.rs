@@ -0,0 +1,150 @@
+use std::cell::RefCell;
+use std::io::{self, Cursor};
+use std::rc::Rc;
+
+use crate::evaluator::Evaluator;
+use crate::test_support::parse;
+
+use super::{Event, Step, Trace, TraceTable};
+
+fn trace(input: &str, stdin: &str) -> Trace {
+    let program = parse(input);
+    let trace = Rc::new(RefCell::new(Trace::new()));
+    let mut evaluator = Evaluator::new().hook(trace.clone()).with_io(
+        Box::new(Cursor::new(stdin.to_string())),
+        Box::new(io::sink()),
+    );
+    evaluator.eval_program(&program).unwrap();
+    trace.take()
+}
+
+#[test]
+fn records_assignments_conditions_and_output() {
+    let trace = trace(
+        "citeste n
+s ← 0
+pentru i ← 1, n {
+  s += i
+}
+daca s > 2 { scrie \"s=\", s }",
+        "2",
+    );
+
+    let steps: Vec<(usize, Event)> = trace
+        .steps()
+        .iter()
+        .map(|Step { line, event }| (*line, event.clone()))
+        .collect();
+    let assign = |target: &str, value: &str| Event::Assignment {
+        target: target.to_string(),
+        value: value.to_string(),
+    };
+    let condition = |condition: &str, value: bool| Event::Condition {
+        condition: condition.to_string(),
+        value,
+    };
+
+    assert_eq!(
+        steps,
+        vec![
+            (1, assign("n", "2")),
+            (2, assign("s", "0")),
+            (3, assign("i", "1")),
+            (3, condition("i <= n", true)),
+            (4, assign("s", "1")),
+            (3, assign("i", "2")),
+            (3, condition("i <= n", true)),
+            (4, assign("s", "3")),
+            (3, assign("i", "3")),
+            (3, condition("i <= n", false)),
+            (6, condition("s > 2", true)),
+            (6, Event::Output("s=3".to_string())),
+        ]
+    );
+}
+
+#[test]
+fn records_loops_and_elements() {
+    let trace = trace(
+        "v ← [0, 0]
+citeste v[0]
+v[1] ← \"x\"
+k ← 2
+cat timp k > 0 { k -= 1 }
+repeta { k += 1 } pana cand (k == 1)
+pentru j ← 2, 1, -1 { }",
+        "7",
+    );
+
+    let table = trace.table();
+    assert_eq!(
+        table.header,
+        vec!["step", "line", "v", "v[0]", "v[1]", "k", "j", "condition"]
+    );
+
+    let cells: Vec<String> = table.rows.iter().map(|row| row[2..].join(",")).collect();
+    assert_eq!(
+        cells,
+        vec![
+            "[0, 0],,,,,",
+            ",7,,,,",
+            ",,\"x\",,,",
+            ",,,2,,",
+            ",,,,,k > 0: true",
+            ",,,1,,",
+            ",,,,,k > 0: true",
+            ",,,0,,",
+            ",,,,,k > 0: false",
+            ",,,1,,",
+            ",,,,,k == 1: true",
+            ",,,,2,",
+            ",,,,,j >= 1: true",
+            ",,,,1,",
+            ",,,,,j >= 1: true",
+            ",,,,0,",
+            ",,,,,j >= 1: false",
+        ]
+    );
+}
+
+fn sample() -> TraceTable {
+    trace("x ← 1\ndaca x == 1 { citeste s; scrie s }", "a,\"b\"|c").table()
+}
+
+#[test]
+fn renders_plain_text() {
+    let expected = "\
+step | line | x | s         | condition    | output
+-----+------+---+-----------+--------------+--------
+1    | 1    | 1 |           |              |
+2    | 2    |   |           | x == 1: true |
+3    | 2    |   | \"a,\"b\"|c\" |              |
+4    | 2    |   |           |              | a,\"b\"|c
+";
+    assert_eq!(sample().to_text(), expected);
+}
+
+#[test]
+fn renders_markdown() {
+    let expected = "\
+| step | line | x | s | condition | output |
+|---|---|---|---|---|---|
+| 1 | 1 | 1 |  |  |  |
+| 2 | 2 |  |  | x == 1: true |  |
+| 3 | 2 |  | \"a,\"b\"\\|c\" |  |  |
+| 4 | 2 |  |  |  | a,\"b\"\\|c |
+";
+    assert_eq!(sample().to_markdown(), expected);
+}
+
+#[test]
+fn renders_csv() {
+    let expected = "\
+step,line,x,s,condition,output
+1,1,1,,,
+2,2,,,x == 1: true,
+3,2,,\"\"\"a,\"\"b\"\"|c\"\"\",,
+4,2,,,,\"a,\"\"b\"\"|c\"
+";
+    assert_eq!(sample().to_csv(), expected);
+}
@@ -0,0 +1,1195 @@
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::mem;
+use std::rc::Rc;
+
+use crate::ast::{
+    ArrayLiteral, AssignStatement, BlockStatement, BooleanLiteral, CallExpression, Expression,
+    ExpressionStatement, ForStatement, FunctionLiteral, Identifier, IfStatement,
+    IndexAssignStatement, IndexExpression, InfixExpression, IntegerPartExpression, IntegralLiteral,
+    LetStatement, MapLiteral, Node, PrefixExpression, Program, ReadStatement, RealLiteral,
+    RepeatStatement, ReturnStatement, Statement, StringLiteral, WhileStatement, WriteStatement,
+};
+use crate::builtins::{self, BUILTINS};
+use crate::evaluator::{compound_operator, RuntimeError};
+use crate::lexer::{Span, TokenType};
+use crate::messages::{self, Lang};
+
+#[cfg(test)]
+mod tests;
+
+/// The type a statically typed target gives a variable, inferred from the
+/// values assigned to it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Type {
+    Unknown,
+    Integer,
+    Real,
+    Boolean,
+    Str,
+    Array(Box<Type>),
+    Map(Box<Type>, Box<Type>),
+}
+
+impl Type {
+    /// The type holding values of both `self` and `other`: an integer
+    /// widens to a real, and an unknown takes whatever the other side is.
+    /// Types that do not mix keep the first one seen.
+    pub fn join(self, other: Type) -> Type {
+        match (self, other) {
+            (Type::Unknown, other) => other,
+            (this, Type::Unknown) => this,
+            (Type::Integer, Type::Real) | (Type::Real, Type::Integer) => Type::Real,
+            (Type::Array(a), Type::Array(b)) => Type::Array(Box::new(a.join(*b))),
+            (Type::Map(k, v), Type::Map(l, w)) => {
+                Type::Map(Box::new(k.join(*l)), Box::new(v.join(*w)))
+            }
+            (this, _) => this,
+        }
+    }
+
+    /// Settles what is still unknown after inference, e.g. a variable that
+    /// is only ever read, as an integer.
+    fn or_integer(self) -> Type {
+        match self {
+            Type::Unknown => Type::Integer,
+            Type::Array(element) => Type::Array(Box::new(element.or_integer())),
+            Type::Map(key, value) => {
+                Type::Map(Box::new(key.or_integer()), Box::new(value.or_integer()))
+            }
+            other => other,
+        }
+    }
+
+    fn is_numeric(&self) -> bool {
+        matches!(self, Type::Integer | Type::Real)
+    }
+}
+
+/// Types read as the evaluator names them, e.g. `ARRAY<REAL>`.
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Type::Unknown => f.write_str("unknown"),
+            Type::Integer => f.write_str("INTEGER"),
+            Type::Real => f.write_str("REAL"),
+            Type::Boolean => f.write_str("BOOLEAN"),
+            Type::Str => f.write_str("STRING"),
+            Type::Array(element) => write!(f, "ARRAY<{}>", element),
+            Type::Map(key, value) => write!(f, "MAP<{}, {}>", key, value),
+        }
+    }
+}
+
+/// Variables in the order they are first assigned.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Variables(Vec<(String, Type)>);
+
+impl Variables {
+    pub fn get(&self, name: &str) -> Option<&Type> {
+        self.0.iter().find(|(n, _)| n == name).map(|(_, t)| t)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &(String, Type)> {
+        self.0.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// Adds `name`, or widens its type to also hold `ty`.
+    fn insert(&mut self, name: &str, ty: Type) {
+        match self.0.iter_mut().find(|(n, _)| n == name) {
+            Some((_, existing)) => *existing = existing.clone().join(ty),
+            None => self.0.push((name.to_string(), ty)),
+        }
+    }
+
+    fn settle(&mut self) {
+        for (_, ty) in &mut self.0 {
+            *ty = ty.clone().or_integer();
+        }
+    }
+}
+
+/// A function declared at the top level of the program.
+#[derive(Debug, Clone)]
+pub struct Function {
+    pub name: String,
+    pub parameters: Variables,
+    /// `None` for a function that never returns a value.
+    pub returns: Option<Type>,
+    /// Variables assigned in the body that are neither parameters nor
+    /// top-level variables.
+    pub locals: Variables,
+    pub body: Rc<BlockStatement>,
+}
+
+/// Where a name is looked up: the main program or one of the functions.
+pub type Scope = Option<usize>;
+
+/// What a translation to a statically typed language needs to know about a
+/// program: the variables of the main program and of each function with
+/// their types, and which top-level variables the functions share.
+#[derive(Debug, Clone)]
+pub struct Analysis {
+    pub variables: Variables,
+    pub functions: Vec<Function>,
+    /// Top-level variables used inside a function, which therefore have to
+    /// be declared outside the main program.
+    pub shared: BTreeSet<String>,
+}
+
+const PASSES: usize = 4;
+
+impl Analysis {
+    pub fn new(program: &Program) -> Self {
+        let mut analysis = Analysis {
+            variables: Variables::default(),
+            functions: Vec::new(),
+            shared: BTreeSet::new(),
+        };
+
+        let mut assigned = Vec::new();
+        for statement in &program.statements {
+            let statement = statement.as_ref().borrow();
+            match function_declaration(&*statement) {
+                Some(declaration) => analysis.functions.push(Function {
+                    name: declaration.name,
+                    parameters: Variables(
+                        declaration
+                            .parameters
+                            .into_iter()
+                            .map(|parameter| (parameter, Type::Unknown))
+                            .collect(),
+                    ),
+                    returns: None,
+                    locals: Variables::default(),
+                    body: declaration.body,
+                }),
+                None => assigned_names(&*statement, &mut assigned),
+            }
+        }
+        for name in assigned {
+            analysis.variables.insert(&name, Type::Unknown);
+        }
+
+        for index in 0..analysis.functions.len() {
+            let mut assigned = Vec::new();
+            for statement in &analysis.functions[index].body.statements {
+                assigned_names(&*statement.as_ref().borrow(), &mut assigned);
+            }
+            for name in assigned {
+                let function = &analysis.functions[index];
+                if !function.parameters.contains(&name)
+                    && !analysis.variables.contains(&name)
+                    && analysis.function(&name).is_none()
+                {
+                    analysis.functions[index]
+                        .locals
+                        .insert(&name, Type::Unknown);
+                }
+            }
+        }
+
+        for _ in 0..PASSES {
+            analysis.statements(None, &program.statements);
+            for index in 0..analysis.functions.len() {
+                let body = Rc::clone(&analysis.functions[index].body);
+                analysis.statements(Some(index), &body.statements);
+            }
+        }
+
+        analysis.variables.settle();
+        for function in &mut analysis.functions {
+            function.parameters.settle();
+            function.locals.settle();
+            function.returns = function.returns.take().map(Type::or_integer);
+        }
+        analysis
+    }
+
+    pub fn function(&self, name: &str) -> Option<(usize, &Function)> {
+        self.functions
+            .iter()
+            .enumerate()
+            .find(|(_, function)| function.name == name)
+    }
+
+    /// The type of a variable as seen from `scope`.
+    pub fn variable(&self, scope: Scope, name: &str) -> Type {
+        let local = scope.and_then(|index| {
+            let function = &self.functions[index];
+            function
+                .parameters
+                .get(name)
+                .or_else(|| function.locals.get(name))
+        });
+        local
+            .or_else(|| self.variables.get(name))
+            .cloned()
+            .unwrap_or(Type::Unknown)
+    }
+
+    /// The type of `expression` evaluated in `scope`.
+    pub fn type_of(&self, scope: Scope, expression: &dyn Expression) -> Type {
+        let any = expression.as_any();
+
+        if any.is::<IntegralLiteral>() {
+            Type::Integer
+        } else if any.is::<RealLiteral>() {
+            Type::Real
+        } else if any.is::<StringLiteral>() {
+            Type::Str
+        } else if any.is::<BooleanLiteral>() {
+            Type::Boolean
+        } else if let Some(identifier) = any.downcast_ref::<Identifier>() {
+            self.variable(scope, &identifier.value)
+        } else if let Some(prefix) = any.downcast_ref::<PrefixExpression>() {
+            match prefix.operator.as_str() {
+                "!" => Type::Boolean,
+                _ => self.optional_type(scope, &prefix.right),
+            }
+        } else if any.is::<IntegerPartExpression>() {
+            Type::Integer
+        } else if let Some(infix) = any.downcast_ref::<InfixExpression>() {
+            let left = self.optional_type(scope, &infix.left);
+            let right = self.optional_type(scope, &infix.right);
+            // An operand of unknown type is assumed to fit the other one;
+            // operands that cannot mix give no type, as the program fails.
+            match infix.operator.as_str() {
+                "+" if matches!(
+                    (&left, &right),
+                    (Type::Str, Type::Str | Type::Unknown) | (Type::Unknown, Type::Str)
+                ) =>
+                {
+                    Type::Str
+                }
+                "+" | "-" | "*" => match left.clone().join(right.clone()) {
+                    ty if ty.is_numeric() => ty,
+                    _ if right == Type::Unknown => left,
+                    _ => Type::Unknown,
+                },
+                "/" => Type::Real,
+                "div" | "%" => Type::Integer,
+                _ => Type::Boolean,
+            }
+        } else if let Some(array) = any.downcast_ref::<ArrayLiteral>() {
+            let element = array.elements.iter().fold(Type::Unknown, |ty, element| {
+                ty.join(self.type_of(scope, &*element.as_ref().borrow()))
+            });
+            Type::Array(Box::new(element))
+        } else if let Some(map) = any.downcast_ref::<MapLiteral>() {
+            let (key, value) =
+                map.pairs
+                    .iter()
+                    .fold((Type::Unknown, Type::Unknown), |(key, value), (k, v)| {
+                        (
+                            key.join(self.type_of(scope, &*k.as_ref().borrow())),
+                            value.join(self.type_of(scope, &*v.as_ref().borrow())),
+                        )
+                    });
+            Type::Map(Box::new(key), Box::new(value))
+        } else if let Some(index) = any.downcast_ref::<IndexExpression>() {
+            match self.optional_type(scope, &index.left) {
+                Type::Array(element) => *element,
+                Type::Map(_, value) => *value,
+                Type::Str => Type::Str,
+                _ => Type::Unknown,
+            }
+        } else if let Some(call) = any.downcast_ref::<CallExpression>() {
+            let argument = |index: usize| {
+                call.arguments
+                    .get(index)
+                    .map(|a| self.type_of(scope, &*a.as_ref().borrow()))
+                    .unwrap_or(Type::Unknown)
+            };
+            match callee(call).as_deref() {
+                Some("len" | "floor" | "ceil" | "int") => Type::Integer,
+                Some("sqrt" | "real") => Type::Real,
+                Some("str") => Type::Str,
+                Some("push" | "abs" | "sort") => argument(0),
+                Some("min" | "max") => match argument(0) {
+                    Type::Array(element) if call.arguments.len() == 1 => *element,
+                    first => (1..call.arguments.len()).fold(first, |ty, i| ty.join(argument(i))),
+                },
+                // An integer to an integer power stays one unless the
+                // exponent is negative.
+                Some("pow") => match (argument(0), argument(1)) {
+                    (Type::Real, _) | (_, Type::Real) => Type::Real,
+                    _ => Type::Integer,
+                },
+                Some("random") if call.arguments.is_empty() => Type::Real,
+                Some("random") => Type::Integer,
+                Some("keys") => match argument(0) {
+                    Type::Map(key, _) => Type::Array(key),
+                    _ => Type::Array(Box::new(Type::Unknown)),
+                },
+                Some("values") => match argument(0) {
+                    Type::Map(_, value) => Type::Array(value),
+                    _ => Type::Array(Box::new(Type::Unknown)),
+                },
+                Some(name) => self
+                    .function(name)
+                    .and_then(|(_, function)| function.returns.clone())
+                    .unwrap_or(Type::Unknown),
+                None => Type::Unknown,
+            }
+        } else {
+            Type::Unknown
+        }
+    }
+
+    pub fn optional_type(
+        &self,
+        scope: Scope,
+        expression: &Option<Rc<RefCell<dyn Expression>>>,
+    ) -> Type {
+        expression
+            .as_ref()
+            .map(|e| self.type_of(scope, &*e.as_ref().borrow()))
+            .unwrap_or(Type::Unknown)
+    }
+
+    fn assign(&mut self, scope: Scope, name: &str, ty: Type) {
+        if let Some(index) = scope {
+            let function = &mut self.functions[index];
+            if function.parameters.contains(name) {
+                return function.parameters.insert(name, ty);
+            }
+            if function.locals.contains(name) {
+                return function.locals.insert(name, ty);
+            }
+            self.shared.insert(name.to_string());
+        }
+        self.variables.insert(name, ty);
+    }
+
+    /// Widens the element type of the array or map `target` refers to,
+    /// e.g. after `v[i] ← 2.5` or `push(v, 2.5)`.
+    fn assign_element(&mut self, scope: Scope, target: &dyn Expression, key: Type, value: Type) {
+        let Some(identifier) = target.as_any().downcast_ref::<Identifier>() else {
+            return;
+        };
+        let ty = match self.variable(scope, &identifier.value) {
+            Type::Map(..) => Type::Map(Box::new(key), Box::new(value)),
+            _ => Type::Array(Box::new(value)),
+        };
+        self.assign(scope, &identifier.value, ty);
+    }
+
+    fn statements(&mut self, scope: Scope, statements: &[Rc<RefCell<dyn Statement>>]) {
+        for statement in statements {
+            self.statement(scope, &*statement.as_ref().borrow());
+        }
+    }
+
+    fn statement(&mut self, scope: Scope, statement: &dyn Statement) {
+        let any = statement.as_any();
+
+        if let Some(statement) = any.downcast_ref::<ExpressionStatement>() {
+            self.optional_expression(scope, &statement.expression);
+        } else if let Some(statement) = any.downcast_ref::<LetStatement>() {
+            if function_declaration(statement).is_some() {
+                return;
+            }
+            self.optional_expression(scope, &statement.value);
+            if let Some(name) = &statement.name {
+                let ty = self.optional_type(scope, &statement.value);
+                self.assign(scope, &name.value, ty);
+            }
+        } else if let Some(statement) = any.downcast_ref::<AssignStatement>() {
+            self.optional_expression(scope, &statement.value);
+            let ty = match statement.token.token_type {
+                TokenType::SlashAssign => Type::Real,
+                _ => self.optional_type(scope, &statement.value),
+            };
+            self.assign(scope, &statement.name.value, ty);
+        } else if let Some(statement) = any.downcast_ref::<IndexAssignStatement>() {
+            self.expression(scope, &*statement.target.as_ref().borrow());
+            self.optional_expression(scope, &statement.value);
+            let target = statement.target.as_ref().borrow();
+            if let Some(index) = target.as_any().downcast_ref::<IndexExpression>() {
+                let key = self.optional_type(scope, &index.index);
+                let value = self.optional_type(scope, &statement.value);
+                if let Some(left) = &index.left {
+                    self.assign_element(scope, &*left.as_ref().borrow(), key, value);
+                }
+            }
+        } else if let Some(statement) = any.downcast_ref::<ReturnStatement>() {
+            self.optional_expression(scope, &statement.return_value);
+            if let (Some(index), Some(_)) = (scope, &statement.return_value) {
+                let ty = self.optional_type(scope, &statement.return_value);
+                let function = &mut self.functions[index];
+                function.returns = Some(function.returns.take().unwrap_or(Type::Unknown).join(ty));
+            }
+        } else if let Some(block) = any.downcast_ref::<BlockStatement>() {
+            self.statements(scope, &block.statements);
+        } else if let Some(statement) = any.downcast_ref::<IfStatement>() {
+            self.optional_expression(scope, &statement.condition);
+            self.statements(scope, &statement.consequence.statements);
+            if let Some(alternative) = &statement.alternative {
+                self.statements(scope, &alternative.statements);
+            }
+        } else if let Some(statement) = any.downcast_ref::<WhileStatement>() {
+            self.optional_expression(scope, &statement.condition);
+            self.statements(scope, &statement.body.statements);
+        } else if let Some(statement) = any.downcast_ref::<RepeatStatement>() {
+            self.statements(scope, &statement.body.statements);
+            self.optional_expression(scope, &statement.condition);
+        } else if let Some(statement) = any.downcast_ref::<ForStatement>() {
+            for bound in [&statement.start, &statement.end, &statement.step] {
+                self.optional_expression(scope, bound);
+            }
+            let ty = self
+                .optional_type(scope, &statement.start)
+                .join(self.optional_type(scope, &statement.step));
+            self.assign(scope, &statement.counter.value, ty);
+            self.statements(scope, &statement.body.statements);
+        } else if let Some(statement) = any.downcast_ref::<ReadStatement>() {
+            for target in &statement.targets {
+                let target = target.as_ref().borrow();
+                self.expression(scope, &*target);
+                if let Some(identifier) = target.as_any().downcast_ref::<Identifier>() {
+                    self.assign(scope, &identifier.value, Type::Unknown);
+                }
+            }
+        } else if let Some(statement) = any.downcast_ref::<WriteStatement>() {
+            for argument in &statement.arguments {
+                self.expression(scope, &*argument.as_ref().borrow());
+            }
+        }
+    }
+
+    fn optional_expression(
+        &mut self,
+        scope: Scope,
+        expression: &Option<Rc<RefCell<dyn Expression>>>,
+    ) {
+        if let Some(expression) = expression {
+            self.expression(scope, &*expression.as_ref().borrow());
+        }
+    }
+
+    /// Records what an expression tells about types elsewhere: the
+    /// arguments of a call type the parameters, `push` types the elements,
+    /// and a top-level name used in a function is shared.
+    fn expression(&mut self, scope: Scope, expression: &dyn Expression) {
+        let any = expression.as_any();
+
+        if let Some(identifier) = any.downcast_ref::<Identifier>() {
+            let name = &identifier.value;
+            if let Some(index) = scope {
+                let function = &self.functions[index];
+                if !function.parameters.contains(name)
+                    && !function.locals.contains(name)
+                    && self.variables.contains(name)
+                {
+                    self.shared.insert(name.clone());
+                }
+            }
+        } else if let Some(prefix) = any.downcast_ref::<PrefixExpression>() {
+            self.optional_expression(scope, &prefix.right);
+        } else if let Some(integer_part) = any.downcast_ref::<IntegerPartExpression>() {
+            self.optional_expression(scope, &integer_part.value);
+        } else if let Some(infix) = any.downcast_ref::<InfixExpression>() {
+            self.optional_expression(scope, &infix.left);
+            self.optional_expression(scope, &infix.right);
+        } else if let Some(array) = any.downcast_ref::<ArrayLiteral>() {
+            for element in &array.elements {
+                self.expression(scope, &*element.as_ref().borrow());
+            }
+        } else if let Some(map) = any.downcast_ref::<MapLiteral>() {
+            for (key, value) in &map.pairs {
+                self.expression(scope, &*key.as_ref().borrow());
+                self.expression(scope, &*value.as_ref().borrow());
+            }
+        } else if let Some(index) = any.downcast_ref::<IndexExpression>() {
+            self.optional_expression(scope, &index.left);
+            self.optional_expression(scope, &index.index);
+        } else if let Some(call) = any.downcast_ref::<CallExpression>() {
+            for argument in &call.arguments {
+                self.expression(scope, &*argument.as_ref().borrow());
+            }
+            let types: Vec<Type> = call
+                .arguments
+                .iter()
+                .map(|a| self.type_of(scope, &*a.as_ref().borrow()))
+                .collect();
+
+            match callee(call).as_deref() {
+                Some("push") if types.len() == 2 => {
+                    let target = call.arguments[0].as_ref().borrow();
+                    self.assign_element(scope, &*target, Type::Unknown, types[1].clone());
+                }
+                Some(name) => {
+                    if let Some((index, _)) = self.function(name) {
+                        let parameters = &mut self.functions[index].parameters.0;
+                        for ((_, parameter), ty) in parameters.iter_mut().zip(types) {
+                            *parameter = parameter.clone().join(ty);
+                        }
+                    }
+                }
+                None => {}
+            }
+        }
+    }
+}
+
+/// A function declared by `functie f(a) { ... }` or `let f = fn(a) { ... }`.
+#[derive(Debug, Clone)]
+pub struct Declaration {
+    pub name: String,
+    pub parameters: Vec<String>,
+    pub body: Rc<BlockStatement>,
+}
+
+pub fn function_declaration(statement: &dyn Statement) -> Option<Declaration> {
+    let statement = statement.as_any().downcast_ref::<LetStatement>()?;
+    let value = statement.value.as_ref()?.as_ref().borrow();
+    let function = value.as_any().downcast_ref::<FunctionLiteral>()?;
+    let name = function
+        .name
+        .clone()
+        .or_else(|| statement.name.as_ref().map(|name| name.value.clone()))?;
+    Some(Declaration {
+        name,
+        parameters: function
+            .parameters
+            .iter()
+            .map(|p| p.value.clone())
+            .collect(),
+        body: Rc::clone(&function.body),
+    })
+}
+
+/// The name of the function a call refers to directly, as in `f(x)`.
+pub fn callee(call: &CallExpression) -> Option<String> {
+    let function = call.function.as_ref()?.as_ref().borrow();
+    function
+        .as_any()
+        .downcast_ref::<Identifier>()
+        .map(|identifier| identifier.value.clone())
+}
+
+/// Names given a value by `statement`, outside of nested functions.
+pub fn assigned_names(statement: &dyn Statement, names: &mut Vec<String>) {
+    let any = statement.as_any();
+    let mut add = |name: &str| {
+        if !names.iter().any(|n| n == name) {
+            names.push(name.to_string());
+        }
+    };
+
+    if let Some(statement) = any.downcast_ref::<LetStatement>() {
+        if function_declaration(statement).is_none() {
+            if let Some(name) = &statement.name {
+                add(&name.value);
+            }
+        }
+    } else if let Some(statement) = any.downcast_ref::<AssignStatement>() {
+        add(&statement.name.value);
+    } else if let Some(statement) = any.downcast_ref::<ForStatement>() {
+        add(&statement.counter.value);
+        block_names(&statement.body, names);
+    } else if let Some(statement) = any.downcast_ref::<ReadStatement>() {
+        for target in &statement.targets {
+            if let Some(identifier) = target
+                .as_ref()
+                .borrow()
+                .as_any()
+                .downcast_ref::<Identifier>()
+            {
+                add(&identifier.value);
+            }
+        }
+    } else if let Some(block) = any.downcast_ref::<BlockStatement>() {
+        block_names(block, names);
+    } else if let Some(statement) = any.downcast_ref::<IfStatement>() {
+        block_names(&statement.consequence, names);
+        if let Some(alternative) = &statement.alternative {
+            block_names(alternative, names);
+        }
+    } else if let Some(statement) = any.downcast_ref::<WhileStatement>() {
+        block_names(&statement.body, names);
+    } else if let Some(statement) = any.downcast_ref::<RepeatStatement>() {
+        block_names(&statement.body, names);
+    }
+}
+
+fn block_names(block: &BlockStatement, names: &mut Vec<String>) {
+    for statement in &block.statements {
+        assigned_names(&*statement.as_ref().borrow(), names);
+    }
+}
+
+/// An operation that is certain to fail when it is reached: `error` is what
+/// evaluating the expression at `span` would raise.
+#[derive(Debug)]
+pub struct TypeError {
+    pub error: RuntimeError,
+    pub span: Span,
+}
+
+impl TypeError {
+    /// The message of the error the evaluator would fail with, and where.
+    pub fn message(&self, lang: Lang) -> String {
+        messages::message(
+            "located",
+            lang,
+            &[
+                &self.error.message(lang),
+                &self.span.line,
+                &self.span.column,
+            ],
+        )
+    }
+}
+
+impl fmt::Display for TypeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message(Lang::En))
+    }
+}
+
+impl std::error::Error for TypeError {}
+
+/// Reports the operations in `program` whose operands have types the
+/// evaluator rejects, without running it.
+///
+/// Types follow the flow of the program: a variable has the type of the
+/// value last assigned to it, and where two paths disagree it is no longer
+/// checked. Function bodies are checked on their own, whatever the calls
+/// pass them, so parameters are never checked, and neither are values read
+/// with `citeste` and top-level variables a function uses. An error is only
+/// reported when every execution reaching it fails.
+pub fn check(program: &Program) -> Vec<TypeError> {
+    let analysis = Analysis::new(program);
+    let mut assigned: BTreeSet<String> = analysis
+        .variables
+        .iter()
+        .map(|(name, _)| name.clone())
+        .collect();
+    for function in &analysis.functions {
+        for (name, _) in function.parameters.iter().chain(function.locals.iter()) {
+            assigned.insert(name.clone());
+        }
+    }
+
+    let mut checker = Checker {
+        functions: analysis
+            .functions
+            .iter()
+            .map(|function| (function.name.clone(), function.parameters.0.len()))
+            .collect(),
+        shared: analysis.shared,
+        assigned,
+        variables: HashMap::new(),
+        errors: Vec::new(),
+        report: true,
+    };
+    checker.statements(&program.statements);
+    checker.errors
+}
+
+type Flow = HashMap<String, Type>;
+
+/// The type of a variable that holds `a` on one path and `b` on another.
+fn unify(a: &Type, b: &Type) -> Type {
+    match (a, b) {
+        (Type::Array(a), Type::Array(b)) => Type::Array(Box::new(unify(a, b))),
+        (Type::Map(k, v), Type::Map(l, w)) => {
+            Type::Map(Box::new(unify(k, l)), Box::new(unify(v, w)))
+        }
+        (a, b) if a == b => a.clone(),
+        _ => Type::Unknown,
+    }
+}
+
+/// Joins the variables of two paths. A name assigned on only one of them
+/// keeps its type, since reading it after the other one fails anyway.
+fn merge(a: &Flow, b: &Flow) -> Flow {
+    let mut merged = a.clone();
+    for (name, ty) in b {
+        let ty = match merged.get(name) {
+            Some(existing) => unify(existing, ty),
+            None => ty.clone(),
+        };
+        merged.insert(name.clone(), ty);
+    }
+    merged
+}
+
+fn type_name(ty: &Type) -> Option<&'static str> {
+    match ty {
+        Type::Unknown => None,
+        Type::Integer => Some("INTEGER"),
+        Type::Real => Some("REAL"),
+        Type::Boolean => Some("BOOLEAN"),
+        Type::Str => Some("STRING"),
+        Type::Array(_) => Some("ARRAY"),
+        Type::Map(..) => Some("MAP"),
+    }
+}
+
+/// Whether the builtin `name` takes `argument` at `index` when called with
+/// `count` arguments, following `builtins`.
+fn accepts(name: &str, index: usize, argument: &Type, count: usize) -> bool {
+    let number = matches!(argument, Type::Integer | Type::Real);
+    match name {
+        "len" => matches!(argument, Type::Str | Type::Array(_) | Type::Map(..)),
+        "push" => index > 0 || matches!(argument, Type::Array(_)),
+        "sort" => matches!(argument, Type::Array(_)),
+        "keys" | "values" => matches!(argument, Type::Map(..)),
+        "abs" | "sqrt" | "pow" | "floor" | "ceil" => number,
+        "min" | "max" => number || (count == 1 && matches!(argument, Type::Array(_))),
+        "int" | "real" => number || *argument == Type::Str,
+        "random" | "seed" => *argument == Type::Integer,
+        _ => true,
+    }
+}
+
+/// The result of `left operator right` for operands of known types,
+/// following `evaluator::eval_infix_expression`.
+fn infix_type(operator: &str, left: &Type, right: &Type) -> Result<Type, RuntimeError> {
+    let comparison = matches!(operator, "<" | "<=" | ">" | ">=" | "==" | "!=");
+    let (Some(left_name), Some(right_name)) = (type_name(left), type_name(right)) else {
+        return Ok(if comparison {
+            Type::Boolean
+        } else {
+            Type::Unknown
+        });
+    };
+    let unknown_operator = || RuntimeError::UnknownInfixOperator {
+        left: left_name,
+        operator: operator.to_string(),
+        right: right_name,
+    };
+
+    match (left, right) {
+        (Type::Integer, Type::Integer) => match operator {
+            "+" | "-" | "*" | "div" | "%" => Ok(Type::Integer),
+            // Exact division only stays integral when the quotient is whole.
+            "/" => Ok(Type::Unknown),
+            _ if comparison => Ok(Type::Boolean),
+            _ => Err(unknown_operator()),
+        },
+        (Type::Str, Type::Str) => match operator {
+            "+" => Ok(Type::Str),
+            _ if comparison => Ok(Type::Boolean),
+            _ => Err(unknown_operator()),
+        },
+        (Type::Boolean, Type::Boolean) => match operator {
+            "==" | "!=" => Ok(Type::Boolean),
+            _ => Err(unknown_operator()),
+        },
+        _ if left.is_numeric() && right.is_numeric() => match operator {
+            "+" | "-" | "*" | "/" => Ok(Type::Real),
+            _ if comparison => Ok(Type::Boolean),
+            _ => Err(unknown_operator()),
+        },
+        _ if left_name != right_name => Err(RuntimeError::TypeMismatch {
+            left: left_name,
+            operator: operator.to_string(),
+            right: right_name,
+        }),
+        _ => Err(unknown_operator()),
+    }
+}
+
+fn is_hashable(ty: &Type) -> bool {
+    matches!(
+        ty,
+        Type::Unknown | Type::Integer | Type::Boolean | Type::Str
+    )
+}
+
+struct Checker {
+    /// The functions declared at the top level and how many parameters
+    /// each takes.
+    functions: HashMap<String, usize>,
+    /// Top-level variables a function reads or assigns, whose type may
+    /// change with any call.
+    shared: BTreeSet<String>,
+    /// Every variable name, so that one hiding a builtin is not checked as
+    /// the builtin.
+    assigned: BTreeSet<String>,
+    variables: Flow,
+    errors: Vec<TypeError>,
+    /// Off while a loop body is walked to find the types it starts with.
+    report: bool,
+}
+
+impl Checker {
+    fn error(&mut self, span: Span, error: RuntimeError) {
+        if self.report {
+            self.errors.push(TypeError { error, span });
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Type {
+        if self.shared.contains(name) {
+            return Type::Unknown;
+        }
+        self.variables.get(name).cloned().unwrap_or(Type::Unknown)
+    }
+
+    fn assign(&mut self, name: &str, ty: Type) {
+        self.variables.insert(name.to_string(), ty);
+    }
+
+    /// Sets the variables to the ones a loop body starts with on any
+    /// iteration: those before the loop joined with those after each pass.
+    fn loop_entry(&mut self, pass: impl Fn(&mut Self)) {
+        let report = mem::replace(&mut self.report, false);
+        let before = self.variables.clone();
+        for _ in 0..PASSES {
+            let entry = self.variables.clone();
+            pass(self);
+            self.variables = merge(&before, &self.variables);
+            if self.variables == entry {
+                break;
+            }
+        }
+        self.report = report;
+    }
+
+    /// Checks a function body on its own: it only knows its parameters are
+    /// there, not what they hold.
+    fn function(&mut self, body: &BlockStatement) {
+        let outer = mem::take(&mut self.variables);
+        self.statements(&body.statements);
+        self.variables = outer;
+    }
+
+    fn statements(&mut self, statements: &[Rc<RefCell<dyn Statement>>]) {
+        for statement in statements {
+            self.statement(&*statement.as_ref().borrow());
+        }
+    }
+
+    fn statement(&mut self, statement: &dyn Statement) {
+        let any = statement.as_any();
+
+        if let Some(statement) = any.downcast_ref::<ExpressionStatement>() {
+            self.optional_type(&statement.expression);
+        } else if let Some(statement) = any.downcast_ref::<LetStatement>() {
+            let ty = self.optional_type(&statement.value);
+            if let Some(name) = &statement.name {
+                self.assign(&name.value, ty);
+            }
+        } else if let Some(statement) = any.downcast_ref::<AssignStatement>() {
+            let value = self.optional_type(&statement.value);
+            let ty = match compound_operator(&statement.token.token_type) {
+                Some(operator) => {
+                    let current = self.lookup(&statement.name.value);
+                    match infix_type(operator, &current, &value) {
+                        Ok(ty) => ty,
+                        Err(error) => {
+                            self.error(statement.span(), error);
+                            Type::Unknown
+                        }
+                    }
+                }
+                None => value,
+            };
+            self.assign(&statement.name.value, ty);
+        } else if let Some(statement) = any.downcast_ref::<IndexAssignStatement>() {
+            let value = self.optional_type(&statement.value);
+            let target = statement.target.as_ref().borrow();
+            let Some(index) = target.as_any().downcast_ref::<IndexExpression>() else {
+                return;
+            };
+            let (left, _) = self.index(index);
+            let identifier = index.left.as_ref().and_then(|left| {
+                let left = left.as_ref().borrow();
+                left.as_any()
+                    .downcast_ref::<Identifier>()
+                    .map(|identifier| identifier.value.clone())
+            });
+            if let Some(name) = identifier {
+                let ty = match left {
+                    Type::Array(element) => Type::Array(Box::new(unify(&element, &value))),
+                    Type::Map(key, element) => Type::Map(key, Box::new(unify(&element, &value))),
+                    other => other,
+                };
+                self.assign(&name, ty);
+            }
+        } else if let Some(statement) = any.downcast_ref::<ReturnStatement>() {
+            self.optional_type(&statement.return_value);
+        } else if let Some(block) = any.downcast_ref::<BlockStatement>() {
+            self.statements(&block.statements);
+        } else if let Some(statement) = any.downcast_ref::<IfStatement>() {
+            self.optional_type(&statement.condition);
+            let before = self.variables.clone();
+            self.statements(&statement.consequence.statements);
+            let consequence = mem::replace(&mut self.variables, before);
+            if let Some(alternative) = &statement.alternative {
+                self.statements(&alternative.statements);
+            }
+            self.variables = merge(&consequence, &self.variables);
+        } else if let Some(statement) = any.downcast_ref::<WhileStatement>() {
+            let pass = |checker: &mut Self| {
+                checker.optional_type(&statement.condition);
+                checker.statements(&statement.body.statements);
+            };
+            self.loop_entry(pass);
+            let entry = self.variables.clone();
+            pass(self);
+            self.variables = merge(&entry, &self.variables);
+        } else if let Some(statement) = any.downcast_ref::<RepeatStatement>() {
+            let pass = |checker: &mut Self| {
+                checker.statements(&statement.body.statements);
+                checker.optional_type(&statement.condition);
+            };
+            self.loop_entry(pass);
+            pass(self);
+        } else if let Some(statement) = any.downcast_ref::<ForStatement>() {
+            let start = self.optional_type(&statement.start);
+            self.optional_type(&statement.end);
+            let step = match &statement.step {
+                Some(_) => self.optional_type(&statement.step),
+                None => Type::Integer,
+            };
+            let counter = match (start, step) {
+                (Type::Integer, Type::Integer) => Type::Integer,
+                (Type::Real, step) if step.is_numeric() => Type::Real,
+                _ => Type::Unknown,
+            };
+            self.assign(&statement.counter.value, counter);
+            let pass = |checker: &mut Self| checker.statements(&statement.body.statements);
+            self.loop_entry(pass);
+            let entry = self.variables.clone();
+            pass(self);
+            self.variables = merge(&entry, &self.variables);
+        } else if let Some(statement) = any.downcast_ref::<ReadStatement>() {
+            for target in &statement.targets {
+                let target = target.as_ref().borrow();
+                match target.as_any().downcast_ref::<Identifier>() {
+                    Some(identifier) => self.assign(&identifier.value, Type::Unknown),
+                    None => {
+                        self.type_of(&*target);
+                    }
+                }
+            }
+        } else if let Some(statement) = any.downcast_ref::<WriteStatement>() {
+            for argument in &statement.arguments {
+                self.type_of(&*argument.as_ref().borrow());
+            }
+        }
+    }
+
+    fn optional_type(&mut self, expression: &Option<Rc<RefCell<dyn Expression>>>) -> Type {
+        expression
+            .as_ref()
+            .map(|e| self.type_of(&*e.as_ref().borrow()))
+            .unwrap_or(Type::Unknown)
+    }
+
+    /// Checks an index expression, returning the type of what is indexed
+    /// and of the element.
+    fn index(&mut self, index: &IndexExpression) -> (Type, Type) {
+        let left = self.optional_type(&index.left);
+        let key = self.optional_type(&index.index);
+        let element = match (&left, type_name(&key)) {
+            (Type::Unknown, _) => Type::Unknown,
+            (Type::Array(element), None | Some("INTEGER")) => (**element).clone(),
+            (Type::Map(_, value), _) if is_hashable(&key) => (**value).clone(),
+            (Type::Map(..), Some(key)) => {
+                self.error(index.span(), RuntimeError::UnhashableKey(key));
+                Type::Unknown
+            }
+            (left, key) => {
+                if let (Some(left), Some(index_name)) = (type_name(left), key) {
+                    self.error(
+                        index.span(),
+                        RuntimeError::IndexNotSupported {
+                            left,
+                            index: index_name,
+                        },
+                    );
+                }
+                Type::Unknown
+            }
+        };
+        (left, element)
+    }
+
+    fn type_of(&mut self, expression: &dyn Expression) -> Type {
+        let any = expression.as_any();
+
+        if any.is::<IntegralLiteral>() {
+            Type::Integer
+        } else if any.is::<RealLiteral>() {
+            Type::Real
+        } else if any.is::<StringLiteral>() {
+            Type::Str
+        } else if any.is::<BooleanLiteral>() {
+            Type::Boolean
+        } else if let Some(identifier) = any.downcast_ref::<Identifier>() {
+            self.lookup(&identifier.value)
+        } else if let Some(prefix) = any.downcast_ref::<PrefixExpression>() {
+            let right = self.optional_type(&prefix.right);
+            match (prefix.operator.as_str(), type_name(&right)) {
+                ("!", _) => Type::Boolean,
+                (_, None) => Type::Unknown,
+                (_, Some(_)) if right.is_numeric() => right,
+                (operator, Some(name)) => {
+                    self.error(
+                        prefix.span(),
+                        RuntimeError::UnknownPrefixOperator {
+                            operator: operator.to_string(),
+                            right: name,
+                        },
+                    );
+                    Type::Unknown
+                }
+            }
+        } else if let Some(integer_part) = any.downcast_ref::<IntegerPartExpression>() {
+            let value = self.optional_type(&integer_part.value);
+            match type_name(&value) {
+                Some(name) if !value.is_numeric() => {
+                    self.error(
+                        integer_part.span(),
+                        RuntimeError::InvalidOperand {
+                            operator: "[]".to_string(),
+                            operand: name,
+                        },
+                    );
+                    Type::Unknown
+                }
+                _ => Type::Integer,
+            }
+        } else if let Some(infix) = any.downcast_ref::<InfixExpression>() {
+            let left = self.optional_type(&infix.left);
+            let right = self.optional_type(&infix.right);
+            if matches!(infix.operator.as_str(), "&&" | "||") {
+                return Type::Boolean;
+            }
+            infix_type(&infix.operator, &left, &right).unwrap_or_else(|error| {
+                self.error(infix.span(), error);
+                Type::Unknown
+            })
+        } else if let Some(array) = any.downcast_ref::<ArrayLiteral>() {
+            let mut element = None;
+            for e in &array.elements {
+                let ty = self.type_of(&*e.as_ref().borrow());
+                element = Some(match element {
+                    Some(element) => unify(&element, &ty),
+                    None => ty,
+                });
+            }
+            Type::Array(Box::new(element.unwrap_or(Type::Unknown)))
+        } else if let Some(map) = any.downcast_ref::<MapLiteral>() {
+            let mut pair: Option<(Type, Type)> = None;
+            for (k, v) in &map.pairs {
+                let k = k.as_ref().borrow();
+                let key = self.type_of(&*k);
+                if let (false, Some(name)) = (is_hashable(&key), type_name(&key)) {
+                    self.error(k.span(), RuntimeError::UnhashableKey(name));
+                }
+                let value = self.type_of(&*v.as_ref().borrow());
+                pair = Some(match pair {
+                    Some((k, v)) => (unify(&k, &key), unify(&v, &value)),
+                    None => (key, value),
+                });
+            }
+            let (key, value) = pair.unwrap_or((Type::Unknown, Type::Unknown));
+            Type::Map(Box::new(key), Box::new(value))
+        } else if let Some(index) = any.downcast_ref::<IndexExpression>() {
+            self.index(index).1
+        } else if let Some(function) = any.downcast_ref::<FunctionLiteral>() {
+            self.function(&function.body);
+            Type::Unknown
+        } else if let Some(call) = any.downcast_ref::<CallExpression>() {
+            self.call(call)
+        } else {
+            Type::Unknown
+        }
+    }
+
+    fn call(&mut self, call: &CallExpression) -> Type {
+        let arguments: Vec<Type> = call
+            .arguments
+            .iter()
+            .map(|a| self.type_of(&*a.as_ref().borrow()))
+            .collect();
+        let Some(name) = callee(call) else {
+            self.optional_type(&call.function);
+            return Type::Unknown;
+        };
+
+        if let Some(&expected) = self.functions.get(&name) {
+            if expected != arguments.len() {
+                self.error(
+                    call.span(),
+                    RuntimeError::WrongArgumentCount {
+                        function: name,
+                        expected,
+                        got: arguments.len(),
+                    },
+                );
+            }
+            return Type::Unknown;
+        }
+        if self.assigned.contains(&name) || !BUILTINS.contains(&name.as_str()) {
+            return Type::Unknown;
+        }
+
+        let arity = builtins::arity(&name).expect("a standard builtin");
+        if !arity.accepts(arguments.len()) {
+            self.error(
+                call.span(),
+                RuntimeError::WrongArgumentCount {
+                    function: name,
+                    expected: arity.nearest(arguments.len()),
+                    got: arguments.len(),
+                },
+            );
+            return Type::Unknown;
+        }
+        let rejected = arguments
+            .iter()
+            .enumerate()
+            .filter(|(index, argument)| !accepts(&name, *index, argument, arguments.len()))
+            .find_map(|(_, argument)| type_name(argument));
+        if let Some(type_name) = rejected {
+            self.error(
+                call.span(),
+                RuntimeError::InvalidArgument {
+                    function: name,
+                    argument: type_name,
+                },
+            );
+            return Type::Unknown;
+        }
+        // Only `random()` takes no arguments.
+        let Some(argument) = arguments.first() else {
+            return Type::Real;
+        };
+
+        match (name.as_str(), argument) {
+            ("push", Type::Array(element)) => {
+                let target = call.arguments[0].as_ref().borrow();
+                if let Some(identifier) = target.as_any().downcast_ref::<Identifier>() {
+                    let element = Type::Array(Box::new(unify(element, &arguments[1])));
+                    self.assign(&identifier.value, element);
+                }
+                Type::Unknown
+            }
+            ("len" | "floor" | "ceil" | "int" | "random", _) => Type::Integer,
+            ("sqrt" | "real", _) => Type::Real,
+            ("str", _) => Type::Str,
+            ("abs" | "sort", argument) => argument.clone(),
+            ("keys", Type::Map(key, _)) => Type::Array(key.clone()),
+            ("values", Type::Map(_, value)) => Type::Array(value.clone()),
+            _ => Type::Unknown,
+        }
+    }
+}
@@ -0,0 +1,186 @@
+use crate::messages::Lang;
+use crate::test_support::parse;
+
+use super::{check, Analysis, Type};
+
+fn errors(input: &str) -> Vec<String> {
+    check(&parse(input))
+        .iter()
+        .map(|error| error.to_string())
+        .collect()
+}
+
+#[test]
+fn infers_variable_types() {
+    let analysis = Analysis::new(&parse(
+        "citeste n
+         v ← []
+         push(v, 2.5)
+         m ← {}
+         m[\"a\"] ← true
+         functie f(a, b) { r ← a + str(b); returneaza r + \"!\" }
+         scrie f(\"x\", n), n / 2",
+    ));
+
+    let variables: Vec<(String, Type)> = analysis.variables.iter().cloned().collect();
+    assert_eq!(
+        variables,
+        vec![
+            ("n".to_string(), Type::Integer),
+            ("v".to_string(), Type::Array(Box::new(Type::Real))),
+            (
+                "m".to_string(),
+                Type::Map(Box::new(Type::Str), Box::new(Type::Boolean))
+            ),
+        ]
+    );
+
+    let function = &analysis.functions[0];
+    let parameters: Vec<(String, Type)> = function.parameters.iter().cloned().collect();
+    assert_eq!(
+        parameters,
+        vec![
+            ("a".to_string(), Type::Str),
+            ("b".to_string(), Type::Integer)
+        ]
+    );
+    assert_eq!(function.locals.get("r"), Some(&Type::Str));
+    assert_eq!(function.returns, Some(Type::Str));
+    assert!(analysis.shared.is_empty());
+}
+
+#[test]
+fn checks_function_bodies() {
+    // Parameters may hold anything, but what the body builds itself is
+    // checked.
+    assert!(errors("functie f(a, b) { returneaza a + b }\nscrie f(\"x\", 1)").is_empty());
+    assert_eq!(
+        errors("functie f(a) {\n  r ← \"x\" + str(a)\n  returneaza r + 1\n}"),
+        vec!["type mismatch: STRING + INTEGER at 3:16"]
+    );
+    assert_eq!(
+        errors("g ← functie() { returneaza [1, 2] * 2 }"),
+        vec!["type mismatch: ARRAY * INTEGER at 1:35"]
+    );
+}
+
+#[test]
+fn reports_type_errors() {
+    let tests = [
+        (
+            "x ← 1 + adevarat",
+            "type mismatch: INTEGER + BOOLEAN at 1:7",
+        ),
+        (
+            "s ← \"a\"\nscrie s - 1",
+            "type mismatch: STRING - INTEGER at 2:9",
+        ),
+        (
+            "scrie 2.5 div 2",
+            "unknown operator: REAL div INTEGER at 1:11",
+        ),
+        (
+            "scrie adevarat < fals",
+            "unknown operator: BOOLEAN < BOOLEAN at 1:16",
+        ),
+        ("scrie -\"a\"", "unknown operator: -STRING at 1:7"),
+        ("scrie [\"a\"]", "invalid operand for []: STRING at 1:7"),
+        (
+            "v ← [1, 2]\nscrie v[\"a\"]",
+            "index operator not supported: ARRAY[STRING] at 2:8",
+        ),
+        (
+            "n ← 3\nscrie n[0]",
+            "index operator not supported: INTEGER[INTEGER] at 2:8",
+        ),
+        ("m ← {2.5: 1}", "unusable as map key: REAL at 1:6"),
+        (
+            "scrie len(1)",
+            "argument to `len` not supported, got INTEGER at 1:10",
+        ),
+        (
+            "scrie push([])",
+            "wrong number of arguments to `push`: got=1, want=2 at 1:11",
+        ),
+        (
+            "functie f(a) { returneaza a }\nscrie f(1, 2)",
+            "wrong number of arguments to `f`: got=2, want=1 at 2:8",
+        ),
+        (
+            "x ← \"a\"\nx += 1",
+            "type mismatch: STRING + INTEGER at 2:1",
+        ),
+        (
+            "v ← [1,]\npush(v, 2)\nscrie len(v) + v[0] * \"x\"",
+            "type mismatch: INTEGER * STRING at 3:21",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        assert_eq!(errors(input), vec![expected.to_string()], "{}", input);
+    }
+}
+
+#[test]
+fn reports_type_errors_in_romanian() {
+    let errors: Vec<_> = check(&parse("s ← \"a\"\nscrie s - 1"))
+        .iter()
+        .map(|error| error.message(Lang::Ro))
+        .collect();
+    assert_eq!(
+        errors,
+        vec!["tipuri incompatibile: STRING - INTEGER la 2:9"]
+    );
+}
+
+#[test]
+fn checks_builtin_calls() {
+    assert!(errors("scrie pow(2, 3), min(1, 2, 3), max([1,]), random(), sort([2, 1])").is_empty());
+    assert_eq!(
+        errors("scrie str(sqrt(2)) - 1, floor(1.5) + \"a\""),
+        vec![
+            "type mismatch: STRING - INTEGER at 1:20",
+            "type mismatch: INTEGER + STRING at 1:36",
+        ]
+    );
+    assert_eq!(
+        errors("scrie abs(\"x\"), random(1, 2.5), pow(2)"),
+        vec![
+            "argument to `abs` not supported, got STRING at 1:10",
+            "argument to `random` not supported, got REAL at 1:23",
+            "wrong number of arguments to `pow`: got=1, want=2 at 1:36",
+        ]
+    );
+}
+
+#[test]
+fn follows_the_flow_of_the_program() {
+    // The type a variable has at each point is that of its last assignment.
+    assert!(errors("x ← \"a\"\nx ← 1\nscrie x + 1").is_empty());
+    assert_eq!(
+        errors("x ← 1\ndaca x > 0 { x ← 2 } altfel { x ← 3 }\nscrie x + \"a\""),
+        vec!["type mismatch: INTEGER + STRING at 3:9"]
+    );
+
+    // Where paths disagree, or the value comes from outside, nothing is known.
+    let unknown = [
+        "x ← 1\ndaca x > 0 { x ← \"a\" }\nscrie x + 1",
+        "x ← 1\ncat timp x < 3 { scrie x * 2; x ← \"a\" }",
+        "x ← \"a\"\nrepeta { x ← 1 } pana cand x > 0\nscrie x + 1",
+        "citeste x\nscrie x + 1, x + \"a\"",
+        "functie f(a) { returneaza a + 1 }\nscrie f(\"a\")",
+        "x ← 1\nfunctie f() { x ← \"a\" }\nf()\nscrie x + 1",
+        "pentru i ← 1, 3 { scrie i / 2 }\nscrie 7 / 2 div 1",
+        "scrie adevarat si 1, fals sau \"a\"",
+        "len ← 2\nscrie len",
+    ];
+    for input in unknown {
+        assert!(errors(input).is_empty(), "{}: {:?}", input, errors(input));
+    }
+
+    // Every iteration starts with the types left by the previous one.
+    assert_eq!(
+        errors("pentru i ← 1, 3 { x ← i }\nx ← adevarat\nscrie -x"),
+        vec!["unknown operator: -BOOLEAN at 3:7"]
+    );
+}
@@ -0,0 +1,423 @@
+use std::collections::BTreeMap;
+use std::io::{BufRead, Write};
+use std::rc::Rc;
+
+use crate::builtins::{Builtins, BUILTINS};
+use crate::code::{self, Opcode};
+use crate::compiler::Bytecode;
+use crate::evaluator::{
+    check_arity, eval_index_expression, eval_infix_expression, eval_integer_part,
+    eval_prefix_expression, for_condition, map_key, result_size, set_index, Console, Failure,
+    Limits, Meter, RuntimeError, StackFrame,
+};
+use crate::lexer::Span;
+use crate::object::{Closure, CompiledFunction, Object};
+
+#[cfg(test)]
+mod tests;
+
+const STACK_SIZE: usize = 2048;
+const MAX_FRAMES: usize = 1024;
+
+#[derive(Debug)]
+struct Frame {
+    closure: Rc<Closure>,
+    ip: usize,
+    base_pointer: usize,
+}
+
+/// Stack machine executing [`Bytecode`]. It raises the same
+/// [`RuntimeError`]s as the evaluator, plus [`RuntimeError::StackOverflow`]
+/// when calls nest too deeply, and locates them with the source spans the
+/// compiler recorded. See [`crate::compiler::Compiler`] for how its
+/// closures differ from the evaluator's.
+#[derive(Debug)]
+pub struct Vm {
+    constants: Vec<Object>,
+    stack: Vec<Object>,
+    /// Whether each stack slot holding a local has been assigned.
+    assigned: Vec<bool>,
+    globals: Vec<Option<Object>>,
+    global_names: Vec<String>,
+    frames: Vec<Frame>,
+    last_popped: Object,
+    one_based: bool,
+    console: Console,
+    builtins: Builtins,
+    meter: Meter,
+    /// The offset of the instruction running in the innermost frame.
+    position: usize,
+}
+
+impl Vm {
+    pub fn new(bytecode: Bytecode) -> Self {
+        let main = Closure {
+            function: Rc::new(bytecode.main),
+            free: Vec::new(),
+        };
+
+        Self {
+            constants: bytecode.constants,
+            stack: Vec::with_capacity(STACK_SIZE),
+            assigned: Vec::with_capacity(STACK_SIZE),
+            globals: vec![None; bytecode.globals.len()],
+            global_names: bytecode.globals,
+            frames: vec![Frame {
+                closure: Rc::new(main),
+                ip: 0,
+                base_pointer: 0,
+            }],
+            last_popped: Object::Null,
+            one_based: false,
+            console: Console::stdio(),
+            builtins: Builtins::new(),
+            meter: Meter::new(Limits::default()),
+            position: 0,
+        }
+    }
+
+    pub fn one_based_arrays(mut self, one_based: bool) -> Self {
+        self.one_based = one_based;
+        self
+    }
+
+    /// Makes `builtins` callable instead of the standard ones. Only those
+    /// with a standard name can be, as the compiler numbers builtins.
+    pub fn builtins(mut self, builtins: Builtins) -> Self {
+        self.builtins = builtins;
+        self
+    }
+
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.meter = Meter::new(limits);
+        self
+    }
+
+    /// Reads `citeste` input from `input` and writes `scrie` output to
+    /// `output` instead of the standard streams.
+    pub fn with_io(mut self, input: Box<dyn BufRead>, output: Box<dyn Write>) -> Self {
+        self.console = Console::new(input, output);
+        self
+    }
+
+    /// The value of the last expression statement run, or of a top-level
+    /// `returneaza`.
+    pub fn last_popped(&self) -> &Object {
+        &self.last_popped
+    }
+
+    pub fn run(&mut self) -> Result<(), Failure> {
+        self.meter.start();
+        self.execute().map_err(|error| self.failure(error))
+    }
+
+    fn execute(&mut self) -> Result<(), RuntimeError> {
+        loop {
+            let frame = self.frames.last_mut().expect("vm has no frame");
+            let function = &frame.closure.function;
+            let Some(&byte) = function.instructions.get(frame.ip) else {
+                return Ok(());
+            };
+            self.position = frame.ip;
+            frame.ip += 1;
+
+            self.meter.step().map_err(RuntimeError::LimitExceeded)?;
+
+            let Some(op) = Opcode::from_byte(byte) else {
+                return Err(RuntimeError::UnsupportedNode(format!("opcode {}", byte)));
+            };
+
+            match op {
+                Opcode::Constant => {
+                    let index = self.read_u16();
+                    self.push(self.constants[index].clone())?;
+                }
+                Opcode::Pop => {
+                    self.last_popped = self.pop();
+                }
+                Opcode::Add
+                | Opcode::Sub
+                | Opcode::Mul
+                | Opcode::Div
+                | Opcode::IntDiv
+                | Opcode::Mod
+                | Opcode::Equal
+                | Opcode::NotEqual
+                | Opcode::LessThan
+                | Opcode::LessEqual
+                | Opcode::GreaterThan
+                | Opcode::GreaterEqual => {
+                    let right = self.pop();
+                    let left = self.pop();
+                    let operator = op.infix_operator().unwrap_or_default();
+                    self.meter
+                        .check_size(result_size(operator, &left, &right))
+                        .map_err(RuntimeError::LimitExceeded)?;
+                    let result = eval_infix_expression(operator, left, right)?;
+                    self.check_allocation(&result)?;
+                    self.push(result)?;
+                }
+                Opcode::Minus | Opcode::Bang => {
+                    let right = self.pop();
+                    let operator = if op == Opcode::Minus { "-" } else { "!" };
+                    self.push(eval_prefix_expression(operator, right)?)?;
+                }
+                Opcode::IntegerPart => {
+                    let value = self.pop();
+                    self.push(eval_integer_part(value)?)?;
+                }
+                Opcode::True => self.push(Object::Boolean(true))?,
+                Opcode::False => self.push(Object::Boolean(false))?,
+                Opcode::Null => self.push(Object::Null)?,
+                Opcode::Jump => {
+                    let target = self.read_u16();
+                    self.current_frame().ip = target;
+                }
+                Opcode::JumpNotTruthy => {
+                    let target = self.read_u16();
+                    if !self.pop().is_truthy() {
+                        self.current_frame().ip = target;
+                    }
+                }
+                Opcode::GetGlobal => {
+                    let index = self.read_u16();
+                    let Some(value) = self.globals[index].clone() else {
+                        let name = self.global_names[index].clone();
+                        return Err(RuntimeError::UndefinedVariable(name));
+                    };
+                    self.push(value)?;
+                }
+                Opcode::SetGlobal => {
+                    let index = self.read_u16();
+                    self.globals[index] = Some(self.pop());
+                }
+                Opcode::GetLocal => {
+                    let index = self.read_u8();
+                    let frame = self.frames.last().expect("vm has no frame");
+                    let slot = frame.base_pointer + index;
+                    if !self.assigned[slot] {
+                        let name = frame.closure.function.locals[index].clone();
+                        return Err(RuntimeError::UndefinedVariable(name));
+                    }
+                    self.push(self.stack[slot].clone())?;
+                }
+                Opcode::SetLocal => {
+                    let index = self.read_u8();
+                    let slot = self.current_frame().base_pointer + index;
+                    self.stack[slot] = self.pop();
+                    self.assigned[slot] = true;
+                }
+                Opcode::GetBuiltin => {
+                    let index = self.read_u8();
+                    self.push(Object::Builtin(BUILTINS[index].into()))?;
+                }
+                Opcode::GetFree => {
+                    let index = self.read_u8();
+                    let value = self.current_frame().closure.free[index].clone();
+                    self.push(value)?;
+                }
+                Opcode::CurrentClosure => {
+                    let closure = Rc::clone(&self.current_frame().closure);
+                    self.push(Object::Closure(closure))?;
+                }
+                Opcode::Closure => {
+                    let index = self.read_u16();
+                    let free_count = self.read_u8();
+                    self.push_closure(index, free_count)?;
+                }
+                Opcode::Call => {
+                    let argument_count = self.read_u8();
+                    self.call(argument_count)?;
+                }
+                Opcode::ReturnValue | Opcode::Return => {
+                    let value = match op {
+                        Opcode::ReturnValue => self.pop(),
+                        _ => Object::Null,
+                    };
+                    if self.frames.len() == 1 {
+                        self.last_popped = value;
+                        return Ok(());
+                    }
+                    let frame = self.frames.pop().expect("vm has no frame");
+                    self.stack.truncate(frame.base_pointer - 1);
+                    self.push(value)?;
+                }
+                Opcode::Array => {
+                    let count = self.read_u16();
+                    let elements = self.stack.split_off(self.stack.len() - count);
+                    let array = Object::array(elements);
+                    self.check_allocation(&array)?;
+                    self.push(array)?;
+                }
+                Opcode::Map => {
+                    let count = self.read_u16();
+                    let entries = self.stack.split_off(self.stack.len() - 2 * count);
+                    let mut pairs = BTreeMap::new();
+                    let mut entries = entries.into_iter();
+                    while let (Some(key), Some(value)) = (entries.next(), entries.next()) {
+                        pairs.insert(map_key(&key)?, value);
+                    }
+                    let map = Object::map(pairs);
+                    self.check_allocation(&map)?;
+                    self.push(map)?;
+                }
+                Opcode::Index => {
+                    let index = self.pop();
+                    let left = self.pop();
+                    self.push(eval_index_expression(left, index, self.one_based)?)?;
+                }
+                Opcode::SetIndex => {
+                    let value = self.pop();
+                    let index = self.pop();
+                    let left = self.pop();
+                    self.check_allocation(&value)?;
+                    set_index(left.clone(), index, value, self.one_based)?;
+                    self.check_allocation(&left)?;
+                }
+                Opcode::DupPair => {
+                    let length = self.stack.len();
+                    self.push(self.stack[length - 2].clone())?;
+                    self.push(self.stack[length - 1].clone())?;
+                }
+                Opcode::Write => {
+                    let count = self.read_u8();
+                    let values = self.stack.split_off(self.stack.len() - count);
+                    self.console.write(&values)?;
+                }
+                Opcode::Read => {
+                    let value = self.console.read()?;
+                    self.check_allocation(&value)?;
+                    self.push(value)?;
+                }
+                Opcode::ForCondition => {
+                    let step = self.pop();
+                    let end = self.pop();
+                    let counter = self.pop();
+                    self.push(Object::Boolean(for_condition(counter, end, step)?))?;
+                }
+            }
+        }
+    }
+
+    fn call(&mut self, argument_count: usize) -> Result<(), RuntimeError> {
+        let callee_slot = self.stack.len() - 1 - argument_count;
+
+        match self.stack[callee_slot].clone() {
+            Object::Closure(closure) => {
+                let function = &closure.function;
+                check_arity(&function.name, function.num_parameters, argument_count)?;
+                self.meter
+                    .check_depth(self.frames.len())
+                    .map_err(RuntimeError::LimitExceeded)?;
+                if self.frames.len() >= MAX_FRAMES {
+                    return Err(RuntimeError::StackOverflow);
+                }
+
+                let base_pointer = callee_slot + 1;
+                let stack_top = base_pointer + function.locals.len();
+                if stack_top > STACK_SIZE {
+                    return Err(RuntimeError::StackOverflow);
+                }
+                self.stack.resize(stack_top, Object::Null);
+                // The caller's locals all sit below `base_pointer`.
+                self.assigned.resize(base_pointer, false);
+                self.assigned.resize(base_pointer + argument_count, true);
+                self.assigned.resize(stack_top, false);
+                self.frames.push(Frame {
+                    closure,
+                    ip: 0,
+                    base_pointer,
+                });
+                Ok(())
+            }
+            Object::Builtin(name) => {
+                let arguments = self.stack.split_off(callee_slot + 1);
+                self.stack.pop();
+                let target = arguments.first().cloned().unwrap_or(Object::Null);
+                self.meter
+                    .check_size(self.builtins.result_size(&name, &arguments))
+                    .map_err(RuntimeError::LimitExceeded)?;
+                let result = self.builtins.call(&name, &arguments)?;
+                self.check_allocation(&target)?;
+                self.push(result)
+            }
+            callee => Err(RuntimeError::NotCallable(callee.type_name())),
+        }
+    }
+
+    fn push_closure(&mut self, index: usize, free_count: usize) -> Result<(), RuntimeError> {
+        let Object::CompiledFunction(function) = &self.constants[index] else {
+            return Err(RuntimeError::NotCallable(self.constants[index].type_name()));
+        };
+
+        let closure = Closure {
+            function: Rc::clone(function),
+            free: self.stack.split_off(self.stack.len() - free_count),
+        };
+        self.push(Object::Closure(Rc::new(closure)))
+    }
+
+    fn check_allocation(&self, value: &Object) -> Result<(), RuntimeError> {
+        self.meter
+            .check_allocation(value)
+            .map_err(RuntimeError::LimitExceeded)
+    }
+
+    /// Locates `error` at the instruction that raised it, inside the calls
+    /// in progress.
+    fn failure(&self, error: RuntimeError) -> Failure {
+        let frame = self.frames.last().expect("vm has no frame");
+        let span = function_span(&frame.closure.function, self.position);
+        // A caller's instruction pointer is just past its `Call`.
+        let stack = self
+            .frames
+            .windows(2)
+            .rev()
+            .map(|pair| StackFrame {
+                function: pair[1].closure.function.name.clone(),
+                call: function_span(&pair[0].closure.function, pair[0].ip - 1),
+            })
+            .collect();
+        Failure { error, span, stack }
+    }
+
+    fn push(&mut self, object: Object) -> Result<(), RuntimeError> {
+        if self.stack.len() >= STACK_SIZE {
+            return Err(RuntimeError::StackOverflow);
+        }
+        self.stack.push(object);
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Object {
+        self.stack.pop().unwrap_or(Object::Null)
+    }
+
+    fn current_frame(&mut self) -> &mut Frame {
+        self.frames.last_mut().expect("vm has no frame")
+    }
+
+    fn read_u8(&mut self) -> usize {
+        let frame = self.current_frame();
+        let operand = frame.closure.function.instructions[frame.ip];
+        frame.ip += 1;
+        operand as usize
+    }
+
+    fn read_u16(&mut self) -> usize {
+        let frame = self.current_frame();
+        let operand = code::read_u16(&frame.closure.function.instructions[frame.ip..]);
+        frame.ip += 2;
+        operand as usize
+    }
+}
+
+fn function_span(function: &CompiledFunction, offset: usize) -> Span {
+    let index = function
+        .positions
+        .partition_point(|(position, _)| *position <= offset);
+    match index {
+        0 => Span::default(),
+        index => function.positions[index - 1].1,
+    }
+}
@@ -0,0 +1,365 @@
+use std::io::Cursor;
+use std::time::Duration;
+
+use crate::compiler::Compiler;
+use crate::evaluator::{Evaluator, Failure, Limit, Limits, RuntimeError};
+use crate::messages::Lang;
+use crate::object::Object;
+use crate::test_support::{parse, Output};
+
+use super::Vm;
+
+fn vm(input: &str, stdin: &str, output: &Output) -> Vm {
+    let bytecode = Compiler::new().compile(&parse(input)).unwrap();
+    Vm::new(bytecode).with_io(
+        Box::new(Cursor::new(stdin.to_string())),
+        Box::new(output.clone()),
+    )
+}
+
+/// Runs `input` on the VM, checking that the evaluator prints the same and
+/// fails in the same place.
+fn run(input: &str, stdin: &str) -> Result<String, Failure> {
+    let output = Output::default();
+    let result = vm(input, stdin, &output).run();
+
+    let expected = Output::default();
+    let mut evaluator = Evaluator::new().with_io(
+        Box::new(Cursor::new(stdin.to_string())),
+        Box::new(expected.clone()),
+    );
+    let evaluated = evaluator.eval_program(&parse(input));
+
+    assert_eq!(result, evaluated.map(|_| ()), "{}", input);
+    assert_eq!(output.text(), expected.text(), "{}", input);
+    result.map(|_| output.text())
+}
+
+fn run_value(input: &str) -> Object {
+    let output = Output::default();
+    let mut vm = vm(input, "", &output);
+    vm.run().unwrap();
+    vm.last_popped().clone()
+}
+
+#[test]
+fn expressions() {
+    let tests = vec![
+        ("1 + 2 * 3", "7"),
+        ("7 / 2", "3.5"),
+        ("-7 div 2", "-3"),
+        ("-7 % 2", "-1"),
+        ("[-2.5]", "-3"),
+        ("!true", "false"),
+        ("1 < 2 si 3 > 4", "false"),
+        ("false sau 5", "true"),
+        (r#""ab" + "c""#, "abc"),
+        ("[1, 2, 3][1]", "2"),
+        ("{1: 2, 3: 4}[3]", "4"),
+        ("len([1, 2])", "2"),
+        ("let a = [1,]; push(a, 2); a", "[1, 2]"),
+        ("returneaza 5; 6", "5"),
+        ("9223372036854775807 + 1", "9223372036854775808"),
+        ("-(-9223372036854775807 - 1)", "9223372036854775808"),
+    ];
+
+    for (input, expected) in tests {
+        assert_eq!(run_value(input).to_string(), expected, "{}", input);
+    }
+}
+
+#[test]
+fn sample_programs() {
+    let tests = vec![
+        (
+            "citeste n
+             s ← 0
+             pentru i ← 1, n executa { s ← s + i }
+             scrie \"suma=\", s",
+            "10",
+            "suma=55\n",
+        ),
+        (
+            "citeste n
+             c ← 0
+             repeta { c ← c + 1; n ← n div 10 } pana cand n == 0
+             scrie c",
+            "90210",
+            "5\n",
+        ),
+        (
+            "citeste a, b
+             cat timp b != 0 executa { r ← a % b; a ← b; b ← r }
+             scrie a",
+            "84 36",
+            "12\n",
+        ),
+        (
+            "functie fact(n) {
+                 daca n <= 1 atunci { returneaza 1 }
+                 returneaza n * fact(n - 1)
+             }
+             pentru i ← 1, 5 { scrie i, \"! = \", fact(i) }",
+            "",
+            "1! = 1\n2! = 2\n3! = 6\n4! = 24\n5! = 120\n",
+        ),
+        (
+            "functie par(n) { daca n == 0 { returneaza true } returneaza impar(n - 1) }
+             functie impar(n) { daca n == 0 { returneaza false } returneaza par(n - 1) }
+             scrie par(10), \" \", impar(7)",
+            "",
+            "true true\n",
+        ),
+        (
+            "citeste n
+             v ← [0, 0, 0, 0, 0]
+             pentru i ← 0, n - 1 { citeste v[i] }
+             pentru i ← 0, n - 2 {
+                 pentru j ← i + 1, n - 1 {
+                     daca v[i] > v[j] { t ← v[i]; v[i] ← v[j]; v[j] ← t }
+                 }
+             }
+             scrie v",
+            "5\n4 2 5 1 3",
+            "[1, 2, 3, 4, 5]\n",
+        ),
+        (
+            "pentru i ← 10, 1, -3 { scrie i }
+             pentru x ← 0, 1, 0.5 { scrie x }
+             scrie i",
+            "",
+            "10\n7\n4\n1\n0\n0.5\n1\n-2\n",
+        ),
+        (
+            "let contor = fn() { let n = 0; return fn() { return n + 1; }; };
+             let c = contor();
+             total ← 0
+             functie aduna(x) { total += x }
+             aduna(c()); aduna(c())
+             scrie total",
+            "",
+            "2\n",
+        ),
+        (
+            "let add = fn(a) { return fn(b) { return fn(c) { return a + b + c; }; }; };
+             scrie add(1)(2)(3)",
+            "",
+            "6\n",
+        ),
+        (
+            "let frecv = {}
+             citeste a, b, c
+             pentru i ← 1, 3 { frecv[i] ← 0 }
+             frecv[a] += 1; frecv[b] += 1; frecv[c] += 1
+             scrie keys(frecv), values(frecv)",
+            "1 3 1",
+            "[1, 2, 3][2, 0, 1]\n",
+        ),
+        (
+            "functie f() { pentru i ← 1, 10 { daca i * i > 20 { returneaza i } } }
+             functie g() { }
+             scrie f(), g()",
+            "",
+            "5null\n",
+        ),
+        (
+            "x ← 3
+             daca x > 5 atunci { scrie \"mare\" }
+             altfel daca x > 2 atunci { scrie \"mediu\" }
+             altfel { scrie \"mic\" }",
+            "",
+            "mediu\n",
+        ),
+        (
+            "citeste n, x
+             f ← 1
+             pentru i ← 2, n { f *= i }
+             scrie f, \" \", x * 2",
+            "25 123456789012345678901234567890",
+            "15511210043330985984000000 246913578024691357802469135780\n",
+        ),
+    ];
+
+    for (input, stdin, expected) in tests {
+        assert_eq!(run(input, stdin).unwrap(), expected, "{}", input);
+    }
+}
+
+#[test]
+fn runtime_errors() {
+    let tests = vec![
+        ("scrie 1 / 0", RuntimeError::DivisionByZero),
+        ("citeste a, b", RuntimeError::InputExhausted),
+        ("pentru i ← 1, 2, 0 { }", RuntimeError::ZeroForStep),
+        ("5()", RuntimeError::NotCallable("INTEGER")),
+        (
+            "daca false { y ← 1 } scrie y",
+            RuntimeError::UndefinedVariable("y".to_string()),
+        ),
+        (
+            "daca false { y ← 1 } y += 1",
+            RuntimeError::UndefinedVariable("y".to_string()),
+        ),
+        (
+            "functie f(a) { daca a { y ← 1 } returneaza y } f(false)",
+            RuntimeError::UndefinedVariable("y".to_string()),
+        ),
+        (
+            "functie f(a) { } f()",
+            RuntimeError::WrongArgumentCount {
+                function: "f".to_string(),
+                expected: 1,
+                got: 0,
+            },
+        ),
+        (
+            "true + 1",
+            RuntimeError::TypeMismatch {
+                left: "BOOLEAN",
+                operator: "+".to_string(),
+                right: "INTEGER",
+            },
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let error = run(input, "1").map_err(|failure| failure.error);
+        assert_eq!(error, Err(expected), "{}", input);
+    }
+}
+
+#[test]
+fn index_errors_report_their_position() {
+    let input = "let v = [1, 2]\nscrie \"x\"\nscrie v[2]";
+    let Err(error) = run(input, "") else {
+        panic!("expected an error");
+    };
+    assert_eq!(
+        error.to_string(),
+        "index 2 out of range for array of length 2 at 3:8"
+    );
+}
+
+#[test]
+fn errors_carry_the_call_stack() {
+    let input = "functie imparte(a, b) {
+  returneaza a div b
+}
+functie medie(v) {
+  returneaza imparte(v[0] + v[1], 0)
+}
+scrie medie([4, 6,])";
+    let failure = run(input, "").unwrap_err();
+    assert_eq!(
+        failure.render(input, Lang::En),
+        "error[E0207]: division by zero
+ --> 2:16
+  |
+2 |   returneaza a div b
+  |                ^^^
+  = in `imparte`, called at 5:21
+  = in `medie`, called at 7:12"
+    );
+
+    let input = "v ← [1, 2]\nf ← functie(i) { returneaza v[i] }\nscrie f(0) + f(2)";
+    let failure = run(input, "").unwrap_err();
+    assert_eq!(
+        failure.to_string(),
+        "index 2 out of range for array of length 2 at 2:30"
+    );
+    assert_eq!(failure.stack[0].function, None);
+}
+
+#[test]
+fn one_based_arrays() {
+    let input = "v ← [10, 20, 30]; v[1] ← 5; v[3] += 1; scrie v, v[2]";
+    let output = Output::default();
+    vm(input, "", &output).one_based_arrays(true).run().unwrap();
+    assert_eq!(output.text(), "[5, 20, 31]20\n");
+}
+
+#[test]
+fn deep_recursion_overflows_the_stack() {
+    let output = Output::default();
+    let result = vm("functie f(n) { returneaza f(n + 1) } f(0)", "", &output).run();
+    let failure = result.unwrap_err();
+    assert_eq!(failure.error, RuntimeError::StackOverflow);
+    assert_eq!(failure.stack.len(), 1023);
+}
+
+#[test]
+fn limits_stop_runaway_programs() {
+    let tests = vec![
+        (
+            Limits {
+                max_steps: Some(1000),
+                ..Limits::default()
+            },
+            "x ← 0\ncat timp true { x += 1 }",
+            Limit::Steps,
+        ),
+        (
+            Limits {
+                max_depth: Some(20),
+                ..Limits::default()
+            },
+            "functie f(n) {\n  returneaza f(n + 1)\n}\nf(0)",
+            Limit::Depth,
+        ),
+        (
+            Limits {
+                max_allocation: Some(8),
+                ..Limits::default()
+            },
+            "a ← []\npentru i ← 1, 100 { push(a, i) }",
+            Limit::Allocation,
+        ),
+        (
+            Limits {
+                max_allocation: Some(8),
+                ..Limits::default()
+            },
+            "m ← {}\npentru i ← 1, 100 { m[i] ← i }",
+            Limit::Allocation,
+        ),
+        (
+            Limits {
+                max_allocation: Some(1024),
+                ..Limits::default()
+            },
+            "x ← 2\npentru i ← 1, 40 { x *= x }",
+            Limit::Allocation,
+        ),
+        (
+            Limits {
+                max_allocation: Some(1024),
+                ..Limits::default()
+            },
+            "x ← 1\nx ← pow(3, 20000000)",
+            Limit::Allocation,
+        ),
+        (
+            Limits {
+                deadline: Some(Duration::from_millis(20)),
+                ..Limits::default()
+            },
+            "x ← 0\nrepeta { x += 1 } pana cand false",
+            Limit::Time,
+        ),
+    ];
+
+    for (limits, input, expected) in tests {
+        let output = Output::default();
+        let result = vm(input, "", &output).limits(limits).run();
+        let Err(Failure {
+            error: RuntimeError::LimitExceeded(limit),
+            span,
+            ..
+        }) = result
+        else {
+            panic!("{}: expected a limit, got {:?}", input, result);
+        };
+        assert_eq!(limit, expected, "{}", input);
+        assert_eq!(span.line, 2, "{}", input);
+    }
+}